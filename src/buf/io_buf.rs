@@ -21,6 +21,13 @@ use std::ops;
 /// region. While the runtime holds ownership to a buffer, the pointer returned
 /// by `stable_ptr` must remain valid even if the `IoBuf` value is moved.
 ///
+/// # Safety
+///
+/// Implementors must guarantee that `stable_ptr` points to at least
+/// `bytes_total` bytes of memory, of which the first `bytes_init` are
+/// initialized, and that the region stays valid and unchanged while the value
+/// is owned by the runtime.
+///
 /// [`slice()`]: IoBuf::slice
 pub unsafe trait IoBuf: Unpin + 'static {
     /// Returns a raw pointer to the vector’s buffer.
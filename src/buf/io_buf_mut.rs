@@ -10,6 +10,12 @@ use crate::buf::IoBuf;
 /// Buffers passed to `io-uring` operations must reference a stable memory
 /// region. While the runtime holds ownership to a buffer, the pointer returned
 /// by `stable_mut_ptr` must remain valid even if the `IoBufMut` value is moved.
+///
+/// # Safety
+///
+/// In addition to the requirements of [`IoBuf`], implementors must guarantee
+/// that `stable_mut_ptr` points to the same region as `stable_ptr` and that the
+/// kernel may write anywhere within the first `bytes_total` bytes.
 pub unsafe trait IoBufMut: IoBuf {
     /// Returns a raw mutable pointer to the vector’s buffer.
     ///
@@ -0,0 +1,69 @@
+use crate::buf::IoBuf;
+
+use std::io;
+
+/// A sequence of `io-uring` compatible buffers, for vectored operations.
+///
+/// The `IoVectoredBuf` trait is implemented by collections of [`IoBuf`]s that
+/// can be passed to scatter/gather operations, such as
+/// [`TcpStream::writev`](crate::net::TcpStream::writev). Users will not need
+/// to use this trait directly.
+///
+/// The runtime builds an array of `iovec`s referring to the buffers, and
+/// keeps it along with the collection until the operation completes. The
+/// kernel accepts at most `IOV_MAX` (1024 on Linux) buffers per operation:
+/// vectored reads and writes of more buffers fail with
+/// [`InvalidInput`](std::io::ErrorKind::InvalidInput), without transferring
+/// any data.
+///
+/// # Safety
+///
+/// Implementors must guarantee that `as_bufs` returns the same buffers, in
+/// the same order, while the value is owned by the runtime.
+pub unsafe trait IoVectoredBuf: Unpin + 'static {
+    /// Type of the buffers.
+    type Buf: IoBuf;
+
+    /// Returns the buffers, in the order their bytes are transferred.
+    fn as_bufs(&self) -> &[Self::Buf];
+}
+
+unsafe impl<T: IoBuf> IoVectoredBuf for Vec<T> {
+    type Buf = T;
+
+    fn as_bufs(&self) -> &[T] {
+        self
+    }
+}
+
+unsafe impl<T: IoBuf, const N: usize> IoVectoredBuf for [T; N] {
+    type Buf = T;
+
+    fn as_bufs(&self) -> &[T] {
+        self
+    }
+}
+
+/// Fails if `bufs` holds more buffers than the kernel accepts in a single
+/// operation, rather than letting the kernel fail it with `EINVAL`.
+pub(crate) fn check_iov_max(bufs: &impl IoVectoredBuf) -> io::Result<()> {
+    if bufs.as_bufs().len() > libc::UIO_MAXIOV as usize {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "more buffers than IOV_MAX in a vectored operation",
+        ));
+    }
+
+    Ok(())
+}
+
+/// Builds the `iovec`s referring to the initialized bytes of each buffer.
+pub(crate) fn iovecs(bufs: &impl IoVectoredBuf) -> Vec<libc::iovec> {
+    bufs.as_bufs()
+        .iter()
+        .map(|buf| libc::iovec {
+            iov_base: buf.stable_ptr() as *mut libc::c_void,
+            iov_len: buf.bytes_init(),
+        })
+        .collect()
+}
@@ -0,0 +1,64 @@
+use crate::buf::{IoBuf, IoBufMut, IoVectoredBuf};
+
+/// A sequence of mutable `io-uring` compatible buffers, for vectored
+/// operations.
+///
+/// The `IoVectoredBufMut` trait is implemented by collections of
+/// [`IoBufMut`]s that can be passed to scatter operations, such as
+/// [`TcpStream::readv`](crate::net::TcpStream::readv). Users will not need to
+/// use this trait directly.
+///
+/// Received bytes fill each buffer up to its capacity before the next one is
+/// written to. The `IOV_MAX` limit of [`IoVectoredBuf`] applies.
+///
+/// # Safety
+///
+/// In addition to the requirements of [`IoVectoredBuf`], implementors must
+/// guarantee that `as_bufs_mut` returns the same buffers as `as_bufs`.
+pub unsafe trait IoVectoredBufMut: IoVectoredBuf<Buf: IoBufMut> {
+    /// Returns the buffers, in the order they are filled.
+    fn as_bufs_mut(&mut self) -> &mut [Self::Buf];
+}
+
+unsafe impl<T: IoBufMut> IoVectoredBufMut for Vec<T> {
+    fn as_bufs_mut(&mut self) -> &mut [T] {
+        self
+    }
+}
+
+unsafe impl<T: IoBufMut, const N: usize> IoVectoredBufMut for [T; N] {
+    fn as_bufs_mut(&mut self) -> &mut [T] {
+        self
+    }
+}
+
+/// Builds the `iovec`s referring to the whole capacity of each buffer.
+pub(crate) fn iovecs_mut(bufs: &mut impl IoVectoredBufMut) -> Vec<libc::iovec> {
+    bufs.as_bufs_mut()
+        .iter_mut()
+        .map(|buf| libc::iovec {
+            iov_base: buf.stable_mut_ptr() as *mut libc::c_void,
+            iov_len: buf.bytes_total(),
+        })
+        .collect()
+}
+
+/// Advances the initialized cursor of each buffer filled by a transfer of
+/// `n` bytes, in order. The count may exceed the capacity of the buffers,
+/// e.g. for datagrams received with `MSG_TRUNC`.
+///
+/// # Safety
+///
+/// The kernel must have written the first `n` bytes across the buffers.
+pub(crate) unsafe fn set_init_vectored(bufs: &mut impl IoVectoredBufMut, n: usize) {
+    let mut remaining = n;
+    for buf in bufs.as_bufs_mut() {
+        let len = remaining.min(buf.bytes_total());
+        if len == 0 {
+            break;
+        }
+
+        buf.set_init(len);
+        remaining -= len;
+    }
+}
@@ -3,6 +3,11 @@
 //! `io-uring` APIs require passing ownership of buffers to the runtime. The
 //! crate defines [`IoBuf`] and [`IoBufMut`] traits which are implemented by buffer
 //! types that respect the `io-uring` contract.
+//!
+//! Alternatively, operations can let the kernel pick a buffer from a
+//! [`BufRing`] once data arrives, or use buffers registered with the kernel
+//! up front through a [`FixedBufPool`]. A [`BufPool`] recycles owned buffers
+//! between operations.
 
 mod io_buf;
 pub use io_buf::IoBuf;
@@ -10,9 +15,22 @@ pub use io_buf::IoBuf;
 mod io_buf_mut;
 pub use io_buf_mut::IoBufMut;
 
+mod io_vectored_buf;
+pub use io_vectored_buf::IoVectoredBuf;
+pub(crate) use io_vectored_buf::{check_iov_max, iovecs};
+
+mod io_vectored_buf_mut;
+pub use io_vectored_buf_mut::IoVectoredBufMut;
+pub(crate) use io_vectored_buf_mut::{iovecs_mut, set_init_vectored};
+
+mod pool;
+pub use pool::{BufPool, PooledBuf};
+
 mod slice;
 pub use slice::Slice;
 
+pub use crate::driver::{BufRing, BufSlot, FixedBuf, FixedBufPool};
+
 pub(crate) fn deref(buf: &impl IoBuf) -> &[u8] {
     // Safety: the `IoBuf` trait is marked as unsafe and is expected to be
     // implemented correctly.
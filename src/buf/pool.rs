@@ -0,0 +1,285 @@
+use crate::buf::{IoBuf, IoBufMut};
+use crate::future::poll_fn;
+
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::rc::Rc;
+use std::task::{Poll, Waker};
+
+/// A pool recycling the allocations of owned buffers.
+///
+/// Operations take their buffers by value, so loops receiving data would
+/// otherwise allocate a buffer per operation. A `BufPool` hands out
+/// [`PooledBuf`]s, `Vec<u8>`-backed buffers of a fixed capacity, and takes
+/// them back when they are dropped, so their memory is reused by the next
+/// [`get`](BufPool::get).
+///
+/// Unlike a [`BufRing`] or a [`FixedBufPool`], the pool is not known to the
+/// kernel, and its buffers work with every operation taking an [`IoBufMut`].
+///
+/// A pool created with [`new`](BufPool::new) allocates a buffer whenever
+/// none is free. One created with [`bounded`](BufPool::bounded) holds at most
+/// a given number of buffers: once they are all in use,
+/// [`try_get`](BufPool::try_get) returns `None`, and [`get`](BufPool::get)
+/// waits for a buffer to be dropped.
+///
+/// `BufPool` is a cheap handle: clones refer to the same pool.
+///
+/// [`BufRing`]: crate::buf::BufRing
+/// [`FixedBufPool`]: crate::buf::FixedBufPool
+///
+/// # Examples
+///
+/// ```no_run
+/// use tokio_uring::buf::BufPool;
+/// use tokio_uring::net::UdpSocket;
+///
+/// fn main() -> std::io::Result<()> {
+///     tokio_uring::start(async {
+///         let socket = UdpSocket::bind("127.0.0.1:8080".parse().unwrap()).await?;
+///         let pool = BufPool::bounded(2048, 64);
+///
+///         loop {
+///             let (res, buf) = socket.recv_from(pool.get().await).await;
+///             let (n, peer) = res?;
+///
+///             // The buffer goes back to the pool once the task is done
+///             tokio_uring::spawn(async move {
+///                 println!("{} bytes from {}", n, peer);
+///                 drop(buf);
+///             });
+///         }
+///     })
+/// }
+/// ```
+#[derive(Clone)]
+pub struct BufPool {
+    inner: Rc<Inner>,
+}
+
+struct Inner {
+    /// Buffers ready to be handed out, cleared.
+    free: RefCell<Vec<Vec<u8>>>,
+
+    /// Capacity of the buffers.
+    buf_capacity: usize,
+
+    /// Maximum number of buffers, if bounded.
+    limit: Option<usize>,
+
+    /// Number of buffers allocated by the pool, in use or free.
+    allocated: Cell<usize>,
+
+    /// Tasks waiting in `get` for a buffer to be dropped.
+    waiters: RefCell<VecDeque<Waker>>,
+}
+
+/// A buffer handed out by a [`BufPool`].
+///
+/// The buffer dereferences to its initialized bytes. Its capacity is fixed,
+/// so every buffer of the pool can be reused by any user. Dropping the buffer
+/// returns it to the pool, cleared.
+pub struct PooledBuf {
+    pool: Rc<Inner>,
+
+    /// Always `Some` until dropped.
+    buf: Option<Vec<u8>>,
+}
+
+impl BufPool {
+    /// Creates a pool of buffers of `buf_capacity` bytes, allocating a new
+    /// buffer whenever none is free.
+    pub fn new(buf_capacity: usize) -> BufPool {
+        BufPool::with_limit(buf_capacity, None)
+    }
+
+    /// Creates a pool of at most `max_bufs` buffers of `buf_capacity` bytes.
+    ///
+    /// The buffers are allocated as they are first needed.
+    pub fn bounded(buf_capacity: usize, max_bufs: usize) -> BufPool {
+        BufPool::with_limit(buf_capacity, Some(max_bufs))
+    }
+
+    fn with_limit(buf_capacity: usize, limit: Option<usize>) -> BufPool {
+        BufPool {
+            inner: Rc::new(Inner {
+                free: RefCell::new(Vec::new()),
+                buf_capacity,
+                limit,
+                allocated: Cell::new(0),
+                waiters: RefCell::new(VecDeque::new()),
+            }),
+        }
+    }
+
+    /// Returns the capacity of the buffers of the pool.
+    pub fn buf_capacity(&self) -> usize {
+        self.inner.buf_capacity
+    }
+
+    /// Returns the number of buffers the pool holds, in use or free.
+    pub fn allocated(&self) -> usize {
+        self.inner.allocated.get()
+    }
+
+    /// Returns the number of free buffers, ready to be handed out without
+    /// allocating.
+    pub fn available(&self) -> usize {
+        self.inner.free.borrow().len()
+    }
+
+    /// Returns a free buffer, allocating one if none is free. Returns `None`
+    /// if the pool is bounded and all of its buffers are in use.
+    pub fn try_get(&self) -> Option<PooledBuf> {
+        let inner = &self.inner;
+
+        let buf = match inner.free.borrow_mut().pop() {
+            Some(buf) => buf,
+            None => {
+                let allocated = inner.allocated.get();
+                if inner.limit.is_some_and(|limit| allocated >= limit) {
+                    return None;
+                }
+
+                inner.allocated.set(allocated + 1);
+                Vec::with_capacity(inner.buf_capacity)
+            }
+        };
+
+        Some(PooledBuf {
+            pool: inner.clone(),
+            buf: Some(buf),
+        })
+    }
+
+    /// Returns a free buffer, allocating one if none is free. If the pool is
+    /// bounded and all of its buffers are in use, waits for one to be
+    /// dropped.
+    pub async fn get(&self) -> PooledBuf {
+        poll_fn(|cx| match self.try_get() {
+            Some(buf) => Poll::Ready(buf),
+            None => {
+                self.inner
+                    .waiters
+                    .borrow_mut()
+                    .push_back(cx.waker().clone());
+                Poll::Pending
+            }
+        })
+        .await
+    }
+}
+
+impl fmt::Debug for BufPool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BufPool")
+            .field("buf_capacity", &self.inner.buf_capacity)
+            .field("limit", &self.inner.limit)
+            .field("allocated", &self.allocated())
+            .field("available", &self.available())
+            .finish()
+    }
+}
+
+impl PooledBuf {
+    /// Returns the capacity of the buffer.
+    pub fn capacity(&self) -> usize {
+        self.buf().capacity()
+    }
+
+    /// Sets the number of initialized bytes to zero, keeping the capacity.
+    pub fn clear(&mut self) {
+        self.buf_mut().clear();
+    }
+
+    /// Takes the buffer out of its pool, which allocates a new one in its
+    /// place when needed.
+    pub fn into_vec(mut self) -> Vec<u8> {
+        let buf = self.buf.take().unwrap();
+        self.pool.allocated.set(self.pool.allocated.get() - 1);
+        self.pool.wake_waiters();
+        buf
+    }
+
+    fn buf(&self) -> &Vec<u8> {
+        self.buf.as_ref().unwrap()
+    }
+
+    fn buf_mut(&mut self) -> &mut Vec<u8> {
+        self.buf.as_mut().unwrap()
+    }
+}
+
+unsafe impl IoBuf for PooledBuf {
+    fn stable_ptr(&self) -> *const u8 {
+        self.buf().as_ptr()
+    }
+
+    fn bytes_init(&self) -> usize {
+        self.buf().len()
+    }
+
+    fn bytes_total(&self) -> usize {
+        self.buf().capacity()
+    }
+}
+
+unsafe impl IoBufMut for PooledBuf {
+    fn stable_mut_ptr(&mut self) -> *mut u8 {
+        self.buf_mut().as_mut_ptr()
+    }
+
+    unsafe fn set_init(&mut self, init_len: usize) {
+        let buf = self.buf_mut();
+        if buf.len() < init_len {
+            buf.set_len(init_len);
+        }
+    }
+}
+
+impl Deref for PooledBuf {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        self.buf()
+    }
+}
+
+impl DerefMut for PooledBuf {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        self.buf_mut()
+    }
+}
+
+impl fmt::Debug for PooledBuf {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PooledBuf")
+            .field("len", &self.buf().len())
+            .field("capacity", &self.capacity())
+            .finish()
+    }
+}
+
+impl Drop for PooledBuf {
+    fn drop(&mut self) {
+        if let Some(mut buf) = self.buf.take() {
+            buf.clear();
+            self.pool.free.borrow_mut().push(buf);
+            self.pool.wake_waiters();
+        }
+    }
+}
+
+impl Inner {
+    /// Wakes the tasks waiting for a buffer. All of them are woken, since
+    /// some may have been dropped since they registered, and they race for
+    /// the free buffers.
+    fn wake_waiters(&self) {
+        let waiters = std::mem::take(&mut *self.waiters.borrow_mut());
+        for waker in waiters {
+            waker.wake();
+        }
+    }
+}
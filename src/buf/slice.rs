@@ -127,19 +127,22 @@ impl<T: IoBuf> ops::Deref for Slice<T> {
     type Target = [u8];
 
     fn deref(&self) -> &[u8] {
-        &super::deref(&self.buf)[self.begin..self.end]
+        let end = self.end.min(self.buf.bytes_init());
+        &super::deref(&self.buf)[self.begin..end]
     }
 }
 
 impl<T: IoBufMut> ops::DerefMut for Slice<T> {
     fn deref_mut(&mut self) -> &mut [u8] {
-        &mut super::deref_mut(&mut self.buf)[self.begin..self.end]
+        let end = self.end.min(self.buf.bytes_init());
+        &mut super::deref_mut(&mut self.buf)[self.begin..end]
     }
 }
 
 unsafe impl<T: IoBuf> IoBuf for Slice<T> {
     fn stable_ptr(&self) -> *const u8 {
-        ops::Deref::deref(self).as_ptr()
+        // Safety: `begin` is within the capacity of the buffer.
+        unsafe { self.buf.stable_ptr().add(self.begin) }
     }
 
     fn bytes_init(&self) -> usize {
@@ -153,7 +156,8 @@ unsafe impl<T: IoBuf> IoBuf for Slice<T> {
 
 unsafe impl<T: IoBufMut> IoBufMut for Slice<T> {
     fn stable_mut_ptr(&mut self) -> *mut u8 {
-        ops::DerefMut::deref_mut(self).as_mut_ptr()
+        // Safety: `begin` is within the capacity of the buffer.
+        unsafe { self.buf.stable_mut_ptr().add(self.begin) }
     }
 
     unsafe fn set_init(&mut self, pos: usize) {
@@ -0,0 +1,152 @@
+use crate::BufResult;
+use std::io;
+
+/// Combinators for [`BufResult`], the result of operations on owned buffers.
+///
+/// Operations return the buffer along with the result, whether or not they
+/// succeeded, so call sites usually destructure the tuple. These methods
+/// transform either half without doing so.
+///
+/// # Examples
+///
+/// ```
+/// use tokio_uring::{BufResult, BufResultExt};
+///
+/// # let read = || -> BufResult<usize, Vec<u8>> { (Ok(4), b"ping".to_vec()) };
+/// // Before
+/// let (res, buf) = read();
+/// let (res, buf) = (res.map(|n| n * 2), buf);
+///
+/// // After
+/// let (res, buf) = read().map_ok(|n| n * 2);
+/// # assert_eq!(res.unwrap(), 8);
+/// # assert_eq!(buf, b"ping");
+/// ```
+pub trait BufResultExt<T, B>: Sized {
+    /// Maps the value of a successful result, keeping the buffer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_uring::{BufResult, BufResultExt};
+    ///
+    /// # let read = || -> BufResult<usize, Vec<u8>> { (Ok(4), b"ping".to_vec()) };
+    /// // Before
+    /// let (res, buf) = read();
+    /// let res = res.map(|n| n == 4);
+    ///
+    /// // After
+    /// let (res, buf) = read().map_ok(|n| n == 4);
+    /// # assert!(res.unwrap());
+    /// ```
+    fn map_ok<U, F>(self, f: F) -> BufResult<U, B>
+    where
+        F: FnOnce(T) -> U;
+
+    /// Maps the buffer, keeping the result, e.g. to recover the buffer a
+    /// [`Slice`](crate::buf::Slice) was taken from.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_uring::buf::{IoBuf, Slice};
+    /// use tokio_uring::{BufResult, BufResultExt};
+    ///
+    /// # let write = |buf: Slice<Vec<u8>>| -> BufResult<usize, Slice<Vec<u8>>> { (Ok(2), buf) };
+    /// // Before
+    /// let (res, slice) = write(b"ping".to_vec().slice(2..));
+    /// let buf = slice.into_inner();
+    ///
+    /// // After
+    /// let (res, buf) = write(b"ping".to_vec().slice(2..)).map_buf(Slice::into_inner);
+    /// # assert_eq!(buf, b"ping");
+    /// ```
+    fn map_buf<C, F>(self, f: F) -> BufResult<T, C>
+    where
+        F: FnOnce(B) -> C;
+
+    /// Calls `f` with the value and the buffer of a successful result,
+    /// returning its result. An error is returned as is, along with the
+    /// buffer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io;
+    /// use tokio_uring::{BufResult, BufResultExt};
+    ///
+    /// fn parse(n: usize, buf: Vec<u8>) -> BufResult<u32, Vec<u8>> {
+    ///     let res = std::str::from_utf8(&buf[..n])
+    ///         .ok()
+    ///         .and_then(|s| s.parse().ok())
+    ///         .ok_or_else(|| io::Error::from(io::ErrorKind::InvalidData));
+    ///     (res, buf)
+    /// }
+    ///
+    /// # let read = || -> BufResult<usize, Vec<u8>> { (Ok(2), b"42".to_vec()) };
+    /// // Before
+    /// let (res, buf) = match read() {
+    ///     (Ok(n), buf) => parse(n, buf),
+    ///     (Err(e), buf) => (Err(e), buf),
+    /// };
+    ///
+    /// // After
+    /// let (res, buf) = read().and_then_buf(parse);
+    /// # assert_eq!(res.unwrap(), 42);
+    /// ```
+    fn and_then_buf<U, F>(self, f: F) -> BufResult<U, B>
+    where
+        F: FnOnce(T, B) -> BufResult<U, B>;
+
+    /// Drops the buffer, returning the result alone, for callers which do
+    /// not reuse the buffer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_uring::{BufResult, BufResultExt};
+    ///
+    /// fn send() -> std::io::Result<usize> {
+    ///     # let write = || -> BufResult<usize, Vec<u8>> { (Ok(4), b"ping".to_vec()) };
+    ///     // Before
+    ///     let (res, _) = write();
+    ///     let n = res?;
+    ///
+    ///     // After
+    ///     let n = write().into_result_dropping_buf()?;
+    ///     Ok(n)
+    /// }
+    /// # assert_eq!(send().unwrap(), 4);
+    /// ```
+    fn into_result_dropping_buf(self) -> io::Result<T>;
+}
+
+impl<T, B> BufResultExt<T, B> for BufResult<T, B> {
+    fn map_ok<U, F>(self, f: F) -> BufResult<U, B>
+    where
+        F: FnOnce(T) -> U,
+    {
+        (self.0.map(f), self.1)
+    }
+
+    fn map_buf<C, F>(self, f: F) -> BufResult<T, C>
+    where
+        F: FnOnce(B) -> C,
+    {
+        (self.0, f(self.1))
+    }
+
+    fn and_then_buf<U, F>(self, f: F) -> BufResult<U, B>
+    where
+        F: FnOnce(T, B) -> BufResult<U, B>,
+    {
+        match self {
+            (Ok(value), buf) => f(value, buf),
+            (Err(e), buf) => (Err(e), buf),
+        }
+    }
+
+    fn into_result_dropping_buf(self) -> io::Result<T> {
+        self.0
+    }
+}
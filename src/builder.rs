@@ -0,0 +1,286 @@
+use crate::runtime::Runtime;
+use crate::MultiThreadRuntime;
+
+use std::convert::TryFrom;
+use std::future::Future;
+use std::io;
+use std::time::Duration;
+
+/// Builds a `tokio-uring` runtime with custom settings.
+///
+/// [`start`](crate::start) uses the default settings, which suit most uses.
+/// The builder tunes how the io-uring instance of the runtime is set up.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::time::Duration;
+///
+/// tokio_uring::builder()
+///     .with_sqpoll(Duration::from_millis(100), None)
+///     .start(async {
+///         // ...
+///     });
+/// ```
+#[derive(Clone)]
+pub struct Builder {
+    /// Number of submission queue entries.
+    pub(crate) entries: u32,
+
+    /// Parameters of the io-uring instance.
+    pub(crate) urb: io_uring::Builder,
+
+    /// Optional setup flags, applied when the kernel supports them.
+    coop_taskrun: bool,
+    single_issuer: bool,
+    defer_taskrun: bool,
+
+    /// Fail instead of ignoring optional setup flags the kernel does not
+    /// support.
+    require_flags: bool,
+
+    /// How long shutting down waits for in-flight operations.
+    pub(crate) shutdown_timeout: Option<Duration>,
+
+    /// Completions awaited per `io_uring_enter`, see
+    /// [`Builder::submit_wait_nr`].
+    pub(crate) submit_wait_nr: u32,
+}
+
+/// Returns a [`Builder`] with the default settings.
+pub fn builder() -> Builder {
+    Builder::new()
+}
+
+impl Builder {
+    /// Returns a builder with the default settings.
+    pub fn new() -> Builder {
+        Builder {
+            entries: 256,
+            urb: io_uring::IoUring::builder(),
+            coop_taskrun: false,
+            single_issuer: false,
+            defer_taskrun: false,
+            require_flags: false,
+            shutdown_timeout: None,
+            submit_wait_nr: 1,
+        }
+    }
+
+    /// Sets the number of submission queue entries, 256 by default. The
+    /// value is rounded up to a power of two.
+    ///
+    /// The kernel accepts at most 32768 entries, unless [`clamp`] is set.
+    ///
+    /// The size only bounds the operations submitted to the kernel at once,
+    /// not those in flight. A full queue is flushed to the kernel to make
+    /// room. If the kernel cannot take the entries yet, the operations wait
+    /// in the driver until completions were reaped, and their futures stay
+    /// pending meanwhile rather than failing.
+    ///
+    /// [`clamp`]: Builder::clamp
+    pub fn entries(&mut self, entries: u32) -> &mut Builder {
+        self.entries = round_up(entries);
+        self
+    }
+
+    /// Sets the number of completion queue entries (`IORING_SETUP_CQSIZE`).
+    /// The value is rounded up to a power of two.
+    ///
+    /// The completion queue defaults to twice the number of submission queue
+    /// entries. A larger one holds bursts of completions without overflowing.
+    /// It must be at least as large as the submission queue, and the kernel
+    /// accepts at most 65536 entries, unless [`clamp`] is set.
+    ///
+    /// [`clamp`]: Builder::clamp
+    pub fn cq_entries(&mut self, cq_entries: u32) -> &mut Builder {
+        self.urb.setup_cqsize(round_up(cq_entries));
+        self
+    }
+
+    /// Clamps the queue sizes to the kernel maximums (`IORING_SETUP_CLAMP`),
+    /// instead of failing when they are too large.
+    pub fn clamp(&mut self) -> &mut Builder {
+        self.urb.setup_clamp();
+        self
+    }
+
+    /// Submits operations through a kernel thread polling the submission
+    /// queue (`IORING_SETUP_SQPOLL`), instead of a system call per batch.
+    ///
+    /// The thread goes to sleep after `idle` without submissions, and is
+    /// woken by the next submission. When `cpu` is given, the thread is
+    /// pinned to that CPU (`IORING_SETUP_SQ_AFF`).
+    ///
+    /// The polling thread keeps a CPU busy while it runs, which trades CPU
+    /// time for lower submission latency. Creating the runtime fails if the
+    /// kernel refuses to set up the thread. On older kernels, it requires
+    /// elevated privileges: `CAP_SYS_ADMIN` before Linux 5.11, and
+    /// `CAP_SYS_NICE` on Linux 5.11 and 5.12.
+    pub fn with_sqpoll(&mut self, idle: Duration, cpu: Option<u32>) -> &mut Builder {
+        let idle = u32::try_from(idle.as_millis()).unwrap_or(u32::MAX);
+        self.urb.setup_sqpoll(idle);
+
+        if let Some(cpu) = cpu {
+            self.urb.setup_sqpoll_cpu(cpu);
+        }
+
+        self
+    }
+
+    /// Stops interrupting the thread to post completions
+    /// (`IORING_SETUP_COOP_TASKRUN`, Linux 5.19).
+    ///
+    /// The kernel posts completions the next time the thread enters it,
+    /// e.g. when the runtime waits for events, instead of interrupting it
+    /// with an inter-processor interrupt. This suits the runtime, which only
+    /// looks at completions at these points anyway.
+    pub fn coop_taskrun(&mut self) -> &mut Builder {
+        self.coop_taskrun = true;
+        self
+    }
+
+    /// Declares that only the thread running the runtime submits operations
+    /// (`IORING_SETUP_SINGLE_ISSUER`, Linux 6.0), which lets the kernel skip
+    /// synchronization.
+    ///
+    /// This always holds: the runtime submits from the thread it runs on.
+    pub fn single_issuer(&mut self) -> &mut Builder {
+        self.single_issuer = true;
+        self
+    }
+
+    /// Defers posting completions until the runtime asks for them
+    /// (`IORING_SETUP_DEFER_TASKRUN`, Linux 6.1). Implies
+    /// [`single_issuer`].
+    ///
+    /// The kernel queues the work completing operations, and the driver runs
+    /// it in a batch each time it reaps completions, with an
+    /// `io_uring_enter(IORING_ENTER_GETEVENTS)`. This saves the most
+    /// overhead, but cannot be combined with [`with_sqpoll`].
+    ///
+    /// [`single_issuer`]: Builder::single_issuer
+    /// [`with_sqpoll`]: Builder::with_sqpoll
+    pub fn defer_taskrun(&mut self) -> &mut Builder {
+        self.single_issuer = true;
+        self.defer_taskrun = true;
+        self
+    }
+
+    /// Fails creating the runtime if the kernel does not support one of the
+    /// flags set by [`coop_taskrun`], [`single_issuer`] or
+    /// [`defer_taskrun`]. By default, unsupported flags are ignored, as they
+    /// only tune performance.
+    ///
+    /// [`coop_taskrun`]: Builder::coop_taskrun
+    /// [`single_issuer`]: Builder::single_issuer
+    /// [`defer_taskrun`]: Builder::defer_taskrun
+    pub fn require_setup_flags(&mut self) -> &mut Builder {
+        self.require_flags = true;
+        self
+    }
+
+    /// Bounds how long shutting down the runtime waits for in-flight
+    /// operations. See [`Runtime::shutdown_timeout`].
+    ///
+    /// By default, shutting down waits for all of them.
+    pub fn shutdown_timeout(&mut self, timeout: Duration) -> &mut Builder {
+        self.shutdown_timeout = Some(timeout);
+        self
+    }
+
+    /// Sets how many completions the runtime waits for before reaping them,
+    /// 1 by default. See [`set_submit_wait_nr`](crate::set_submit_wait_nr),
+    /// which changes it once the runtime runs.
+    pub fn submit_wait_nr(&mut self, n: u32) -> &mut Builder {
+        self.submit_wait_nr = n;
+        self
+    }
+
+    /// Creates a runtime with these settings.
+    pub fn build(&self) -> io::Result<Runtime> {
+        Runtime::new(self)
+    }
+
+    /// Creates a runtime of `workers` threads, each running a runtime with
+    /// these settings. See [`Runtime::new_multi_thread`].
+    pub fn build_multi_thread(&self, workers: usize) -> io::Result<MultiThreadRuntime> {
+        MultiThreadRuntime::new(self, workers)
+    }
+
+    /// Returns the parameters of the io-uring instance, along with whether
+    /// completions are deferred.
+    pub(crate) fn setup(&self) -> io::Result<(io_uring::Builder, bool)> {
+        let mut urb = self.urb.clone();
+
+        if !(self.coop_taskrun || self.single_issuer || self.defer_taskrun) {
+            return Ok((urb, false));
+        }
+
+        let probe = crate::probe()?;
+        let supported = |requested: bool, supported: bool, flag: &str| {
+            if !requested || supported {
+                Ok(requested)
+            } else if self.require_flags {
+                Err(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    format!("{} is not supported by the kernel", flag),
+                ))
+            } else {
+                Ok(false)
+            }
+        };
+
+        if supported(
+            self.coop_taskrun,
+            probe.supports_coop_taskrun(),
+            "IORING_SETUP_COOP_TASKRUN",
+        )? {
+            urb.setup_coop_taskrun();
+        }
+
+        if supported(
+            self.single_issuer,
+            probe.supports_single_issuer(),
+            "IORING_SETUP_SINGLE_ISSUER",
+        )? {
+            urb.setup_single_issuer();
+        }
+
+        let defer_taskrun = supported(
+            self.defer_taskrun,
+            probe.supports_defer_taskrun(),
+            "IORING_SETUP_DEFER_TASKRUN",
+        )?;
+        if defer_taskrun {
+            urb.setup_defer_taskrun();
+        }
+
+        Ok((urb, defer_taskrun))
+    }
+
+    /// Starts a runtime with these settings, and runs `future` on it.
+    ///
+    /// See [`start`](crate::start) for details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the runtime cannot be created.
+    pub fn start<F: Future>(&self, future: F) -> F::Output {
+        let mut rt = self.build().unwrap();
+        rt.block_on(future)
+    }
+}
+
+impl Default for Builder {
+    fn default() -> Builder {
+        Builder::new()
+    }
+}
+
+fn round_up(entries: u32) -> u32 {
+    entries
+        .max(1)
+        .checked_next_power_of_two()
+        .unwrap_or(1 << 31)
+}
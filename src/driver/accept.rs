@@ -7,27 +7,33 @@ pub(crate) struct Accept {
 }
 
 impl Op<Accept> {
-    pub(crate) fn accept(fd: &SharedFd) -> io::Result<Op<Accept>> {
-        use io_uring::{opcode, types};
+    /// Submit an `accept4(2)`, setting the `SOCK_*` `flags` on the accepted
+    /// socket.
+    pub(crate) fn accept(fd: &SharedFd, flags: i32) -> io::Result<Op<Accept>> {
+        use io_uring::opcode;
 
         let socketaddr = Box::new((
             unsafe { std::mem::zeroed() },
             std::mem::size_of::<libc::sockaddr_storage>() as libc::socklen_t,
         ));
-        Op::submit_with(
+        let op = Op::submit_with(
             Accept {
                 fd: fd.clone(),
                 socketaddr,
             },
             |accept| {
-                opcode::Accept::new(
-                    types::Fd(accept.fd.raw_fd()),
+                with_fd!(accept.fd, |fd| opcode::Accept::new(
+                    fd,
                     &mut accept.socketaddr.0 as *mut _ as *mut _,
                     &mut accept.socketaddr.1,
                 )
-                .flags(libc::O_CLOEXEC)
-                .build()
+                .flags(flags)
+                .build())
             },
-        )
+        )?;
+
+        // A connection accepted after the submitter lost interest is closed
+        op.set_fd_result();
+        Ok(op)
     }
 }
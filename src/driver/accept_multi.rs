@@ -0,0 +1,142 @@
+use crate::driver::{Op, SharedFd, Socket};
+use futures_core::Stream;
+use io_uring::cqueue;
+use std::{
+    io,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+pub(crate) struct AcceptMulti {
+    /// Holds a strong ref to the FD, preventing the socket from being closed
+    /// while the operation is in-flight.
+    #[allow(dead_code)]
+    fd: SharedFd,
+}
+
+impl Op<AcceptMulti> {
+    /// Submit a multishot `accept(2)`, posting a completion for each new
+    /// connection.
+    pub(crate) fn accept_multi(fd: &SharedFd) -> io::Result<Op<AcceptMulti>> {
+        use io_uring::opcode;
+
+        let op = Op::submit_with(AcceptMulti { fd: fd.clone() }, |accept| {
+            with_fd!(accept.fd, |fd| opcode::AcceptMulti::new(fd)
+                .flags(libc::O_CLOEXEC)
+                .build())
+        })?;
+
+        // Connections accepted after the submitter lost interest are closed
+        op.set_fd_result();
+        Ok(op)
+    }
+
+    /// Polls the next connection. Along with the connection, returns whether
+    /// the operation keeps accepting.
+    pub(crate) fn poll_next_socket(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<(io::Result<Socket>, bool)>> {
+        let (result, flags) = match ready!(self.poll_next(cx)) {
+            Some(completion) => completion,
+            None => return Poll::Ready(None),
+        };
+
+        let socket = result.map(|fd| Socket::from_shared_fd(SharedFd::new(fd as _)));
+        Poll::Ready(Some((socket, cqueue::more(flags))))
+    }
+}
+
+/// Stream of connections accepted with a multishot `accept(2)`.
+///
+/// Whenever the kernel terminates the multishot operation, the next poll
+/// submits a new one. Errors caused by a lack of resources, like running out
+/// of file descriptors (`EMFILE`), are reported, after which the stream keeps
+/// accepting. Any other error ends the stream.
+pub(crate) struct AcceptMultiStream {
+    fd: SharedFd,
+
+    /// In-flight multishot operation, if any.
+    op: Option<Op<AcceptMulti>>,
+
+    /// Set once the stream yielded a non-recoverable error.
+    done: bool,
+}
+
+impl AcceptMultiStream {
+    pub(crate) fn new(fd: SharedFd) -> AcceptMultiStream {
+        AcceptMultiStream {
+            fd,
+            op: None,
+            done: false,
+        }
+    }
+}
+
+impl Stream for AcceptMultiStream {
+    type Item = io::Result<Socket>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let me = self.get_mut();
+
+        loop {
+            if me.done {
+                return Poll::Ready(None);
+            }
+
+            let op = match &mut me.op {
+                Some(op) => op,
+                None => match Op::accept_multi(&me.fd) {
+                    Ok(op) => me.op.insert(op),
+                    Err(e) => {
+                        me.done = true;
+                        return Poll::Ready(Some(Err(e)));
+                    }
+                },
+            };
+
+            let (result, more) = match ready!(op.poll_next_socket(cx)) {
+                Some(next) => next,
+                None => {
+                    me.op = None;
+                    continue;
+                }
+            };
+
+            if !more {
+                // The kernel terminated the operation, re-arm on the next poll
+                me.op = None;
+
+                if let Err(e) = &result {
+                    me.done = !is_recoverable(e);
+                }
+            }
+
+            return Poll::Ready(Some(result));
+        }
+    }
+}
+
+impl Drop for AcceptMultiStream {
+    fn drop(&mut self) {
+        // A multishot operation only terminates on its own on errors.
+        if let Some(op) = &self.op {
+            op.cancel();
+        }
+    }
+}
+
+/// Whether accepting may succeed again after failing with `e`.
+fn is_recoverable(e: &io::Error) -> bool {
+    matches!(
+        e.raw_os_error(),
+        Some(
+            libc::EMFILE
+                | libc::ENFILE
+                | libc::ENOBUFS
+                | libc::ENOMEM
+                | libc::ECONNABORTED
+                | libc::EINTR
+        )
+    )
+}
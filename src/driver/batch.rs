@@ -0,0 +1,80 @@
+use crate::driver;
+use crate::future::poll_fn;
+
+use std::future::Future;
+
+/// Submits the operations of `future` in batches.
+///
+/// Operations are normally submitted to the kernel as soon as they are
+/// created, with an `io_uring_enter` each. While `future` is polled,
+/// operations are only queued instead, and the whole queue is submitted at
+/// once when the poll returns. Operations created in a single poll, e.g. by
+/// sends joined together, share a single system call.
+///
+/// Awaiting inside the batch works as usual: each time `future` yields, the
+/// operations it queued are submitted, so nothing it waits for is held
+/// back. The queue is still flushed early when it fills up. Batches can be
+/// nested, the outermost one submits.
+///
+/// # Examples
+///
+/// ```
+/// use tokio_uring::net::UdpSocket;
+///
+/// fn main() -> std::io::Result<()> {
+///     tokio_uring::start(async {
+///         let socket = UdpSocket::bind("127.0.0.1:0".parse().unwrap()).await?;
+///         let addr = socket.local_addr()?;
+///
+///         // The three sends are submitted with a single system call
+///         let (a, b, c) = tokio_uring::batch(async {
+///             tokio::join!(
+///                 socket.send_to(b"a".to_vec(), addr),
+///                 socket.send_to(b"b".to_vec(), addr),
+///                 socket.send_to(b"c".to_vec(), addr),
+///             )
+///         })
+///         .await;
+///
+///         for (res, _) in [a, b, c] {
+///             assert_eq!(res?, 1);
+///         }
+///
+///         Ok(())
+///     })
+/// }
+/// ```
+///
+/// # Panics
+///
+/// Panics if polled outside of a `tokio-uring` runtime.
+pub async fn batch<F: Future>(future: F) -> F::Output {
+    let mut future = std::pin::pin!(future);
+
+    poll_fn(|cx| {
+        let _batch = Batch::enter();
+        future.as_mut().poll(cx)
+    })
+    .await
+}
+
+/// Defers submissions while alive, submitting on drop, even if polling
+/// panicked.
+struct Batch;
+
+impl Batch {
+    fn enter() -> Batch {
+        driver::CURRENT.with(|inner| inner.borrow_mut().batch_depth += 1);
+        Batch
+    }
+}
+
+impl Drop for Batch {
+    fn drop(&mut self) {
+        driver::CURRENT.with(|inner| {
+            let mut inner = inner.borrow_mut();
+            inner.batch_depth -= 1;
+            inner.submit_eagerly();
+        });
+    }
+}
@@ -0,0 +1,336 @@
+use crate::driver::{self, Handle};
+
+use io_uring::types::BufRingEntry;
+use slab::Slab;
+use std::alloc::{self, Layout};
+use std::cell::{Cell, RefCell};
+use std::fmt;
+use std::io;
+use std::ops::Deref;
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::ptr::NonNull;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicU16, Ordering};
+
+/// A ring of buffers provided to the kernel.
+///
+/// Operations that select their buffer from a ring do not own a buffer while
+/// they are in-flight. Instead, the kernel picks a free buffer from the ring
+/// when data arrives. This lets many operations, or a single multishot
+/// operation, share a fixed amount of memory.
+///
+/// Buffers picked by the kernel are handed to the caller and go back to the
+/// ring once the caller is done with them. When all buffers are in use,
+/// operations selecting from the ring fail with `ENOBUFS`.
+///
+/// `BufRing` is a cheap handle: clones refer to the same ring. The ring is
+/// unregistered once the last handle is dropped and no operation uses it
+/// anymore.
+///
+/// Buffer rings require Linux 5.19 or newer.
+///
+/// # Examples
+///
+/// ```no_run
+/// use tokio_uring::buf::BufRing;
+///
+/// tokio_uring::start(async {
+///     // 64 buffers of 2048 bytes each
+///     let ring = BufRing::register(64, 2048).unwrap();
+///     assert_eq!(ring.buf_len(), 2048);
+/// });
+/// ```
+#[derive(Clone)]
+pub struct BufRing {
+    inner: Rc<Inner>,
+}
+
+struct Inner {
+    /// Keeps the io-uring instance the ring is registered with alive.
+    #[allow(dead_code)]
+    driver: Handle,
+
+    /// Raw fd of the io-uring instance, used to unregister the ring without
+    /// borrowing the driver.
+    uring_fd: RawFd,
+
+    /// Buffer group id the ring is registered under.
+    bgid: u16,
+
+    /// Buffer group id allocator of the driver.
+    groups: Rc<RefCell<Slab<()>>>,
+
+    /// Ring entries shared with the kernel.
+    ring: NonNull<BufRingEntry>,
+    ring_layout: Layout,
+    entries: u16,
+
+    /// Local copy of the ring tail. Only the application advances the tail.
+    tail: Cell<u16>,
+
+    /// Backing memory of all buffers, `buf_len` bytes per buffer. The memory
+    /// is written to by the kernel, so it is only accessed through raw
+    /// pointers.
+    bufs: NonNull<u8>,
+    bufs_layout: Layout,
+    buf_len: usize,
+}
+
+/// A buffer of a [`BufRing`], picked by the kernel for an operation.
+///
+/// Dereferences to the data the operation received. The buffer is handed
+/// back to the ring when the slot is dropped.
+///
+/// See [`TcpStream::recv_ring`](crate::net::TcpStream::recv_ring).
+pub struct BufSlot {
+    buf_ring: BufRing,
+    bid: u16,
+    len: usize,
+}
+
+/// Parameters of `IORING_UNREGISTER_PBUF_RING`.
+#[repr(C)]
+struct BufReg {
+    ring_addr: u64,
+    ring_entries: u32,
+    bgid: u16,
+    pad: u16,
+    resv: [u64; 3],
+}
+
+const IORING_UNREGISTER_PBUF_RING: libc::c_uint = 23;
+
+impl BufRing {
+    /// Registers a ring of `entries` buffers of `buf_len` bytes each with the
+    /// current runtime.
+    ///
+    /// `entries` must be a power of two no larger than 32768.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a `tokio-uring` runtime.
+    pub fn register(entries: u16, buf_len: usize) -> io::Result<BufRing> {
+        if !entries.is_power_of_two() || entries > 32768 || buf_len == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "entries must be a power of two no larger than 32768, and buf_len non-zero",
+            ));
+        }
+
+        let invalid = |_| io::Error::new(io::ErrorKind::InvalidInput, "buffer ring too large");
+
+        // The kernel requires the ring to be page aligned.
+        let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as usize;
+        let ring_layout = Layout::from_size_align(
+            entries as usize * std::mem::size_of::<BufRingEntry>(),
+            page_size,
+        )
+        .map_err(invalid)?;
+        let bufs_layout = Layout::array::<u8>(entries as usize * buf_len).map_err(invalid)?;
+
+        driver::CURRENT.with(|driver| {
+            let inner = driver.borrow();
+            let groups = inner.buf_groups.clone();
+
+            let bgid = groups.borrow_mut().insert(());
+            if bgid > u16::MAX as usize {
+                groups.borrow_mut().remove(bgid);
+                return Err(io::Error::other("too many buffer rings registered"));
+            }
+
+            // Safety: both layouts have a non-zero size.
+            let ring =
+                NonNull::new(unsafe { alloc::alloc_zeroed(ring_layout) } as *mut BufRingEntry)
+                    .unwrap_or_else(|| alloc::handle_alloc_error(ring_layout));
+
+            // The ring memory stays valid until the ring is unregistered on
+            // drop.
+            let res = inner.uring.submitter().register_buf_ring(
+                ring.as_ptr() as u64,
+                entries,
+                bgid as u16,
+            );
+
+            if let Err(e) = res {
+                groups.borrow_mut().remove(bgid);
+                // Safety: the kernel never saw the ring.
+                unsafe { alloc::dealloc(ring.as_ptr() as *mut u8, ring_layout) };
+                return Err(e);
+            }
+
+            let bufs = NonNull::new(unsafe { alloc::alloc(bufs_layout) })
+                .unwrap_or_else(|| alloc::handle_alloc_error(bufs_layout));
+
+            let buf_ring = BufRing {
+                inner: Rc::new(Inner {
+                    driver: driver.clone(),
+                    uring_fd: inner.uring.as_raw_fd(),
+                    bgid: bgid as u16,
+                    groups,
+                    ring,
+                    ring_layout,
+                    entries,
+                    tail: Cell::new(0),
+                    bufs,
+                    bufs_layout,
+                    buf_len,
+                }),
+            };
+
+            // Provide all buffers to the kernel
+            for bid in 0..entries {
+                buf_ring.push(bid);
+            }
+            buf_ring.publish();
+
+            Ok(buf_ring)
+        })
+    }
+
+    /// Returns the number of buffers in the ring.
+    pub fn entries(&self) -> u16 {
+        self.inner.entries
+    }
+
+    /// Returns the length of each buffer in the ring.
+    pub fn buf_len(&self) -> usize {
+        self.inner.buf_len
+    }
+
+    /// Buffer group id to pass to operations selecting from the ring.
+    pub(crate) fn bgid(&self) -> u16 {
+        self.inner.bgid
+    }
+
+    /// Returns the first `len` bytes of buffer `bid`.
+    ///
+    /// # Safety
+    ///
+    /// The buffer must have been picked by the kernel and not been recycled
+    /// yet, and the kernel must have written at least `len` bytes to it.
+    pub(crate) unsafe fn buf(&self, bid: u16, len: usize) -> &[u8] {
+        debug_assert!(bid < self.inner.entries && len <= self.inner.buf_len);
+
+        std::slice::from_raw_parts(self.buf_ptr(bid), len)
+    }
+
+    /// Hands buffer `bid` back to the kernel.
+    pub(crate) fn recycle(&self, bid: u16) {
+        self.push(bid);
+        self.publish();
+    }
+
+    fn buf_ptr(&self, bid: u16) -> *mut u8 {
+        // Safety: `bid` is within the allocation.
+        unsafe {
+            self.inner
+                .bufs
+                .as_ptr()
+                .add(bid as usize * self.inner.buf_len)
+        }
+    }
+
+    // Write buffer `bid` at the tail of the ring, without making it visible
+    // to the kernel yet.
+    fn push(&self, bid: u16) {
+        let inner = &self.inner;
+        let tail = inner.tail.get();
+        let index = tail & (inner.entries - 1);
+
+        // Safety: `index` is within the ring, and the kernel does not read
+        // entries past the published tail.
+        unsafe {
+            let entry = &mut *inner.ring.as_ptr().add(index as usize);
+            entry.set_addr(self.buf_ptr(bid) as u64);
+            entry.set_len(inner.buf_len as u32);
+            entry.set_bid(bid);
+        }
+
+        inner.tail.set(tail.wrapping_add(1));
+    }
+
+    // Make the buffers pushed so far visible to the kernel.
+    fn publish(&self) {
+        // Safety: the tail overlays the reserved field of the first entry,
+        // and is only ever written by the application.
+        unsafe {
+            let tail = BufRingEntry::tail(self.inner.ring.as_ptr()) as *const AtomicU16;
+            (*tail).store(self.inner.tail.get(), Ordering::Release);
+        }
+    }
+}
+
+impl BufSlot {
+    pub(crate) fn new(buf_ring: BufRing, bid: u16, len: usize) -> BufSlot {
+        BufSlot { buf_ring, bid, len }
+    }
+
+    /// Returns the id of the buffer in its ring.
+    pub fn bid(&self) -> u16 {
+        self.bid
+    }
+}
+
+impl Deref for BufSlot {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        // Safety: the buffer belongs to the slot until it is dropped, and the
+        // kernel wrote `len` bytes to it.
+        unsafe { self.buf_ring.buf(self.bid, self.len) }
+    }
+}
+
+impl fmt::Debug for BufSlot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BufSlot")
+            .field("bid", &self.bid)
+            .field("len", &self.len)
+            .finish()
+    }
+}
+
+impl Drop for BufSlot {
+    fn drop(&mut self) {
+        self.buf_ring.recycle(self.bid);
+    }
+}
+
+impl Drop for Inner {
+    fn drop(&mut self) {
+        let reg = BufReg {
+            ring_addr: 0,
+            ring_entries: 0,
+            bgid: self.bgid,
+            pad: 0,
+            resv: [0; 3],
+        };
+
+        // Unregister through the raw syscall: the last handle may be dropped
+        // while the driver is borrowed, e.g. when an ignored operation
+        // completes. If unregistering fails, the memory must be leaked as the
+        // kernel may still write to it.
+        let ret = unsafe {
+            libc::syscall(
+                libc::SYS_io_uring_register,
+                self.uring_fd,
+                IORING_UNREGISTER_PBUF_RING,
+                &reg as *const BufReg,
+                1,
+            )
+        };
+
+        if ret < 0 {
+            return;
+        }
+
+        self.groups.borrow_mut().remove(self.bgid as usize);
+
+        // Safety: the memory was allocated with these layouts and the kernel
+        // no longer references it.
+        unsafe {
+            alloc::dealloc(self.ring.as_ptr() as *mut u8, self.ring_layout);
+            alloc::dealloc(self.bufs.as_ptr(), self.bufs_layout);
+        }
+    }
+}
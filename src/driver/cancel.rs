@@ -0,0 +1,145 @@
+use crate::driver::Op;
+use crate::future::poll_fn;
+
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::future::Future;
+use std::io;
+use std::rc::Rc;
+
+/// Cancels the operations of a future on request.
+///
+/// Operations are canceled when their future is dropped. A `CancelHandle`
+/// instead cancels them while their future keeps running: the operations
+/// submitted by a future run through [`run`] are tracked, and [`cancel`]
+/// submits an `IORING_OP_ASYNC_CANCEL` for each of them that is still in
+/// flight. Canceled operations complete with an error of kind
+/// [`Interrupted`] or `ECANCELED`, and hand their buffer back as usual.
+///
+/// The handle is cheap to clone, so one clone can run a future while
+/// another, held by a different task, cancels it.
+///
+/// # Examples
+///
+/// ```
+/// use tokio_uring::{net::UdpSocket, CancelHandle};
+///
+/// fn main() -> std::io::Result<()> {
+///     tokio_uring::start(async {
+///         let socket = UdpSocket::bind("127.0.0.1:0".parse().unwrap()).await?;
+///         let handle = CancelHandle::new();
+///
+///         let recv = tokio_uring::spawn({
+///             let handle = handle.clone();
+///             async move { handle.run(socket.recv_from(vec![0; 32])).await }
+///         });
+///
+///         // Let the receive start, then abort it
+///         tokio::task::yield_now().await;
+///         assert!(handle.cancel().await?);
+///
+///         let (res, buf) = recv.await.unwrap();
+///         assert!(res.is_err());
+///         assert_eq!(buf.capacity(), 32);
+///
+///         Ok(())
+///     })
+/// }
+/// ```
+///
+/// [`run`]: CancelHandle::run
+/// [`cancel`]: CancelHandle::cancel
+/// [`Interrupted`]: std::io::ErrorKind::Interrupted
+#[derive(Clone, Default)]
+pub struct CancelHandle {
+    scope: Rc<Scope>,
+}
+
+/// Operations submitted within a [`CancelHandle::run`].
+#[derive(Default)]
+pub(crate) struct Scope {
+    /// Indices of the live operations.
+    ops: RefCell<HashSet<usize>>,
+}
+
+thread_local! {
+    /// Scope of the future being polled by a `CancelHandle`, if any.
+    static CURRENT: RefCell<Option<Rc<Scope>>> = const { RefCell::new(None) };
+}
+
+pub(crate) struct AsyncCancel;
+
+impl CancelHandle {
+    /// Returns a handle not tracking any operation yet.
+    pub fn new() -> CancelHandle {
+        CancelHandle::default()
+    }
+
+    /// Runs `future`, tracking the operations it submits so they can be
+    /// canceled.
+    pub async fn run<F: Future>(&self, future: F) -> F::Output {
+        let mut future = std::pin::pin!(future);
+
+        poll_fn(|cx| {
+            let prev = CURRENT.with(|current| current.replace(Some(self.scope.clone())));
+            let res = future.as_mut().poll(cx);
+            CURRENT.with(|current| *current.borrow_mut() = prev);
+            res
+        })
+        .await
+    }
+
+    /// Cancels the operations of the futures run through this handle that
+    /// are in flight. Returns whether the kernel found any of them.
+    ///
+    /// An operation the kernel already started may not be interruptible, in
+    /// which case it still counts as found, but runs to completion, and may
+    /// succeed. Operations submitted after this call are not affected.
+    pub async fn cancel(&self) -> io::Result<bool> {
+        let indices: Vec<usize> = self.scope.ops.borrow().iter().copied().collect();
+
+        // The cancellations themselves must not be tracked
+        let prev = CURRENT.with(|current| current.borrow_mut().take());
+        let ops: io::Result<Vec<_>> = indices.into_iter().map(Op::async_cancel).collect();
+        CURRENT.with(|current| *current.borrow_mut() = prev);
+
+        let mut found = false;
+        for op in ops? {
+            match op.await.result {
+                Ok(_) => found = true,
+                Err(e) if e.raw_os_error() == Some(libc::EALREADY) => found = true,
+                Err(e) if e.raw_os_error() == Some(libc::ENOENT) => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(found)
+    }
+}
+
+impl Scope {
+    /// Returns the scope of the future being polled, if any.
+    pub(crate) fn current() -> Option<Rc<Scope>> {
+        CURRENT.with(|current| current.borrow().clone())
+    }
+
+    pub(crate) fn insert(&self, index: usize) {
+        self.ops.borrow_mut().insert(index);
+    }
+
+    pub(crate) fn remove(&self, index: usize) {
+        self.ops.borrow_mut().remove(&index);
+    }
+}
+
+impl Op<AsyncCancel> {
+    /// Submit an `IORING_OP_ASYNC_CANCEL` for the operation stored at
+    /// `index`.
+    fn async_cancel(index: usize) -> io::Result<Op<AsyncCancel>> {
+        use io_uring::opcode;
+
+        Op::submit_with(AsyncCancel, |_| {
+            opcode::AsyncCancel::new(index as _).build()
+        })
+    }
+}
@@ -0,0 +1,404 @@
+use crate::buf::{IoBuf, IoBufMut};
+use crate::driver::{open::Open, Op, SharedFd};
+use crate::fs::{File, OpenOptions};
+use crate::net::TcpStream;
+
+use io_uring::{opcode, squeue, types};
+use std::any::Any;
+use std::io;
+use std::net::Shutdown;
+use std::path::Path;
+
+/// A chain of operations executed in order by the kernel.
+///
+/// The operations of a chain are submitted at once with `IOSQE_IO_LINK` set
+/// between them, so the kernel starts each of them once the previous one
+/// completed, without a round trip to user space. This orders dependent
+/// operations, e.g. writing a header to a file and syncing it, or writing to
+/// a socket and shutting it down.
+///
+/// The chain has soft links by default: when an operation fails, the
+/// remaining ones are not started and complete with `ECANCELED`. A read or
+/// write transferring fewer bytes than requested counts as a failure. With
+/// [`hard_links`], the remaining operations run regardless of the outcome
+/// of the previous ones, which only orders them.
+///
+/// The whole chain must fit in the submission queue of the runtime.
+///
+/// # Examples
+///
+/// ```no_run
+/// use tokio_uring::{fs::File, Chain};
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     tokio_uring::start(async {
+///         let file = File::create("data.bin").await?;
+///
+///         let mut chain = Chain::new();
+///         chain.write_at(&file, b"header".to_vec(), 0).sync_all(&file);
+///
+///         let mut output = chain.submit().await;
+///         for res in output.results() {
+///             if let Err(e) = res {
+///                 return Err(e.to_string().into());
+///             }
+///         }
+///
+///         // The buffers are handed back
+///         let header: Vec<u8> = output.take_buf(0).unwrap();
+///         assert_eq!(header, b"header");
+///
+///         Ok(())
+///     })
+/// }
+/// ```
+///
+/// [`hard_links`]: Chain::hard_links
+pub struct Chain {
+    links: Vec<Link>,
+
+    /// Flag linking the operations.
+    link: squeue::Flags,
+
+    /// Error preparing an operation, reported when submitting the chain.
+    error: Option<io::Error>,
+}
+
+/// Outcome of a [`Chain`].
+///
+/// Holds the result of each operation, in the order they were added to the
+/// chain, along with the buffers passed to them.
+pub struct ChainOutput {
+    results: Vec<io::Result<usize>>,
+    bufs: Vec<Option<Box<dyn Any>>>,
+}
+
+pub(crate) struct Link {
+    /// Holds a strong ref to the FD, preventing the file from being closed
+    /// while the operation is in-flight. Operations on a registered file
+    /// slot have none.
+    #[allow(dead_code)]
+    fd: Option<SharedFd>,
+
+    /// Buffer of the operation, if any.
+    buf: Option<Box<dyn Any>>,
+
+    /// Other memory referenced by the entry, if any.
+    #[allow(dead_code)]
+    args: Option<Box<dyn Any>>,
+
+    /// Updates the initialized length of a buffer read into.
+    set_init: Option<fn(&mut dyn Any, usize)>,
+
+    sqe: squeue::Entry,
+}
+
+impl Chain {
+    /// Returns an empty chain with soft links.
+    pub fn new() -> Chain {
+        Chain {
+            links: Vec::new(),
+            link: squeue::Flags::IO_LINK,
+            error: None,
+        }
+    }
+
+    /// Links the operations with `IOSQE_IO_HARDLINK` instead of
+    /// `IOSQE_IO_LINK`: a failed operation does not cancel the following
+    /// ones.
+    pub fn hard_links(&mut self) -> &mut Chain {
+        self.link = squeue::Flags::IO_HARDLINK;
+        self
+    }
+
+    /// Returns the number of operations in the chain.
+    pub fn len(&self) -> usize {
+        self.links.len()
+    }
+
+    /// Returns `true` if the chain holds no operations.
+    pub fn is_empty(&self) -> bool {
+        self.links.is_empty()
+    }
+
+    /// Adds a read from `file` at `pos` into `buf`.
+    pub fn read_at<T: IoBufMut>(&mut self, file: &File, mut buf: T, pos: u64) -> &mut Chain {
+        let fd = file.as_shared_fd();
+        let ptr = buf.stable_mut_ptr();
+        let len = buf.bytes_total();
+        let sqe = with_fd!(fd, |fd| opcode::Read::new(fd, ptr, len as _)
+            .offset(pos as _)
+            .build());
+
+        self.push(fd, Some(Box::new(buf)), Some(set_init::<T>), sqe)
+    }
+
+    /// Adds a write of the initialized bytes of `buf` to `file` at `pos`.
+    pub fn write_at<T: IoBuf>(&mut self, file: &File, buf: T, pos: u64) -> &mut Chain {
+        let fd = file.as_shared_fd();
+        let ptr = buf.stable_ptr();
+        let len = buf.bytes_init();
+        let sqe = with_fd!(fd, |fd| opcode::Write::new(fd, ptr, len as _)
+            .offset(pos as _)
+            .build());
+
+        self.push(fd, Some(Box::new(buf)), None, sqe)
+    }
+
+    /// Adds a sync of the data and metadata of `file`, as
+    /// [`File::sync_all`].
+    pub fn sync_all(&mut self, file: &File) -> &mut Chain {
+        let fd = file.as_shared_fd();
+        let sqe = with_fd!(fd, |fd| opcode::Fsync::new(fd).build());
+
+        self.push(fd, None, None, sqe)
+    }
+
+    /// Adds a sync of the data of `file`, as [`File::sync_data`].
+    pub fn sync_data(&mut self, file: &File) -> &mut Chain {
+        let fd = file.as_shared_fd();
+        let sqe = with_fd!(fd, |fd| opcode::Fsync::new(fd)
+            .flags(types::FsyncFlags::DATASYNC)
+            .build());
+
+        self.push(fd, None, None, sqe)
+    }
+
+    /// Adds an open of the file at `path` with `options`, installing it in
+    /// the registered file `slot` returned by
+    /// [`reserve_fd`](crate::reserve_fd). The file is a direct descriptor:
+    /// the following operations of the chain reach it through the slot, with
+    /// [`read_at_slot`], [`write_at_slot`] and [`sync_all_slot`]. A file
+    /// previously in the slot is closed. The open reports `0` on success.
+    ///
+    /// If the open cannot be prepared, e.g. because `path` holds a nul byte,
+    /// submitting the chain reports the error as if it could not be
+    /// submitted.
+    ///
+    /// Requires Linux 5.15 or later.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_uring::{fs::OpenOptions, Chain};
+    ///
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     tokio_uring::start(async {
+    ///         let slot = tokio_uring::reserve_fd()?;
+    ///
+    ///         let mut chain = Chain::new();
+    ///         chain
+    ///             .open_at("data.bin", OpenOptions::new().write(true).create(true), slot)
+    ///             .write_at_slot(slot, b"header".to_vec(), 0)
+    ///             .sync_all_slot(slot);
+    ///
+    ///         for res in chain.submit().await.into_results() {
+    ///             res?;
+    ///         }
+    ///
+    ///         // Closes the file
+    ///         tokio_uring::unregister_fd(slot)?;
+    ///         Ok(())
+    ///     })
+    /// }
+    /// ```
+    ///
+    /// [`read_at_slot`]: Chain::read_at_slot
+    /// [`write_at_slot`]: Chain::write_at_slot
+    /// [`sync_all_slot`]: Chain::sync_all_slot
+    pub fn open_at(
+        &mut self,
+        path: impl AsRef<Path>,
+        options: &OpenOptions,
+        slot: u32,
+    ) -> &mut Chain {
+        let open = Open::new(None, path.as_ref(), options, true).and_then(|open| {
+            let dest = types::DestinationSlot::try_from_slot_target(slot)
+                .map_err(|_| io::Error::from_raw_os_error(libc::EINVAL))?;
+            let sqe = open.sqe(Some(dest));
+            Ok((open, sqe))
+        });
+
+        match open {
+            Ok((open, sqe)) => {
+                self.push_link(None, None, None, sqe);
+                self.links.last_mut().unwrap().args = Some(Box::new(open));
+            }
+            Err(e) => {
+                // Keeps the results in step with the operations added
+                self.error.get_or_insert(e);
+                self.push_link(None, None, None, opcode::Nop::new().build());
+            }
+        }
+        self
+    }
+
+    /// Adds a read at `pos` into `buf` from the file in the registered file
+    /// `slot`, see [`open_at`](Chain::open_at).
+    pub fn read_at_slot<T: IoBufMut>(&mut self, slot: u32, mut buf: T, pos: u64) -> &mut Chain {
+        let ptr = buf.stable_mut_ptr();
+        let len = buf.bytes_total();
+        let sqe = opcode::Read::new(types::Fixed(slot), ptr, len as _)
+            .offset(pos as _)
+            .build();
+
+        self.push_link(None, Some(Box::new(buf)), Some(set_init::<T>), sqe)
+    }
+
+    /// Adds a write of the initialized bytes of `buf` at `pos` to the file in
+    /// the registered file `slot`, see [`open_at`](Chain::open_at).
+    pub fn write_at_slot<T: IoBuf>(&mut self, slot: u32, buf: T, pos: u64) -> &mut Chain {
+        let ptr = buf.stable_ptr();
+        let len = buf.bytes_init();
+        let sqe = opcode::Write::new(types::Fixed(slot), ptr, len as _)
+            .offset(pos as _)
+            .build();
+
+        self.push_link(None, Some(Box::new(buf)), None, sqe)
+    }
+
+    /// Adds a sync of the data and metadata of the file in the registered
+    /// file `slot`, see [`open_at`](Chain::open_at).
+    pub fn sync_all_slot(&mut self, slot: u32) -> &mut Chain {
+        let sqe = opcode::Fsync::new(types::Fixed(slot)).build();
+
+        self.push_link(None, None, None, sqe)
+    }
+
+    /// Adds a send of the initialized bytes of `buf` on `stream`.
+    pub fn write<T: IoBuf>(&mut self, stream: &TcpStream, buf: T) -> &mut Chain {
+        let fd = stream.inner.as_shared_fd();
+        let ptr = buf.stable_ptr();
+        let len = buf.bytes_init();
+        let sqe = with_fd!(fd, |fd| opcode::Send::new(fd, ptr, len as _).build());
+
+        self.push(fd, Some(Box::new(buf)), None, sqe)
+    }
+
+    /// Adds a shutdown of the read, write, or both halves of `stream`.
+    ///
+    /// Requires Linux 5.11 or later.
+    pub fn shutdown(&mut self, stream: &TcpStream, how: Shutdown) -> &mut Chain {
+        let how = match how {
+            Shutdown::Write => libc::SHUT_WR,
+            Shutdown::Read => libc::SHUT_RD,
+            Shutdown::Both => libc::SHUT_RDWR,
+        };
+
+        let fd = stream.inner.as_shared_fd();
+        let sqe = with_fd!(fd, |fd| opcode::Shutdown::new(fd, how).build());
+
+        self.push(fd, None, None, sqe)
+    }
+
+    /// Submits the chain and waits for all of its operations to complete.
+    ///
+    /// If the chain cannot be submitted, the first operation reports the
+    /// error and the remaining ones `ECANCELED`, as if the first one failed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a `tokio-uring` runtime.
+    pub async fn submit(self) -> ChainOutput {
+        let len = self.links.len();
+        let mut results = Vec::with_capacity(len);
+        let mut bufs = Vec::with_capacity(len);
+
+        let submitted = match self.error {
+            Some(e) => Err((e, self.links)),
+            None => Op::submit_chain_with(self.links, self.link, |_, link| link.sqe.clone()),
+        };
+
+        match submitted {
+            Ok(ops) => {
+                for op in ops {
+                    let completion = op.await;
+                    let mut link = completion.data;
+                    let result = completion.result.map(|n| n as usize);
+
+                    if let (Ok(n), Some(buf), Some(set_init)) =
+                        (&result, &mut link.buf, link.set_init)
+                    {
+                        set_init(&mut **buf, *n);
+                    }
+
+                    results.push(result);
+                    bufs.push(link.buf);
+                }
+            }
+            Err((e, links)) => {
+                results.push(Err(e));
+                results
+                    .extend((1..len).map(|_| Err(io::Error::from_raw_os_error(libc::ECANCELED))));
+                bufs.extend(links.into_iter().map(|link| link.buf));
+            }
+        }
+
+        ChainOutput { results, bufs }
+    }
+
+    fn push(
+        &mut self,
+        fd: &SharedFd,
+        buf: Option<Box<dyn Any>>,
+        set_init: Option<fn(&mut dyn Any, usize)>,
+        sqe: squeue::Entry,
+    ) -> &mut Chain {
+        self.push_link(Some(fd.clone()), buf, set_init, sqe)
+    }
+
+    fn push_link(
+        &mut self,
+        fd: Option<SharedFd>,
+        buf: Option<Box<dyn Any>>,
+        set_init: Option<fn(&mut dyn Any, usize)>,
+        sqe: squeue::Entry,
+    ) -> &mut Chain {
+        self.links.push(Link {
+            fd,
+            buf,
+            args: None,
+            set_init,
+            sqe,
+        });
+        self
+    }
+}
+
+impl Default for Chain {
+    fn default() -> Chain {
+        Chain::new()
+    }
+}
+
+impl ChainOutput {
+    /// Returns the result of each operation, in the order they were added to
+    /// the chain. Reads and writes report the number of bytes transferred.
+    pub fn results(&self) -> &[io::Result<usize>] {
+        &self.results
+    }
+
+    /// Consumes the output, returning the result of each operation.
+    pub fn into_results(self) -> Vec<io::Result<usize>> {
+        self.results
+    }
+
+    /// Takes the buffer passed to the operation at `index` in the chain.
+    /// Returns `None` if the operation has no buffer, the buffer was already
+    /// taken, or it is not a `T`.
+    pub fn take_buf<T: 'static>(&mut self, index: usize) -> Option<T> {
+        let slot = self.bufs.get_mut(index)?;
+        if !slot.as_ref()?.is::<T>() {
+            return None;
+        }
+
+        slot.take()?.downcast().ok().map(|buf| *buf)
+    }
+}
+
+fn set_init<T: IoBufMut>(buf: &mut dyn Any, n: usize) {
+    if let Some(buf) = buf.downcast_mut::<T>() {
+        // Safety: the kernel wrote `n` bytes into the buffer
+        unsafe { buf.set_init(n) };
+    }
+}
@@ -1,4 +1,4 @@
-use crate::driver::Op;
+use crate::driver::{self, Op};
 
 use std::io;
 use std::os::unix::io::RawFd;
@@ -8,9 +8,16 @@ pub(crate) struct Close {
 }
 
 impl Op<Close> {
+    /// Submit an `IORING_OP_CLOSE`. Fails if the kernel lacks the opcode, or
+    /// when the operation cannot be submitted, e.g. off runtime, in which
+    /// case the caller closes `fd` itself.
     pub(crate) fn close(fd: RawFd) -> io::Result<Op<Close>> {
         use io_uring::{opcode, types};
 
+        if !driver::probe().is_ok_and(|probe| probe.is_supported(opcode::Close::CODE)) {
+            return Err(io::ErrorKind::Unsupported.into());
+        }
+
         Op::try_submit_with(Close { fd }, |close| {
             opcode::Close::new(types::Fd(close.fd)).build()
         })
@@ -0,0 +1,161 @@
+use std::mem;
+
+/// Buffer of control messages, the ancillary data passed along with
+/// `sendmsg(2)` and `recvmsg(2)`.
+pub(crate) struct CmsgBuf {
+    /// Backing memory, aligned for `cmsghdr`.
+    buf: Vec<u64>,
+
+    /// Number of bytes holding control messages.
+    len: usize,
+}
+
+/// A control message read from a [`CmsgBuf`].
+pub(crate) struct Cmsg<'a> {
+    pub(crate) level: libc::c_int,
+    pub(crate) ty: libc::c_int,
+    pub(crate) data: &'a [u8],
+}
+
+impl CmsgBuf {
+    /// Returns an empty buffer with room for `space` bytes of control
+    /// messages. Use [`CmsgBuf::space`] to compute the room needed for a
+    /// message.
+    pub(crate) fn with_space(space: usize) -> CmsgBuf {
+        CmsgBuf {
+            buf: vec![0; space.div_ceil(8)],
+            len: 0,
+        }
+    }
+
+    /// Returns the room needed for a control message with `len` bytes of
+    /// data.
+    pub(crate) fn space(len: usize) -> usize {
+        // Safety: only computes a length.
+        unsafe { libc::CMSG_SPACE(len as _) as usize }
+    }
+
+    /// Appends a control message holding `value`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the buffer has no room left for the message.
+    pub(crate) fn push<T: Copy>(&mut self, level: libc::c_int, ty: libc::c_int, value: T) {
+        let space = CmsgBuf::space(mem::size_of::<T>());
+        assert!(self.len + space <= self.capacity(), "control buffer full");
+
+        // Safety: the message fits in the buffer, and messages start at
+        // aligned offsets.
+        unsafe {
+            let hdr = self.as_mut_ptr().add(self.len) as *mut libc::cmsghdr;
+            (*hdr).cmsg_level = level;
+            (*hdr).cmsg_type = ty;
+            (*hdr).cmsg_len = libc::CMSG_LEN(mem::size_of::<T>() as _) as _;
+            std::ptr::write_unaligned(libc::CMSG_DATA(hdr) as *mut T, value);
+        }
+
+        self.len += space;
+    }
+
+    /// Appends a control message holding the elements of `values`, e.g. the
+    /// file descriptors of an `SCM_RIGHTS` message.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the buffer has no room left for the message.
+    pub(crate) fn push_slice<T: Copy>(
+        &mut self,
+        level: libc::c_int,
+        ty: libc::c_int,
+        values: &[T],
+    ) {
+        let len = mem::size_of_val(values);
+        let space = CmsgBuf::space(len);
+        assert!(self.len + space <= self.capacity(), "control buffer full");
+
+        // Safety: the message fits in the buffer, and messages start at
+        // aligned offsets.
+        unsafe {
+            let hdr = self.as_mut_ptr().add(self.len) as *mut libc::cmsghdr;
+            (*hdr).cmsg_level = level;
+            (*hdr).cmsg_type = ty;
+            (*hdr).cmsg_len = libc::CMSG_LEN(len as _) as _;
+            std::ptr::copy_nonoverlapping(values.as_ptr() as *const u8, libc::CMSG_DATA(hdr), len);
+        }
+
+        self.len += space;
+    }
+
+    /// Pointer to pass as `msg_control`.
+    pub(crate) fn as_mut_ptr(&mut self) -> *mut u8 {
+        self.buf.as_mut_ptr() as *mut u8
+    }
+
+    /// Size of the buffer.
+    pub(crate) fn capacity(&self) -> usize {
+        self.buf.len() * 8
+    }
+
+    /// Number of bytes holding control messages, to pass as `msg_controllen`
+    /// when sending.
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Records that the kernel wrote `len` bytes of control messages.
+    pub(crate) fn set_len(&mut self, len: usize) {
+        self.len = len.min(self.capacity());
+    }
+
+    /// Iterates over the control messages in the buffer.
+    pub(crate) fn iter(&self) -> impl Iterator<Item = Cmsg<'_>> {
+        // Safety: the backing memory is initialized.
+        let bytes = unsafe { std::slice::from_raw_parts(self.buf.as_ptr() as *const u8, self.len) };
+        let header_len = unsafe { libc::CMSG_LEN(0) } as usize;
+        let mut offset = 0;
+
+        std::iter::from_fn(move || {
+            if offset + header_len > bytes.len() {
+                return None;
+            }
+
+            // Safety: a header fits at `offset`, which is aligned.
+            let hdr = unsafe { &*(bytes.as_ptr().add(offset) as *const libc::cmsghdr) };
+            let cmsg_len = hdr.cmsg_len as usize;
+            if cmsg_len < header_len || offset + cmsg_len > bytes.len() {
+                return None;
+            }
+
+            let data = &bytes[offset + header_len..offset + cmsg_len];
+            offset += CmsgBuf::space(cmsg_len - header_len);
+
+            Some(Cmsg {
+                level: hdr.cmsg_level,
+                ty: hdr.cmsg_type,
+                data,
+            })
+        })
+    }
+}
+
+impl Cmsg<'_> {
+    /// Reads the data of the message as a `T`, if it is large enough.
+    pub(crate) fn read<T: Copy>(&self) -> Option<T> {
+        if self.data.len() < mem::size_of::<T>() {
+            return None;
+        }
+
+        // Safety: the data holds at least `size_of::<T>()` bytes.
+        Some(unsafe { std::ptr::read_unaligned(self.data.as_ptr() as *const T) })
+    }
+
+    /// Reads the data of the message as an array of `T`. Trailing bytes not
+    /// forming a whole `T` are ignored.
+    pub(crate) fn read_slice<T: Copy>(&self) -> Vec<T> {
+        self.data
+            .chunks_exact(mem::size_of::<T>())
+            // Safety: each chunk holds `size_of::<T>()` bytes.
+            .map(|chunk| unsafe { std::ptr::read_unaligned(chunk.as_ptr() as *const T) })
+            .collect()
+    }
+}
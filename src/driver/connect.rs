@@ -1,31 +1,65 @@
 use crate::driver::{Op, SharedFd};
+use io_uring::types::Timespec;
 use socket2::SockAddr;
-use std::io;
+use std::{io, time::Duration};
 
-/// Open a file
+/// Connect a socket
 pub(crate) struct Connect {
     fd: SharedFd,
-    socket_addr: SockAddr,
+    socket_addr: Box<SockAddr>,
+    /// Deadline of a linked timeout, read by the kernel on submission.
+    timeout: Option<Box<Timespec>>,
+}
+
+impl Connect {
+    fn build(&self) -> io_uring::squeue::Entry {
+        use io_uring::opcode;
+
+        with_fd!(self.fd, |fd| opcode::Connect::new(
+            fd,
+            self.socket_addr.as_ptr(),
+            self.socket_addr.len(),
+        )
+        .build())
+    }
 }
 
 impl Op<Connect> {
     /// Submit a request to connect.
     pub(crate) fn connect(fd: &SharedFd, socket_addr: SockAddr) -> io::Result<Op<Connect>> {
-        use io_uring::{opcode, types};
-
         Op::submit_with(
             Connect {
                 fd: fd.clone(),
-                socket_addr,
-            },
-            |connect| {
-                opcode::Connect::new(
-                    types::Fd(connect.fd.raw_fd()),
-                    connect.socket_addr.as_ptr(),
-                    connect.socket_addr.len(),
-                )
-                .build()
+                socket_addr: Box::new(socket_addr),
+                timeout: None,
             },
+            |connect| connect.build(),
         )
     }
+
+    /// Submit a request to connect, linked to a timeout. If the connection
+    /// is not established within `timeout`, the connect completes with
+    /// `ECANCELED`.
+    pub(crate) fn connect_with_timeout(
+        fd: &SharedFd,
+        socket_addr: SockAddr,
+        timeout: Duration,
+    ) -> io::Result<Op<Connect>> {
+        use io_uring::opcode;
+
+        let connect = Connect {
+            fd: fd.clone(),
+            socket_addr: Box::new(socket_addr),
+            timeout: Some(Box::new(
+                Timespec::new()
+                    .sec(timeout.as_secs())
+                    .nsec(timeout.subsec_nanos()),
+            )),
+        };
+
+        Op::submit_with_link_timeout(connect, |connect| {
+            let timespec = connect.timeout.as_deref().unwrap() as *const _;
+            (connect.build(), opcode::LinkTimeout::new(timespec).build())
+        })
+    }
 }
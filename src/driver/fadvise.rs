@@ -0,0 +1,26 @@
+use crate::driver::{Op, SharedFd};
+
+use std::io;
+
+pub(crate) struct Fadvise {
+    fd: SharedFd,
+}
+
+impl Op<Fadvise> {
+    /// Submit a `posix_fadvise(2)` of `advice`, a `POSIX_FADV_*` value, for
+    /// `len` bytes starting at `offset`.
+    pub(crate) fn fadvise(
+        fd: &SharedFd,
+        offset: u64,
+        len: u64,
+        advice: i32,
+    ) -> io::Result<Op<Fadvise>> {
+        use io_uring::opcode;
+
+        Op::submit_with(Fadvise { fd: fd.clone() }, |fadvise| {
+            with_fd!(fadvise.fd, |fd| opcode::Fadvise::new(fd, len as _, advice)
+                .offset(offset as _)
+                .build())
+        })
+    }
+}
@@ -0,0 +1,27 @@
+use crate::driver::{Op, SharedFd};
+
+use std::io;
+
+pub(crate) struct Fallocate {
+    fd: SharedFd,
+}
+
+impl Op<Fallocate> {
+    /// Submit an `fallocate(2)` of `len` bytes starting at `offset`, with the
+    /// `FALLOC_FL_*` flags of `mode`.
+    pub(crate) fn fallocate(
+        fd: &SharedFd,
+        offset: u64,
+        len: u64,
+        mode: i32,
+    ) -> io::Result<Op<Fallocate>> {
+        use io_uring::opcode;
+
+        Op::submit_with(Fallocate { fd: fd.clone() }, |fallocate| {
+            with_fd!(fallocate.fd, |fd| opcode::Fallocate64::new(fd, len as _)
+                .offset(offset as _)
+                .mode(mode)
+                .build())
+        })
+    }
+}
@@ -0,0 +1,284 @@
+use crate::buf::{IoBuf, IoBufMut};
+use crate::driver::{self, Handle};
+
+use std::cell::RefCell;
+use std::io;
+use std::ops::{Deref, DerefMut};
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::rc::Rc;
+
+/// A set of buffers registered with the kernel as fixed buffers.
+///
+/// The kernel maps registered buffers once, instead of pinning the pages of a
+/// buffer for every operation using it. Buffers are checked out of the pool
+/// as [`FixedBuf`] handles, passed to fixed operations like
+/// [`TcpStream::read_fixed`], and checked back in when the handle is dropped.
+/// A buffer is never checked out twice at the same time.
+///
+/// An io-uring instance has a single table of fixed buffers, so only one pool
+/// can be registered with a runtime at a time. `FixedBufPool` is a cheap
+/// handle: clones refer to the same pool. The buffers are unregistered once
+/// the last handle and the last checked out buffer are dropped, which
+/// happens after all operations using them completed, or explicitly with
+/// [`unregister`](FixedBufPool::unregister).
+///
+/// # Examples
+///
+/// ```no_run
+/// use tokio_uring::buf::FixedBufPool;
+///
+/// tokio_uring::start(async {
+///     let bufs = (0..8).map(|_| Vec::with_capacity(4096)).collect();
+///     let pool = FixedBufPool::register(bufs).unwrap();
+///
+///     let buf = pool.try_next().unwrap();
+///     assert_eq!(buf.capacity(), 4096);
+/// });
+/// ```
+///
+/// [`TcpStream::read_fixed`]: crate::net::TcpStream::read_fixed
+#[derive(Clone)]
+pub struct FixedBufPool {
+    inner: Rc<Inner>,
+}
+
+struct Inner {
+    /// Keeps the io-uring instance the buffers are registered with alive.
+    #[allow(dead_code)]
+    driver: Handle,
+
+    /// Raw fd of the io-uring instance, used to unregister the buffers
+    /// without borrowing the driver.
+    uring_fd: RawFd,
+
+    /// Registered buffers, `None` while checked out.
+    bufs: RefCell<Vec<Option<Vec<u8>>>>,
+
+    /// Cleared once the buffers are unregistered explicitly.
+    registered: bool,
+}
+
+/// A buffer checked out of a [`FixedBufPool`].
+///
+/// The buffer dereferences to its initialized bytes. Its capacity is fixed,
+/// as the registered memory cannot be reallocated. Dropping the buffer checks
+/// it back into the pool.
+pub struct FixedBuf {
+    pool: Rc<Inner>,
+    index: u16,
+
+    /// Always `Some` until dropped.
+    buf: Option<Vec<u8>>,
+}
+
+const IORING_UNREGISTER_BUFFERS: libc::c_uint = 1;
+
+impl FixedBufPool {
+    /// Registers `bufs` as fixed buffers with the current runtime. Each
+    /// buffer is registered with its full capacity, and keeps its contents.
+    ///
+    /// At most 16384 buffers can be registered, each no larger than 1 GiB.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a `tokio-uring` runtime.
+    pub fn register(bufs: Vec<Vec<u8>>) -> io::Result<FixedBufPool> {
+        if bufs.is_empty() || bufs.len() > 16384 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "the number of buffers must be between 1 and 16384",
+            ));
+        }
+
+        let mut bufs = bufs;
+        let iovecs: Vec<libc::iovec> = bufs
+            .iter_mut()
+            .map(|buf| libc::iovec {
+                iov_base: buf.as_mut_ptr().cast(),
+                iov_len: buf.capacity(),
+            })
+            .collect();
+
+        driver::CURRENT.with(|driver| {
+            let inner = driver.borrow();
+
+            // The buffers are moved into the pool, which keeps their memory
+            // in place until they are unregistered on drop.
+            inner.uring.submitter().register_buffers(&iovecs)?;
+
+            Ok(FixedBufPool {
+                inner: Rc::new(Inner {
+                    driver: driver.clone(),
+                    uring_fd: inner.uring.as_raw_fd(),
+                    bufs: RefCell::new(bufs.into_iter().map(Some).collect()),
+                    registered: true,
+                }),
+            })
+        })
+    }
+
+    /// Unregisters the buffers from the kernel (`IORING_UNREGISTER_BUFFERS`),
+    /// returning them so they can be reused or registered again.
+    ///
+    /// Fails with [`ResourceBusy`] if a buffer is checked out, as an
+    /// operation may still be using it, or if the pool has other handles. The
+    /// buffers then stay registered until the remaining handles and buffers
+    /// are dropped.
+    ///
+    /// [`ResourceBusy`]: io::ErrorKind::ResourceBusy
+    pub fn unregister(self) -> io::Result<Vec<Vec<u8>>> {
+        let mut inner = Rc::try_unwrap(self.inner).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::ResourceBusy,
+                "the pool has checked out buffers or other handles",
+            )
+        })?;
+
+        inner.unregister_raw()?;
+        inner.registered = false;
+
+        let bufs = std::mem::take(inner.bufs.get_mut());
+        Ok(bufs.into_iter().map(Option::unwrap).collect())
+    }
+
+    /// Returns the number of buffers in the pool.
+    pub fn len(&self) -> usize {
+        self.inner.bufs.borrow().len()
+    }
+
+    /// Returns `true` if the pool holds no buffers.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Checks out the buffer at `index`, the position of the buffer in the
+    /// registered vector. Returns `None` if it is already checked out, or out
+    /// of bounds.
+    pub fn check_out(&self, index: u16) -> Option<FixedBuf> {
+        let buf = self
+            .inner
+            .bufs
+            .borrow_mut()
+            .get_mut(index as usize)?
+            .take()?;
+
+        Some(FixedBuf {
+            pool: self.inner.clone(),
+            index,
+            buf: Some(buf),
+        })
+    }
+
+    /// Checks out any available buffer. Returns `None` if all of them are
+    /// checked out.
+    pub fn try_next(&self) -> Option<FixedBuf> {
+        let index = self.inner.bufs.borrow().iter().position(Option::is_some)?;
+        self.check_out(index as u16)
+    }
+}
+
+impl FixedBuf {
+    /// Index of the buffer in its pool, as passed to fixed operations.
+    pub fn index(&self) -> u16 {
+        self.index
+    }
+
+    /// Returns the registered size of the buffer.
+    pub fn capacity(&self) -> usize {
+        self.buf().capacity()
+    }
+
+    /// Sets the number of initialized bytes to zero, keeping the capacity.
+    pub fn clear(&mut self) {
+        self.buf_mut().clear();
+    }
+
+    fn buf(&self) -> &Vec<u8> {
+        self.buf.as_ref().unwrap()
+    }
+
+    fn buf_mut(&mut self) -> &mut Vec<u8> {
+        self.buf.as_mut().unwrap()
+    }
+}
+
+unsafe impl IoBuf for FixedBuf {
+    fn stable_ptr(&self) -> *const u8 {
+        self.buf().as_ptr()
+    }
+
+    fn bytes_init(&self) -> usize {
+        self.buf().len()
+    }
+
+    fn bytes_total(&self) -> usize {
+        self.buf().capacity()
+    }
+}
+
+unsafe impl IoBufMut for FixedBuf {
+    fn stable_mut_ptr(&mut self) -> *mut u8 {
+        self.buf_mut().as_mut_ptr()
+    }
+
+    unsafe fn set_init(&mut self, init_len: usize) {
+        let buf = self.buf_mut();
+        if buf.len() < init_len {
+            buf.set_len(init_len);
+        }
+    }
+}
+
+impl Deref for FixedBuf {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        self.buf()
+    }
+}
+
+impl DerefMut for FixedBuf {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        self.buf_mut()
+    }
+}
+
+impl Drop for FixedBuf {
+    fn drop(&mut self) {
+        // Check the buffer back in, its memory stays registered.
+        self.pool.bufs.borrow_mut()[self.index as usize] = self.buf.take();
+    }
+}
+
+impl Inner {
+    /// Unregisters the buffers through the raw syscall: the last buffer may
+    /// be dropped while the driver is borrowed, e.g. when an ignored
+    /// operation completes.
+    fn unregister_raw(&self) -> io::Result<()> {
+        let ret = unsafe {
+            libc::syscall(
+                libc::SYS_io_uring_register,
+                self.uring_fd,
+                IORING_UNREGISTER_BUFFERS,
+                std::ptr::null::<libc::c_void>(),
+                0,
+            )
+        };
+
+        if ret < 0 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl Drop for Inner {
+    fn drop(&mut self) {
+        // If unregistering fails, the memory must be leaked as the kernel may
+        // still access it.
+        if self.registered && self.unregister_raw().is_err() {
+            std::mem::forget(std::mem::take(self.bufs.get_mut()));
+        }
+    }
+}
@@ -0,0 +1,128 @@
+use crate::driver::Handle;
+
+use slab::Slab;
+use std::cell::RefCell;
+use std::io;
+use std::os::unix::io::RawFd;
+use std::rc::Rc;
+
+/// Number of slots in the registered file table of a driver.
+pub(crate) const FILE_TABLE_SIZE: u32 = 1024;
+
+/// The table of files registered with an io-uring instance.
+///
+/// Operations on a registered file name its slot in the table instead of its
+/// file descriptor, which saves the kernel looking the file up for every
+/// operation. The table is registered sparse, and slots are reused once
+/// released.
+pub(crate) struct FileTable {
+    /// Raw fd of the io-uring instance, used to update the table without
+    /// borrowing the driver.
+    uring_fd: RawFd,
+
+    /// Allocated slots.
+    slots: RefCell<Slab<()>>,
+}
+
+/// A file registered in a slot of a [`FileTable`]. The slot is released on
+/// drop.
+pub(crate) struct FixedFile {
+    /// Keeps the io-uring instance the table is registered with alive.
+    #[allow(dead_code)]
+    driver: Handle,
+
+    table: Rc<FileTable>,
+    slot: u32,
+}
+
+/// Parameters of `IORING_REGISTER_FILES_UPDATE`.
+#[repr(C)]
+struct FilesUpdate {
+    offset: u32,
+    resv: u32,
+    fds: u64,
+}
+
+const IORING_REGISTER_FILES_UPDATE: libc::c_uint = 6;
+
+impl FileTable {
+    /// Registers an empty table with the io-uring instance.
+    pub(crate) fn new(uring: &io_uring::IoUring) -> io::Result<FileTable> {
+        use std::os::unix::io::AsRawFd;
+
+        uring.submitter().register_files_sparse(FILE_TABLE_SIZE)?;
+
+        Ok(FileTable {
+            uring_fd: uring.as_raw_fd(),
+            slots: RefCell::new(Slab::new()),
+        })
+    }
+
+    /// Registers `fd` in a free slot. The kernel keeps its own reference to
+    /// the file, so the slot stays valid until released, even if `fd` is
+    /// closed.
+    pub(crate) fn register(self: &Rc<Self>, driver: &Handle, fd: RawFd) -> io::Result<FixedFile> {
+        let file = self.reserve(driver)?;
+        self.update(file.slot, fd)?;
+        Ok(file)
+    }
+
+    /// Allocates a free slot, left empty until an operation installs a file
+    /// in it.
+    pub(crate) fn reserve(self: &Rc<Self>, driver: &Handle) -> io::Result<FixedFile> {
+        let slot = self.slots.borrow_mut().insert(());
+        if slot >= FILE_TABLE_SIZE as usize {
+            self.slots.borrow_mut().remove(slot);
+            return Err(io::Error::other("registered file table full"));
+        }
+
+        Ok(FixedFile {
+            driver: driver.clone(),
+            table: self.clone(),
+            slot: slot as u32,
+        })
+    }
+
+    // Point `slot` at `fd`, or clear it if `fd` is -1. Goes through the raw
+    // syscall as slots may be released while the driver is borrowed.
+    fn update(&self, slot: u32, fd: RawFd) -> io::Result<()> {
+        let update = FilesUpdate {
+            offset: slot,
+            resv: 0,
+            fds: &fd as *const RawFd as u64,
+        };
+
+        let ret = unsafe {
+            libc::syscall(
+                libc::SYS_io_uring_register,
+                self.uring_fd,
+                IORING_REGISTER_FILES_UPDATE,
+                &update as *const FilesUpdate,
+                1,
+            )
+        };
+
+        if ret < 0 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl FixedFile {
+    /// Slot of the file, to name it in operations with `IOSQE_FIXED_FILE`.
+    pub(crate) fn slot(&self) -> u32 {
+        self.slot
+    }
+}
+
+impl Drop for FixedFile {
+    fn drop(&mut self) {
+        // If clearing the slot fails, it is leaked rather than reused, as it
+        // still references the file.
+        if self.table.update(self.slot, -1).is_ok() {
+            self.table.slots.borrow_mut().remove(self.slot as usize);
+        }
+    }
+}
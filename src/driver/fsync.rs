@@ -11,15 +11,29 @@ pub(crate) struct Fsync {
 impl Op<Fsync> {
     pub(crate) fn fsync(fd: &SharedFd) -> io::Result<Op<Fsync>> {
         Op::submit_with(Fsync { fd: fd.clone() }, |fsync| {
-            opcode::Fsync::new(types::Fd(fsync.fd.raw_fd())).build()
+            with_fd!(fsync.fd, |fd| opcode::Fsync::new(fd).build())
         })
     }
 
     pub(crate) fn datasync(fd: &SharedFd) -> io::Result<Op<Fsync>> {
         Op::submit_with(Fsync { fd: fd.clone() }, |fsync| {
-            opcode::Fsync::new(types::Fd(fsync.fd.raw_fd()))
+            with_fd!(fsync.fd, |fd| opcode::Fsync::new(fd)
                 .flags(types::FsyncFlags::DATASYNC)
-                .build()
+                .build())
+        })
+    }
+
+    pub(crate) fn sync_file_range(
+        fd: &SharedFd,
+        offset: u64,
+        nbytes: u32,
+        flags: u32,
+    ) -> io::Result<Op<Fsync>> {
+        Op::submit_with(Fsync { fd: fd.clone() }, |fsync| {
+            with_fd!(fsync.fd, |fd| opcode::SyncFileRange::new(fd, nbytes)
+                .offset(offset as _)
+                .flags(flags)
+                .build())
         })
     }
 }
@@ -0,0 +1,93 @@
+/// A snapshot of the counters of a runtime.
+///
+/// Returned by [`metrics`](crate::metrics) and [`Runtime::metrics`].
+///
+/// [`Runtime::metrics`]: crate::Runtime::metrics
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Metrics {
+    pub(crate) sqes_submitted: u64,
+    pub(crate) submit_syscalls: u64,
+    pub(crate) cqes_reaped: u64,
+    pub(crate) ops_in_flight: u64,
+    pub(crate) ops_backlogged: u64,
+    pub(crate) cq_overflow_flushes: u64,
+    pub(crate) cq_dropped: u64,
+}
+
+/// Counters updated by the driver. The driver is only used from the thread
+/// running it, so plain integers do.
+#[derive(Default)]
+pub(crate) struct MetricsCounters {
+    pub(crate) sqes_submitted: u64,
+    pub(crate) submit_syscalls: u64,
+    pub(crate) cqes_reaped: u64,
+    pub(crate) cq_overflow_flushes: u64,
+}
+
+impl Metrics {
+    /// Number of submission queue entries consumed by the kernel.
+    ///
+    /// Cancellations and linked timeouts submitted by the driver count as
+    /// entries of their own.
+    pub fn sqes_submitted(&self) -> u64 {
+        self.sqes_submitted
+    }
+
+    /// Number of `io_uring_enter` system calls made to submit entries.
+    ///
+    /// With a polling thread, see
+    /// [`Builder::with_sqpoll`](crate::Builder::with_sqpoll), only the
+    /// calls waking the thread up are made. Comparing this with
+    /// [`sqes_submitted`](Metrics::sqes_submitted) tells how well
+    /// submissions are batched.
+    pub fn submit_syscalls(&self) -> u64 {
+        self.submit_syscalls
+    }
+
+    /// Number of completion queue entries reaped by the driver.
+    pub fn cqes_reaped(&self) -> u64 {
+        self.cqes_reaped
+    }
+
+    /// Number of operations in flight when the snapshot was taken,
+    /// including those whose future was dropped and whose completion the
+    /// driver still waits for.
+    pub fn ops_in_flight(&self) -> u64 {
+        self.ops_in_flight
+    }
+
+    /// Number of operations waiting for room in the submission queue when
+    /// the snapshot was taken.
+    ///
+    /// These operations are counted in
+    /// [`ops_in_flight`](Metrics::ops_in_flight) too. The driver retries
+    /// submitting them on every tick, a count staying above zero means the
+    /// submission queue is too small for the load, see
+    /// [`Builder::entries`](crate::Builder::entries).
+    pub fn ops_backlogged(&self) -> u64 {
+        self.ops_backlogged
+    }
+
+    /// Number of times the driver had the kernel flush completions which did
+    /// not fit in the completion queue.
+    ///
+    /// The kernel holds such completions back until the driver drained the
+    /// queue, and new submissions wait meanwhile, so no completion is lost.
+    /// One flush moves all the completions held back at the time, so this
+    /// counts overflowing bursts rather than completions.
+    /// A growing count means the completion queue is too small for the load,
+    /// see [`Builder::cq_entries`](crate::Builder::cq_entries).
+    pub fn cq_overflow_flushes(&self) -> u64 {
+        self.cq_overflow_flushes
+    }
+
+    /// Number of completions the kernel dropped, as reported by the
+    /// completion queue overflow counter.
+    ///
+    /// The kernel only drops completions when it fails to allocate memory to
+    /// hold them back. The operations they belong to never complete, so any
+    /// non-zero count warrants an alarm.
+    pub fn cq_dropped(&self) -> u64 {
+        self.cq_dropped
+    }
+}
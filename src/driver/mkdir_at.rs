@@ -0,0 +1,44 @@
+use crate::driver::{self, Op, SharedFd};
+
+use std::ffi::CString;
+use std::io;
+use std::path::Path;
+
+/// Create a directory relative to a directory, or to the current working
+/// directory of the caller's process.
+pub(crate) struct MkDirAt {
+    /// Holds a strong ref to the FD of the directory, if any.
+    #[allow(dead_code)]
+    dir: Option<SharedFd>,
+
+    path: CString,
+}
+
+impl Op<MkDirAt> {
+    /// Submit a request to create the directory `path`, with the permissions
+    /// `mode` before the process umask is applied.
+    pub(crate) fn mkdir_at(
+        dir: Option<&SharedFd>,
+        path: &Path,
+        mode: libc::mode_t,
+    ) -> io::Result<Op<MkDirAt>> {
+        use io_uring::{opcode, types};
+
+        let path = driver::util::cstr(path)?;
+        let dirfd = driver::util::dirfd(dir);
+
+        Op::submit_with(
+            MkDirAt {
+                dir: dir.cloned(),
+                path,
+            },
+            |mkdir| {
+                // The string is held by the operation state until the
+                // operation completes.
+                opcode::MkDirAt::new(types::Fd(dirfd), mkdir.path.as_ptr())
+                    .mode(mode)
+                    .build()
+            },
+        )
+    }
+}
@@ -1,45 +1,160 @@
+/// Expands `$body` with `$target` naming the file of `$fd` in an operation:
+/// its registered slot if it has one, its file descriptor otherwise.
+macro_rules! with_fd {
+    ($fd:expr, |$target:ident| $body:expr) => {
+        match $fd.fixed_slot() {
+            Some(slot) => {
+                let $target = io_uring::types::Fixed(slot);
+                $body
+            }
+            None => {
+                let $target = io_uring::types::Fd($fd.raw_fd());
+                $body
+            }
+        }
+    };
+}
+
 mod accept;
 
+mod accept_multi;
+pub(crate) use accept_multi::AcceptMultiStream;
+
+mod batch;
+pub use batch::batch;
+
+mod buf_ring;
+pub use buf_ring::{BufRing, BufSlot};
+
+mod cancel;
+pub use cancel::CancelHandle;
+
+mod chain;
+pub use chain::{Chain, ChainOutput};
+
+mod cmsg;
+pub(crate) use cmsg::CmsgBuf;
+
 mod close;
 pub(crate) use close::Close;
 
 mod connect;
 
+mod fadvise;
+
+mod fallocate;
+
+mod fixed_buf;
+pub use fixed_buf::{FixedBuf, FixedBufPool};
+
+mod fixed_file;
+use fixed_file::FileTable;
+pub(crate) use fixed_file::FixedFile;
+
 mod fsync;
 
+mod metrics;
+pub use metrics::Metrics;
+use metrics::MetricsCounters;
+
+mod mkdir_at;
+
+mod napi;
+
 mod op;
-pub(crate) use op::Op;
+pub(crate) use op::{LinkResult, Op};
 
 mod open;
 
+mod poll;
+pub(crate) use poll::PollMultiStream;
+
+mod probe;
+pub use probe::{probe, Probe};
+
+mod raw;
+pub use raw::submit_raw;
+
 mod read;
+pub(crate) use read::Read;
+
+mod read_fixed;
+
+mod readv;
+
+mod recv;
+
+mod recv_cancelable;
+pub(crate) use recv_cancelable::RecvReturning;
+pub use recv_cancelable::{CancelableRecv, RecvGuard};
 
 mod recv_from;
 
+mod recv_select;
+
+mod recv_msg;
+pub(crate) use recv_msg::RecvMsgResult;
+
+mod recv_msg_multi;
+pub(crate) use recv_msg_multi::RecvMsgMultiStream;
+
+mod rename_at;
+
+mod send;
+
+mod send_zc;
+
+mod send_msg;
+pub(crate) use send_msg::SendMsgResult;
+
 mod send_to;
 
 mod shared_fd;
 pub(crate) use shared_fd::SharedFd;
 
+mod sleep;
+pub(crate) use sleep::Sleep;
+
 mod socket;
-pub(crate) use socket::Socket;
+pub(crate) use socket::{BindOptions, Socket};
+
+mod splice;
+pub(crate) use splice::splice;
+
+mod statx;
+pub(crate) use statx::Statx;
+
+mod timeout;
+pub use timeout::with_timeout;
 
 mod unlink_at;
 
 mod util;
 
 mod write;
+pub(crate) use write::Write;
 
-use io_uring::{cqueue, IoUring};
+mod write_fixed;
+
+mod writev;
+pub(crate) use writev::Writev;
+
+use io_uring::{cqueue, opcode, squeue, types, IoUring};
 use scoped_tls::scoped_thread_local;
 use slab::Slab;
 use std::cell::RefCell;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::io;
 use std::os::unix::io::{AsRawFd, RawFd};
 use std::rc::Rc;
+use std::time::{Duration, Instant};
 
 pub(crate) struct Driver {
     inner: Handle,
+
+    /// How long dropping the driver waits for in-flight operations, forever
+    /// if `None`.
+    shutdown_timeout: Option<Duration>,
 }
 
 type Handle = Rc<RefCell<Inner>>;
@@ -50,78 +165,670 @@ struct Inner {
 
     /// IoUring bindings
     uring: IoUring,
+
+    /// Allocated buffer group ids, shared with the registered buffer rings so
+    /// they can release their id without borrowing the driver.
+    buf_groups: Rc<RefCell<Slab<()>>>,
+
+    /// Table of registered files, created on first use.
+    files: Option<Rc<FileTable>>,
+
+    /// Files registered through [`register_fd`], by slot.
+    user_files: HashMap<u32, FixedFile>,
+
+    /// Counters maintained by the driver.
+    metrics: MetricsCounters,
+
+    /// Depth of the [`batch`] scopes being polled. Operations are only
+    /// queued while it is not zero, and submitted when the outermost scope
+    /// is done polling.
+    batch_depth: usize,
+
+    /// Completions are only posted when asked for, with
+    /// `IORING_SETUP_DEFER_TASKRUN`.
+    defer_taskrun: bool,
+
+    /// Completions to wait for before reaping, see [`set_submit_wait_nr`].
+    submit_wait_nr: u32,
+
+    /// Entries submitted since completions were last reaped. Operations
+    /// submitted earlier may stay idle for long, like an accept, so only
+    /// these are waited for by a batch.
+    submitted: usize,
+
+    /// Set while an eventfd is registered to be signaled on completions.
+    cq_eventfd: bool,
+
+    /// Entries of operations which found the submission queue full, and
+    /// could not make room, along with the indices of their operations. They
+    /// are pushed in order once completions were reaped, see
+    /// [`Inner::push`].
+    backlog: VecDeque<(Vec<usize>, Vec<squeue::Entry>)>,
 }
 
 // When dropping the driver, all in-flight operations must have completed. This
 // type wraps the slab and ensures that, on drop, the slab is empty.
-struct Ops(Slab<op::Lifecycle>);
+struct Ops {
+    lifecycle: Slab<op::Lifecycle>,
+
+    /// Buffer rings used by in-flight operations, keyed by operation index.
+    buf_rings: HashMap<usize, BufRing>,
+
+    /// Operations whose successful results are file descriptors, which must
+    /// be closed if nobody claims them.
+    fd_results: HashSet<usize>,
+
+    /// Poll operations, canceled with `IORING_OP_POLL_REMOVE`.
+    polls: HashSet<usize>,
+
+    /// Timespecs of the timeouts linked to in-flight operations by
+    /// [`with_timeout`], keyed by operation index.
+    timeouts: HashMap<usize, Box<types::Timespec>>,
+}
+
+const IORING_ENTER_GETEVENTS: libc::c_uint = 1;
+
+/// Longest time the driver waits for a batch of completions to fill up, see
+/// [`set_submit_wait_nr`].
+const SUBMIT_WAIT_TIMEOUT: Duration = Duration::from_millis(1);
+
+/// Times a submission failing with `EAGAIN` is retried, after reaping
+/// completions, before the error is returned. The kernel is then short of
+/// memory for the requests, which reaping does not necessarily free. A
+/// submission failing with `EBUSY` only waits for completions held back by
+/// the kernel, and is retried until they were reaped.
+const SUBMIT_RETRIES: usize = 16;
 
 scoped_thread_local!(static CURRENT: Rc<RefCell<Inner>>);
 
 impl Driver {
-    pub(crate) fn new() -> io::Result<Driver> {
-        let uring = IoUring::new(256)?;
+    pub(crate) fn new(builder: &crate::Builder) -> io::Result<Driver> {
+        let (urb, defer_taskrun) = builder.setup()?;
+        let uring = urb.build(builder.entries)?;
 
         let inner = Rc::new(RefCell::new(Inner {
             ops: Ops::new(),
             uring,
+            buf_groups: Rc::new(RefCell::new(Slab::new())),
+            files: None,
+            user_files: HashMap::new(),
+            metrics: MetricsCounters::default(),
+            batch_depth: 0,
+            defer_taskrun,
+            submit_wait_nr: builder.submit_wait_nr,
+            submitted: 0,
+            cq_eventfd: false,
+            backlog: VecDeque::new(),
         }));
 
-        Ok(Driver { inner })
+        Ok(Driver {
+            inner,
+            shutdown_timeout: builder.shutdown_timeout,
+        })
     }
 
     /// Enter the driver context. This enables using uring types.
     pub(crate) fn with<R>(&self, f: impl FnOnce() -> R) -> R {
-        CURRENT.set(&self.inner, || f())
+        CURRENT.set(&self.inner, f)
     }
 
     pub(crate) fn tick(&self) {
         let mut inner = self.inner.borrow_mut();
+        inner.wait_batch();
         inner.tick();
+        inner.flush_backlog();
+    }
+
+    /// Returns whether operations wait in the backlog for room in the
+    /// submission queue.
+    pub(crate) fn has_backlog(&self) -> bool {
+        !self.inner.borrow().backlog.is_empty()
     }
 
-    fn wait(&self) -> io::Result<usize> {
+    pub(crate) fn set_submit_wait_nr(&self, n: u32) {
+        self.inner.borrow_mut().submit_wait_nr = n;
+    }
+
+    /// Sets how long dropping the driver waits for in-flight operations.
+    pub(crate) fn set_shutdown_timeout(&mut self, timeout: Option<Duration>) {
+        self.shutdown_timeout = timeout;
+    }
+
+    /// Waits for a completion, or until `deadline`. Signals interrupting the
+    /// wait are ignored.
+    fn wait(&self, deadline: Option<Instant>) -> io::Result<usize> {
         let mut inner = self.inner.borrow_mut();
         let inner = &mut *inner;
 
-        inner.uring.submit_and_wait(1)
+        loop {
+            let res = match deadline {
+                Some(deadline) => {
+                    let left = deadline.saturating_duration_since(Instant::now());
+                    let timespec = types::Timespec::new()
+                        .sec(left.as_secs())
+                        .nsec(left.subsec_nanos());
+                    let args = types::SubmitArgs::new().timespec(&timespec);
+
+                    inner.uring.submitter().submit_with_args(1, &args)
+                }
+                None => inner.uring.submit_and_wait(1),
+            };
+
+            match res {
+                Err(ref e) if e.raw_os_error() == Some(libc::EINTR) => {}
+                res => return res,
+            }
+        }
+    }
+
+    pub(crate) fn metrics(&self) -> Metrics {
+        self.inner.borrow_mut().metrics()
+    }
+
+    pub(crate) fn register_napi(
+        &self,
+        busy_poll_usecs: u32,
+        prefer_busy_poll: bool,
+    ) -> io::Result<()> {
+        napi::register(self.as_raw_fd(), busy_poll_usecs, prefer_busy_poll)
+    }
+
+    pub(crate) fn unregister_napi(&self) -> io::Result<()> {
+        napi::unregister(self.as_raw_fd())
+    }
+
+    /// Sets the limits of the io-wq worker pool, returning the previous ones.
+    /// A limit of 0 is left unchanged.
+    pub(crate) fn register_iowq_max_workers(&self, mut max: [u32; 2]) -> io::Result<[u32; 2]> {
+        let inner = self.inner.borrow();
+        inner
+            .uring
+            .submitter()
+            .register_iowq_max_workers(&mut max)?;
+        Ok(max)
+    }
+
+    /// Registers `fd` as the eventfd signaled when completions are posted,
+    /// or only those of operations which did not complete inline with
+    /// `async_only`.
+    pub(crate) fn register_eventfd(&self, fd: RawFd, async_only: bool) -> io::Result<()> {
+        let mut inner = self.inner.borrow_mut();
+        let submitter = inner.uring.submitter();
+        if async_only {
+            submitter.register_eventfd_async(fd)?;
+        } else {
+            submitter.register_eventfd(fd)?;
+        }
+        inner.cq_eventfd = true;
+        Ok(())
+    }
+
+    pub(crate) fn unregister_eventfd(&self) -> io::Result<()> {
+        let mut inner = self.inner.borrow_mut();
+        inner.uring.submitter().unregister_eventfd()?;
+        inner.cq_eventfd = false;
+        Ok(())
     }
 
     fn num_operations(&self) -> usize {
         let inner = self.inner.borrow();
-        inner.ops.0.len()
+        inner.ops.lifecycle.len()
+    }
+}
+
+/// Registers `fd` in the file table of the current driver.
+///
+/// # Panics
+///
+/// Panics if called outside of a `tokio-uring` runtime.
+pub(crate) fn register_file(fd: RawFd) -> io::Result<FixedFile> {
+    CURRENT.with(|driver| file_table(driver)?.register(driver, fd))
+}
+
+/// Returns the file table of `driver`, registering it on first use.
+fn file_table(driver: &Handle) -> io::Result<Rc<FileTable>> {
+    let mut inner = driver.borrow_mut();
+    match &inner.files {
+        Some(table) => Ok(table.clone()),
+        None => {
+            let table = Rc::new(FileTable::new(&inner.uring)?);
+            inner.files = Some(table.clone());
+            Ok(table)
+        }
+    }
+}
+
+/// Registers `fd` in the file table of the current runtime, returning the
+/// slot it was registered in.
+///
+/// Operations can name a registered file by its slot with
+/// `IOSQE_FIXED_FILE`, which saves the kernel looking the file up for every
+/// operation. The kernel holds its own reference to the file, so it stays
+/// open until [`unregister_fd`] releases the slot, even if `fd` is closed.
+/// Released slots are reused.
+///
+/// Sockets of this crate register themselves through methods like
+/// [`TcpStream::use_fixed_file`], and release their slot when closed.
+///
+/// The table has 1024 slots. Registering files requires Linux 5.19 or
+/// newer.
+///
+/// # Panics
+///
+/// Panics if called outside of a `tokio-uring` runtime.
+///
+/// [`TcpStream::use_fixed_file`]: crate::net::TcpStream::use_fixed_file
+pub fn register_fd(fd: RawFd) -> io::Result<u32> {
+    let file = register_file(fd)?;
+    let slot = file.slot();
+
+    CURRENT.with(|driver| driver.borrow_mut().user_files.insert(slot, file));
+    Ok(slot)
+}
+
+/// Allocates an empty slot in the file table of the current runtime, for an
+/// operation to open a file into, see [`Chain::open_at`]. Files opened this
+/// way are direct descriptors: they only exist in the table, and operations
+/// name them by slot.
+///
+/// The slot is released by [`unregister_fd`], which closes the file it
+/// holds, if any. See [`register_fd`] for the limits of the table.
+///
+/// # Panics
+///
+/// Panics if called outside of a `tokio-uring` runtime.
+///
+/// [`Chain::open_at`]: crate::Chain::open_at
+pub fn reserve_fd() -> io::Result<u32> {
+    let file = CURRENT.with(|driver| file_table(driver)?.reserve(driver))?;
+    let slot = file.slot();
+
+    CURRENT.with(|driver| driver.borrow_mut().user_files.insert(slot, file));
+    Ok(slot)
+}
+
+/// Releases a slot returned by [`register_fd`] or [`reserve_fd`].
+///
+/// Returns an error if the slot was not registered with `register_fd` or
+/// `reserve_fd`.
+///
+/// # Panics
+///
+/// Panics if called outside of a `tokio-uring` runtime.
+pub fn unregister_fd(slot: u32) -> io::Result<()> {
+    let file = CURRENT.with(|driver| driver.borrow_mut().user_files.remove(&slot));
+
+    match file {
+        Some(file) => {
+            drop(file);
+            Ok(())
+        }
+        None => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "slot was not registered with `register_fd` or `reserve_fd`",
+        )),
     }
 }
 
+/// Registers a ring of `entries` buffers of `buf_len` bytes each with the
+/// current runtime.
+///
+/// This is a shorthand for [`BufRing::register`], see there for details.
+///
+/// # Panics
+///
+/// Panics if called outside of a `tokio-uring` runtime.
+pub fn register_buf_ring(entries: u16, buf_len: usize) -> io::Result<BufRing> {
+    BufRing::register(entries, buf_len)
+}
+
+/// Returns a snapshot of the counters of the current runtime.
+///
+/// # Panics
+///
+/// Panics if called outside of a `tokio-uring` runtime.
+pub fn metrics() -> Metrics {
+    CURRENT.with(|driver| driver.borrow_mut().metrics())
+}
+
+/// Sets how many completions the current runtime waits for before reaping
+/// them, trading latency for throughput.
+///
+/// Whenever a completion is posted, the runtime reaps the completion queue
+/// and wakes the tasks waiting on it. With the default of `1`, it does so
+/// right away, which gives the lowest latency. With a larger `n`, it first
+/// waits in a single `io_uring_enter` until `n` completions are ready, or for
+/// at most 1 ms, then reaps them together. Under load this amortizes the cost
+/// of waking up and polling the tasks over several completions, at the cost
+/// of up to 1 ms of latency per completion. The wait never asks for more
+/// completions than entries were submitted since completions were last
+/// reaped, so operations staying idle for long, like an accept, do not hold
+/// the wait back.
+///
+/// Values between 8 and 32 suit throughput-oriented servers with many
+/// concurrent operations. `0` behaves as `1`. This takes effect from the next
+/// time the runtime reaps completions, and can be changed at any time. See
+/// [`Builder::submit_wait_nr`](crate::Builder::submit_wait_nr) to set it
+/// when building the runtime.
+///
+/// # Panics
+///
+/// Panics if called outside of a `tokio-uring` runtime.
+pub fn set_submit_wait_nr(n: u32) {
+    CURRENT.with(|driver| driver.borrow_mut().submit_wait_nr = n);
+}
+
 impl Inner {
+    /// Waits, briefly, until `submit_wait_nr` completions are ready, so they
+    /// are reaped together. Queued submissions are submitted on the way.
+    fn wait_batch(&mut self) {
+        let pending = self.submitted + self.uring.submission().len();
+        let want = (self.submit_wait_nr as usize)
+            .min(pending)
+            .min(self.ops.lifecycle.len());
+        if want <= 1 || self.uring.completion().len() >= want {
+            return;
+        }
+
+        let deadline = Instant::now() + SUBMIT_WAIT_TIMEOUT;
+        loop {
+            let left = deadline.saturating_duration_since(Instant::now());
+            let timespec = types::Timespec::new()
+                .sec(left.as_secs())
+                .nsec(left.subsec_nanos());
+            let args = types::SubmitArgs::new().timespec(&timespec);
+
+            self.metrics.submit_syscalls += 1;
+
+            match self.uring.submitter().submit_with_args(want, &args) {
+                Ok(n) => {
+                    self.metrics.sqes_submitted += n as u64;
+                    self.submitted += n;
+                    return;
+                }
+                // A signal does not end the wait, the batch keeps filling up
+                // for the time left
+                Err(ref e) if e.raw_os_error() == Some(libc::EINTR) && !left.is_zero() => {}
+                // Timing out only ends the wait early
+                Err(_) => return,
+            }
+        }
+    }
+
+    fn metrics(&mut self) -> Metrics {
+        Metrics {
+            sqes_submitted: self.metrics.sqes_submitted,
+            submit_syscalls: self.metrics.submit_syscalls,
+            cqes_reaped: self.metrics.cqes_reaped,
+            ops_in_flight: self.ops.lifecycle.len() as u64,
+            ops_backlogged: self
+                .backlog
+                .iter()
+                .map(|(indices, _)| indices.len() as u64)
+                .sum(),
+            cq_overflow_flushes: self.metrics.cq_overflow_flushes,
+            cq_dropped: self.uring.completion().overflow() as u64,
+        }
+    }
+
     fn tick(&mut self) {
-        let mut cq = self.uring.completion();
-        cq.sync();
+        if self.defer_taskrun {
+            // Run the deferred work posting completions. If this fails, the
+            // completions are posted by the next `io_uring_enter`.
+            let _ = self.get_events();
+        }
+
+        loop {
+            let mut cq = self.uring.completion();
+            cq.sync();
+
+            for cqe in cq {
+                self.metrics.cqes_reaped += 1;
+
+                if cqe.user_data() == u64::MAX {
+                    // Result of the cancellation action, or of a linked timeout.
+                    // There isn't anything we need to do here. We must wait for
+                    // the CQE for the operation that was canceled.
+                    continue;
+                }
+
+                let index = cqe.user_data() as _;
+
+                self.ops.complete(index, resultify(&cqe), cqe.flags());
+            }
+
+            // Completions that did not fit in the completion queue are held
+            // by the kernel until the next `io_uring_enter`, which does not
+            // signal the ring fd again. Flush them now that there is room.
+            if !self.uring.submission().cq_overflow() || self.flush_overflow().is_err() {
+                break;
+            }
+        }
+
+        self.submitted = 0;
+    }
+
+    /// Have the kernel move held back completions to the completion queue.
+    fn flush_overflow(&mut self) -> io::Result<()> {
+        self.metrics.cq_overflow_flushes += 1;
+        self.get_events()
+    }
+
+    /// Enter the kernel to post pending completions, without submitting:
+    /// submitting could recurse into `tick`, and with a polling thread
+    /// `submit` may skip entering the kernel altogether.
+    /// Interrupted calls are repeated.
+    fn get_events(&mut self) -> io::Result<()> {
+        loop {
+            let ret = unsafe {
+                libc::syscall(
+                    libc::SYS_io_uring_enter,
+                    self.uring.as_raw_fd(),
+                    0,
+                    0,
+                    IORING_ENTER_GETEVENTS,
+                    std::ptr::null::<libc::sigset_t>(),
+                    0,
+                )
+            };
+
+            if ret >= 0 {
+                return Ok(());
+            }
+
+            let err = io::Error::last_os_error();
+            if err.raw_os_error() != Some(libc::EINTR) {
+                return Err(err);
+            }
+        }
+    }
+
+    /// Push the entries of the operation stored at `index` to the submission
+    /// queue, flushing the queue to the kernel first if it is too full.
+    ///
+    /// If the queue stays full, e.g. because the kernel keeps failing the
+    /// submission with `EAGAIN`, the entries wait in the backlog instead of
+    /// failing the operation. The operation is then pending like any other,
+    /// which holds back the task awaiting it until its entries could be
+    /// submitted and it completed: producers outpacing the completions are
+    /// slowed down to their pace. The entries are pushed together, so linked
+    /// entries stay in one submission.
+    fn push(&mut self, index: usize, entries: &[squeue::Entry]) {
+        self.push_linked(std::slice::from_ref(&index), entries)
+    }
+
+    /// Push the entries of the operations stored at `indices`, which must
+    /// reach the kernel together, like the operations of a chain. See
+    /// [`Inner::push`].
+    fn push_linked(&mut self, indices: &[usize], entries: &[squeue::Entry]) {
+        // Operations waiting in the backlog go first
+        let fits = self.backlog.is_empty()
+            && (self.has_room(entries.len())
+                || (self.submit().is_ok() && self.has_room(entries.len())));
+
+        if !fits {
+            self.backlog.push_back((indices.to_vec(), entries.to_vec()));
+            return;
+        }
+
+        let mut sq = self.uring.submission();
+        for entry in entries {
+            // Safety: the entries reference resources held by the operation
+            // until it completes.
+            if unsafe { sq.push(entry).is_err() } {
+                unreachable!("submission queue full");
+            }
+        }
+    }
+
+    /// Returns whether `n` more entries fit in the submission queue.
+    fn has_room(&mut self, n: usize) -> bool {
+        let sq = self.uring.submission();
+        sq.capacity() - sq.len() >= n
+    }
+
+    /// Move the entries waiting in the backlog to the submission queue, in
+    /// order, as room allows, and submit them. Entries that still do not fit
+    /// wait for the next tick.
+    fn flush_backlog(&mut self) {
+        while !self.backlog.is_empty() {
+            while let Some((_, entries)) = self.backlog.front() {
+                if !self.has_room(entries.len()) {
+                    break;
+                }
+
+                let (_, entries) = self.backlog.pop_front().unwrap();
+                let mut sq = self.uring.submission();
+                for entry in &entries {
+                    // Safety: as in `push`.
+                    if unsafe { sq.push(entry).is_err() } {
+                        unreachable!("submission queue full");
+                    }
+                }
+            }
+
+            if self.submit().is_err() {
+                return;
+            }
+        }
+    }
+
+    /// Request the cancellation of all in-flight operations.
+    fn cancel_all(&mut self) {
+        let indices: Vec<usize> = self.ops.lifecycle.iter().map(|(index, _)| index).collect();
 
-        for cqe in cq {
-            if cqe.user_data() == u64::MAX {
-                // Result of the cancellation action. There isn't anything we
-                // need to do here. We must wait for the CQE for the operation
-                // that was canceled.
-                continue;
+        for index in indices {
+            self.cancel(index);
+        }
+    }
+
+    /// Submit an `IORING_OP_ASYNC_CANCEL` for the operation stored at
+    /// `index`. The operation itself still posts a completion.
+    fn cancel(&mut self, index: usize) {
+        // The kernel never saw operations waiting in the backlog, they are
+        // completed right away. The operations of a chain reach the kernel
+        // together, so canceling one of them cancels the whole chain.
+        let backlogged = self
+            .backlog
+            .iter()
+            .position(|(indices, _)| indices.contains(&index));
+        if let Some(pos) = backlogged {
+            let (indices, _) = self.backlog.remove(pos).unwrap();
+            for index in indices {
+                self.ops
+                    .complete(index, Err(io::Error::from_raw_os_error(libc::ECANCELED)), 0);
             }
+            return;
+        }
 
-            let index = cqe.user_data() as _;
+        let sqe = if self.ops.polls.contains(&index) {
+            opcode::PollRemove::new(index as _).build()
+        } else {
+            opcode::AsyncCancel::new(index as _).build()
+        }
+        .user_data(u64::MAX);
 
-            self.ops.complete(index, resultify(&cqe), cqe.flags());
+        if self.uring.submission().is_full() {
+            // Make room for the cancellation. If this fails, the push below
+            // fails too and the operation runs to completion.
+            let _ = self.submit();
+        }
+
+        // Safety: the cancellation does not reference any memory.
+        if unsafe { self.uring.submission().push(&sqe).is_ok() } {
+            let _ = self.submit();
+        }
+    }
+
+    /// Submit the queued operations right away, unless a [`batch`] defers
+    /// submissions. Errors are left to a future `io_uring_enter` to retry.
+    fn submit_eagerly(&mut self) {
+        if self.batch_depth == 0 && !self.uring.submission().is_empty() {
+            let _ = self.submit();
         }
     }
 
+    /// Submit the queued operations.
+    ///
+    /// Transient failures of `io_uring_enter` are retried instead of being
+    /// returned, and from there surfaced to the operations being submitted:
+    ///
+    /// * `EINTR`: a signal interrupted the call. It is repeated right away.
+    /// * `EBUSY`: the completion queue, and the kernel's overflow list, are
+    ///   full. Completions are reaped to make room, then the call is repeated.
+    /// * `EAGAIN`: the kernel lacked the resources to allocate requests.
+    ///   Completions are reaped, releasing the requests of completed
+    ///   operations, then the call is repeated.
+    ///
+    /// `EAGAIN` is returned once it persists after [`SUBMIT_RETRIES`]
+    /// retries, as is any other error. The entries that
+    /// could not be submitted stay queued, for a future `io_uring_enter`.
     fn submit(&mut self) -> io::Result<()> {
+        let mut retries = 0;
+
         loop {
-            match self.uring.submit() {
-                Ok(_) => {
+            // Completions are held back by the kernel, don't add to the load
+            // until they are drained.
+            if self.uring.submission().cq_overflow() {
+                self.tick();
+            }
+
+            // With a polling thread, the kernel is only entered to wake it
+            let enters =
+                !self.uring.params().is_setup_sqpoll() || self.uring.submission().need_wakeup();
+            if enters {
+                self.metrics.submit_syscalls += 1;
+            }
+
+            let res = self.uring.submit().and_then(|n| {
+                self.metrics.sqes_submitted += n as u64;
+                self.submitted += n;
+
+                self.uring.submission().sync();
+
+                // With a polling thread, submitting does not wait for the
+                // kernel to consume the queue. Wait for room, so callers
+                // flushing a full queue can push again.
+                if self.uring.params().is_setup_sqpoll() && self.uring.submission().is_full() {
+                    self.uring.submitter().squeue_wait()?;
                     self.uring.submission().sync();
-                    return Ok(());
                 }
+
+                Ok(())
+            });
+
+            match res {
+                Ok(()) => return Ok(()),
+                Err(ref e) if e.raw_os_error() == Some(libc::EINTR) => {}
                 Err(ref e) if e.raw_os_error() == Some(libc::EBUSY) => {
                     self.tick();
                 }
+                Err(ref e)
+                    if e.raw_os_error() == Some(libc::EAGAIN) && retries < SUBMIT_RETRIES =>
+                {
+                    retries += 1;
+                    self.tick();
+                }
                 Err(e) => {
                     return Err(e);
                 }
@@ -138,44 +845,150 @@ impl AsRawFd for Driver {
 
 impl Drop for Driver {
     fn drop(&mut self) {
+        // The tasks were dropped, which requested the cancellation of their
+        // operations. Request it again in case it could not be submitted, or
+        // the operations are held outside of the runtime.
+        self.inner.borrow_mut().cancel_all();
+
+        let deadline = self
+            .shutdown_timeout
+            .map(|timeout| Instant::now() + timeout);
+
         while self.num_operations() > 0 {
+            if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                // The kernel may still access the resources of the remaining
+                // operations, which are leaked. Closing the ring then cancels
+                // the operations.
+                self.inner.borrow_mut().ops.leak();
+                break;
+            }
+
             // If waiting fails, ignore the error. The wait will be attempted
             // again on the next loop.
-            let _ = self.wait().unwrap();
+            let _ = self.wait(deadline);
             self.tick();
         }
+
+        // Stop signaling the eventfd, which may outlive the ring
+        if self.inner.borrow().cq_eventfd {
+            let _ = self.unregister_eventfd();
+        }
+
+        // Release the files left registered, which keep the driver alive
+        let user_files = std::mem::take(&mut self.inner.borrow_mut().user_files);
+        drop(user_files);
     }
 }
 
 impl Ops {
     fn new() -> Ops {
-        Ops(Slab::with_capacity(64))
+        Ops {
+            lifecycle: Slab::with_capacity(64),
+            buf_rings: HashMap::new(),
+            fd_results: HashSet::new(),
+            polls: HashSet::new(),
+            timeouts: HashMap::new(),
+        }
     }
 
     fn get_mut(&mut self, index: usize) -> Option<&mut op::Lifecycle> {
-        self.0.get_mut(index)
+        self.lifecycle.get_mut(index)
     }
 
     // Insert a new operation
     fn insert(&mut self) -> usize {
-        self.0.insert(op::Lifecycle::Submitted)
+        self.lifecycle.insert(op::Lifecycle::Submitted)
     }
 
     // Remove an operation
     fn remove(&mut self, index: usize) {
-        self.0.remove(index);
+        self.lifecycle.remove(index);
+        self.buf_rings.remove(&index);
+        self.fd_results.remove(&index);
+        self.polls.remove(&index);
+        self.timeouts.remove(&index);
+    }
+
+    // Associate the buffer ring the operation selects buffers from
+    fn set_buf_ring(&mut self, index: usize, buf_ring: BufRing) {
+        self.buf_rings.insert(index, buf_ring);
+    }
+
+    // Store the timespec of the timeout linked to the operation, returning
+    // a pointer valid until the operation is removed
+    fn set_timeout(&mut self, index: usize, timespec: types::Timespec) -> *const types::Timespec {
+        let timespec = Box::new(timespec);
+        let ptr = &*timespec as *const _;
+        self.timeouts.insert(index, timespec);
+        ptr
+    }
+
+    // Mark the results of the operation as file descriptors
+    fn set_fd_result(&mut self, index: usize) {
+        self.fd_results.insert(index);
+    }
+
+    // Mark the operation as a poll
+    fn set_poll(&mut self, index: usize) {
+        self.polls.insert(index);
+    }
+
+    // Release what a completion nobody looks at handed over: the buffer it
+    // selected goes back to the operation's ring, and the file descriptor it
+    // returned is closed.
+    fn discard(&mut self, index: usize, result: &io::Result<u32>, flags: u32) {
+        if let (Some(bid), Some(buf_ring)) =
+            (cqueue::buffer_select(flags), self.buf_rings.get(&index))
+        {
+            buf_ring.recycle(bid);
+        }
+
+        if let (Ok(fd), true) = (result, self.fd_results.contains(&index)) {
+            // Safety: the descriptor was never handed to anyone.
+            unsafe { libc::close(*fd as RawFd) };
+        }
+    }
+
+    // Forget all operations without releasing their resources, which the
+    // kernel may still access
+    fn leak(&mut self) {
+        for lifecycle in self.lifecycle.drain() {
+            std::mem::forget(lifecycle);
+        }
+
+        std::mem::forget(std::mem::take(&mut self.buf_rings));
+        std::mem::forget(std::mem::take(&mut self.timeouts));
+        self.fd_results.clear();
+        self.polls.clear();
     }
 
     fn complete(&mut self, index: usize, result: io::Result<u32>, flags: u32) {
-        if self.0[index].complete(result, flags) {
-            self.0.remove(index);
+        // The linked timeout expired, unless the operation was canceled for
+        // another reason, which cannot be told apart.
+        let result = match result {
+            Err(e)
+                if e.raw_os_error() == Some(libc::ECANCELED)
+                    && self.timeouts.contains_key(&index) =>
+            {
+                Err(io::ErrorKind::TimedOut.into())
+            }
+            result => result,
+        };
+
+        if let op::Lifecycle::Ignored(..) = self.lifecycle[index] {
+            // Nobody is going to look at the completion.
+            self.discard(index, &result, flags);
+        }
+
+        if self.lifecycle[index].complete(result, flags) {
+            self.remove(index);
         }
     }
 }
 
 impl Drop for Ops {
     fn drop(&mut self) {
-        assert!(self.0.is_empty());
+        assert!(self.lifecycle.is_empty());
     }
 }
 
@@ -0,0 +1,84 @@
+use std::io;
+use std::os::unix::io::RawFd;
+
+/// Parameters of `IORING_REGISTER_NAPI`.
+#[repr(C)]
+#[derive(Default)]
+struct Napi {
+    busy_poll_to: u32,
+    prefer_busy_poll: u8,
+    pad: [u8; 3],
+    resv: u64,
+}
+
+const IORING_REGISTER_NAPI: libc::c_uint = 27;
+const IORING_UNREGISTER_NAPI: libc::c_uint = 28;
+
+/// Enables NAPI busy polling on the io-uring instance `uring_fd`.
+pub(super) fn register(
+    uring_fd: RawFd,
+    busy_poll_usecs: u32,
+    prefer_busy_poll: bool,
+) -> io::Result<()> {
+    check_supported()?;
+
+    let mut napi = Napi {
+        busy_poll_to: busy_poll_usecs,
+        prefer_busy_poll: prefer_busy_poll as u8,
+        ..Napi::default()
+    };
+
+    register_raw(uring_fd, IORING_REGISTER_NAPI, &mut napi)
+}
+
+/// Disables NAPI busy polling on the io-uring instance `uring_fd`.
+pub(super) fn unregister(uring_fd: RawFd) -> io::Result<()> {
+    check_supported()?;
+
+    register_raw(uring_fd, IORING_UNREGISTER_NAPI, &mut Napi::default())
+}
+
+/// Returns whether the kernel supports NAPI busy polling, by registering it
+/// on the io-uring instance `uring_fd`, then unregistering it.
+///
+/// Any failure to register counts as a lack of support: older kernels reject
+/// the opcode with `EINVAL`, and kernels built without busy polling with
+/// `EOPNOTSUPP`.
+pub(super) fn probe(uring_fd: RawFd) -> bool {
+    if register_raw(uring_fd, IORING_REGISTER_NAPI, &mut Napi::default()).is_err() {
+        return false;
+    }
+
+    // The probe instance is dropped right after, which unregisters it anyway
+    let _ = register_raw(uring_fd, IORING_UNREGISTER_NAPI, &mut Napi::default());
+    true
+}
+
+fn check_supported() -> io::Result<()> {
+    if crate::probe()?.supports_napi() {
+        Ok(())
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "NAPI busy polling is not supported by the kernel",
+        ))
+    }
+}
+
+fn register_raw(uring_fd: RawFd, opcode: libc::c_uint, napi: &mut Napi) -> io::Result<()> {
+    let ret = unsafe {
+        libc::syscall(
+            libc::SYS_io_uring_register,
+            uring_fd,
+            opcode,
+            napi as *mut Napi,
+            1,
+        )
+    };
+
+    if ret < 0 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(())
+    }
+}
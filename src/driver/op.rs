@@ -1,13 +1,19 @@
 use std::cell::RefCell;
+use std::collections::VecDeque;
 use std::future::Future;
 use std::io;
 use std::pin::Pin;
 use std::rc::Rc;
 use std::task::{Context, Poll, Waker};
 
-use io_uring::squeue;
+use io_uring::{cqueue, opcode, squeue};
 
-use crate::driver;
+use crate::buf::BufRing;
+use crate::driver::{self, cancel::Scope};
+
+/// Outcome of submitting a chain of linked operations with data of type `D`.
+/// On failure, the data is handed back along with the error.
+pub(crate) type LinkResult<T, D> = Result<Vec<Op<T>>, (io::Error, Vec<D>)>;
 
 /// In-flight operation
 pub(crate) struct Op<T: 'static> {
@@ -19,6 +25,9 @@ pub(crate) struct Op<T: 'static> {
 
     // Per-operation data
     data: Option<T>,
+
+    // Cancellation scope tracking the operation, if any
+    scope: Option<Rc<Scope>>,
 }
 
 /// Operation completion. Returns stored state with the result of the operation.
@@ -26,8 +35,6 @@ pub(crate) struct Op<T: 'static> {
 pub(crate) struct Completion<T> {
     pub(crate) data: T,
     pub(crate) result: io::Result<u32>,
-    // the field is currently only read in tests
-    #[cfg_attr(not(test), allow(dead_code))]
     pub(crate) flags: u32,
 }
 
@@ -40,19 +47,40 @@ pub(crate) enum Lifecycle {
 
     /// The submitter no longer has interest in the operation result. The state
     /// must be passed to the driver and held until the operation completes.
-    Ignored(Box<dyn std::any::Any>),
+    Ignored(#[allow(dead_code)] Box<dyn std::any::Any>),
 
     /// The operation has completed.
     Completed(io::Result<u32>, u32),
+
+    /// A multishot operation posted completions that have not been consumed
+    /// yet. The operation is finished once an entry without
+    /// `IORING_CQE_F_MORE` is queued.
+    CompletionList(VecDeque<(io::Result<u32>, u32)>),
 }
 
 impl<T> Op<T> {
     /// Create a new operation
     fn new(data: T, inner: &mut driver::Inner, inner_rc: &Rc<RefCell<driver::Inner>>) -> Op<T> {
+        let index = inner.ops.insert();
+
+        let scope = Scope::current();
+        if let Some(scope) = &scope {
+            scope.insert(index);
+        }
+
         Op {
             driver: inner_rc.clone(),
-            index: inner.ops.insert(),
+            index,
             data: Some(data),
+            scope,
+        }
+    }
+
+    /// Stops tracking the operation in its cancellation scope, once it is
+    /// finished or dropped.
+    fn untrack(&mut self) {
+        if let Some(scope) = self.scope.take() {
+            scope.remove(self.index);
         }
     }
 
@@ -61,6 +89,25 @@ impl<T> Op<T> {
     /// `state` is stored during the operation tracking any state submitted to
     /// the kernel.
     pub(super) fn submit_with<F>(data: T, f: F) -> io::Result<Op<T>>
+    where
+        F: FnOnce(&mut T) -> squeue::Entry,
+    {
+        Op::submit(data, None, f)
+    }
+
+    /// Submit an operation that selects its buffers from `buf_ring`.
+    ///
+    /// The driver keeps a reference to the ring for as long as the operation
+    /// is in-flight, so buffers picked by the kernel after the submitter lost
+    /// interest in the operation are handed back to the ring.
+    pub(super) fn submit_with_buf_ring<F>(data: T, buf_ring: &BufRing, f: F) -> io::Result<Op<T>>
+    where
+        F: FnOnce(&mut T) -> squeue::Entry,
+    {
+        Op::submit(data, Some(buf_ring.clone()), f)
+    }
+
+    fn submit<F>(data: T, buf_ring: Option<BufRing>, f: F) -> io::Result<Op<T>>
     where
         F: FnOnce(&mut T) -> squeue::Entry,
     {
@@ -68,42 +115,180 @@ impl<T> Op<T> {
             let mut inner_ref = inner_rc.borrow_mut();
             let inner = &mut *inner_ref;
 
-            // If the submission queue is full, flush it to the kernel
-            if inner.uring.submission().is_full() {
-                inner.submit()?;
-            }
+            // Operations submitted within `with_timeout` are linked to a
+            // timeout, pushed along with them.
+            let timeout = driver::timeout::current();
 
             // Create the operation
             let mut op = Op::new(data, inner, inner_rc);
 
+            if let Some(buf_ring) = buf_ring {
+                inner.ops.set_buf_ring(op.index, buf_ring);
+            }
+
             // Configure the SQE
-            let sqe = f(op.data.as_mut().unwrap()).user_data(op.index as _);
+            let mut sqe = f(op.data.as_mut().unwrap()).user_data(op.index as _);
+
+            match timeout {
+                Some(timeout) => {
+                    sqe = sqe.flags(squeue::Flags::IO_LINK);
 
-            {
-                let mut sq = inner.uring.submission();
+                    // The timespec must outlive the operation
+                    let timespec = inner.ops.set_timeout(op.index, timeout);
+                    let timeout = opcode::LinkTimeout::new(timespec)
+                        .build()
+                        .user_data(u64::MAX);
 
-                // Push the new operation
-                if unsafe { sq.push(&sqe).is_err() } {
-                    unimplemented!("when is this hit?");
+                    inner.push(op.index, &[sqe, timeout]);
                 }
+                None => inner.push(op.index, &[sqe]),
             }
 
             // Submit the new operation. At this point, the operation has been
             // pushed onto the queue and the tail pointer has been updated, so
-            // the submission entry is visible to the kernel. If there is an
-            // error here (probably EAGAIN), we still return the operation. A
-            // future `io_uring_enter` will fully submit the event.
-            let _ = inner.submit();
+            // the submission entry is visible to the kernel, or it waits in
+            // the backlog. If there is an error here (probably EAGAIN), we
+            // still return the operation. A future `io_uring_enter` will fully
+            // submit the event.
+            inner.submit_eagerly();
             Ok(op)
         })
     }
 
-    /// Try submitting an operation to uring
+    /// Submit an operation linked to an `IORING_OP_LINK_TIMEOUT`.
+    ///
+    /// `f` returns the entry of the operation followed by the entry of the
+    /// timeout. If the timeout expires first, the kernel cancels the
+    /// operation, which completes with `ECANCELED`. The completion of the
+    /// timeout itself is ignored. The timespec referenced by the timeout
+    /// must be stored in `data`.
+    pub(super) fn submit_with_link_timeout<F>(data: T, f: F) -> io::Result<Op<T>>
+    where
+        F: FnOnce(&mut T) -> (squeue::Entry, squeue::Entry),
+    {
+        driver::CURRENT.with(|inner_rc| {
+            let mut inner_ref = inner_rc.borrow_mut();
+            let inner = &mut *inner_ref;
+
+            let mut op = Op::new(data, inner, inner_rc);
+            let (sqe, timeout) = f(op.data.as_mut().unwrap());
+            let sqe = sqe.flags(squeue::Flags::IO_LINK).user_data(op.index as _);
+            let timeout = timeout.user_data(u64::MAX);
+
+            // Both entries are pushed together
+            inner.push(op.index, &[sqe, timeout]);
+
+            inner.submit_eagerly();
+            Ok(op)
+        })
+    }
+
+    /// Submit a chain of linked operations, one for each element of `data`.
+    ///
+    /// The kernel starts each operation once the previous one completed. If
+    /// an operation fails, the remaining ones complete with `ECANCELED`. The
+    /// whole chain must fit in the submission queue, otherwise `data` is
+    /// handed back along with the error. The chain waits in the backlog as a
+    /// whole if the queue is full.
+    pub(super) fn submit_linked_with<F>(
+        data: Vec<T>,
+        f: F,
+    ) -> Result<Vec<Op<T>>, (io::Error, Vec<T>)>
+    where
+        F: FnMut(usize, &mut T) -> squeue::Entry,
+    {
+        Op::submit_chain_with(data, squeue::Flags::IO_LINK, f)
+    }
+
+    /// Submit a chain of operations linked with `link`, either
+    /// `IOSQE_IO_LINK` or `IOSQE_IO_HARDLINK`. Hard links keep the chain
+    /// going when an operation fails. See [`Op::submit_linked_with`].
+    pub(super) fn submit_chain_with<F>(
+        data: Vec<T>,
+        link: squeue::Flags,
+        mut f: F,
+    ) -> Result<Vec<Op<T>>, (io::Error, Vec<T>)>
+    where
+        F: FnMut(usize, &mut T) -> squeue::Entry,
+    {
+        driver::CURRENT.with(|inner_rc| {
+            let mut inner_ref = inner_rc.borrow_mut();
+            let inner = &mut *inner_ref;
+
+            if data.len() > inner.uring.params().sq_entries() as usize {
+                let err = io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "linked operations do not fit in the submission queue",
+                );
+                return Err((err, data));
+            }
+
+            let len = data.len();
+            let mut ops = Vec::with_capacity(len);
+            let mut indices = Vec::with_capacity(len);
+            let mut sqes = Vec::with_capacity(len);
+
+            for (i, data) in data.into_iter().enumerate() {
+                let mut op = Op::new(data, inner, inner_rc);
+                let mut sqe = f(i, op.data.as_mut().unwrap()).user_data(op.index as _);
+
+                if i + 1 < len {
+                    sqe = sqe.flags(link);
+                }
+
+                indices.push(op.index);
+                ops.push(op);
+                sqes.push(sqe);
+            }
+
+            // The chain is pushed at once, or waits in the backlog as a whole
+            inner.push_linked(&indices, &sqes);
+
+            // As with single operations, a failed submit is retried by a future
+            // `io_uring_enter`.
+            inner.submit_eagerly();
+            Ok(ops)
+        })
+    }
+
+    /// Submit independent operations, one for each element of `data`.
+    ///
+    /// All entries are pushed before the submission queue is flushed, so
+    /// the batch reaches the kernel in a single `io_uring_enter` when it fits
+    /// in the queue. Entries which do not fit wait in the backlog, each on
+    /// its own, as for single operations.
+    pub(super) fn submit_batch_with<F>(data: Vec<T>, mut f: F) -> Vec<Op<T>>
+    where
+        F: FnMut(&mut T) -> squeue::Entry,
+    {
+        driver::CURRENT.with(|inner_rc| {
+            let mut inner_ref = inner_rc.borrow_mut();
+            let inner = &mut *inner_ref;
+
+            let mut ops = Vec::with_capacity(data.len());
+
+            for data in data {
+                let mut op = Op::new(data, inner, inner_rc);
+                let sqe = f(op.data.as_mut().unwrap()).user_data(op.index as _);
+
+                inner.push(op.index, &[sqe]);
+                ops.push(op);
+            }
+
+            inner.submit_eagerly();
+            ops
+        })
+    }
+
+    /// Try submitting an operation to uring. Fails when off runtime, or from
+    /// within the driver, e.g. when the resources of a completed operation
+    /// are released while reaping completions.
     pub(super) fn try_submit_with<F>(data: T, f: F) -> io::Result<Op<T>>
     where
         F: FnOnce(&mut T) -> squeue::Entry,
     {
-        if driver::CURRENT.is_set() {
+        if driver::CURRENT.is_set() && driver::CURRENT.with(|inner| inner.try_borrow_mut().is_ok())
+        {
             Op::submit_with(data, f)
         } else {
             Err(io::ErrorKind::Other.into())
@@ -138,8 +323,11 @@ where
                 Poll::Pending
             }
             Lifecycle::Ignored(..) => unreachable!(),
+            Lifecycle::CompletionList(..) => unreachable!("multishot operation polled as a future"),
             Lifecycle::Completed(result, flags) => {
                 inner.ops.remove(me.index);
+                drop(inner);
+                me.untrack();
                 me.index = usize::MAX;
 
                 Poll::Ready(Completion {
@@ -152,8 +340,108 @@ where
     }
 }
 
+impl<T> Op<T> {
+    /// Polls the next completion of a multishot operation.
+    ///
+    /// Completions are returned in the order the kernel posted them, along
+    /// with their flags. Once a completion without `IORING_CQE_F_MORE` has been
+    /// returned, the operation is finished and `None` is returned from then on.
+    pub(crate) fn poll_next(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<(io::Result<u32>, u32)>> {
+        use std::mem;
+
+        if self.index == usize::MAX {
+            return Poll::Ready(None);
+        }
+
+        let mut inner = self.driver.borrow_mut();
+        let lifecycle = inner
+            .ops
+            .get_mut(self.index)
+            .expect("invalid internal state");
+
+        let (result, flags) = match mem::replace(lifecycle, Lifecycle::Submitted) {
+            Lifecycle::Submitted => {
+                *lifecycle = Lifecycle::Waiting(cx.waker().clone());
+                return Poll::Pending;
+            }
+            Lifecycle::Waiting(waker) if !waker.will_wake(cx.waker()) => {
+                *lifecycle = Lifecycle::Waiting(cx.waker().clone());
+                return Poll::Pending;
+            }
+            Lifecycle::Waiting(waker) => {
+                *lifecycle = Lifecycle::Waiting(waker);
+                return Poll::Pending;
+            }
+            Lifecycle::Ignored(..) => unreachable!(),
+            Lifecycle::CompletionList(mut list) => {
+                let completion = list.pop_front().expect("empty completion list");
+
+                *lifecycle = if list.is_empty() {
+                    Lifecycle::Waiting(cx.waker().clone())
+                } else {
+                    Lifecycle::CompletionList(list)
+                };
+
+                completion
+            }
+            Lifecycle::Completed(result, flags) => (result, flags),
+        };
+
+        if !cqueue::more(flags) {
+            inner.ops.remove(self.index);
+            drop(inner);
+            self.untrack();
+            self.index = usize::MAX;
+        }
+
+        Poll::Ready(Some((result, flags)))
+    }
+
+    /// Takes the operation state back once a multishot operation is
+    /// finished, i.e. `poll_next` returned `None`.
+    pub(crate) fn take_data(&mut self) -> Option<T> {
+        if self.index != usize::MAX {
+            return None;
+        }
+
+        self.data.take()
+    }
+
+    /// Returns a reference to the operation state.
+    pub(crate) fn get_ref(&self) -> &T {
+        self.data.as_ref().expect("unexpected operation state")
+    }
+
+    /// Marks the results of the operation as file descriptors, which the
+    /// driver closes if the operation is dropped before they are consumed.
+    pub(super) fn set_fd_result(&self) {
+        self.driver.borrow_mut().ops.set_fd_result(self.index);
+    }
+
+    /// Marks the operation as a poll, which the driver cancels with
+    /// `IORING_OP_POLL_REMOVE`.
+    pub(super) fn set_poll(&self) {
+        self.driver.borrow_mut().ops.set_poll(self.index);
+    }
+
+    /// Requests the kernel to cancel the operation.
+    ///
+    /// The operation stays in-flight until the kernel posts its final
+    /// completion, which may still report success.
+    pub(crate) fn cancel(&self) {
+        if self.index != usize::MAX {
+            self.driver.borrow_mut().cancel(self.index);
+        }
+    }
+}
+
 impl<T> Drop for Op<T> {
     fn drop(&mut self) {
+        self.untrack();
+
         let mut inner = self.driver.borrow_mut();
         let lifecycle = match inner.ops.get_mut(self.index) {
             Some(lifecycle) => lifecycle,
@@ -162,11 +450,36 @@ impl<T> Drop for Op<T> {
 
         match lifecycle {
             Lifecycle::Submitted | Lifecycle::Waiting(_) => {
+                // The kernel may still write into the resources held by the
+                // operation, so they stay alive until its completion lands.
                 *lifecycle = Lifecycle::Ignored(Box::new(self.data.take()));
+                inner.cancel(self.index);
             }
             Lifecycle::Completed(..) => {
+                // The completion was never consumed
+                let completed = std::mem::replace(lifecycle, Lifecycle::Submitted);
+                if let Lifecycle::Completed(result, flags) = completed {
+                    inner.ops.discard(self.index, &result, flags);
+                }
                 inner.ops.remove(self.index);
             }
+            Lifecycle::CompletionList(list) => {
+                let finished = list.iter().any(|(_, flags)| !cqueue::more(*flags));
+                let list = std::mem::take(list);
+
+                // Completions that were never consumed hand their buffers
+                // back, and close their file descriptors.
+                for (result, flags) in list {
+                    inner.ops.discard(self.index, &result, flags);
+                }
+
+                if finished {
+                    inner.ops.remove(self.index);
+                } else {
+                    *inner.ops.get_mut(self.index).unwrap() =
+                        Lifecycle::Ignored(Box::new(self.data.take()));
+                }
+            }
             Lifecycle::Ignored(..) => unreachable!(),
         }
     }
@@ -176,17 +489,39 @@ impl Lifecycle {
     pub(super) fn complete(&mut self, result: io::Result<u32>, flags: u32) -> bool {
         use std::mem;
 
+        // Multishot operations post more completions until one without
+        // `IORING_CQE_F_MORE`.
+        let more = cqueue::more(flags);
+
         match mem::replace(self, Lifecycle::Submitted) {
+            Lifecycle::Submitted if more => {
+                *self = Lifecycle::CompletionList(VecDeque::from(vec![(result, flags)]));
+                false
+            }
             Lifecycle::Submitted => {
                 *self = Lifecycle::Completed(result, flags);
                 false
             }
             Lifecycle::Waiting(waker) => {
-                *self = Lifecycle::Completed(result, flags);
+                *self = if more {
+                    Lifecycle::CompletionList(VecDeque::from(vec![(result, flags)]))
+                } else {
+                    Lifecycle::Completed(result, flags)
+                };
                 waker.wake();
                 false
             }
-            Lifecycle::Ignored(..) => true,
+            Lifecycle::Ignored(data) => {
+                if more {
+                    *self = Lifecycle::Ignored(data);
+                }
+                !more
+            }
+            Lifecycle::CompletionList(mut list) => {
+                list.push_back((result, flags));
+                *self = Lifecycle::CompletionList(list);
+                false
+            }
             Lifecycle::Completed(..) => unreachable!("invalid operation state"),
         }
     }
@@ -311,10 +646,181 @@ mod test {
         release(driver);
     }
 
+    #[test]
+    fn poll_multishot_completions() {
+        let (mut op, driver, ..) = init();
+        let mut task = task::spawn(());
+        assert_pending!(task.enter(|cx, _| op.poll_next(cx)));
+
+        complete_with_flags(&op, Ok(1), IORING_CQE_F_MORE);
+        complete_with_flags(&op, Ok(2), IORING_CQE_F_MORE);
+        complete_with_flags(&op, Ok(3), 0);
+        assert_eq!(1, driver.num_operations());
+        assert!(task.is_woken());
+
+        for (expected, flags) in [(1, IORING_CQE_F_MORE), (2, IORING_CQE_F_MORE), (3, 0)] {
+            let (result, f) = assert_ready!(task.enter(|cx, _| op.poll_next(cx))).unwrap();
+            assert_eq!(expected, result.unwrap());
+            assert_eq!(flags, f);
+        }
+
+        // The final completion removed the operation
+        assert_eq!(0, driver.num_operations());
+        assert!(assert_ready!(task.enter(|cx, _| op.poll_next(cx))).is_none());
+
+        drop(op);
+        release(driver);
+    }
+
+    #[test]
+    fn multishot_complete_after_drop() {
+        let (op, driver, data) = init();
+        let index = op.index;
+        complete_with_flags(&op, Ok(1), IORING_CQE_F_MORE);
+        drop(op);
+
+        assert_eq!(2, Rc::strong_count(&data));
+        assert_eq!(1, driver.num_operations());
+
+        // Stays in the slab until the final completion
+        driver
+            .inner
+            .borrow_mut()
+            .ops
+            .complete(index, Ok(2), IORING_CQE_F_MORE);
+        assert_eq!(1, driver.num_operations());
+
+        driver.inner.borrow_mut().ops.complete(index, Ok(3), 0);
+        assert_eq!(1, Rc::strong_count(&data));
+        assert_eq!(0, driver.num_operations());
+        release(driver);
+    }
+
+    #[test]
+    fn unclaimed_fd_closed_after_drop() {
+        let (op, driver, _) = init();
+        let index = op.index;
+        op.set_fd_result();
+        drop(op);
+
+        let fd = open_fd();
+        driver
+            .inner
+            .borrow_mut()
+            .ops
+            .complete(index, Ok(fd as _), 0);
+        assert_eq!(0, driver.num_operations());
+        assert!(!is_open(fd));
+        release(driver);
+    }
+
+    #[test]
+    fn unclaimed_fd_closed_on_drop() {
+        let (op, driver, _) = init();
+        op.set_fd_result();
+
+        let fd = open_fd();
+        complete(&op, Ok(fd as _));
+        assert!(is_open(fd));
+
+        drop(op);
+        assert_eq!(0, driver.num_operations());
+        assert!(!is_open(fd));
+        release(driver);
+    }
+
+    #[test]
+    fn backlogged_op_removed_on_drop() {
+        let (op, driver, data) = init();
+        backlog_nop(&op);
+        drop(op);
+
+        // The kernel never saw the operation
+        assert_eq!(1, Rc::strong_count(&data));
+        assert_eq!(0, driver.num_operations());
+        assert!(driver.inner.borrow().backlog.is_empty());
+        release(driver);
+    }
+
+    #[test]
+    fn backlog_submitted_on_tick() {
+        let (op, driver, _) = init();
+        backlog_nop(&op);
+        let mut op = task::spawn(op);
+        assert_pending!(op.poll());
+
+        while !op.is_woken() {
+            driver.tick();
+        }
+        assert!(driver.inner.borrow().backlog.is_empty());
+
+        let Completion { result, .. } = assert_ready!(op.poll());
+        assert_eq!(0, result.unwrap());
+
+        drop(op);
+        release(driver);
+    }
+
+    #[test]
+    fn failing_submit_fills_backlog() {
+        use crate::driver::Driver;
+
+        // The kernel fails submissions to a disabled ring with `EBADFD`
+        let mut builder = crate::Builder::new();
+        builder.entries(1);
+        builder.urb.setup_r_disabled();
+        let driver = Driver::new(&builder).unwrap();
+
+        let nop = || Op::submit_with((), |_| opcode::Nop::new().build());
+        let (first, second) = driver.with(|| (nop().unwrap(), nop().unwrap()));
+        assert_eq!(1, driver.metrics().ops_backlogged());
+        assert!(driver.has_backlog());
+
+        let mut first = task::spawn(first);
+        let mut second = task::spawn(second);
+        assert_pending!(first.poll());
+        assert_pending!(second.poll());
+
+        driver
+            .inner
+            .borrow()
+            .uring
+            .submitter()
+            .register_enable_rings()
+            .unwrap();
+        while !(first.is_woken() && second.is_woken()) {
+            driver.tick();
+        }
+        assert_eq!(0, driver.metrics().ops_backlogged());
+
+        assert_eq!(0, assert_ready!(first.poll()).result.unwrap());
+        assert_eq!(0, assert_ready!(second.poll()).result.unwrap());
+    }
+
+    fn backlog_nop(op: &Op<Rc<()>>) {
+        let sqe = opcode::Nop::new().build().user_data(op.index as _);
+        op.driver
+            .borrow_mut()
+            .backlog
+            .push_back((vec![op.index], vec![sqe]));
+    }
+
+    fn open_fd() -> std::os::unix::io::RawFd {
+        use std::os::unix::io::IntoRawFd;
+
+        std::fs::File::open("/dev/null").unwrap().into_raw_fd()
+    }
+
+    fn is_open(fd: std::os::unix::io::RawFd) -> bool {
+        unsafe { libc::fcntl(fd, libc::F_GETFD) != -1 }
+    }
+
+    const IORING_CQE_F_MORE: u32 = 1 << 1;
+
     fn init() -> (Op<Rc<()>>, crate::driver::Driver, Rc<()>) {
         use crate::driver::Driver;
 
-        let driver = Driver::new().unwrap();
+        let driver = Driver::new(&crate::Builder::new()).unwrap();
         let handle = driver.inner.clone();
         let data = Rc::new(());
 
@@ -327,11 +833,15 @@ mod test {
     }
 
     fn complete(op: &Op<Rc<()>>, result: io::Result<u32>) {
-        op.driver.borrow_mut().ops.complete(op.index, result, 0);
+        complete_with_flags(op, result, 0);
+    }
+
+    fn complete_with_flags(op: &Op<Rc<()>>, result: io::Result<u32>, flags: u32) {
+        op.driver.borrow_mut().ops.complete(op.index, result, flags);
     }
 
     fn release(driver: crate::driver::Driver) {
         // Clear ops, we aren't really doing any I/O
-        driver.inner.borrow_mut().ops.0.clear();
+        driver.inner.borrow_mut().ops.lifecycle.clear();
     }
 }
@@ -1,34 +1,113 @@
-use crate::driver::{self, Op};
+use crate::driver::{self, Op, SharedFd};
 use crate::fs::OpenOptions;
 
+use io_uring::squeue;
+use io_uring::types::DestinationSlot;
 use std::ffi::CString;
 use std::io;
+use std::os::unix::io::RawFd;
 use std::path::Path;
 
 /// Open a file
 #[allow(dead_code)]
 pub(crate) struct Open {
+    /// Holds a strong ref to the FD of the directory the path is relative to,
+    /// if any.
+    dir: Option<SharedFd>,
+    dirfd: RawFd,
     pub(crate) path: CString,
     pub(crate) flags: libc::c_int,
+    mode: libc::mode_t,
+
+    /// Parameter for `openat2(2)`, referred to by the operation.
+    how: Box<io_uring::types::OpenHow>,
+    openat2: bool,
 }
 
-impl Op<Open> {
-    /// Submit a request to open a file.
-    pub(crate) fn open(path: &Path, options: &OpenOptions) -> io::Result<Op<Open>> {
+impl Open {
+    /// Prepare opening a file, relative to the directory `dir` or to the
+    /// current working directory. A file opened as a direct descriptor, see
+    /// [`Open::sqe`], has no close-on-exec flag, so `direct` leaves it out.
+    ///
+    /// With resolve flags, the file is opened with `openat2(2)`. They are
+    /// never ignored: if the kernel does not support `openat2(2)`, this fails
+    /// with [`Unsupported`](io::ErrorKind::Unsupported).
+    pub(crate) fn new(
+        dir: Option<&SharedFd>,
+        path: &Path,
+        options: &OpenOptions,
+        direct: bool,
+    ) -> io::Result<Open> {
         use io_uring::{opcode, types};
         let path = driver::util::cstr(path)?;
-        let flags = libc::O_CLOEXEC | options.access_mode()? | options.creation_mode()?;
+        let dirfd = driver::util::dirfd(dir);
+        let cloexec = if direct { 0 } else { libc::O_CLOEXEC };
+        let flags = cloexec | options.access_mode()? | options.creation_mode()?;
 
-        Op::submit_with(Open { path, flags }, |open| {
-            // Get a reference to the memory. The string will be held by the
-            // operation state and will not be accessed again until the operation
-            // completes.
-            let p_ref = open.path.as_c_str().as_ptr();
+        let resolve = options.resolve.bits();
+        let openat2 = resolve != 0;
+        if openat2 && !driver::probe()?.is_supported(opcode::OpenAt2::CODE) {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "resolve flags require openat2, which the kernel does not support",
+            ));
+        }
+        // `openat2(2)` rejects a mode when not creating a file
+        let mode = if flags & (libc::O_CREAT | libc::O_TMPFILE) != 0 {
+            options.mode as u64
+        } else {
+            0
+        };
+        let how = types::OpenHow::new()
+            .flags(flags as u64)
+            .mode(mode)
+            .resolve(resolve);
 
-            opcode::OpenAt::new(types::Fd(libc::AT_FDCWD), p_ref)
-                .flags(flags)
-                .mode(options.mode)
-                .build()
+        Ok(Open {
+            dir: dir.cloned(),
+            dirfd,
+            path,
+            flags,
+            mode: options.mode,
+            how: Box::new(how),
+            openat2,
         })
     }
+
+    /// Returns the entry opening the file, as a direct descriptor installed
+    /// in the registered file `slot` if any. The entry references the path
+    /// and `openat2(2)` parameter held here.
+    pub(crate) fn sqe(&self, slot: Option<DestinationSlot>) -> squeue::Entry {
+        use io_uring::{opcode, types};
+
+        let p_ref = self.path.as_c_str().as_ptr();
+
+        if self.openat2 {
+            opcode::OpenAt2::new(types::Fd(self.dirfd), p_ref, self.how.as_ref())
+                .file_index(slot)
+                .build()
+        } else {
+            opcode::OpenAt::new(types::Fd(self.dirfd), p_ref)
+                .flags(self.flags)
+                .mode(self.mode)
+                .file_index(slot)
+                .build()
+        }
+    }
+}
+
+impl Op<Open> {
+    /// Submit a request to open a file, relative to the directory `dir` or to
+    /// the current working directory. See [`Open::new`].
+    pub(crate) fn open(
+        dir: Option<&SharedFd>,
+        path: &Path,
+        options: &OpenOptions,
+    ) -> io::Result<Op<Open>> {
+        let open = Open::new(dir, path, options, false)?;
+
+        // The path is held by the operation state and will not be accessed
+        // again until the operation completes.
+        Op::submit_with(open, |open| open.sqe(None))
+    }
 }
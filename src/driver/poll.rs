@@ -0,0 +1,118 @@
+use crate::driver::Op;
+use futures_core::Stream;
+use io_uring::cqueue;
+use std::{
+    io,
+    os::unix::io::RawFd,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+pub(crate) struct PollAdd {
+    fd: RawFd,
+    events: u32,
+}
+
+impl Op<PollAdd> {
+    /// Submit an `IORING_OP_POLL_ADD` waiting for `events` on `fd`. With
+    /// `multi`, the poll posts a completion each time `fd` becomes ready.
+    ///
+    /// The descriptor is not owned by the operation: the kernel holds a
+    /// reference to its file while the poll is armed.
+    pub(crate) fn poll_add(fd: RawFd, events: u32, multi: bool) -> io::Result<Op<PollAdd>> {
+        use io_uring::{opcode, types};
+
+        let op = Op::submit_with(PollAdd { fd, events }, |poll| {
+            opcode::PollAdd::new(types::Fd(poll.fd), poll.events)
+                .multi(multi)
+                .build()
+        })?;
+
+        // Canceled with `IORING_OP_POLL_REMOVE`
+        op.set_poll();
+        Ok(op)
+    }
+
+    /// Waits for the poll to complete, returning the events `fd` is ready
+    /// for.
+    pub(crate) async fn ready(self) -> io::Result<u32> {
+        self.await.result
+    }
+}
+
+/// Stream of the readiness events of a descriptor, polled with a multishot
+/// `IORING_OP_POLL_ADD`.
+///
+/// Whenever the kernel terminates the multishot operation, the next poll
+/// submits a new one. An error ends the stream.
+pub(crate) struct PollMultiStream {
+    fd: RawFd,
+    events: u32,
+
+    /// In-flight multishot operation, if any.
+    op: Option<Op<PollAdd>>,
+
+    /// Set once the stream yielded an error.
+    done: bool,
+}
+
+impl PollMultiStream {
+    pub(crate) fn new(fd: RawFd, events: u32) -> PollMultiStream {
+        PollMultiStream {
+            fd,
+            events,
+            op: None,
+            done: false,
+        }
+    }
+}
+
+impl Stream for PollMultiStream {
+    type Item = io::Result<u32>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let me = self.get_mut();
+
+        loop {
+            if me.done {
+                return Poll::Ready(None);
+            }
+
+            let op = match &mut me.op {
+                Some(op) => op,
+                None => match Op::poll_add(me.fd, me.events, true) {
+                    Ok(op) => me.op.insert(op),
+                    Err(e) => {
+                        me.done = true;
+                        return Poll::Ready(Some(Err(e)));
+                    }
+                },
+            };
+
+            let (result, flags) = match ready!(op.poll_next(cx)) {
+                Some(next) => next,
+                None => {
+                    me.op = None;
+                    continue;
+                }
+            };
+
+            if !cqueue::more(flags) {
+                // The kernel terminated the operation, re-arm on the next poll
+                me.op = None;
+            }
+            me.done = result.is_err();
+
+            return Poll::Ready(Some(result));
+        }
+    }
+}
+
+impl Drop for PollMultiStream {
+    fn drop(&mut self) {
+        // A multishot poll only terminates on its own on errors.
+        if let Some(op) = &self.op {
+            op.cancel();
+        }
+    }
+}
@@ -0,0 +1,238 @@
+use io_uring::{opcode, squeue, types, IoUring};
+use std::alloc::{self, Layout};
+use std::io;
+use std::os::unix::io::AsRawFd;
+use std::sync::OnceLock;
+
+/// The io-uring features supported by the running kernel.
+///
+/// Kernels gain opcodes, setup flags and features over time. Code using a
+/// recent one optionally, e.g. zero copy sends, checks for it at runtime
+/// with [`probe`]. The predicates of the form `supports_*` cover features the
+/// kernel does not report directly. Most of them are probed by trying the
+/// feature out, the others are inferred from an opcode added in the same
+/// release, as their documentation says.
+///
+/// # Examples
+///
+/// ```
+/// let probe = tokio_uring::probe().unwrap();
+///
+/// if probe.supports_multishot_accept() {
+///     // ...
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct Probe {
+    /// Bitmap of the supported opcodes.
+    opcodes: [u64; 4],
+
+    fast_poll: bool,
+    nodrop: bool,
+    submit_stable: bool,
+    ext_arg: bool,
+
+    coop_taskrun: bool,
+    single_issuer: bool,
+    defer_taskrun: bool,
+
+    multishot_accept: bool,
+    buf_ring: bool,
+    napi: bool,
+}
+
+/// Probes the io-uring features supported by the running kernel.
+///
+/// The kernel is probed through a small io-uring instance of its own, with
+/// `IORING_REGISTER_PROBE` for the opcodes, by setting up instances with
+/// each setup flag, and by trying out the other features on it. The result
+/// is cached: only the first call probes the kernel, and it can be called
+/// outside of a runtime.
+pub fn probe() -> io::Result<Probe> {
+    static PROBE: OnceLock<io::Result<Probe>> = OnceLock::new();
+
+    // A failure is cached too, rather than setting up a ring on every call
+    match PROBE.get_or_init(Probe::new) {
+        Ok(probe) => Ok(probe.clone()),
+        Err(e) => Err(match e.raw_os_error() {
+            Some(code) => io::Error::from_raw_os_error(code),
+            None => io::Error::new(e.kind(), e.to_string()),
+        }),
+    }
+}
+
+impl Probe {
+    fn new() -> io::Result<Probe> {
+        let mut uring = IoUring::new(1)?;
+
+        let mut raw = io_uring::Probe::new();
+        uring.submitter().register_probe(&mut raw)?;
+
+        let mut opcodes = [0; 4];
+        for opcode in 0..=u8::MAX {
+            if raw.is_supported(opcode) {
+                opcodes[opcode as usize / 64] |= 1 << (opcode % 64);
+            }
+        }
+
+        let params = uring.params();
+        let setup = |f: fn(&mut io_uring::Builder) -> &mut io_uring::Builder| {
+            f(&mut IoUring::builder()).build(1).is_ok()
+        };
+
+        Ok(Probe {
+            opcodes,
+            fast_poll: params.is_feature_fast_poll(),
+            nodrop: params.is_feature_nodrop(),
+            submit_stable: params.is_feature_submit_stable(),
+            ext_arg: params.is_feature_ext_arg(),
+            coop_taskrun: setup(|b| b.setup_coop_taskrun()),
+            single_issuer: setup(|b| b.setup_single_issuer()),
+            defer_taskrun: setup(|b| b.setup_single_issuer().setup_defer_taskrun()),
+            multishot_accept: !rejects(
+                &mut uring,
+                opcode::AcceptMulti::new(types::Fd(-1)).build(),
+            )?,
+            buf_ring: probe_buf_ring(&uring)?,
+            napi: super::napi::probe(uring.as_raw_fd()),
+        })
+    }
+
+    /// Returns `true` if the kernel supports `opcode`, an `IORING_OP_*`
+    /// value such as [`io_uring::opcode::SendZc::CODE`].
+    pub fn is_supported(&self, opcode: u8) -> bool {
+        self.opcodes[opcode as usize / 64] & (1 << (opcode % 64)) != 0
+    }
+
+    /// Returns `true` if accepts can be multishot (Linux 5.19).
+    ///
+    /// This is probed with a multishot accept, which kernels lacking them
+    /// reject with `EINVAL`.
+    pub fn supports_multishot_accept(&self) -> bool {
+        self.multishot_accept
+    }
+
+    /// Returns `true` if receives can be multishot (Linux 6.0).
+    ///
+    /// This is inferred from `IORING_OP_SEND_ZC`, added in the same release:
+    /// kernels before 5.19 ignore the flag of a multishot receive instead of
+    /// rejecting it, so it cannot be probed.
+    pub fn supports_multishot_recv(&self) -> bool {
+        self.is_supported(opcode::SendZc::CODE)
+    }
+
+    /// Returns `true` if zero copy sends are supported (Linux 6.0).
+    pub fn supports_send_zc(&self) -> bool {
+        self.is_supported(opcode::SendZc::CODE)
+    }
+
+    /// Returns `true` if buffers can be provided with
+    /// `IORING_OP_PROVIDE_BUFFERS` (Linux 5.7).
+    pub fn supports_provide_buffers(&self) -> bool {
+        self.is_supported(opcode::ProvideBuffers::CODE)
+    }
+
+    /// Returns `true` if buffer rings can be registered, as with
+    /// [`register_buf_ring`](crate::register_buf_ring) (Linux 5.19).
+    ///
+    /// This is probed by registering a buffer ring.
+    pub fn supports_buf_ring(&self) -> bool {
+        self.buf_ring
+    }
+
+    /// Returns `true` if NAPI busy polling can be registered, as with
+    /// [`Runtime::register_napi`](crate::Runtime::register_napi) (Linux
+    /// 6.9).
+    ///
+    /// This is probed by registering NAPI busy polling.
+    pub fn supports_napi(&self) -> bool {
+        self.napi
+    }
+
+    /// Returns `true` if polling for readiness is done internally, instead of
+    /// punting operations on sockets to worker threads (`IORING_FEAT_FAST_POLL`).
+    pub fn has_fast_poll(&self) -> bool {
+        self.fast_poll
+    }
+
+    /// Returns `true` if the kernel never drops completions, holding back
+    /// those that overflow the completion queue (`IORING_FEAT_NODROP`).
+    pub fn has_nodrop(&self) -> bool {
+        self.nodrop
+    }
+
+    /// Returns `true` if the data of an operation is consumed when it is
+    /// submitted (`IORING_FEAT_SUBMIT_STABLE`).
+    pub fn has_submit_stable(&self) -> bool {
+        self.submit_stable
+    }
+
+    /// Returns `true` if waiting for completions accepts a timeout
+    /// (`IORING_FEAT_EXT_ARG`).
+    pub fn has_ext_arg(&self) -> bool {
+        self.ext_arg
+    }
+
+    /// Returns `true` if `IORING_SETUP_COOP_TASKRUN` is supported.
+    pub fn supports_coop_taskrun(&self) -> bool {
+        self.coop_taskrun
+    }
+
+    /// Returns `true` if `IORING_SETUP_SINGLE_ISSUER` is supported.
+    pub fn supports_single_issuer(&self) -> bool {
+        self.single_issuer
+    }
+
+    /// Returns `true` if `IORING_SETUP_DEFER_TASKRUN` is supported.
+    pub fn supports_defer_taskrun(&self) -> bool {
+        self.defer_taskrun
+    }
+}
+
+/// Submits `sqe` to `uring`, and returns whether it failed with `EINVAL`, as
+/// operations with flags the kernel does not know do.
+fn rejects(uring: &mut IoUring, sqe: squeue::Entry) -> io::Result<bool> {
+    // Safety: the probes reference no memory.
+    unsafe { uring.submission().push(&sqe) }
+        .map_err(|_| io::Error::other("probe submission queue full"))?;
+    uring.submit_and_wait(1)?;
+
+    let cqe = uring
+        .completion()
+        .next()
+        .ok_or_else(|| io::Error::other("probe completion missing"))?;
+    Ok(cqe.result() == -libc::EINVAL)
+}
+
+/// Registers, then unregisters, a buffer ring of a single entry on `uring`.
+fn probe_buf_ring(uring: &IoUring) -> io::Result<bool> {
+    // The ring must be page aligned
+    let layout = Layout::from_size_align(16, 4096).unwrap();
+    // Safety: the layout has a non-zero size.
+    let ring = unsafe { alloc::alloc_zeroed(layout) };
+    if ring.is_null() {
+        alloc::handle_alloc_error(layout);
+    }
+
+    match uring.submitter().register_buf_ring(ring as u64, 1, 0) {
+        Ok(()) => {
+            // The kernel may still access a ring it failed to unregister,
+            // which is leaked rather than freed
+            if uring.submitter().unregister_buf_ring(0).is_ok() {
+                // Safety: the ring is no longer registered.
+                unsafe { alloc::dealloc(ring, layout) };
+            }
+            Ok(true)
+        }
+        Err(e) => {
+            // Safety: the ring was never registered.
+            unsafe { alloc::dealloc(ring, layout) };
+
+            if e.raw_os_error() == Some(libc::EINVAL) {
+                Ok(false)
+            } else {
+                Err(e)
+            }
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use crate::driver::Op;
+
+use std::{future::Future, io};
+
+use io_uring::{opcode, squeue};
+
+/// An operation prepared by the caller of [`submit_raw`].
+pub(crate) struct Raw;
+
+impl Op<Raw> {
+    fn raw<F>(prep: F) -> io::Result<Op<Raw>>
+    where
+        F: FnOnce(&mut squeue::Entry),
+    {
+        Op::submit_with(Raw, |_| {
+            let mut sqe = opcode::Nop::new().build();
+            prep(&mut sqe);
+            sqe
+        })
+    }
+}
+
+/// Submits a submission queue entry prepared by `prep` to the current
+/// runtime, and returns a future resolving to the `res` field of its
+/// completion.
+///
+/// This is an escape hatch for opcodes the crate does not wrap, like
+/// `IORING_OP_FUTEX_WAIT` or `IORING_OP_URING_CMD`. `prep` receives an
+/// `IORING_OP_NOP` entry, which it overwrites, e.g. with an entry built by
+/// the [`opcode`] module of the `io-uring` crate, version 0.5. The entry is
+/// a `#[repr(C)]` 64-byte `struct io_uring_sqe`, so opcodes the `io-uring`
+/// crate lacks can be written through a pointer cast. The runtime then sets
+/// the `user_data` field, routing the completion to the future, and submits
+/// the entry once `prep` returns, before the future is first polled.
+///
+/// The future resolves to the result of the operation as the kernel
+/// reports it, a negated `errno` value on failure. A failure to submit the
+/// entry resolves the same way, and so does cancellation: dropping the
+/// future before it completes cancels the operation, as for any other
+/// operation.
+///
+/// [`opcode`]: io_uring::opcode
+///
+/// # Safety
+///
+/// The entry is handed to the kernel as is. The caller must ensure that:
+///
+/// * Any memory the entry references, like buffers, iovecs or addresses,
+///   stays valid, and is not otherwise accessed in conflicting ways, until
+///   the operation completes. This also holds if the future is dropped
+///   first, as the kernel may still use the memory until the cancellation
+///   took effect, so memory shared with a dropped future has to be leaked
+///   or outlive the runtime.
+/// * The entry posts exactly one completion: multishot operations and the
+///   `IOSQE_CQE_SKIP_SUCCESS` flag are not supported.
+/// * The entry does not set `IOSQE_IO_LINK` or `IOSQE_IO_HARDLINK`, which
+///   would link it to an unrelated operation, and any registered file or
+///   buffer it names is registered with the runtime.
+///
+/// # Panics
+///
+/// Panics if called outside of a `tokio-uring` runtime.
+///
+/// # Examples
+///
+/// ```
+/// use io_uring::opcode;
+///
+/// fn main() {
+///     tokio_uring::start(async {
+///         let res = unsafe {
+///             tokio_uring::submit_raw(|sqe| *sqe = opcode::Nop::new().build())
+///         }
+///         .await;
+///         assert_eq!(res, 0);
+///     });
+/// }
+/// ```
+pub unsafe fn submit_raw<F>(prep: F) -> impl Future<Output = i32>
+where
+    F: FnOnce(&mut squeue::Entry),
+{
+    let op = Op::raw(prep);
+
+    async move {
+        let result = match op {
+            Ok(op) => op.await.result,
+            Err(e) => Err(e),
+        };
+
+        match result {
+            Ok(res) => res as i32,
+            Err(e) => -e.raw_os_error().unwrap_or(libc::EIO),
+        }
+    }
+}
@@ -17,7 +17,7 @@ pub(crate) struct Read<T> {
 
 impl<T: IoBufMut> Op<Read<T>> {
     pub(crate) fn read_at(fd: &SharedFd, buf: T, offset: u64) -> io::Result<Op<Read<T>>> {
-        use io_uring::{opcode, types};
+        use io_uring::opcode;
 
         Op::submit_with(
             Read {
@@ -28,9 +28,9 @@ impl<T: IoBufMut> Op<Read<T>> {
                 // Get raw buffer info
                 let ptr = read.buf.stable_mut_ptr();
                 let len = read.buf.bytes_total();
-                opcode::Read::new(types::Fd(fd.raw_fd()), ptr, len as _)
+                with_fd!(fd, |fd| opcode::Read::new(fd, ptr, len as _)
                     .offset(offset as _)
-                    .build()
+                    .build())
             },
         )
     }
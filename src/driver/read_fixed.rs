@@ -0,0 +1,64 @@
+use crate::buf::IoBufMut;
+use crate::driver::{FixedBuf, Op, SharedFd};
+use crate::BufResult;
+
+use std::io;
+
+pub(crate) struct ReadFixed {
+    /// Holds a strong ref to the FD, preventing the file from being closed
+    /// while the operation is in-flight.
+    #[allow(dead_code)]
+    fd: SharedFd,
+
+    /// The fixed buffer being read into. It keeps the registration alive.
+    buf: FixedBuf,
+}
+
+impl Op<ReadFixed> {
+    pub(crate) fn read_fixed_at(
+        fd: &SharedFd,
+        buf: FixedBuf,
+        offset: u64,
+    ) -> io::Result<Op<ReadFixed>> {
+        use io_uring::opcode;
+
+        Op::submit_with(
+            ReadFixed {
+                fd: fd.clone(),
+                buf,
+            },
+            |read| {
+                // Get raw buffer info
+                let ptr = read.buf.stable_mut_ptr();
+                let len = read.buf.capacity();
+                with_fd!(fd, |fd| opcode::ReadFixed::new(
+                    fd,
+                    ptr,
+                    len as _,
+                    read.buf.index()
+                )
+                .offset(offset as _)
+                .build())
+            },
+        )
+    }
+
+    pub(crate) async fn read(self) -> BufResult<usize, FixedBuf> {
+        let complete = self.await;
+
+        // Convert the operation result to `usize`
+        let res = complete.result.map(|v| v as usize);
+        // Recover the buffer
+        let mut buf = complete.data.buf;
+
+        // If the operation was successful, advance the initialized cursor.
+        if let Ok(n) = res {
+            // Safety: the kernel wrote `n` bytes to the buffer.
+            unsafe {
+                buf.set_init(n);
+            }
+        }
+
+        (res, buf)
+    }
+}
@@ -0,0 +1,74 @@
+use crate::{
+    buf::{self, IoVectoredBufMut},
+    driver::{Op, SharedFd},
+    BufResult,
+};
+use libc::iovec;
+use std::{
+    io,
+    task::{Context, Poll},
+};
+
+pub(crate) struct Readv<T> {
+    /// Holds a strong ref to the FD, preventing the file from being closed
+    /// while the operation is in-flight.
+    #[allow(dead_code)]
+    fd: SharedFd,
+
+    pub(crate) bufs: T,
+
+    /// Parameter for `io_uring::op::Readv`, referring to `bufs`.
+    iovs: Vec<iovec>,
+}
+
+impl<T: IoVectoredBufMut> Op<Readv<T>> {
+    pub(crate) fn readv_at(fd: &SharedFd, mut bufs: T, offset: u64) -> io::Result<Op<Readv<T>>> {
+        use io_uring::opcode;
+
+        // Build the iovec array. The buffers are owned by the operation, so
+        // the pointers stay valid until the operation completes.
+        let iovs = buf::iovecs_mut(&mut bufs);
+
+        Op::submit_with(
+            Readv {
+                fd: fd.clone(),
+                bufs,
+                iovs,
+            },
+            |readv| {
+                with_fd!(fd, |fd| opcode::Readv::new(
+                    fd,
+                    readv.iovs.as_ptr(),
+                    readv.iovs.len() as u32,
+                )
+                .offset(offset as _)
+                .build())
+            },
+        )
+    }
+
+    pub(crate) async fn readv(mut self) -> BufResult<usize, T> {
+        crate::future::poll_fn(move |cx| self.poll_readv(cx)).await
+    }
+
+    pub(crate) fn poll_readv(&mut self, cx: &mut Context<'_>) -> Poll<BufResult<usize, T>> {
+        use std::future::Future;
+        use std::pin::Pin;
+
+        let complete = ready!(Pin::new(self).poll(cx));
+
+        // Convert the operation result to `usize`
+        let res = complete.result.map(|v| v as usize);
+        // Recover the buffers
+        let mut bufs = complete.data.bufs;
+
+        // If the operation was successful, advance the initialized cursor of
+        // each buffer the kernel filled, in order.
+        if let Ok(n) = res {
+            // Safety: the kernel wrote `n` bytes across the buffers.
+            unsafe { buf::set_init_vectored(&mut bufs, n) };
+        }
+
+        Poll::Ready((res, bufs))
+    }
+}
@@ -0,0 +1,72 @@
+use crate::{
+    buf::IoBufMut,
+    driver::{Op, SharedFd},
+    BufResult,
+};
+use std::{
+    io,
+    task::{Context, Poll},
+};
+
+pub(crate) struct Recv<T> {
+    /// Holds a strong ref to the FD, preventing the socket from being closed
+    /// while the operation is in-flight.
+    #[allow(dead_code)]
+    fd: SharedFd,
+
+    /// Reference to the in-flight buffer.
+    pub(crate) buf: T,
+}
+
+impl<T: IoBufMut> Op<Recv<T>> {
+    /// Submit a `recv(2)` on a connected socket. `flags` are the `MSG_*`
+    /// flags passed to the kernel.
+    pub(crate) fn recv_with_flags(fd: &SharedFd, buf: T, flags: i32) -> io::Result<Op<Recv<T>>> {
+        use io_uring::opcode;
+
+        Op::submit_with(
+            Recv {
+                fd: fd.clone(),
+                buf,
+            },
+            |recv| {
+                // Get raw buffer info
+                let ptr = recv.buf.stable_mut_ptr();
+                let len = recv.buf.bytes_total();
+                with_fd!(fd, |fd| opcode::Recv::new(fd, ptr, len as _)
+                    .flags(flags)
+                    .build())
+            },
+        )
+    }
+
+    pub(crate) async fn recv(mut self) -> BufResult<usize, T> {
+        use crate::future::poll_fn;
+
+        poll_fn(move |cx| self.poll_recv(cx)).await
+    }
+
+    pub(crate) fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<BufResult<usize, T>> {
+        use std::future::Future;
+        use std::pin::Pin;
+
+        let complete = ready!(Pin::new(self).poll(cx));
+
+        // Convert the operation result to `usize`
+        let res = complete.result.map(|v| v as usize);
+        // Recover the buffer
+        let mut buf = complete.data.buf;
+
+        // If the operation was successful, advance the initialized cursor.
+        if let Ok(n) = res {
+            // Safety: the kernel wrote `n` bytes to the buffer. With
+            // `MSG_TRUNC` the kernel may report more bytes than the buffer
+            // holds, so never go past the end of the buffer.
+            unsafe {
+                buf.set_init(n.min(buf.bytes_total()));
+            }
+        }
+
+        Poll::Ready((res, buf))
+    }
+}
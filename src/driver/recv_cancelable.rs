@@ -0,0 +1,181 @@
+use crate::{
+    buf::IoBufMut,
+    driver::{Op, SharedFd},
+    BufResult,
+};
+use std::{
+    cell::RefCell,
+    fmt,
+    future::Future,
+    io,
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll, Waker},
+};
+
+/// A `recv(2)` whose buffer goes to a [`RecvGuard`] if it is canceled.
+pub(crate) struct RecvReturning<T> {
+    /// Holds a strong ref to the FD, preventing the socket from being closed
+    /// while the operation is in-flight.
+    #[allow(dead_code)]
+    fd: SharedFd,
+
+    /// The in-flight buffer, taken by the future when it completes.
+    buf: Option<T>,
+
+    guard: Rc<RefCell<Guarded<T>>>,
+}
+
+/// State shared by a receive and its guard.
+struct Guarded<T> {
+    /// The buffer of a canceled receive, once its operation completed.
+    buf: Option<T>,
+
+    /// Set once the operation released its buffer.
+    released: bool,
+
+    waker: Option<Waker>,
+}
+
+impl<T> Drop for RecvReturning<T> {
+    fn drop(&mut self) {
+        // Dropped either by the future once it completed, or by the driver
+        // once the operation of a canceled receive completed.
+        let mut guard = self.guard.borrow_mut();
+        guard.buf = self.buf.take();
+        guard.released = true;
+        if let Some(waker) = guard.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+impl<T: IoBufMut> Op<RecvReturning<T>> {
+    pub(crate) fn recv_cancelable(
+        fd: &SharedFd,
+        buf: T,
+    ) -> io::Result<(Op<RecvReturning<T>>, RecvGuard<T>)> {
+        use io_uring::opcode;
+
+        let guard = Rc::new(RefCell::new(Guarded {
+            buf: None,
+            released: false,
+            waker: None,
+        }));
+
+        let op = Op::submit_with(
+            RecvReturning {
+                fd: fd.clone(),
+                buf: Some(buf),
+                guard: guard.clone(),
+            },
+            |recv| {
+                let buf = recv.buf.as_mut().unwrap();
+                let ptr = buf.stable_mut_ptr();
+                let len = buf.bytes_total();
+                with_fd!(fd, |fd| opcode::Recv::new(fd, ptr, len as _).build())
+            },
+        )?;
+
+        Ok((op, RecvGuard { state: guard }))
+    }
+}
+
+/// A receive made by `recv_cancelable`, e.g.
+/// [`TcpStream::recv_cancelable`](crate::net::TcpStream::recv_cancelable).
+///
+/// Awaiting it returns the result of the receive along with the buffer, as
+/// `recv` does. If it is dropped before completing, the operation is
+/// canceled, and its buffer is handed to the [`RecvGuard`] returned along
+/// with it once the kernel released it.
+pub struct CancelableRecv<T: 'static> {
+    op: Op<RecvReturning<T>>,
+}
+
+impl<T: IoBufMut> CancelableRecv<T> {
+    pub(crate) fn new(op: Op<RecvReturning<T>>) -> CancelableRecv<T> {
+        CancelableRecv { op }
+    }
+}
+
+impl<T: IoBufMut> Future for CancelableRecv<T> {
+    type Output = BufResult<usize, T>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut complete = ready!(Pin::new(&mut self.op).poll(cx));
+
+        // Convert the operation result to `usize`
+        let res = complete.result.map(|v| v as usize);
+        // Recover the buffer, the guard is released without it
+        let mut buf = complete.data.buf.take().unwrap();
+
+        if let Ok(n) = res {
+            // Safety: the kernel wrote `n` bytes to the buffer.
+            unsafe {
+                buf.set_init(n.min(buf.bytes_total()));
+            }
+        }
+
+        Poll::Ready((res, buf))
+    }
+}
+
+impl<T: 'static> fmt::Debug for CancelableRecv<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CancelableRecv").finish()
+    }
+}
+
+/// Hands back the buffer of a [`CancelableRecv`] that was canceled.
+///
+/// The kernel owns the buffer of a receive until the receive completes, even
+/// after it was canceled, so the buffer only becomes available once the
+/// completion of the canceled operation was reaped. Awaiting the guard waits
+/// for this, and returns:
+///
+/// * `Some(buf)` if the receive was dropped before completing. The contents
+///   of the buffer are unspecified, as the kernel may have written data to
+///   it before the cancellation took effect. That data is lost.
+/// * `None` if the receive completed, in which case its future returned the
+///   buffer.
+///
+/// Dropping the guard lets the buffer be dropped once it is released.
+pub struct RecvGuard<T> {
+    state: Rc<RefCell<Guarded<T>>>,
+}
+
+impl<T> RecvGuard<T> {
+    /// Returns whether the receive released its buffer, in which case
+    /// awaiting the guard completes immediately.
+    pub fn is_released(&self) -> bool {
+        self.state.borrow().released
+    }
+
+    /// Takes the buffer of a canceled receive, if it was released already.
+    pub fn try_take(&mut self) -> Option<T> {
+        self.state.borrow_mut().buf.take()
+    }
+}
+
+impl<T> Future for RecvGuard<T> {
+    type Output = Option<T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        let mut state = self.state.borrow_mut();
+
+        if state.released {
+            Poll::Ready(state.buf.take())
+        } else {
+            state.waker = Some(cx.waker().clone());
+            Poll::Pending
+        }
+    }
+}
+
+impl<T> fmt::Debug for RecvGuard<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RecvGuard")
+            .field("released", &self.is_released())
+            .finish()
+    }
+}
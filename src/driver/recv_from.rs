@@ -1,12 +1,14 @@
 use crate::{
     buf::IoBufMut,
-    driver::{Op, SharedFd},
+    driver::{CmsgBuf, LinkResult, Op, SharedFd},
     BufResult,
 };
+use io_uring::types::Timespec;
 use socket2::SockAddr;
 use std::{
     io::IoSliceMut,
     task::{Context, Poll},
+    time::Duration,
     {boxed::Box, io, net::SocketAddr},
 };
 
@@ -17,12 +19,14 @@ pub(crate) struct RecvFrom<T> {
     io_slices: Vec<IoSliceMut<'static>>,
     pub(crate) socket_addr: Box<SockAddr>,
     pub(crate) msghdr: Box<libc::msghdr>,
+    /// Receives the control messages, if requested.
+    control: Option<CmsgBuf>,
+    /// Deadline of a linked timeout, read by the kernel on submission.
+    timeout: Option<Box<Timespec>>,
 }
 
-impl<T: IoBufMut> Op<RecvFrom<T>> {
-    pub(crate) fn recv_from(fd: &SharedFd, mut buf: T) -> io::Result<Op<RecvFrom<T>>> {
-        use io_uring::{opcode, types};
-
+impl<T: IoBufMut> RecvFrom<T> {
+    fn new(fd: &SharedFd, mut buf: T, mut control: Option<CmsgBuf>) -> io::Result<RecvFrom<T>> {
         let mut io_slices = vec![IoSliceMut::new(unsafe {
             std::slice::from_raw_parts_mut(buf.stable_mut_ptr(), buf.bytes_total())
         })];
@@ -35,22 +39,109 @@ impl<T: IoBufMut> Op<RecvFrom<T>> {
         msghdr.msg_name = socket_addr.as_ptr() as *mut libc::c_void;
         msghdr.msg_namelen = socket_addr.len();
 
-        Op::submit_with(
-            RecvFrom {
-                fd: fd.clone(),
-                buf,
-                io_slices,
-                socket_addr,
-                msghdr,
-            },
-            |recv_from| {
-                opcode::RecvMsg::new(
-                    types::Fd(recv_from.fd.raw_fd()),
-                    recv_from.msghdr.as_mut() as *mut _,
-                )
-                .build()
-            },
+        if let Some(control) = control.as_mut() {
+            msghdr.msg_control = control.as_mut_ptr().cast();
+            msghdr.msg_controllen = control.capacity() as _;
+        }
+
+        Ok(RecvFrom {
+            fd: fd.clone(),
+            buf,
+            io_slices,
+            socket_addr,
+            msghdr,
+            control,
+            timeout: None,
+        })
+    }
+
+    fn build(&mut self, flags: u32) -> io_uring::squeue::Entry {
+        use io_uring::opcode;
+
+        with_fd!(self.fd, |fd| opcode::RecvMsg::new(
+            fd,
+            self.msghdr.as_mut() as *mut _
         )
+        .flags(flags)
+        .build())
+    }
+}
+
+impl<T: IoBufMut> Op<RecvFrom<T>> {
+    /// Submit a `recvmsg(2)` returning the source address. `flags` are the
+    /// `MSG_*` flags passed to the kernel.
+    pub(crate) fn recv_from_with_flags(
+        fd: &SharedFd,
+        buf: T,
+        flags: u32,
+    ) -> io::Result<Op<RecvFrom<T>>> {
+        Op::submit_with(RecvFrom::new(fd, buf, None)?, |recv_from| {
+            recv_from.build(flags)
+        })
+    }
+
+    /// Submit a `recvmsg(2)` returning the source address, along with the
+    /// control messages that fit in `control`.
+    pub(crate) fn recv_from_with_control(
+        fd: &SharedFd,
+        buf: T,
+        flags: u32,
+        control: CmsgBuf,
+    ) -> io::Result<Op<RecvFrom<T>>> {
+        Op::submit_with(RecvFrom::new(fd, buf, Some(control))?, |recv_from| {
+            recv_from.build(flags)
+        })
+    }
+
+    /// Submit a `recvmsg(2)` returning the source address, linked to a
+    /// timeout. If no datagram arrives within `timeout`, the receive
+    /// completes with `ECANCELED`.
+    pub(crate) fn recv_from_with_timeout(
+        fd: &SharedFd,
+        buf: T,
+        timeout: Duration,
+    ) -> io::Result<Op<RecvFrom<T>>> {
+        use io_uring::opcode;
+
+        let mut recv_from = RecvFrom::new(fd, buf, None)?;
+        recv_from.timeout = Some(Box::new(
+            Timespec::new()
+                .sec(timeout.as_secs())
+                .nsec(timeout.subsec_nanos()),
+        ));
+
+        Op::submit_with_link_timeout(recv_from, |recv_from| {
+            let timespec = recv_from.timeout.as_deref().unwrap() as *const _;
+            (
+                recv_from.build(0),
+                opcode::LinkTimeout::new(timespec).build(),
+            )
+        })
+    }
+
+    /// Submit a chain of linked `recvmsg(2)`, one for each buffer. The first
+    /// receive waits for a datagram, the following ones only pick up
+    /// datagrams that are already queued. The chain stops at the first empty
+    /// receive, and the remaining operations complete with `ECANCELED`.
+    pub(crate) fn recv_from_linked(fd: &SharedFd, bufs: Vec<T>) -> LinkResult<RecvFrom<T>, T> {
+        let mut data = Vec::with_capacity(bufs.len());
+        let mut bufs = bufs.into_iter();
+
+        while let Some(buf) = bufs.next() {
+            match RecvFrom::new(fd, buf, None) {
+                Ok(recv_from) => data.push(recv_from),
+                Err(e) => {
+                    let bufs = data.into_iter().map(|r| r.buf).chain(bufs).collect();
+                    return Err((e, bufs));
+                }
+            }
+        }
+
+        Op::submit_linked_with(data, |i, recv_from| {
+            let flags = if i == 0 { 0 } else { libc::MSG_DONTWAIT as u32 };
+            recv_from.build(flags)
+        })
+        .map_err(|(e, data)| (e, data.into_iter().map(|r| r.buf).collect()))
     }
 
     pub(crate) async fn recv(mut self) -> BufResult<(usize, SocketAddr), T> {
@@ -59,31 +150,85 @@ impl<T: IoBufMut> Op<RecvFrom<T>> {
         poll_fn(move |cx| self.poll_recv_from(cx)).await
     }
 
+    /// Waits for the completion, returning the source address whatever its
+    /// family.
+    pub(crate) async fn recv_sockaddr(mut self) -> BufResult<(usize, SockAddr), T> {
+        use crate::future::poll_fn;
+
+        let (res, _) = poll_fn(move |cx| self.poll_complete(cx)).await;
+        res
+    }
+
+    /// Waits for the completion, and returns the control messages received
+    /// along with the datagram.
+    pub(crate) async fn recv_with_control(
+        mut self,
+    ) -> (io::Result<(usize, SocketAddr)>, T, CmsgBuf) {
+        use crate::future::poll_fn;
+
+        let ((result, buf), control) = poll_fn(move |cx| self.poll_complete(cx)).await;
+        let control = control.expect("operation submitted without control buffer");
+        (result.and_then(as_socket_addr), buf, control)
+    }
+
     pub(crate) fn poll_recv_from(
         &mut self,
         cx: &mut Context<'_>,
     ) -> Poll<BufResult<(usize, SocketAddr), T>> {
+        let ((res, buf), _) = ready!(self.poll_complete(cx));
+        Poll::Ready((res.and_then(as_socket_addr), buf))
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn poll_complete(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<(BufResult<(usize, SockAddr), T>, Option<CmsgBuf>)> {
         use std::future::Future;
         use std::pin::Pin;
 
         let complete = ready!(Pin::new(self).poll(cx));
+        let data = complete.data;
 
         // Recover the buffer
-        let mut buf = complete.data.buf;
+        let mut buf = data.buf;
+        let mut control = data.control;
 
         let result = match complete.result {
             Ok(v) => {
                 let v = v as usize;
-                let socket_addr: Option<SocketAddr> = (*complete.data.socket_addr).as_socket();
                 // If the operation was successful, advance the initialized cursor.
-                // Safety: the kernel wrote `v` bytes to the buffer.
+                // Safety: the kernel wrote `v` bytes to the buffer. With
+                // `MSG_TRUNC` the kernel may report more bytes than the buffer
+                // holds, so never go past the end of the buffer.
                 unsafe {
-                    buf.set_init(v);
+                    buf.set_init(v.min(buf.bytes_total()));
                 }
-                Ok((v, socket_addr.unwrap()))
+                // The kernel updated the length to cover the control
+                // messages it wrote.
+                if let Some(control) = control.as_mut() {
+                    control.set_len(data.msghdr.msg_controllen as _);
+                }
+                // The kernel updated the length of the address too.
+                // Safety: the storage and length were written by the kernel.
+                let socket_addr = unsafe {
+                    let storage = std::ptr::read(data.socket_addr.as_ptr().cast());
+                    SockAddr::new(storage, data.msghdr.msg_namelen)
+                };
+                Ok((v, socket_addr))
             }
             Err(e) => Err(e),
         };
-        Poll::Ready((result, buf))
+        Poll::Ready(((result, buf), control))
+    }
+}
+
+fn as_socket_addr((n, socket_addr): (usize, SockAddr)) -> io::Result<(usize, SocketAddr)> {
+    match socket_addr.as_socket() {
+        Some(socket_addr) => Ok((n, socket_addr)),
+        None => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "unexpected datagram source address",
+        )),
     }
 }
@@ -0,0 +1,196 @@
+use crate::buf::{self, IoVectoredBufMut};
+use crate::driver::{CmsgBuf, Op, SharedFd};
+use socket2::SockAddr;
+use std::io;
+use std::task::{Context, Poll};
+
+/// Outcome of a [`RecvMsg`], along with the buffers and the control
+/// messages.
+pub(crate) type RecvMsgResult<T> = (io::Result<RecvMsgOut>, T, Option<CmsgBuf>);
+
+/// A `recvmsg(2)` owning everything the message header points to: the
+/// buffers and their iovecs, the storage of the source address, and the
+/// control buffer. They stay in place until the operation completes.
+pub(crate) struct RecvMsg<T> {
+    #[allow(dead_code)]
+    fd: SharedFd,
+    pub(crate) bufs: T,
+    #[allow(dead_code)]
+    iovs: Vec<libc::iovec>,
+    socket_addr: Option<Box<SockAddr>>,
+    control: Option<CmsgBuf>,
+    msghdr: Box<libc::msghdr>,
+}
+
+/// A message received by a [`RecvMsg`].
+#[derive(Debug)]
+pub(crate) struct RecvMsgOut {
+    /// Number of bytes received, scattered over the buffers in order.
+    pub(crate) len: usize,
+
+    /// Source address, if requested and provided by the socket.
+    #[allow(dead_code)]
+    pub(crate) socket_addr: Option<SockAddr>,
+
+    /// Flags of the received message, e.g. `MSG_CTRUNC` when control
+    /// messages did not fit in the control buffer.
+    pub(crate) flags: libc::c_int,
+}
+
+impl<T: IoVectoredBufMut> Op<RecvMsg<T>> {
+    /// Submit a `recvmsg(2)` scattering the data over the whole capacity of
+    /// `bufs`, in order. With `want_addr`, the source address is returned.
+    /// Control messages are received into `control`, if any. `flags` are the
+    /// `MSG_*` flags passed to the kernel.
+    pub(crate) fn recv_msg(
+        fd: &SharedFd,
+        mut bufs: T,
+        want_addr: bool,
+        mut control: Option<CmsgBuf>,
+        flags: i32,
+    ) -> io::Result<Op<RecvMsg<T>>> {
+        use io_uring::opcode;
+
+        let mut iovs = buf::iovecs_mut(&mut bufs);
+
+        let socket_addr = if want_addr {
+            Some(Box::new(unsafe { SockAddr::init(|_, _| Ok(()))?.1 }))
+        } else {
+            None
+        };
+
+        let mut msghdr: Box<libc::msghdr> = Box::new(unsafe { std::mem::zeroed() });
+        msghdr.msg_iov = iovs.as_mut_ptr();
+        msghdr.msg_iovlen = iovs.len() as _;
+
+        if let Some(socket_addr) = socket_addr.as_ref() {
+            msghdr.msg_name = socket_addr.as_ptr() as *mut libc::c_void;
+            msghdr.msg_namelen = socket_addr.len();
+        }
+
+        if let Some(control) = control.as_mut() {
+            msghdr.msg_control = control.as_mut_ptr().cast();
+            msghdr.msg_controllen = control.capacity() as _;
+        }
+
+        Op::submit_with(
+            RecvMsg {
+                fd: fd.clone(),
+                bufs,
+                iovs,
+                socket_addr,
+                control,
+                msghdr,
+            },
+            |recv_msg| {
+                with_fd!(recv_msg.fd, |fd| opcode::RecvMsg::new(
+                    fd,
+                    recv_msg.msghdr.as_mut() as *mut _
+                )
+                .flags(flags as _)
+                .build())
+            },
+        )
+    }
+
+    pub(crate) async fn recv(mut self) -> RecvMsgResult<T> {
+        use crate::future::poll_fn;
+
+        poll_fn(move |cx| self.poll_recv(cx)).await
+    }
+
+    pub(crate) fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<RecvMsgResult<T>> {
+        use std::future::Future;
+        use std::pin::Pin;
+
+        let complete = ready!(Pin::new(self).poll(cx));
+        let RecvMsg {
+            mut bufs,
+            mut control,
+            socket_addr,
+            msghdr,
+            ..
+        } = complete.data;
+
+        let result = complete.result.map(|n| {
+            let len = n as usize;
+
+            // Advance the initialized cursor of each buffer the kernel
+            // filled, in order. With `MSG_TRUNC` the kernel may report more
+            // bytes than the buffers hold.
+            //
+            // Safety: the kernel wrote the bytes across the buffers.
+            unsafe { buf::set_init_vectored(&mut bufs, len) };
+
+            // The kernel updated the lengths of the control messages and of
+            // the address it wrote.
+            if let Some(control) = control.as_mut() {
+                control.set_len(msghdr.msg_controllen as _);
+            }
+
+            let socket_addr = socket_addr
+                .filter(|_| msghdr.msg_namelen > 0)
+                .map(|socket_addr| {
+                    // Safety: the storage and length were written by the
+                    // kernel.
+                    unsafe {
+                        let storage = std::ptr::read(socket_addr.as_ptr().cast());
+                        SockAddr::new(storage, msghdr.msg_namelen)
+                    }
+                });
+
+            RecvMsgOut {
+                len,
+                socket_addr,
+                flags: msghdr.msg_flags,
+            }
+        });
+
+        Poll::Ready((result, bufs, control))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::driver::Socket;
+    use std::os::unix::io::IntoRawFd;
+    use std::os::unix::net::UnixDatagram;
+
+    #[test]
+    fn round_trip_cmsg() {
+        crate::start(async {
+            let (a, b) = UnixDatagram::pair().unwrap();
+            let (a, b) = (Socket::from_std(a), Socket::from_std(b));
+
+            // Pass the descriptor of a fresh socket through `SCM_RIGHTS`
+            let (passed, _) = UnixDatagram::pair().unwrap();
+            let passed = passed.into_raw_fd();
+            let mut control = CmsgBuf::with_space(CmsgBuf::space(4));
+            control.push(libc::SOL_SOCKET, libc::SCM_RIGHTS, passed);
+
+            let bufs = vec![b"hello ".to_vec(), b"world".to_vec()];
+            let (res, _, _) = a.send_msg(bufs, None, Some(control), 0).await;
+            assert_eq!(res.unwrap(), 11);
+            unsafe { libc::close(passed) };
+
+            let bufs = vec![Vec::with_capacity(4), Vec::with_capacity(16)];
+            let control = CmsgBuf::with_space(CmsgBuf::space(4));
+            let (res, bufs, control) = b.recv_msg(bufs, true, Some(control), 0).await;
+
+            let out = res.unwrap();
+            assert_eq!(out.len, 11);
+            assert_eq!(out.flags & libc::MSG_CTRUNC, 0);
+            assert_eq!(bufs[0], b"hell");
+            assert_eq!(bufs[1], b"o world");
+
+            let cmsg = control.unwrap();
+            let cmsg = cmsg.iter().next().unwrap();
+            assert_eq!((cmsg.level, cmsg.ty), (libc::SOL_SOCKET, libc::SCM_RIGHTS));
+            let received: libc::c_int = cmsg.read().unwrap();
+            assert!(received >= 0);
+            unsafe { libc::close(received) };
+        });
+    }
+}
@@ -0,0 +1,209 @@
+use crate::{
+    buf::BufRing,
+    driver::{Op, SharedFd},
+    net::RecvSlot,
+};
+use futures_core::Stream;
+use io_uring::{cqueue, types::RecvMsgOut};
+use socket2::SockAddr;
+use std::{
+    io,
+    net::SocketAddr,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+pub(crate) struct RecvMsgMulti {
+    /// Holds a strong ref to the FD, preventing the socket from being closed
+    /// while the operation is in-flight.
+    #[allow(dead_code)]
+    fd: SharedFd,
+
+    /// Ring the kernel picks buffers from.
+    buf_ring: BufRing,
+
+    /// Describes the layout of each buffer to the kernel. Only the name and
+    /// control lengths are used.
+    msghdr: Box<libc::msghdr>,
+}
+
+impl Op<RecvMsgMulti> {
+    /// Submit a multishot `recvmsg(2)`. Each datagram is received into a
+    /// buffer selected from `buf_ring`, prefixed by its source address.
+    pub(crate) fn recv_msg_multi(
+        fd: &SharedFd,
+        buf_ring: &BufRing,
+    ) -> io::Result<Op<RecvMsgMulti>> {
+        use io_uring::opcode;
+
+        let mut msghdr: Box<libc::msghdr> = Box::new(unsafe { std::mem::zeroed() });
+        // Datagram sockets only receive from IPv4 and IPv6 peers.
+        msghdr.msg_namelen = std::mem::size_of::<libc::sockaddr_in6>() as _;
+
+        Op::submit_with_buf_ring(
+            RecvMsgMulti {
+                fd: fd.clone(),
+                buf_ring: buf_ring.clone(),
+                msghdr,
+            },
+            buf_ring,
+            |recv| {
+                with_fd!(fd, |fd| opcode::RecvMsgMulti::new(
+                    fd,
+                    recv.msghdr.as_ref() as *const _,
+                    recv.buf_ring.bgid(),
+                )
+                .build())
+            },
+        )
+    }
+
+    /// Polls the next datagram. Along with the datagram, returns whether the
+    /// operation keeps receiving.
+    pub(crate) fn poll_next_datagram(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<(io::Result<RecvSlot>, bool)>> {
+        let (result, flags) = match ready!(self.poll_next(cx)) {
+            Some(completion) => completion,
+            None => return Poll::Ready(None),
+        };
+
+        let more = cqueue::more(flags);
+        let result = result.and_then(|n| {
+            let bid = cqueue::buffer_select(flags).expect("no buffer selected");
+            let recv = self.get_ref();
+
+            let parsed = parse_datagram(&recv.buf_ring, bid, n as usize, &recv.msghdr);
+            match parsed {
+                Ok((offset, len, addr)) => {
+                    Ok(RecvSlot::new(recv.buf_ring.clone(), bid, offset, len, addr))
+                }
+                Err(e) => {
+                    recv.buf_ring.recycle(bid);
+                    Err(e)
+                }
+            }
+        });
+
+        Poll::Ready(Some((result, more)))
+    }
+}
+
+/// Locates the source address and payload in buffer `bid`, which holds `n`
+/// bytes written by a multishot `recvmsg(2)`. Returns the payload offset and
+/// length along with the source address.
+fn parse_datagram(
+    buf_ring: &BufRing,
+    bid: u16,
+    n: usize,
+    msghdr: &libc::msghdr,
+) -> io::Result<(usize, usize, SocketAddr)> {
+    // Safety: the kernel picked buffer `bid` and wrote `n` bytes to it.
+    let buf = unsafe { buf_ring.buf(bid, n) };
+    let out = RecvMsgOut::parse(buf, msghdr)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "malformed recvmsg buffer"))?;
+
+    let name = out.name_data();
+    // Safety: the kernel wrote a socket address of `name.len()` bytes.
+    let (_, addr) = unsafe {
+        SockAddr::init(|storage, len| {
+            std::ptr::copy_nonoverlapping(name.as_ptr(), storage as *mut u8, name.len());
+            *len = name.len() as _;
+            Ok(())
+        })?
+    };
+    let addr = addr.as_socket().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            "unexpected datagram source address",
+        )
+    })?;
+
+    let payload = out.payload_data();
+    let offset = payload.as_ptr() as usize - buf.as_ptr() as usize;
+
+    Ok((offset, payload.len(), addr))
+}
+
+/// Stream of datagrams received with a multishot `recvmsg(2)`.
+///
+/// Whenever the kernel terminates the multishot operation, the next poll
+/// submits a new one. Running out of buffers (`ENOBUFS`) is reported as an
+/// error, after which the stream keeps receiving. Any other error ends the
+/// stream.
+pub(crate) struct RecvMsgMultiStream {
+    fd: SharedFd,
+
+    buf_ring: BufRing,
+
+    /// In-flight multishot operation, if any.
+    op: Option<Op<RecvMsgMulti>>,
+
+    /// Set once the stream yielded a non-recoverable error.
+    done: bool,
+}
+
+impl RecvMsgMultiStream {
+    pub(crate) fn new(fd: SharedFd, buf_ring: BufRing) -> RecvMsgMultiStream {
+        RecvMsgMultiStream {
+            fd,
+            buf_ring,
+            op: None,
+            done: false,
+        }
+    }
+}
+
+impl Stream for RecvMsgMultiStream {
+    type Item = io::Result<RecvSlot>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let me = self.get_mut();
+
+        loop {
+            if me.done {
+                return Poll::Ready(None);
+            }
+
+            let op = match &mut me.op {
+                Some(op) => op,
+                None => match Op::recv_msg_multi(&me.fd, &me.buf_ring) {
+                    Ok(op) => me.op.insert(op),
+                    Err(e) => {
+                        me.done = true;
+                        return Poll::Ready(Some(Err(e)));
+                    }
+                },
+            };
+
+            let (result, more) = match ready!(op.poll_next_datagram(cx)) {
+                Some(next) => next,
+                None => {
+                    me.op = None;
+                    continue;
+                }
+            };
+
+            if !more {
+                // The kernel terminated the operation, re-arm on the next poll
+                me.op = None;
+
+                if let Err(e) = &result {
+                    me.done = e.raw_os_error() != Some(libc::ENOBUFS);
+                }
+            }
+
+            return Poll::Ready(Some(result));
+        }
+    }
+}
+
+impl Drop for RecvMsgMultiStream {
+    fn drop(&mut self) {
+        // A multishot operation only terminates on its own on errors.
+        if let Some(op) = &self.op {
+            op.cancel();
+        }
+    }
+}
@@ -0,0 +1,59 @@
+use crate::{
+    buf::{BufRing, BufSlot},
+    driver::{Op, SharedFd},
+};
+use io_uring::{cqueue, squeue};
+use std::io;
+
+pub(crate) struct RecvSelect {
+    /// Holds a strong ref to the FD, preventing the socket from being closed
+    /// while the operation is in-flight.
+    #[allow(dead_code)]
+    fd: SharedFd,
+
+    /// Ring the kernel picks the buffer from.
+    buf_ring: BufRing,
+}
+
+impl Op<RecvSelect> {
+    /// Submit a `recv(2)` on a connected socket, into a buffer selected from
+    /// `buf_ring` once data arrives.
+    pub(crate) fn recv_select(fd: &SharedFd, buf_ring: &BufRing) -> io::Result<Op<RecvSelect>> {
+        use io_uring::opcode;
+
+        Op::submit_with_buf_ring(
+            RecvSelect {
+                fd: fd.clone(),
+                buf_ring: buf_ring.clone(),
+            },
+            buf_ring,
+            |recv| {
+                let len = recv.buf_ring.buf_len() as u32;
+                with_fd!(fd, |fd| opcode::Recv::new(fd, std::ptr::null_mut(), len)
+                    .buf_group(recv.buf_ring.bgid())
+                    .build()
+                    .flags(squeue::Flags::BUFFER_SELECT))
+            },
+        )
+    }
+
+    /// Returns the received data, or `None` at the end of the stream.
+    pub(crate) async fn recv(self) -> io::Result<Option<BufSlot>> {
+        let complete = self.await;
+        let buf_ring = complete.data.buf_ring;
+        let n = complete.result? as usize;
+
+        // The kernel does not pick a buffer when it has nothing to write
+        let bid = match cqueue::buffer_select(complete.flags) {
+            Some(bid) => bid,
+            None => return Ok(None),
+        };
+
+        if n == 0 {
+            buf_ring.recycle(bid);
+            return Ok(None);
+        }
+
+        Ok(Some(BufSlot::new(buf_ring, bid, n)))
+    }
+}
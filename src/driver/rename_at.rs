@@ -0,0 +1,54 @@
+use crate::driver::{self, Op, SharedFd};
+
+use std::ffi::CString;
+use std::io;
+use std::path::Path;
+
+/// Rename a path, each side relative to a directory, or to the current
+/// working directory of the caller's process.
+pub(crate) struct RenameAt {
+    /// Hold strong refs to the FDs of the directories, if any.
+    #[allow(dead_code)]
+    dirs: (Option<SharedFd>, Option<SharedFd>),
+
+    from: CString,
+    to: CString,
+}
+
+impl Op<RenameAt> {
+    /// Submit a request to rename `from` to `to`, with the `RENAME_*` flags
+    /// of `renameat2(2)`.
+    pub(crate) fn rename_at(
+        from_dir: Option<&SharedFd>,
+        from: &Path,
+        to_dir: Option<&SharedFd>,
+        to: &Path,
+        flags: u32,
+    ) -> io::Result<Op<RenameAt>> {
+        use io_uring::{opcode, types};
+
+        let from = driver::util::cstr(from)?;
+        let to = driver::util::cstr(to)?;
+        let (from_dirfd, to_dirfd) = (driver::util::dirfd(from_dir), driver::util::dirfd(to_dir));
+
+        Op::submit_with(
+            RenameAt {
+                dirs: (from_dir.cloned(), to_dir.cloned()),
+                from,
+                to,
+            },
+            |rename| {
+                // The strings are held by the operation state until the
+                // operation completes.
+                opcode::RenameAt::new(
+                    types::Fd(from_dirfd),
+                    rename.from.as_ptr(),
+                    types::Fd(to_dirfd),
+                    rename.to.as_ptr(),
+                )
+                .flags(flags)
+                .build()
+            },
+        )
+    }
+}
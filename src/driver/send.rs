@@ -0,0 +1,56 @@
+use crate::{
+    buf::IoBuf,
+    driver::{Op, SharedFd},
+    BufResult,
+};
+use std::{
+    io,
+    task::{Context, Poll},
+};
+
+pub(crate) struct Send<T> {
+    /// Holds a strong ref to the FD, preventing the socket from being closed
+    /// while the operation is in-flight.
+    #[allow(dead_code)]
+    fd: SharedFd,
+
+    pub(crate) buf: T,
+}
+
+impl<T: IoBuf> Op<Send<T>> {
+    /// Submit a `send(2)` on a connected socket. `flags` are the `MSG_*`
+    /// flags passed to the kernel.
+    pub(crate) fn send_with_flags(fd: &SharedFd, buf: T, flags: i32) -> io::Result<Op<Send<T>>> {
+        use io_uring::opcode;
+
+        Op::submit_with(
+            Send {
+                fd: fd.clone(),
+                buf,
+            },
+            |send| {
+                // Get raw buffer info
+                let ptr = send.buf.stable_ptr();
+                let len = send.buf.bytes_init();
+
+                with_fd!(fd, |fd| opcode::Send::new(fd, ptr, len as _)
+                    .flags(flags)
+                    .build())
+            },
+        )
+    }
+
+    pub(crate) async fn send(mut self) -> BufResult<usize, T> {
+        use crate::future::poll_fn;
+
+        poll_fn(move |cx| self.poll_send(cx)).await
+    }
+
+    pub(crate) fn poll_send(&mut self, cx: &mut Context<'_>) -> Poll<BufResult<usize, T>> {
+        use std::future::Future;
+        use std::pin::Pin;
+
+        let complete = ready!(Pin::new(self).poll(cx));
+        Poll::Ready((complete.result.map(|v| v as _), complete.data.buf))
+    }
+}
@@ -0,0 +1,93 @@
+use crate::buf::{self, IoVectoredBuf};
+use crate::driver::{CmsgBuf, Op, SharedFd};
+use socket2::SockAddr;
+use std::io;
+use std::task::{Context, Poll};
+
+/// Outcome of a [`SendMsg`]: the number of bytes sent, along with the
+/// buffers and the control messages.
+pub(crate) type SendMsgResult<T> = (io::Result<usize>, T, Option<CmsgBuf>);
+
+/// A `sendmsg(2)` owning everything the message header points to: the
+/// buffers and their iovecs, the destination address, and the control
+/// messages. They stay in place until the operation completes.
+pub(crate) struct SendMsg<T> {
+    #[allow(dead_code)]
+    fd: SharedFd,
+    pub(crate) bufs: T,
+    #[allow(dead_code)]
+    iovs: Vec<libc::iovec>,
+    #[allow(dead_code)]
+    socket_addr: Option<Box<SockAddr>>,
+    control: Option<CmsgBuf>,
+    msghdr: Box<libc::msghdr>,
+}
+
+impl<T: IoVectoredBuf> Op<SendMsg<T>> {
+    /// Submit a `sendmsg(2)` of the initialized bytes of `bufs`, gathered in
+    /// order. `socket_addr` is the destination of unconnected sockets, and
+    /// `control` the control messages sent along. `flags` are the `MSG_*`
+    /// flags passed to the kernel.
+    pub(crate) fn send_msg(
+        fd: &SharedFd,
+        bufs: T,
+        socket_addr: Option<SockAddr>,
+        mut control: Option<CmsgBuf>,
+        flags: i32,
+    ) -> io::Result<Op<SendMsg<T>>> {
+        use io_uring::opcode;
+
+        let iovs = buf::iovecs(&bufs);
+
+        let socket_addr = socket_addr.map(Box::new);
+
+        let mut msghdr: Box<libc::msghdr> = Box::new(unsafe { std::mem::zeroed() });
+        msghdr.msg_iov = iovs.as_ptr() as *mut _;
+        msghdr.msg_iovlen = iovs.len() as _;
+
+        if let Some(socket_addr) = socket_addr.as_ref() {
+            msghdr.msg_name = socket_addr.as_ptr() as *mut libc::c_void;
+            msghdr.msg_namelen = socket_addr.len();
+        }
+
+        if let Some(control) = control.as_mut() {
+            msghdr.msg_control = control.as_mut_ptr().cast();
+            msghdr.msg_controllen = control.len() as _;
+        }
+
+        Op::submit_with(
+            SendMsg {
+                fd: fd.clone(),
+                bufs,
+                iovs,
+                socket_addr,
+                control,
+                msghdr,
+            },
+            |send_msg| {
+                with_fd!(send_msg.fd, |fd| opcode::SendMsg::new(
+                    fd,
+                    send_msg.msghdr.as_ref() as *const _
+                )
+                .flags(flags as _)
+                .build())
+            },
+        )
+    }
+
+    pub(crate) async fn send(mut self) -> SendMsgResult<T> {
+        use crate::future::poll_fn;
+
+        poll_fn(move |cx| self.poll_send(cx)).await
+    }
+
+    pub(crate) fn poll_send(&mut self, cx: &mut Context<'_>) -> Poll<SendMsgResult<T>> {
+        use std::future::Future;
+        use std::pin::Pin;
+
+        let complete = ready!(Pin::new(self).poll(cx));
+        let data = complete.data;
+
+        Poll::Ready((complete.result.map(|n| n as usize), data.bufs, data.control))
+    }
+}
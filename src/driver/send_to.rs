@@ -1,5 +1,5 @@
 use crate::buf::IoBuf;
-use crate::driver::{Op, SharedFd};
+use crate::driver::{CmsgBuf, LinkResult, Op, SharedFd};
 use crate::BufResult;
 use socket2::SockAddr;
 use std::io::IoSlice;
@@ -14,22 +14,24 @@ pub(crate) struct SendTo<T> {
     io_slices: Vec<IoSlice<'static>>,
     #[allow(dead_code)]
     socket_addr: Box<SockAddr>,
+    /// Control messages sent along with the datagram.
+    #[allow(dead_code)]
+    control: Option<CmsgBuf>,
     pub(crate) msghdr: Box<libc::msghdr>,
 }
 
-impl<T: IoBuf> Op<SendTo<T>> {
-    pub(crate) fn send_to(
+impl<T: IoBuf> SendTo<T> {
+    fn new(
         fd: &SharedFd,
         buf: T,
-        socket_addr: SocketAddr,
-    ) -> io::Result<Op<SendTo<T>>> {
-        use io_uring::{opcode, types};
-
+        socket_addr: impl Into<SockAddr>,
+        mut control: Option<CmsgBuf>,
+    ) -> SendTo<T> {
         let io_slices = vec![IoSlice::new(unsafe {
             std::slice::from_raw_parts(buf.stable_ptr(), buf.bytes_init())
         })];
 
-        let socket_addr = Box::new(SockAddr::from(socket_addr));
+        let socket_addr = Box::new(socket_addr.into());
 
         let mut msghdr: Box<libc::msghdr> = Box::new(unsafe { std::mem::zeroed() });
         msghdr.msg_iov = io_slices.as_ptr() as *mut _;
@@ -37,24 +39,85 @@ impl<T: IoBuf> Op<SendTo<T>> {
         msghdr.msg_name = socket_addr.as_ptr() as *mut libc::c_void;
         msghdr.msg_namelen = socket_addr.len();
 
+        if let Some(control) = control.as_mut() {
+            msghdr.msg_control = control.as_mut_ptr().cast();
+            msghdr.msg_controllen = control.len() as _;
+        }
+
+        SendTo {
+            fd: fd.clone(),
+            buf,
+            io_slices,
+            socket_addr,
+            control,
+            msghdr,
+        }
+    }
+
+    fn build(&self) -> io_uring::squeue::Entry {
+        use io_uring::opcode;
+
+        with_fd!(self.fd, |fd| opcode::SendMsg::new(
+            fd,
+            self.msghdr.as_ref() as *const _,
+        )
+        .build())
+    }
+}
+
+impl<T: IoBuf> Op<SendTo<T>> {
+    /// Submit a `sendmsg(2)` to `socket_addr`, which may be of any family.
+    pub(crate) fn send_to(
+        fd: &SharedFd,
+        buf: T,
+        socket_addr: impl Into<SockAddr>,
+    ) -> io::Result<Op<SendTo<T>>> {
+        Op::submit_with(SendTo::new(fd, buf, socket_addr, None), |send_to| {
+            send_to.build()
+        })
+    }
+
+    /// Submit a `sendmsg(2)` carrying the control messages in `control`.
+    pub(crate) fn send_to_with_control(
+        fd: &SharedFd,
+        buf: T,
+        socket_addr: SocketAddr,
+        control: CmsgBuf,
+    ) -> io::Result<Op<SendTo<T>>> {
         Op::submit_with(
-            SendTo {
-                fd: fd.clone(),
-                buf,
-                io_slices,
-                socket_addr,
-                msghdr,
-            },
-            |send_to| {
-                opcode::SendMsg::new(
-                    types::Fd(send_to.fd.raw_fd()),
-                    send_to.msghdr.as_ref() as *const _,
-                )
-                .build()
-            },
+            SendTo::new(fd, buf, socket_addr, Some(control)),
+            |send_to| send_to.build(),
         )
     }
 
+    /// Submit a chain of linked `sendmsg(2)`, one for each packet. If a send
+    /// fails, the remaining operations complete with `ECANCELED`.
+    pub(crate) fn send_to_linked(
+        fd: &SharedFd,
+        packets: Vec<(T, SocketAddr)>,
+    ) -> LinkResult<SendTo<T>, T> {
+        let data = packets
+            .into_iter()
+            .map(|(buf, socket_addr)| SendTo::new(fd, buf, socket_addr, None))
+            .collect();
+
+        Op::submit_linked_with(data, |_, send_to| send_to.build())
+            .map_err(|(e, data)| (e, data.into_iter().map(|s| s.buf).collect()))
+    }
+
+    /// Submit a batch of independent `sendmsg(2)`, one for each packet.
+    pub(crate) fn send_to_batch(
+        fd: &SharedFd,
+        packets: Vec<(T, SocketAddr)>,
+    ) -> Vec<Op<SendTo<T>>> {
+        let data = packets
+            .into_iter()
+            .map(|(buf, socket_addr)| SendTo::new(fd, buf, socket_addr, None))
+            .collect();
+
+        Op::submit_batch_with(data, |send_to| send_to.build())
+    }
+
     pub(crate) async fn send(mut self) -> BufResult<usize, T> {
         use crate::future::poll_fn;
 
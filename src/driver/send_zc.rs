@@ -0,0 +1,58 @@
+use crate::{
+    buf::IoBuf,
+    driver::{Op, SharedFd},
+    BufResult,
+};
+use std::io;
+
+/// Set on the completion telling the buffer of a zero copy send is no longer
+/// used by the kernel.
+const IORING_CQE_F_NOTIF: u32 = 1 << 3;
+
+pub(crate) struct SendZc<T> {
+    /// Holds a strong ref to the FD, preventing the socket from being closed
+    /// while the operation is in-flight.
+    #[allow(dead_code)]
+    fd: SharedFd,
+
+    pub(crate) buf: T,
+}
+
+impl<T: IoBuf> Op<SendZc<T>> {
+    /// Submit a zero copy send on a connected socket. Requires Linux 6.0.
+    pub(crate) fn send_zc(fd: &SharedFd, buf: T) -> io::Result<Op<SendZc<T>>> {
+        use io_uring::opcode;
+
+        Op::submit_with(
+            SendZc {
+                fd: fd.clone(),
+                buf,
+            },
+            |send| {
+                // Get raw buffer info
+                let ptr = send.buf.stable_ptr();
+                let len = send.buf.bytes_init();
+
+                with_fd!(fd, |fd| opcode::SendZc::new(fd, ptr, len as _).build())
+            },
+        )
+    }
+
+    /// Waits for the result of the send, then for the notification that the
+    /// kernel released the buffer, which is only handed back then.
+    pub(crate) async fn send(mut self) -> BufResult<usize, T> {
+        use crate::future::poll_fn;
+
+        // The send posts its result with `IORING_CQE_F_MORE` when a
+        // notification follows. A failed send posts no notification.
+        let mut result = Err(io::ErrorKind::UnexpectedEof.into());
+        while let Some((res, flags)) = poll_fn(|cx| self.poll_next(cx)).await {
+            if flags & IORING_CQE_F_NOTIF == 0 {
+                result = res.map(|n| n as usize);
+            }
+        }
+
+        let send = self.take_data().expect("unexpected operation state");
+        (result, send.buf)
+    }
+}
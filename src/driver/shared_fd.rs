@@ -1,10 +1,11 @@
-use crate::driver::{Close, Op};
+use crate::driver::{self, Close, FixedFile, Op};
 use crate::future::poll_fn;
 
 use std::cell::RefCell;
-use std::os::unix::io::{FromRawFd, RawFd};
+use std::io;
+use std::os::unix::io::RawFd;
 use std::rc::Rc;
-use std::task::Waker;
+use std::task::{Poll, Waker};
 
 // Tracks in-flight operations on a file descriptor. Ensures all in-flight
 // operations complete before submitting the close.
@@ -17,6 +18,9 @@ struct Inner {
     // Open file descriptor
     fd: RawFd,
 
+    // Slot of the FD in the registered file table, if it is registered.
+    fixed: RefCell<Option<FixedFile>>,
+
     // Waker to notify when the close operation completes.
     state: RefCell<State>,
 }
@@ -40,6 +44,7 @@ impl SharedFd {
         SharedFd {
             inner: Rc::new(Inner {
                 fd,
+                fixed: RefCell::new(None),
                 state: RefCell::new(State::Init),
             }),
         }
@@ -50,55 +55,118 @@ impl SharedFd {
         self.inner.fd
     }
 
+    /// Returns the slot of the FD in the registered file table, if it is
+    /// registered.
+    pub(crate) fn fixed_slot(&self) -> Option<u32> {
+        self.inner.fixed.borrow().as_ref().map(FixedFile::slot)
+    }
+
+    /// Registers the FD in the file table of the driver, so operations name
+    /// it by its slot. Does nothing if it is already registered. The slot is
+    /// released when the FD is closed.
+    pub(crate) fn register(&self) -> io::Result<()> {
+        let mut fixed = self.inner.fixed.borrow_mut();
+        if fixed.is_none() {
+            *fixed = Some(driver::register_file(self.inner.fd)?);
+        }
+
+        Ok(())
+    }
+
+    /// Hands back the RawFd without closing it, if there are no in-flight
+    /// operations on it. Otherwise, returns `self`.
+    pub(crate) fn try_into_raw_fd(self) -> Result<RawFd, SharedFd> {
+        // Move the reference out, without running `Drop`
+        let this = std::mem::ManuallyDrop::new(self);
+        // Safety: `this` is not used after, nor dropped.
+        let inner = unsafe { std::ptr::read(&this.inner) };
+
+        match Rc::try_unwrap(inner) {
+            Ok(mut inner) => {
+                // The slot would keep the file open behind the caller's back
+                inner.fixed.get_mut().take();
+
+                // Prevent `Drop` from closing the FD
+                *RefCell::get_mut(&mut inner.state) = State::Closed;
+                Ok(inner.fd)
+            }
+            Err(inner) => Err(SharedFd { inner }),
+        }
+    }
+
     /// An FD cannot be closed until all in-flight operation have completed.
     /// This prevents bugs where in-flight reads could operate on the incorrect
     /// file descriptor.
     ///
-    /// TO model this, if there are no in-flight operations, then
-    pub(crate) async fn close(mut self) {
-        // Get a mutable reference to Inner, indicating there are no
-        // in-flight operations on the FD.
+    /// To model this, the close is only submitted once this is the last
+    /// reference to the FD, the operations holding the others having
+    /// completed. Returns the result of the close.
+    pub(crate) async fn close(mut self) -> io::Result<()> {
+        // Wait for the in-flight operations to release the FD. The last one
+        // to do so wakes us up.
+        poll_fn(|cx| {
+            if Rc::get_mut(&mut self.inner).is_some() {
+                return Poll::Ready(());
+            }
+
+            *self.inner.state.borrow_mut() = State::Waiting(Some(cx.waker().clone()));
+            Poll::Pending
+        })
+        .await;
+
         if let Some(inner) = Rc::get_mut(&mut self.inner) {
-            // Submit the close operation
-            inner.submit_close_op();
+            inner.submit_close_op()?;
         }
 
-        self.inner.closed().await;
+        self.inner.closed().await
+    }
+}
+
+impl Drop for SharedFd {
+    fn drop(&mut self) {
+        // Wake up a `close` waiting for this reference to go away
+        if Rc::strong_count(&self.inner) == 2 {
+            if let State::Waiting(Some(waker)) = &*self.inner.state.borrow() {
+                waker.wake_by_ref();
+            }
+        }
     }
 }
 
 impl Inner {
-    /// If there are no in-flight operations, submit the operation.
-    fn submit_close_op(&mut self) {
+    /// If there are no in-flight operations, submit the operation. Returns the
+    /// result of the close if it was done synchronously.
+    fn submit_close_op(&mut self) -> io::Result<()> {
+        // Release the slot first, it holds a reference to the file
+        self.fixed.get_mut().take();
+
         // Close the FD
         let state = RefCell::get_mut(&mut self.state);
 
         // Submit a close operation
-        *state = match Op::close(self.fd) {
-            Ok(op) => State::Closing(op),
+        match Op::close(self.fd) {
+            Ok(op) => {
+                *state = State::Closing(op);
+                Ok(())
+            }
             Err(_) => {
                 // Submitting the operation failed, we fall back on a
                 // synchronous `close`. This is safe as, at this point, we
                 // guarantee all in-flight operations have completed. The most
-                // common cause for an error is attempting to close the FD while
-                // off runtime.
-                //
-                // This is done by initializing a `File` with the FD and
-                // dropping it.
-                //
-                // TODO: Should we warn?
-                let _ = unsafe { std::fs::File::from_raw_fd(self.fd) };
-
-                State::Closed
+                // common causes for an error are attempting to close the FD
+                // while off runtime, or from within the driver when the last
+                // reference was held by a completed operation, and kernels
+                // lacking `IORING_OP_CLOSE`.
+                *state = State::Closed;
+                syscall!(close(self.fd)).map(|_| ())
             }
-        };
+        }
     }
 
-    /// Completes when the FD has been closed.
-    async fn closed(&self) {
+    /// Completes when the FD has been closed, with the result of the close.
+    async fn closed(&self) -> io::Result<()> {
         use std::future::Future;
         use std::pin::Pin;
-        use std::task::Poll;
 
         poll_fn(|cx| {
             let mut state = self.state.borrow_mut();
@@ -120,15 +188,14 @@ impl Inner {
                     Poll::Pending
                 }
                 State::Closing(op) => {
-                    // Nothing to do if the close opeation failed.
-                    let _ = ready!(Pin::new(op).poll(cx));
+                    let completion = ready!(Pin::new(op).poll(cx));
                     *state = State::Closed;
-                    Poll::Ready(())
+                    Poll::Ready(completion.result.map(|_| ()))
                 }
-                State::Closed => Poll::Ready(()),
+                State::Closed => Poll::Ready(Ok(())),
             }
         })
-        .await;
+        .await
     }
 }
 
@@ -137,7 +204,8 @@ impl Drop for Inner {
         // Submit the close operation, if needed
         match RefCell::get_mut(&mut self.state) {
             State::Init | State::Waiting(..) => {
-                self.submit_close_op();
+                // Nobody is left to observe the result
+                let _ = self.submit_close_op();
             }
             _ => {}
         }
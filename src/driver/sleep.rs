@@ -0,0 +1,61 @@
+use crate::driver::Op;
+
+use std::io;
+use std::time::{Duration, Instant};
+
+use io_uring::{opcode, types};
+
+pub(crate) struct Sleep {
+    /// Referenced by the kernel until the timeout completes.
+    timespec: Box<types::Timespec>,
+}
+
+impl Op<Sleep> {
+    /// Submit an `IORING_OP_TIMEOUT` completing once `duration` elapsed.
+    pub(crate) fn sleep(duration: Duration) -> io::Result<Op<Sleep>> {
+        Op::timeout(timespec(duration), types::TimeoutFlags::empty())
+    }
+
+    /// Submit an `IORING_OP_TIMEOUT` completing at `deadline`, with
+    /// `IORING_TIMEOUT_ABS` so the time spent submitting does not push the
+    /// deadline back.
+    pub(crate) fn sleep_until(deadline: Instant) -> io::Result<Op<Sleep>> {
+        // `Instant` is based on `CLOCK_MONOTONIC`, the clock of absolute
+        // timeouts, but does not expose its value.
+        let mut now = libc::timespec {
+            tv_sec: 0,
+            tv_nsec: 0,
+        };
+        syscall!(clock_gettime(libc::CLOCK_MONOTONIC, &mut now))?;
+        let now = Duration::new(now.tv_sec as u64, now.tv_nsec as u32);
+        let deadline = now + deadline.saturating_duration_since(Instant::now());
+
+        Op::timeout(timespec(deadline), types::TimeoutFlags::ABS)
+    }
+
+    fn timeout(timespec: types::Timespec, flags: types::TimeoutFlags) -> io::Result<Op<Sleep>> {
+        Op::submit_with(
+            Sleep {
+                timespec: Box::new(timespec),
+            },
+            |sleep| opcode::Timeout::new(&*sleep.timespec).flags(flags).build(),
+        )
+    }
+
+    pub(crate) async fn wait(self) -> io::Result<()> {
+        let complete = self.await;
+
+        // An expired timeout completes with `ETIME`
+        match complete.result {
+            Ok(_) => Ok(()),
+            Err(e) if e.raw_os_error() == Some(libc::ETIME) => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+fn timespec(duration: Duration) -> types::Timespec {
+    types::Timespec::new()
+        .sec(duration.as_secs())
+        .nsec(duration.subsec_nanos())
+}
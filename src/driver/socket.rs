@@ -1,15 +1,22 @@
-use socket2::Domain;
+use socket2::{Domain, SockAddr};
 
 use crate::{
-    buf::{IoBuf, IoBufMut},
-    driver::{Op, SharedFd},
+    buf::{self, BufRing, BufSlot, IoBuf, IoBufMut, IoVectoredBuf, IoVectoredBufMut},
+    driver::{
+        self, AcceptMultiStream, CancelableRecv, CmsgBuf, FixedBuf, Op, Read, RecvGuard,
+        RecvMsgMultiStream, RecvReturning, SharedFd, Write, Writev,
+    },
 };
 use std::{
+    convert::TryFrom,
     io,
-    net::SocketAddr,
-    os::unix::io::{AsRawFd, IntoRawFd, RawFd},
+    net::{Shutdown, SocketAddr},
+    os::unix::io::{AsRawFd, FromRawFd, IntoRawFd, RawFd},
     path::Path,
+    task::{Context, Poll},
+    time::Duration,
 };
+use tokio::io::ReadBuf;
 
 #[derive(Clone)]
 pub(crate) struct Socket {
@@ -17,6 +24,34 @@ pub(crate) struct Socket {
     fd: SharedFd,
 }
 
+/// Options applied to a socket before it is bound.
+#[derive(Clone, Copy)]
+pub(crate) struct BindOptions {
+    /// Sets `SO_REUSEADDR`.
+    pub(crate) reuse_address: bool,
+
+    /// Sets `SO_REUSEPORT`.
+    pub(crate) reuse_port: bool,
+
+    /// Sets `IPV6_V6ONLY` on IPv6 sockets. When unset, the system default
+    /// applies.
+    pub(crate) only_v6: Option<bool>,
+
+    /// Sets `IP_TRANSPARENT` and `IP_FREEBIND`, or their IPv6 counterparts.
+    pub(crate) transparent: bool,
+}
+
+impl Default for BindOptions {
+    fn default() -> BindOptions {
+        BindOptions {
+            reuse_address: true,
+            reuse_port: true,
+            only_v6: None,
+            transparent: false,
+        }
+    }
+}
+
 pub(crate) fn get_domain(socket_addr: SocketAddr) -> libc::c_int {
     match socket_addr {
         SocketAddr::V4(_) => libc::AF_INET,
@@ -33,6 +68,44 @@ impl Socket {
         Ok(Socket { fd })
     }
 
+    /// Creates a socket bound to `local`, to be connected to `remote`, with
+    /// `IP_TRANSPARENT` and `IP_FREEBIND` set before binding if `transparent`
+    /// is. Fails with `InvalidInput` if the addresses are of different
+    /// families.
+    pub(crate) fn new_bound(
+        local: SocketAddr,
+        remote: SocketAddr,
+        socket_type: libc::c_int,
+        transparent: bool,
+    ) -> io::Result<Socket> {
+        if local.is_ipv4() != remote.is_ipv4() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "local and remote addresses are of different families",
+            ));
+        }
+
+        let socket = Socket::new(local, socket_type)?;
+
+        if transparent {
+            socket.set_ip_flag(local.is_ipv6(), TRANSPARENT, true)?;
+            socket.set_ip_flag(local.is_ipv6(), FREEBIND, true)?;
+        }
+
+        // Leave the choice of the port to the connect, which can then share a
+        // port between connections to different peers
+        if local.port() == 0 && socket_type == libc::SOCK_STREAM {
+            socket.setsockopt(
+                libc::SOL_IP,
+                libc::IP_BIND_ADDRESS_NO_PORT,
+                1 as libc::c_int,
+            )?;
+        }
+
+        socket2::SockRef::from(&socket).bind(&local.into())?;
+        Ok(socket)
+    }
+
     pub(crate) fn new_unix(socket_type: libc::c_int) -> io::Result<Socket> {
         let socket_type = socket_type | libc::SOCK_CLOEXEC;
         let domain = libc::AF_UNIX;
@@ -46,6 +119,173 @@ impl Socket {
         op.write().await
     }
 
+    pub(crate) async fn write_all<T: IoBuf>(&self, mut buf: T) -> crate::BufResult<(), T> {
+        let len = buf.bytes_init();
+        let mut written = 0;
+
+        while written < len {
+            let (res, slice) = self.write(buf.slice(written..len)).await;
+            buf = slice.into_inner();
+
+            match res {
+                Ok(0) => {
+                    return (
+                        Err(io::Error::new(
+                            io::ErrorKind::WriteZero,
+                            "failed to write whole buffer",
+                        )),
+                        buf,
+                    )
+                }
+                Ok(n) => written += n,
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return (Err(e), buf),
+            }
+        }
+
+        (Ok(()), buf)
+    }
+
+    pub(crate) async fn read_exact<T: IoBufMut>(&self, mut buf: T) -> crate::BufResult<(), T> {
+        let len = buf.bytes_total();
+        let mut filled = 0;
+
+        while filled < len {
+            let (res, slice) = self.read(buf.slice(filled..len)).await;
+            buf = slice.into_inner();
+
+            match res {
+                Ok(0) => {
+                    return (
+                        Err(io::Error::new(
+                            io::ErrorKind::UnexpectedEof,
+                            "failed to fill whole buffer",
+                        )),
+                        buf,
+                    )
+                }
+                Ok(n) => filled += n,
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return (Err(e), buf),
+            }
+        }
+
+        (Ok(()), buf)
+    }
+
+    pub(crate) async fn write_fixed(&self, buf: FixedBuf) -> crate::BufResult<usize, FixedBuf> {
+        let op = Op::write_fixed_at(&self.fd, buf, 0).unwrap();
+        op.write().await
+    }
+
+    pub(crate) async fn writev<T: IoVectoredBuf>(&self, bufs: T) -> crate::BufResult<usize, T> {
+        if let Err(e) = buf::check_iov_max(&bufs) {
+            return (Err(e), bufs);
+        }
+
+        let op = Op::writev_at(&self.fd, bufs, 0).unwrap();
+        op.writev().await
+    }
+
+    /// Polls a write of `buf`, driving the in-flight operation stored in `op`.
+    ///
+    /// When no operation is in flight, the contents of `buf` are copied into
+    /// an owned buffer and a new write is submitted. The caller is expected to
+    /// pass the same data again until the write completes, as required by
+    /// `AsyncWrite::poll_write`.
+    pub(crate) fn poll_write(
+        &self,
+        op: &mut Option<Op<Write<Vec<u8>>>>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let in_flight = match op {
+            Some(in_flight) => in_flight,
+            None => op.insert(Op::write_at(&self.fd, buf.to_vec(), 0)?),
+        };
+
+        let (res, _) = ready!(in_flight.poll_write(cx));
+        *op = None;
+
+        Poll::Ready(res)
+    }
+
+    /// Polls a vectored write of `bufs`, driving the in-flight operation
+    /// stored in `op`.
+    ///
+    /// See [`Socket::poll_write`] for the buffer handling.
+    pub(crate) fn poll_write_vectored(
+        &self,
+        op: &mut Option<Op<Writev<Vec<Vec<u8>>>>>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        let in_flight = match op {
+            Some(in_flight) => in_flight,
+            None => {
+                let bufs = bufs.iter().map(|buf| buf.to_vec()).collect();
+                op.insert(Op::writev_at(&self.fd, bufs, 0)?)
+            }
+        };
+
+        let (res, _) = ready!(in_flight.poll_writev(cx));
+        *op = None;
+
+        Poll::Ready(res)
+    }
+
+    pub(crate) async fn send<T: IoBuf>(&self, buf: T) -> crate::BufResult<usize, T> {
+        self.send_with_flags(buf, 0).await
+    }
+
+    pub(crate) async fn send_with_flags<T: IoBuf>(
+        &self,
+        buf: T,
+        flags: i32,
+    ) -> crate::BufResult<usize, T> {
+        let op = Op::send_with_flags(&self.fd, buf, flags).unwrap();
+        op.send().await
+    }
+
+    pub(crate) async fn send_zc<T: IoBuf>(&self, buf: T) -> crate::BufResult<usize, T> {
+        // Fall back to copying the buffer on kernels without zero copy sends
+        if !driver::probe().is_ok_and(|probe| probe.supports_send_zc()) {
+            return self.send(buf).await;
+        }
+
+        let op = Op::send_zc(&self.fd, buf).unwrap();
+        op.send().await
+    }
+
+    /// Sends the initialized bytes of `bufs` as one message, to
+    /// `socket_addr` if the socket is not connected, along with the control
+    /// messages in `control`. `flags` are the `MSG_*` flags passed to the
+    /// kernel.
+    pub(crate) async fn send_msg<T: IoVectoredBuf>(
+        &self,
+        bufs: T,
+        socket_addr: Option<SockAddr>,
+        control: Option<CmsgBuf>,
+        flags: i32,
+    ) -> driver::SendMsgResult<T> {
+        let op = Op::send_msg(&self.fd, bufs, socket_addr, control, flags).unwrap();
+        op.send().await
+    }
+
+    /// Receives a message into `bufs`, returning its source address if
+    /// `want_addr` is set, and its control messages if given a `control`
+    /// buffer. `flags` are the `MSG_*` flags passed to the kernel.
+    pub(crate) async fn recv_msg<T: IoVectoredBufMut>(
+        &self,
+        bufs: T,
+        want_addr: bool,
+        control: Option<CmsgBuf>,
+        flags: i32,
+    ) -> driver::RecvMsgResult<T> {
+        let op = Op::recv_msg(&self.fd, bufs, want_addr, control, flags).unwrap();
+        op.recv().await
+    }
+
     pub(crate) async fn send_to<T: IoBuf>(
         &self,
         buf: T,
@@ -55,21 +295,237 @@ impl Socket {
         op.send().await
     }
 
+    /// Sends `buf` to an address of any family.
+    pub(crate) async fn send_to_sockaddr<T: IoBuf>(
+        &self,
+        buf: T,
+        socket_addr: socket2::SockAddr,
+    ) -> crate::BufResult<usize, T> {
+        let op = Op::send_to(&self.fd, buf, socket_addr).unwrap();
+        op.send().await
+    }
+
     pub(crate) async fn read<T: IoBufMut>(&self, buf: T) -> crate::BufResult<usize, T> {
         let op = Op::read_at(&self.fd, buf, 0).unwrap();
         op.read().await
     }
 
+    pub(crate) async fn read_fixed(&self, buf: FixedBuf) -> crate::BufResult<usize, FixedBuf> {
+        let op = Op::read_fixed_at(&self.fd, buf, 0).unwrap();
+        op.read().await
+    }
+
+    pub(crate) async fn recv_ring(&self, buf_ring: &BufRing) -> io::Result<Option<BufSlot>> {
+        Op::recv_select(&self.fd, buf_ring)?.recv().await
+    }
+
+    pub(crate) async fn readv<T: IoVectoredBufMut>(&self, bufs: T) -> crate::BufResult<usize, T> {
+        if let Err(e) = buf::check_iov_max(&bufs) {
+            return (Err(e), bufs);
+        }
+
+        let op = Op::readv_at(&self.fd, bufs, 0).unwrap();
+        op.readv().await
+    }
+
+    /// Polls a read into `buf`, driving the in-flight operation stored in
+    /// `op`.
+    ///
+    /// When no operation is in flight, a new one is submitted with a buffer
+    /// sized to the space remaining in `buf`. The operation, and the buffer
+    /// it owns, is kept in `op` across polls so the memory referenced by the
+    /// kernel is never released while the read is pending. Once the read
+    /// completes, as many bytes as fit are copied into `buf`.
+    pub(crate) fn poll_read(
+        &self,
+        op: &mut Option<Op<Read<Vec<u8>>>>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        if op.is_none() && buf.remaining() == 0 {
+            return Poll::Ready(Ok(()));
+        }
+
+        let in_flight = match op {
+            Some(in_flight) => in_flight,
+            None => op.insert(Op::read_at(
+                &self.fd,
+                Vec::with_capacity(buf.remaining()),
+                0,
+            )?),
+        };
+
+        let (res, data) = ready!(in_flight.poll_read(cx));
+        *op = None;
+
+        let n = res?.min(buf.remaining());
+        buf.put_slice(&data[..n]);
+
+        Poll::Ready(Ok(()))
+    }
+
+    pub(crate) async fn send_to_with_control<T: IoBuf>(
+        &self,
+        buf: T,
+        socket_addr: SocketAddr,
+        control: CmsgBuf,
+    ) -> crate::BufResult<usize, T> {
+        let op = Op::send_to_with_control(&self.fd, buf, socket_addr, control).unwrap();
+        op.send().await
+    }
+
+    pub(crate) async fn send_to_batch<T: IoBuf>(
+        &self,
+        packets: Vec<(T, SocketAddr)>,
+    ) -> Vec<crate::BufResult<usize, T>> {
+        let ops = Op::send_to_batch(&self.fd, packets);
+
+        let mut results = Vec::with_capacity(ops.len());
+        for op in ops {
+            results.push(op.send().await);
+        }
+        results
+    }
+
+    pub(crate) async fn send_mmsg<T: IoBuf>(
+        &self,
+        packets: Vec<(T, SocketAddr)>,
+    ) -> (usize, Option<io::Error>, Vec<T>) {
+        let ops = match Op::send_to_linked(&self.fd, packets) {
+            Ok(ops) => ops,
+            Err((e, bufs)) => return (0, Some(e), bufs),
+        };
+
+        let mut sent = 0;
+        let mut bufs = Vec::with_capacity(ops.len());
+        let mut error = None;
+
+        // Every operation in the chain completes before its buffer is
+        // recovered, including the canceled ones.
+        for (i, op) in ops.into_iter().enumerate() {
+            let (res, buf) = op.send().await;
+            bufs.push(buf);
+
+            match res {
+                Ok(_) if sent == i => sent += 1,
+                // The first failure cuts the batch short, the following sends
+                // are canceled
+                Err(e) if sent == i => error = Some(e),
+                _ => {}
+            }
+        }
+
+        (sent, error, bufs)
+    }
+
+    pub(crate) async fn recv<T: IoBufMut>(&self, buf: T) -> crate::BufResult<usize, T> {
+        self.recv_with_flags(buf, 0).await
+    }
+
+    pub(crate) fn recv_cancelable<T: IoBufMut>(&self, buf: T) -> (CancelableRecv<T>, RecvGuard<T>) {
+        let (op, guard) = Op::<RecvReturning<T>>::recv_cancelable(&self.fd, buf).unwrap();
+        (CancelableRecv::new(op), guard)
+    }
+
+    pub(crate) async fn recv_with_flags<T: IoBufMut>(
+        &self,
+        buf: T,
+        flags: i32,
+    ) -> crate::BufResult<usize, T> {
+        let op = Op::recv_with_flags(&self.fd, buf, flags).unwrap();
+        op.recv().await
+    }
+
     pub(crate) async fn recv_from<T: IoBufMut>(
         &self,
         buf: T,
     ) -> crate::BufResult<(usize, SocketAddr), T> {
-        let op = Op::recv_from(&self.fd, buf).unwrap();
+        self.recv_from_with_flags(buf, 0).await
+    }
+
+    pub(crate) async fn recv_from_with_flags<T: IoBufMut>(
+        &self,
+        buf: T,
+        flags: i32,
+    ) -> crate::BufResult<(usize, SocketAddr), T> {
+        let op = Op::recv_from_with_flags(&self.fd, buf, flags as _).unwrap();
         op.recv().await
     }
 
-    pub(crate) async fn accept(&self) -> io::Result<(Socket, Option<SocketAddr>)> {
-        let op = Op::accept(&self.fd)?;
+    /// Receives a datagram from a peer of any family.
+    pub(crate) async fn recv_from_sockaddr<T: IoBufMut>(
+        &self,
+        buf: T,
+    ) -> crate::BufResult<(usize, socket2::SockAddr), T> {
+        let op = Op::recv_from_with_flags(&self.fd, buf, 0).unwrap();
+        op.recv_sockaddr().await
+    }
+
+    pub(crate) async fn recv_from_timeout<T: IoBufMut>(
+        &self,
+        buf: T,
+        timeout: Duration,
+    ) -> crate::BufResult<(usize, SocketAddr), T> {
+        let op = Op::recv_from_with_timeout(&self.fd, buf, timeout).unwrap();
+        let (res, buf) = op.recv().await;
+
+        // The linked timeout cancels the receive when it expires
+        let res = res.map_err(|e| match e.raw_os_error() {
+            Some(libc::ECANCELED) => io::Error::from(io::ErrorKind::TimedOut),
+            _ => e,
+        });
+
+        (res, buf)
+    }
+
+    pub(crate) async fn recv_mmsg<T: IoBufMut>(
+        &self,
+        bufs: Vec<T>,
+    ) -> (io::Result<usize>, Vec<(usize, SocketAddr)>, Vec<T>) {
+        let ops = match Op::recv_from_linked(&self.fd, bufs) {
+            Ok(ops) => ops,
+            Err((e, bufs)) => return (Err(e), vec![], bufs),
+        };
+
+        let mut received = Vec::with_capacity(ops.len());
+        let mut bufs = Vec::with_capacity(ops.len());
+        let mut error = None;
+
+        // Every operation in the chain completes, so all buffers are recovered.
+        for (i, op) in ops.into_iter().enumerate() {
+            let (res, buf) = op.recv().await;
+            bufs.push(buf);
+
+            match res {
+                Ok(datagram) if received.len() == i => received.push(datagram),
+                // Only the first receive waits, an error there fails the batch.
+                // Later ones fail with `EAGAIN` once the queue is drained, and
+                // cancel the rest of the chain.
+                Err(e) if i == 0 => error = Some(e),
+                _ => {}
+            }
+        }
+
+        match error {
+            Some(e) => (Err(e), received, bufs),
+            None => (Ok(received.len()), received, bufs),
+        }
+    }
+
+    pub(crate) async fn recv_from_with_control<T: IoBufMut>(
+        &self,
+        buf: T,
+        control: CmsgBuf,
+    ) -> (io::Result<(usize, SocketAddr)>, T, CmsgBuf) {
+        let op = Op::recv_from_with_control(&self.fd, buf, 0, control).unwrap();
+        op.recv_with_control().await
+    }
+
+    pub(crate) async fn accept_with_flags(
+        &self,
+        flags: i32,
+    ) -> io::Result<(Socket, Option<SocketAddr>)> {
+        let op = Op::accept(&self.fd, flags)?;
         let completion = op.await;
         let fd = completion.result?;
         let fd = SharedFd::new(fd as i32);
@@ -92,11 +548,38 @@ impl Socket {
         Ok(())
     }
 
+    pub(crate) async fn connect_timeout(
+        &self,
+        socket_addr: socket2::SockAddr,
+        timeout: Duration,
+    ) -> io::Result<()> {
+        let op = Op::connect_with_timeout(&self.fd, socket_addr, timeout)?;
+        let completion = op.await;
+
+        // The linked timeout cancels the connect when it expires
+        match completion.result {
+            Ok(_) => Ok(()),
+            Err(e) if e.raw_os_error() == Some(libc::ECANCELED) => {
+                Err(io::ErrorKind::TimedOut.into())
+            }
+            Err(e) => Err(e),
+        }
+    }
+
     pub(crate) fn bind(socket_addr: SocketAddr, socket_type: libc::c_int) -> io::Result<Socket> {
+        Self::bind_with_options(socket_addr, socket_type, BindOptions::default())
+    }
+
+    pub(crate) fn bind_with_options(
+        socket_addr: SocketAddr,
+        socket_type: libc::c_int,
+        options: BindOptions,
+    ) -> io::Result<Socket> {
         Self::bind_internal(
             socket_addr.into(),
             get_domain(socket_addr).into(),
             socket_type.into(),
+            options,
         )
     }
 
@@ -104,9 +587,39 @@ impl Socket {
         Self::bind_internal_todevice(device_name, Domain::IPV4, socket_type.into())
     }
 
-    pub(crate) fn from_raw_fd(fd: RawFd) -> io::Result<Socket> {
-        let fd = SharedFd::new(fd);
-        Ok(Self { fd })
+    /// Releases the file descriptor into a `T`. Fails if operations are still
+    /// in-flight on the socket, in which case the socket is closed once they
+    /// complete.
+    pub(crate) fn into_std<T: FromRawFd>(self) -> io::Result<T> {
+        match self.fd.try_into_raw_fd() {
+            // Safety: the driver no longer references the descriptor.
+            Ok(fd) => Ok(unsafe { T::from_raw_fd(fd) }),
+            Err(_) => Err(io::Error::other(
+                "operations are still in-flight on the socket",
+            )),
+        }
+    }
+
+    pub(crate) async fn close(self) -> io::Result<()> {
+        self.fd.close().await
+    }
+
+    pub(crate) fn use_fixed_file(&self) -> io::Result<()> {
+        self.fd.register()
+    }
+
+    pub(crate) fn as_shared_fd(&self) -> &SharedFd {
+        &self.fd
+    }
+
+    pub(crate) fn from_shared_fd(fd: SharedFd) -> Socket {
+        Socket { fd }
+    }
+
+    /// Takes ownership of the file descriptor of `socket`.
+    pub(crate) fn from_std<T: IntoRawFd>(socket: T) -> Socket {
+        let fd = SharedFd::new(socket.into_raw_fd());
+        Socket { fd }
     }
 
     fn bind_internal_todevice(
@@ -133,35 +646,262 @@ impl Socket {
         socket_type: libc::c_int,
     ) -> io::Result<Socket> {
         let addr = socket2::SockAddr::unix(path.as_ref())?;
-        Self::bind_internal(addr, libc::AF_UNIX.into(), socket_type.into())
+        Self::bind_internal(
+            addr,
+            libc::AF_UNIX.into(),
+            socket_type.into(),
+            BindOptions::default(),
+        )
     }
 
     fn bind_internal(
         socket_addr: socket2::SockAddr,
         domain: socket2::Domain,
         socket_type: socket2::Type,
+        options: BindOptions,
     ) -> io::Result<Socket> {
         let sys_listener = socket2::Socket::new(domain, socket_type, None)?;
-        let addr = socket2::SockAddr::from(socket_addr);
+        let addr = socket_addr;
 
-        sys_listener.set_reuse_port(true)?;
-        sys_listener.set_reuse_address(true)?;
+        // Address reuse has no meaning for Unix domain sockets, and recent
+        // kernels reject `SO_REUSEPORT` on them with `EOPNOTSUPP`.
+        if domain != Domain::UNIX {
+            if options.reuse_port {
+                sys_listener.set_reuse_port(true)?;
+            }
+            if options.reuse_address {
+                sys_listener.set_reuse_address(true)?;
+            }
+        }
+
+        if let (Domain::IPV6, Some(only_v6)) = (domain, options.only_v6) {
+            sys_listener.set_only_v6(only_v6)?;
+        }
 
         // TODO: config for buffer sizes
         // sys_listener.set_send_buffer_size(send_buf_size)?;
         // sys_listener.set_recv_buffer_size(recv_buf_size)?;
 
-        sys_listener.bind(&addr)?;
-
         let fd = SharedFd::new(sys_listener.into_raw_fd());
+        let socket = Self { fd };
 
-        Ok(Self { fd })
+        if options.transparent {
+            let ipv6 = domain == Domain::IPV6;
+            socket.set_ip_flag(ipv6, TRANSPARENT, true)?;
+            socket.set_ip_flag(ipv6, FREEBIND, true)?;
+        }
+
+        socket2::SockRef::from(&socket).bind(&addr)?;
+        Ok(socket)
     }
 
     pub(crate) fn listen(&self, backlog: libc::c_int) -> io::Result<()> {
         syscall!(listen(self.as_raw_fd(), backlog))?;
         Ok(())
     }
+
+    pub(crate) fn accept_multishot(&self) -> AcceptMultiStream {
+        AcceptMultiStream::new(self.fd.clone())
+    }
+
+    pub(crate) fn recv_multishot(&self, buf_ring: BufRing) -> RecvMsgMultiStream {
+        RecvMsgMultiStream::new(self.fd.clone(), buf_ring)
+    }
+
+    /// Returns the address the socket is bound to, using `getsockname(2)`.
+    pub(crate) fn local_addr(&self) -> io::Result<SocketAddr> {
+        as_socket_addr(self.local_sockaddr()?)
+    }
+
+    /// Returns the address of the connected peer, using `getpeername(2)`.
+    pub(crate) fn peer_addr(&self) -> io::Result<SocketAddr> {
+        as_socket_addr(self.peer_sockaddr()?)
+    }
+
+    /// Returns the address the socket is bound to, of any family.
+    pub(crate) fn local_sockaddr(&self) -> io::Result<socket2::SockAddr> {
+        // Safety: `getsockname` writes at most `len` bytes.
+        let (_, addr) = unsafe {
+            socket2::SockAddr::init(|storage, len| {
+                if libc::getsockname(self.as_raw_fd(), storage.cast(), len) == -1 {
+                    return Err(io::Error::last_os_error());
+                }
+                Ok(())
+            })?
+        };
+        Ok(addr)
+    }
+
+    /// Returns the address of the connected peer, of any family.
+    pub(crate) fn peer_sockaddr(&self) -> io::Result<socket2::SockAddr> {
+        // Safety: `getpeername` writes at most `len` bytes.
+        let (_, addr) = unsafe {
+            socket2::SockAddr::init(|storage, len| {
+                if libc::getpeername(self.as_raw_fd(), storage.cast(), len) == -1 {
+                    return Err(io::Error::last_os_error());
+                }
+                Ok(())
+            })?
+        };
+        Ok(addr)
+    }
+
+    /// Sets the socket option `name` at `level` to `value`.
+    pub(crate) fn setsockopt<T>(
+        &self,
+        level: libc::c_int,
+        name: libc::c_int,
+        value: T,
+    ) -> io::Result<()> {
+        syscall!(setsockopt(
+            self.as_raw_fd(),
+            level,
+            name,
+            &value as *const T as *const libc::c_void,
+            std::mem::size_of::<T>() as libc::socklen_t,
+        ))?;
+        Ok(())
+    }
+
+    /// Returns the value of the socket option `name` at `level`.
+    pub(crate) fn getsockopt<T: Copy>(
+        &self,
+        level: libc::c_int,
+        name: libc::c_int,
+    ) -> io::Result<T> {
+        let mut value = std::mem::MaybeUninit::<T>::zeroed();
+        let mut len = std::mem::size_of::<T>() as libc::socklen_t;
+        syscall!(getsockopt(
+            self.as_raw_fd(),
+            level,
+            name,
+            value.as_mut_ptr() as *mut libc::c_void,
+            &mut len,
+        ))?;
+        // Safety: the kernel wrote the option, or left it zeroed.
+        Ok(unsafe { value.assume_init() })
+    }
+
+    /// Sets the socket option `name` at `level` to the raw bytes `value`.
+    pub(crate) fn set_sockopt_bytes(
+        &self,
+        level: libc::c_int,
+        name: libc::c_int,
+        value: &[u8],
+    ) -> io::Result<()> {
+        let len = sockopt_len(value.len())?;
+        syscall!(setsockopt(
+            self.as_raw_fd(),
+            level,
+            name,
+            value.as_ptr().cast(),
+            len,
+        ))?;
+        Ok(())
+    }
+
+    /// Reads the socket option `name` at `level` into `buf`, returning the
+    /// length of the value.
+    pub(crate) fn get_sockopt_bytes(
+        &self,
+        level: libc::c_int,
+        name: libc::c_int,
+        buf: &mut [u8],
+    ) -> io::Result<usize> {
+        let mut len = sockopt_len(buf.len())?;
+        syscall!(getsockopt(
+            self.as_raw_fd(),
+            level,
+            name,
+            buf.as_mut_ptr().cast(),
+            &mut len,
+        ))?;
+        Ok(len as usize)
+    }
+
+    /// Sets `IP_TRANSPARENT`, or `IPV6_TRANSPARENT` on IPv6 sockets.
+    pub(crate) fn set_transparent(&self, on: bool) -> io::Result<()> {
+        self.set_ip_flag(self.is_ipv6()?, TRANSPARENT, on)
+    }
+
+    pub(crate) fn transparent(&self) -> io::Result<bool> {
+        self.ip_flag(TRANSPARENT)
+    }
+
+    /// Sets `IP_FREEBIND`, or `IPV6_FREEBIND` on IPv6 sockets.
+    pub(crate) fn set_freebind(&self, on: bool) -> io::Result<()> {
+        self.set_ip_flag(self.is_ipv6()?, FREEBIND, on)
+    }
+
+    pub(crate) fn freebind(&self) -> io::Result<bool> {
+        self.ip_flag(FREEBIND)
+    }
+
+    /// Sets a boolean IP option, the IPv4 or the IPv6 one of `names`
+    /// depending on `ipv6`.
+    fn set_ip_flag(
+        &self,
+        ipv6: bool,
+        (v4, v6): (libc::c_int, libc::c_int),
+        on: bool,
+    ) -> io::Result<()> {
+        if ipv6 {
+            self.setsockopt(libc::SOL_IPV6, v6, on as libc::c_int)
+        } else {
+            self.setsockopt(libc::SOL_IP, v4, on as libc::c_int)
+        }
+    }
+
+    fn ip_flag(&self, (v4, v6): (libc::c_int, libc::c_int)) -> io::Result<bool> {
+        let on: libc::c_int = if self.is_ipv6()? {
+            self.getsockopt(libc::SOL_IPV6, v6)?
+        } else {
+            self.getsockopt(libc::SOL_IP, v4)?
+        };
+        Ok(on != 0)
+    }
+
+    fn is_ipv6(&self) -> io::Result<bool> {
+        let domain: libc::c_int = self.getsockopt(libc::SOL_SOCKET, libc::SO_DOMAIN)?;
+        Ok(domain == libc::AF_INET6)
+    }
+
+    /// Takes the pending error of the socket (`SO_ERROR`), clearing it.
+    pub(crate) fn so_error(&self) -> io::Result<Option<io::Error>> {
+        let errno: libc::c_int = self.getsockopt(libc::SOL_SOCKET, libc::SO_ERROR)?;
+        Ok((errno != 0).then(|| io::Error::from_raw_os_error(errno)))
+    }
+
+    pub(crate) fn shutdown(&self, how: Shutdown) -> io::Result<()> {
+        let how = match how {
+            Shutdown::Write => libc::SHUT_WR,
+            Shutdown::Read => libc::SHUT_RD,
+            Shutdown::Both => libc::SHUT_RDWR,
+        };
+        syscall!(shutdown(self.as_raw_fd(), how))?;
+        Ok(())
+    }
+}
+
+/// `IP_TRANSPARENT` and its IPv6 counterpart.
+const TRANSPARENT: (libc::c_int, libc::c_int) = (libc::IP_TRANSPARENT, libc::IPV6_TRANSPARENT);
+
+/// `IP_FREEBIND` and its IPv6 counterpart.
+const FREEBIND: (libc::c_int, libc::c_int) = (libc::IP_FREEBIND, libc::IPV6_FREEBIND);
+
+/// Converts the length of a socket option value for the kernel.
+fn sockopt_len(len: usize) -> io::Result<libc::socklen_t> {
+    libc::socklen_t::try_from(len)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "socket option is too long"))
+}
+
+fn as_socket_addr(addr: socket2::SockAddr) -> io::Result<SocketAddr> {
+    addr.as_socket().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "socket address is not an IP address",
+        )
+    })
 }
 
 impl AsRawFd for Socket {
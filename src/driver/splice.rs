@@ -0,0 +1,112 @@
+use crate::driver::{Op, SharedFd};
+
+use std::{convert::TryFrom, io, os::unix::io::RawFd};
+
+use io_uring::opcode;
+
+/// Largest pipe requested for a single splice. Linux caps unprivileged pipes
+/// at `/proc/sys/fs/pipe-max-size`, which defaults to 1 MiB.
+const MAX_PIPE_SIZE: usize = 1024 * 1024;
+
+pub(crate) struct Splice {
+    #[allow(dead_code)]
+    fd_in: SharedFd,
+    #[allow(dead_code)]
+    fd_out: SharedFd,
+}
+
+impl Op<Splice> {
+    /// Submit a `splice(2)` of up to `len` bytes from `fd_in` to `fd_out`.
+    /// One of the two must be a pipe. Files are read and written at the
+    /// given offsets, or at their current position with `None`.
+    pub(crate) fn splice(
+        fd_in: &SharedFd,
+        off_in: Option<u64>,
+        fd_out: &SharedFd,
+        off_out: Option<u64>,
+        len: u32,
+    ) -> io::Result<Op<Splice>> {
+        let off_in = off_in.map_or(-1, |off| off as i64);
+        let off_out = off_out.map_or(-1, |off| off as i64);
+
+        Op::submit_with(
+            Splice {
+                fd_in: fd_in.clone(),
+                fd_out: fd_out.clone(),
+            },
+            |splice| {
+                with_fd!(splice.fd_in, |fd_in| with_fd!(splice.fd_out, |fd_out| {
+                    opcode::Splice::new(fd_in, off_in, fd_out, off_out, len)
+                        .flags(libc::SPLICE_F_MOVE)
+                        .build()
+                }))
+            },
+        )
+    }
+
+    pub(crate) async fn splice_len(self) -> io::Result<usize> {
+        let complete = self.await;
+        complete.result.map(|n| n as usize)
+    }
+}
+
+/// Moves up to `len` bytes from `src` to `dst` through a pipe, without
+/// copying them to user space. Returns the number of bytes written to `dst`.
+/// Files are read and written at the given offsets, or at their current
+/// position with `None`.
+///
+/// A single call moves at most one pipe buffer of data. The pipe is grown to
+/// fit `len`, up to [`MAX_PIPE_SIZE`], if the kernel allows it.
+pub(crate) async fn splice(
+    src: &SharedFd,
+    src_off: Option<u64>,
+    dst: &SharedFd,
+    dst_off: Option<u64>,
+    len: usize,
+) -> io::Result<usize> {
+    let (pipe_out, pipe_in) = pipe(len)?;
+    let capacity = pipe_size(pipe_in.raw_fd()).unwrap_or(len);
+    let len = u32::try_from(len.min(capacity)).unwrap_or(u32::MAX);
+
+    let filled = Op::splice(src, src_off, &pipe_in, None, len)?
+        .splice_len()
+        .await?;
+
+    // Drain the pipe, bytes left in it would be lost when it is closed
+    let mut written = 0;
+    while written < filled {
+        let off = dst_off.map(|off| off + written as u64);
+        let res = Op::splice(&pipe_out, None, dst, off, (filled - written) as u32)?
+            .splice_len()
+            .await;
+
+        match res {
+            Ok(0) => break,
+            Ok(n) => written += n,
+            Err(e) if written == 0 => return Err(e),
+            Err(_) => break,
+        }
+    }
+
+    Ok(written)
+}
+
+/// Creates a pipe able to hold `len` bytes, if the kernel allows it. Returns
+/// the read and write ends.
+fn pipe(len: usize) -> io::Result<(SharedFd, SharedFd)> {
+    let mut fds = [0; 2];
+    syscall!(pipe2(fds.as_mut_ptr(), libc::O_CLOEXEC))?;
+    let (pipe_out, pipe_in) = (SharedFd::new(fds[0]), SharedFd::new(fds[1]));
+
+    if len > pipe_size(fds[1])? {
+        // The default size is still usable, so failing to grow is not an error
+        let size = len.min(MAX_PIPE_SIZE) as libc::c_int;
+        let _ = syscall!(fcntl(fds[1], libc::F_SETPIPE_SZ, size));
+    }
+
+    Ok((pipe_out, pipe_in))
+}
+
+fn pipe_size(fd: RawFd) -> io::Result<usize> {
+    syscall!(fcntl(fd, libc::F_GETPIPE_SZ)).map(|size| size as usize)
+}
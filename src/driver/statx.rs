@@ -0,0 +1,59 @@
+use crate::driver::{self, Op, SharedFd};
+
+use std::ffi::CString;
+use std::io;
+use std::path::Path;
+
+/// Retrieve the status of a path, or of an open file.
+pub(crate) struct Statx {
+    /// Holds a strong ref to the FD of the file, if any.
+    #[allow(dead_code)]
+    fd: Option<SharedFd>,
+
+    #[allow(dead_code)]
+    path: CString,
+
+    /// Written by the kernel.
+    pub(crate) statx: Box<libc::statx>,
+}
+
+impl Op<Statx> {
+    /// Submit a `statx(2)` of the file `fd` refers to, with `AT_EMPTY_PATH`.
+    pub(crate) fn statx_fd(fd: &SharedFd, mask: u32) -> io::Result<Op<Statx>> {
+        Self::statx(Some(fd), CString::default(), libc::AT_EMPTY_PATH, mask)
+    }
+
+    /// Submit a `statx(2)` of `path`, relative to the current working
+    /// directory. Symbolic links are followed.
+    pub(crate) fn statx_path(path: &Path, mask: u32) -> io::Result<Op<Statx>> {
+        let path = driver::util::cstr(path)?;
+        Self::statx(None, path, 0, mask)
+    }
+
+    fn statx(fd: Option<&SharedFd>, path: CString, flags: i32, mask: u32) -> io::Result<Op<Statx>> {
+        use io_uring::{opcode, types};
+
+        let dirfd = driver::util::dirfd(fd);
+
+        Op::submit_with(
+            Statx {
+                fd: fd.cloned(),
+                path,
+                // Safety: all-zero is a valid `struct statx`.
+                statx: Box::new(unsafe { std::mem::zeroed() }),
+            },
+            |statx| {
+                // The path and the buffer are held by the operation state and
+                // will not be accessed again until the operation completes.
+                opcode::Statx::new(
+                    types::Fd(dirfd),
+                    statx.path.as_ptr(),
+                    statx.statx.as_mut() as *mut libc::statx as *mut types::statx,
+                )
+                .flags(flags)
+                .mask(mask)
+                .build()
+            },
+        )
+    }
+}
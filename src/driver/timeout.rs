@@ -0,0 +1,83 @@
+use crate::future::poll_fn;
+
+use io_uring::types::Timespec;
+use std::cell::Cell;
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+thread_local! {
+    /// Deadline of the future being polled by `with_timeout`, if any.
+    static DEADLINE: Cell<Option<Instant>> = const { Cell::new(None) };
+}
+
+/// Bounds the operations of `future` by `timeout`.
+///
+/// Each io-uring operation `future` submits is linked to an
+/// `IORING_OP_LINK_TIMEOUT` expiring when the deadline passes, only leaving
+/// the operation the remaining time. When the deadline passes first, the
+/// kernel cancels the operation, which fails with
+/// [`TimedOut`](std::io::ErrorKind::TimedOut). As opposed to wrapping the
+/// future in [`tokio::time::timeout`], the operation is not left running in
+/// the background, and its buffer is handed back along with the error.
+///
+/// This works uniformly for reads, writes, accepts, connects, and any other
+/// single operation. Time `future` spends waiting on anything else than an
+/// operation, and operations submitted as part of a [`Chain`], are not
+/// bounded. Nested timeouts use the earliest deadline.
+///
+/// An operation canceled for another reason while a timeout is linked to it
+/// also reports `TimedOut`.
+///
+/// An operation finding the submission queue full waits in the driver's
+/// backlog, and its timeout only starts once it is submitted. The timeout
+/// still runs for the time left when the operation was created, so the
+/// operation may fail up to the time spent in the backlog after the
+/// deadline.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use tokio_uring::net::TcpListener;
+///
+/// fn main() -> std::io::Result<()> {
+///     tokio_uring::start(async {
+///         let listener = TcpListener::bind("127.0.0.1:0".parse().unwrap())?;
+///
+///         let res = tokio_uring::with_timeout(listener.accept(), Duration::from_millis(10)).await;
+///         assert_eq!(res.err().unwrap().kind(), std::io::ErrorKind::TimedOut);
+///
+///         Ok(())
+///     })
+/// }
+/// ```
+///
+/// [`Chain`]: crate::Chain
+pub async fn with_timeout<F: Future>(future: F, timeout: Duration) -> F::Output {
+    let deadline = Instant::now() + timeout;
+    let mut future = std::pin::pin!(future);
+
+    poll_fn(|cx| {
+        let prev = DEADLINE.with(|current| current.get());
+        let deadline = prev.map_or(deadline, |prev| prev.min(deadline));
+
+        DEADLINE.with(|current| current.set(Some(deadline)));
+        let res = future.as_mut().poll(cx);
+        DEADLINE.with(|current| current.set(prev));
+        res
+    })
+    .await
+}
+
+/// Returns the time left to operations submitted now, if they are bounded by
+/// a [`with_timeout`].
+pub(crate) fn current() -> Option<Timespec> {
+    let deadline = DEADLINE.with(|current| current.get())?;
+    let left = deadline.saturating_duration_since(Instant::now());
+
+    Some(
+        Timespec::new()
+            .sec(left.as_secs())
+            .nsec(left.subsec_nanos()),
+    )
+}
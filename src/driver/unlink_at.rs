@@ -1,39 +1,55 @@
-use crate::driver::{self, Op};
+use crate::driver::{self, Op, SharedFd};
 
 use std::ffi::CString;
 use std::io;
 use std::path::Path;
 
-/// Unlink a path relative to the current working directory of the caller's process.
+/// Unlink a path relative to a directory, or to the current working
+/// directory of the caller's process.
 pub(crate) struct Unlink {
+    /// Holds a strong ref to the FD of the directory, if any.
+    #[allow(dead_code)]
+    dir: Option<SharedFd>,
+
     pub(crate) path: CString,
 }
 
 impl Op<Unlink> {
     /// Submit a request to unlink a directory with provided flags.
-    pub(crate) fn unlink_dir(path: &Path) -> io::Result<Op<Unlink>> {
-        Self::unlink(path, libc::AT_REMOVEDIR)
+    pub(crate) fn unlink_dir(dir: Option<&SharedFd>, path: &Path) -> io::Result<Op<Unlink>> {
+        Self::unlink(dir, path, libc::AT_REMOVEDIR)
     }
 
     /// Submit a request to unlink a file with provided flags.
-    pub(crate) fn unlink_file(path: &Path) -> io::Result<Op<Unlink>> {
-        Self::unlink(path, 0)
+    pub(crate) fn unlink_file(dir: Option<&SharedFd>, path: &Path) -> io::Result<Op<Unlink>> {
+        Self::unlink(dir, path, 0)
     }
 
     /// Submit a request to unlink a specifed path with provided flags.
-    pub(crate) fn unlink(path: &Path, flags: i32) -> io::Result<Op<Unlink>> {
+    pub(crate) fn unlink(
+        dir: Option<&SharedFd>,
+        path: &Path,
+        flags: i32,
+    ) -> io::Result<Op<Unlink>> {
         use io_uring::{opcode, types};
 
         let path = driver::util::cstr(path)?;
+        let dirfd = driver::util::dirfd(dir);
 
-        Op::submit_with(Unlink { path }, |unlink| {
-            // Get a reference to the memory. The string will be held by the
-            // operation state and will not be accessed again until the operation
-            // completes.
-            let p_ref = unlink.path.as_c_str().as_ptr();
-            opcode::UnlinkAt::new(types::Fd(libc::AT_FDCWD), p_ref)
-                .flags(flags)
-                .build()
-        })
+        Op::submit_with(
+            Unlink {
+                dir: dir.cloned(),
+                path,
+            },
+            |unlink| {
+                // Get a reference to the memory. The string will be held by the
+                // operation state and will not be accessed again until the operation
+                // completes.
+                let p_ref = unlink.path.as_c_str().as_ptr();
+                opcode::UnlinkAt::new(types::Fd(dirfd), p_ref)
+                    .flags(flags)
+                    .build()
+            },
+        )
     }
 }
@@ -1,8 +1,17 @@
+use crate::driver::SharedFd;
+
 use std::ffi::CString;
 use std::io;
+use std::os::unix::io::RawFd;
 use std::path::Path;
 
 pub(super) fn cstr(p: &Path) -> io::Result<CString> {
     use std::os::unix::ffi::OsStrExt;
     Ok(CString::new(p.as_os_str().as_bytes())?)
 }
+
+/// The fd relative paths are resolved against: the directory `dir`, or the
+/// current working directory.
+pub(super) fn dirfd(dir: Option<&SharedFd>) -> RawFd {
+    dir.map_or(libc::AT_FDCWD, SharedFd::raw_fd)
+}
@@ -19,7 +19,7 @@ pub(crate) struct Write<T> {
 
 impl<T: IoBuf> Op<Write<T>> {
     pub(crate) fn write_at(fd: &SharedFd, buf: T, offset: u64) -> io::Result<Op<Write<T>>> {
-        use io_uring::{opcode, types};
+        use io_uring::opcode;
 
         Op::submit_with(
             Write {
@@ -31,9 +31,9 @@ impl<T: IoBuf> Op<Write<T>> {
                 let ptr = write.buf.stable_ptr();
                 let len = write.buf.bytes_init();
 
-                opcode::Write::new(types::Fd(fd.raw_fd()), ptr, len as _)
+                with_fd!(fd, |fd| opcode::Write::new(fd, ptr, len as _)
                     .offset(offset as _)
-                    .build()
+                    .build())
             },
         )
     }
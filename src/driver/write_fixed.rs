@@ -0,0 +1,50 @@
+use crate::buf::IoBuf;
+use crate::driver::{FixedBuf, Op, SharedFd};
+use crate::BufResult;
+
+use std::io;
+
+pub(crate) struct WriteFixed {
+    /// Holds a strong ref to the FD, preventing the file from being closed
+    /// while the operation is in-flight.
+    #[allow(dead_code)]
+    fd: SharedFd,
+
+    /// The fixed buffer being written from. It keeps the registration alive.
+    buf: FixedBuf,
+}
+
+impl Op<WriteFixed> {
+    pub(crate) fn write_fixed_at(
+        fd: &SharedFd,
+        buf: FixedBuf,
+        offset: u64,
+    ) -> io::Result<Op<WriteFixed>> {
+        use io_uring::opcode;
+
+        Op::submit_with(
+            WriteFixed {
+                fd: fd.clone(),
+                buf,
+            },
+            |write| {
+                // Get raw buffer info
+                let ptr = write.buf.stable_ptr();
+                let len = write.buf.bytes_init();
+                with_fd!(fd, |fd| opcode::WriteFixed::new(
+                    fd,
+                    ptr,
+                    len as _,
+                    write.buf.index()
+                )
+                .offset(offset as _)
+                .build())
+            },
+        )
+    }
+
+    pub(crate) async fn write(self) -> BufResult<usize, FixedBuf> {
+        let complete = self.await;
+        (complete.result.map(|v| v as _), complete.data.buf)
+    }
+}
@@ -0,0 +1,61 @@
+use crate::{
+    buf::{self, IoVectoredBuf},
+    driver::{Op, SharedFd},
+    BufResult,
+};
+use libc::iovec;
+use std::{
+    io,
+    task::{Context, Poll},
+};
+
+pub(crate) struct Writev<T> {
+    /// Holds a strong ref to the FD, preventing the file from being closed
+    /// while the operation is in-flight.
+    #[allow(dead_code)]
+    fd: SharedFd,
+
+    pub(crate) bufs: T,
+
+    /// Parameter for `io_uring::op::Writev`, referring to `bufs`.
+    iovs: Vec<iovec>,
+}
+
+impl<T: IoVectoredBuf> Op<Writev<T>> {
+    pub(crate) fn writev_at(fd: &SharedFd, bufs: T, offset: u64) -> io::Result<Op<Writev<T>>> {
+        use io_uring::opcode;
+
+        // Build the iovec array. The buffers are owned by the operation, so
+        // the pointers stay valid until the operation completes.
+        let iovs = buf::iovecs(&bufs);
+
+        Op::submit_with(
+            Writev {
+                fd: fd.clone(),
+                bufs,
+                iovs,
+            },
+            |writev| {
+                with_fd!(fd, |fd| opcode::Writev::new(
+                    fd,
+                    writev.iovs.as_ptr(),
+                    writev.iovs.len() as u32,
+                )
+                .offset(offset as _)
+                .build())
+            },
+        )
+    }
+
+    pub(crate) async fn writev(mut self) -> BufResult<usize, T> {
+        crate::future::poll_fn(move |cx| self.poll_writev(cx)).await
+    }
+
+    pub(crate) fn poll_writev(&mut self, cx: &mut Context<'_>) -> Poll<BufResult<usize, T>> {
+        use std::future::Future;
+        use std::pin::Pin;
+
+        let complete = ready!(Pin::new(self).poll(cx));
+        Poll::Ready((complete.result.map(|v| v as _), complete.data.bufs))
+    }
+}
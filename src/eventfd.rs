@@ -0,0 +1,175 @@
+use crate::driver::{Op, SharedFd};
+
+use std::{
+    fmt, io,
+    os::unix::io::{AsRawFd, FromRawFd, OwnedFd, RawFd},
+};
+
+/// An `eventfd(2)` counter, read and written through the driver.
+///
+/// An `EventFd` lets other threads, or other subsystems, wake a task of the
+/// runtime: the task awaits [`read`], which completes once the counter is
+/// non-zero. Since an `EventFd` is bound to its runtime, other threads signal
+/// it through an [`EventFdWriter`], created with [`writer`].
+///
+/// In the default mode, a read returns the value of the counter and resets it
+/// to zero, so several writes between two reads are coalesced. In semaphore
+/// mode, created with [`new_semaphore`], a read returns `1` and decrements the
+/// counter.
+///
+/// [`read`]: EventFd::read
+/// [`writer`]: EventFd::writer
+/// [`new_semaphore`]: EventFd::new_semaphore
+///
+/// # Examples
+///
+/// ```
+/// use tokio_uring::EventFd;
+///
+/// fn main() -> std::io::Result<()> {
+///     tokio_uring::start(async {
+///         let eventfd = EventFd::new(0)?;
+///         let writer = eventfd.writer()?;
+///
+///         let producer = std::thread::spawn(move || writer.write(3));
+///
+///         assert_eq!(eventfd.read().await?, 3);
+///         producer.join().unwrap()?;
+///
+///         Ok(())
+///     })
+/// }
+/// ```
+pub struct EventFd {
+    fd: SharedFd,
+}
+
+impl EventFd {
+    /// Creates an eventfd with the counter set to `initval`.
+    pub fn new(initval: u32) -> io::Result<EventFd> {
+        EventFd::with_flags(initval, 0)
+    }
+
+    /// Creates an eventfd in semaphore mode (`EFD_SEMAPHORE`), with the
+    /// counter set to `initval`. Each read returns `1` and decrements the
+    /// counter, so each unit written wakes one read.
+    pub fn new_semaphore(initval: u32) -> io::Result<EventFd> {
+        EventFd::with_flags(initval, libc::EFD_SEMAPHORE)
+    }
+
+    fn with_flags(initval: u32, flags: libc::c_int) -> io::Result<EventFd> {
+        let fd = syscall!(eventfd(
+            initval,
+            flags | libc::EFD_CLOEXEC | libc::EFD_NONBLOCK
+        ))?;
+
+        Ok(EventFd {
+            fd: SharedFd::new(fd),
+        })
+    }
+
+    /// Waits for the counter to be non-zero, then reads it.
+    ///
+    /// Returns the value of the counter, which is reset to zero, or `1` in
+    /// semaphore mode, in which case the counter is decremented.
+    pub async fn read(&self) -> io::Result<u64> {
+        let op = Op::read_at(&self.fd, Vec::with_capacity(8), 0).unwrap();
+        let (res, buf) = op.read().await;
+        res?;
+
+        let mut value = [0; 8];
+        value.copy_from_slice(&buf[..8]);
+        Ok(u64::from_ne_bytes(value))
+    }
+
+    /// Adds `value` to the counter, waking a pending read.
+    ///
+    /// If the counter would exceed `u64::MAX - 1`, the write waits for a read
+    /// to make room.
+    ///
+    /// # Errors
+    ///
+    /// Fails with [`InvalidInput`](io::ErrorKind::InvalidInput) if `value` is
+    /// `u64::MAX`.
+    pub async fn write(&self, value: u64) -> io::Result<()> {
+        let op = Op::write_at(&self.fd, value.to_ne_bytes().to_vec(), 0).unwrap();
+        let (res, _) = op.write().await;
+        res.map(|_| ())
+    }
+
+    /// Creates a handle writing to this eventfd from any thread.
+    ///
+    /// The handle owns a duplicate of the descriptor, so it stays valid after
+    /// the `EventFd` is closed, but then no read observes its writes.
+    pub fn writer(&self) -> io::Result<EventFdWriter> {
+        let fd = syscall!(fcntl(self.fd.raw_fd(), libc::F_DUPFD_CLOEXEC, 0))?;
+
+        Ok(EventFdWriter {
+            fd: unsafe { OwnedFd::from_raw_fd(fd) },
+        })
+    }
+
+    /// Closes the eventfd.
+    ///
+    /// The method completes once the close operation has completed.
+    pub async fn close(self) -> io::Result<()> {
+        self.fd.close().await
+    }
+}
+
+impl AsRawFd for EventFd {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd.raw_fd()
+    }
+}
+
+impl fmt::Debug for EventFd {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EventFd")
+            .field("fd", &self.fd.raw_fd())
+            .finish()
+    }
+}
+
+/// A handle writing to an [`EventFd`] from any thread, created by
+/// [`EventFd::writer`].
+///
+/// Writes are plain `write(2)` calls, which do not block: they fail instead
+/// if the counter would overflow.
+#[derive(Debug)]
+pub struct EventFdWriter {
+    fd: OwnedFd,
+}
+
+impl EventFdWriter {
+    /// Adds `value` to the counter of the eventfd, waking a pending read.
+    ///
+    /// # Errors
+    ///
+    /// Fails with [`WouldBlock`](io::ErrorKind::WouldBlock) if the counter
+    /// would exceed `u64::MAX - 1`, and with
+    /// [`InvalidInput`](io::ErrorKind::InvalidInput) if `value` is
+    /// `u64::MAX`.
+    pub fn write(&self, value: u64) -> io::Result<()> {
+        let value = value.to_ne_bytes();
+        syscall!(write(
+            self.fd.as_raw_fd(),
+            value.as_ptr().cast(),
+            value.len()
+        ))
+        .map(|_| ())
+    }
+
+    /// Creates another handle to the same eventfd.
+    pub fn try_clone(&self) -> io::Result<EventFdWriter> {
+        Ok(EventFdWriter {
+            fd: self.fd.try_clone()?,
+        })
+    }
+}
+
+impl AsRawFd for EventFdWriter {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd.as_raw_fd()
+    }
+}
@@ -0,0 +1,44 @@
+/// Expected access pattern of a file range, passed to
+/// [`File::fadvise`](crate::fs::File::fadvise).
+///
+/// The variants map to the `POSIX_FADV_*` constants of `posix_fadvise(2)`.
+/// Advice is a hint: the kernel may ignore it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Advice {
+    /// No particular access pattern, the default (`POSIX_FADV_NORMAL`).
+    Normal,
+
+    /// The range is read sequentially, from lower to higher offsets. Linux
+    /// doubles the readahead window (`POSIX_FADV_SEQUENTIAL`).
+    Sequential,
+
+    /// The range is read in random order. Linux disables readahead
+    /// (`POSIX_FADV_RANDOM`).
+    Random,
+
+    /// The range is read once (`POSIX_FADV_NOREUSE`).
+    NoReuse,
+
+    /// The range is read soon. Linux starts reading it into the page cache
+    /// in the background (`POSIX_FADV_WILLNEED`).
+    WillNeed,
+
+    /// The range is not read again soon. Linux drops its clean pages from
+    /// the page cache; dirty pages are kept until written back
+    /// (`POSIX_FADV_DONTNEED`).
+    DontNeed,
+}
+
+impl Advice {
+    pub(crate) fn as_raw(self) -> i32 {
+        match self {
+            Advice::Normal => libc::POSIX_FADV_NORMAL,
+            Advice::Sequential => libc::POSIX_FADV_SEQUENTIAL,
+            Advice::Random => libc::POSIX_FADV_RANDOM,
+            Advice::NoReuse => libc::POSIX_FADV_NOREUSE,
+            Advice::WillNeed => libc::POSIX_FADV_WILLNEED,
+            Advice::DontNeed => libc::POSIX_FADV_DONTNEED,
+        }
+    }
+}
@@ -0,0 +1,236 @@
+use crate::fs::File;
+
+use std::io::{self, SeekFrom};
+
+/// Default capacity of the buffer of a [`BufReader`] or a
+/// [`BufWriter`](crate::fs::BufWriter).
+pub(crate) const DEFAULT_CAPACITY: usize = 8 * 1024;
+
+/// Adds buffering to reads from a [`File`].
+///
+/// `BufReader` owns a buffer, fills it with large positional reads, and
+/// serves smaller reads, such as [`read_line`], from it. This makes small
+/// and line-oriented reads cheap, at the cost of a copy out of the buffer.
+///
+/// The reader tracks its own position in the file, starting at the beginning,
+/// and moves it with [`seek`]. It does not use or change the position of the
+/// file.
+///
+/// If a read is cancelled by dropping its future, the buffered data is
+/// discarded, and the next read starts over from the position of the reader.
+///
+/// [`read_line`]: BufReader::read_line
+/// [`seek`]: BufReader::seek
+///
+/// # Examples
+///
+/// ```no_run
+/// use tokio_uring::fs::{BufReader, File};
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     tokio_uring::start(async {
+///         let file = File::open("foo.txt").await?;
+///         let mut reader = BufReader::new(file);
+///
+///         let mut line = String::new();
+///         while reader.read_line(&mut line).await? > 0 {
+///             print!("{}", line);
+///             line.clear();
+///         }
+///
+///         Ok(())
+///     })
+/// }
+/// ```
+pub struct BufReader {
+    file: File,
+    buf: Vec<u8>,
+    capacity: usize,
+    // Start of the unread data in `buf`
+    pos: usize,
+    // Offset in the file of the end of `buf`
+    offset: u64,
+}
+
+impl BufReader {
+    /// Creates a new `BufReader` with a default buffer capacity, currently
+    /// 8 KiB, reading from the beginning of `file`.
+    pub fn new(file: File) -> BufReader {
+        BufReader::with_capacity(DEFAULT_CAPACITY, file)
+    }
+
+    /// Creates a new `BufReader` with a buffer of at least `capacity` bytes,
+    /// reading from the beginning of `file`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is 0.
+    pub fn with_capacity(capacity: usize, file: File) -> BufReader {
+        assert!(capacity > 0, "the capacity of a BufReader must not be 0");
+
+        BufReader {
+            file,
+            buf: Vec::with_capacity(capacity),
+            capacity,
+            pos: 0,
+            offset: 0,
+        }
+    }
+
+    /// Gets a reference to the underlying file.
+    pub fn get_ref(&self) -> &File {
+        &self.file
+    }
+
+    /// Unwraps this `BufReader`, returning the underlying file. Buffered data
+    /// is lost.
+    pub fn into_inner(self) -> File {
+        self.file
+    }
+
+    /// Returns the buffered data, without reading more.
+    pub fn buffer(&self) -> &[u8] {
+        &self.buf[self.pos..]
+    }
+
+    /// Returns the capacity of the buffer.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the position of the reader in the file: the offset of the
+    /// next byte a read returns.
+    pub fn position(&self) -> u64 {
+        self.offset - self.buffer().len() as u64
+    }
+
+    /// Returns the buffered data, reading more from the file if the buffer is
+    /// empty. An empty slice means the end of the file.
+    ///
+    /// The data is not consumed: it is returned again until it is marked
+    /// as read with [`consume`](BufReader::consume).
+    pub async fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        if self.pos >= self.buf.len() {
+            // The buffer is taken by the read, and lost if it is cancelled
+            let mut buf = std::mem::take(&mut self.buf);
+            buf.clear();
+            if buf.capacity() == 0 {
+                buf.reserve(self.capacity);
+            }
+            self.pos = 0;
+
+            let (res, buf) = self.file.read_at(buf, self.offset).await;
+            self.buf = buf;
+            self.offset += res? as u64;
+        }
+
+        Ok(self.buffer())
+    }
+
+    /// Marks `amt` bytes of the buffered data as read, so they are no longer
+    /// returned by [`fill_buf`](BufReader::fill_buf). `amt` is clamped to the
+    /// length of the buffered data.
+    pub fn consume(&mut self, amt: usize) {
+        self.pos = (self.pos + amt).min(self.buf.len());
+    }
+
+    /// Reads some bytes into `dst` from the buffer, filling it first if it is
+    /// empty, and returns how many were read. A return value of `0` means the
+    /// end of the file, or that `dst` is empty.
+    pub async fn read(&mut self, dst: &mut [u8]) -> io::Result<usize> {
+        let src = self.fill_buf().await?;
+        let n = src.len().min(dst.len());
+        dst[..n].copy_from_slice(&src[..n]);
+        self.consume(n);
+        Ok(n)
+    }
+
+    /// Reads bytes into `buf` until the delimiter `byte` or the end of the
+    /// file is reached, returning how many bytes were read. The delimiter is
+    /// included, if found.
+    ///
+    /// The bytes read are appended to `buf` even if an error is returned.
+    /// A return value of `0` means the end of the file.
+    pub async fn read_until(&mut self, byte: u8, buf: &mut Vec<u8>) -> io::Result<usize> {
+        let mut read = 0;
+
+        loop {
+            let available = self.fill_buf().await?;
+            let (done, used) = match memchr(byte, available) {
+                Some(i) => (true, i + 1),
+                None => (available.is_empty(), available.len()),
+            };
+
+            buf.extend_from_slice(&available[..used]);
+            self.consume(used);
+            read += used;
+
+            if done {
+                return Ok(read);
+            }
+        }
+    }
+
+    /// Reads a line into `buf`, up to and including the next newline, or to
+    /// the end of the file. Returns how many bytes were read. A return value
+    /// of `0` means the end of the file.
+    ///
+    /// # Errors
+    ///
+    /// Fails with [`InvalidData`](io::ErrorKind::InvalidData) if the line is
+    /// not valid UTF-8. The line is consumed nonetheless. `buf` is left
+    /// unchanged on any error.
+    pub async fn read_line(&mut self, buf: &mut String) -> io::Result<usize> {
+        let mut bytes = Vec::new();
+        let n = self.read_until(b'\n', &mut bytes).await?;
+
+        let line = String::from_utf8(bytes).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                "stream did not contain valid UTF-8",
+            )
+        })?;
+        buf.push_str(&line);
+
+        Ok(n)
+    }
+
+    /// Moves the reader to a new position in the file, returning it.
+    ///
+    /// The buffered data is kept if the new position falls within it, so
+    /// short seeks do not read the file again. Seeking from the end queries
+    /// the size of the file.
+    ///
+    /// # Errors
+    ///
+    /// Fails with [`InvalidInput`](io::ErrorKind::InvalidInput) if the new
+    /// position would be before the beginning of the file.
+    pub async fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let target = match pos {
+            SeekFrom::Start(off) => Some(off),
+            SeekFrom::Current(off) => self.position().checked_add_signed(off),
+            SeekFrom::End(off) => self.file.statx().await?.len().checked_add_signed(off),
+        };
+        let target = target.ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            )
+        })?;
+
+        let start = self.offset - self.buf.len() as u64;
+        if (start..=self.offset).contains(&target) {
+            self.pos = (target - start) as usize;
+        } else {
+            self.buf.clear();
+            self.pos = 0;
+            self.offset = target;
+        }
+
+        Ok(target)
+    }
+}
+
+fn memchr(byte: u8, haystack: &[u8]) -> Option<usize> {
+    haystack.iter().position(|&b| b == byte)
+}
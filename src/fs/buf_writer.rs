@@ -0,0 +1,198 @@
+use crate::buf::IoBuf;
+use crate::fs::{buf_reader::DEFAULT_CAPACITY, File};
+
+use std::io::{self, SeekFrom};
+
+/// Adds buffering to writes to a [`File`].
+///
+/// `BufWriter` owns a buffer, collects small writes in it, and writes it to
+/// the file with large positional writes once it is full, or on [`flush`].
+///
+/// The writer tracks its own position in the file, starting at the
+/// beginning, and moves it with [`seek`]. It does not use or change the
+/// position of the file.
+///
+/// Buffered data is **not** written when the `BufWriter` is dropped, as that
+/// would need to block: call [`flush`], or [`into_inner`], before.
+///
+/// [`flush`]: BufWriter::flush
+/// [`into_inner`]: BufWriter::into_inner
+/// [`seek`]: BufWriter::seek
+///
+/// # Examples
+///
+/// ```no_run
+/// use tokio_uring::fs::{BufWriter, File};
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     tokio_uring::start(async {
+///         let file = File::create("foo.txt").await?;
+///         let mut writer = BufWriter::new(file);
+///
+///         for i in 0..100 {
+///             writer.write_all(format!("line {}\n", i).as_bytes()).await?;
+///         }
+///
+///         let file = writer.into_inner().await?;
+///         file.close().await?;
+///         Ok(())
+///     })
+/// }
+/// ```
+pub struct BufWriter {
+    file: File,
+    buf: Vec<u8>,
+    capacity: usize,
+    // Offset in the file of the start of `buf`
+    offset: u64,
+}
+
+impl BufWriter {
+    /// Creates a new `BufWriter` with a default buffer capacity, currently
+    /// 8 KiB, writing from the beginning of `file`.
+    pub fn new(file: File) -> BufWriter {
+        BufWriter::with_capacity(DEFAULT_CAPACITY, file)
+    }
+
+    /// Creates a new `BufWriter` with a buffer of at least `capacity` bytes,
+    /// writing from the beginning of `file`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is 0.
+    pub fn with_capacity(capacity: usize, file: File) -> BufWriter {
+        assert!(capacity > 0, "the capacity of a BufWriter must not be 0");
+
+        BufWriter {
+            file,
+            buf: Vec::with_capacity(capacity),
+            capacity,
+            offset: 0,
+        }
+    }
+
+    /// Gets a reference to the underlying file.
+    ///
+    /// Data written through the file directly may be overwritten by buffered
+    /// data when it is flushed.
+    pub fn get_ref(&self) -> &File {
+        &self.file
+    }
+
+    /// Flushes the buffered data, then unwraps this `BufWriter`, returning
+    /// the underlying file.
+    ///
+    /// On error, the `BufWriter` is dropped along with the data that was not
+    /// written.
+    pub async fn into_inner(mut self) -> io::Result<File> {
+        self.flush().await?;
+        Ok(self.file)
+    }
+
+    /// Returns the buffered data, not yet written to the file.
+    pub fn buffer(&self) -> &[u8] {
+        &self.buf
+    }
+
+    /// Returns the capacity of the buffer.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the position of the writer in the file: the offset the next
+    /// byte written goes to.
+    pub fn position(&self) -> u64 {
+        self.offset + self.buf.len() as u64
+    }
+
+    /// Writes all of `src` into the buffer, writing the buffer to the file
+    /// each time it fills up.
+    ///
+    /// On error, the bytes of `src` before the failed flush were buffered,
+    /// or written, but the caller cannot tell how many.
+    pub async fn write_all(&mut self, mut src: &[u8]) -> io::Result<()> {
+        while !src.is_empty() {
+            if self.buf.len() >= self.capacity {
+                self.flush().await?;
+            }
+            if self.buf.capacity() == 0 {
+                // The buffer was lost to a cancelled flush
+                self.buf.reserve(self.capacity);
+            }
+
+            let n = (self.capacity - self.buf.len()).min(src.len());
+            self.buf.extend_from_slice(&src[..n]);
+            src = &src[n..];
+        }
+
+        Ok(())
+    }
+
+    /// Writes the buffered data to the file.
+    ///
+    /// The data is written with as many writes as needed. On error, the data
+    /// that was written is removed from the buffer and the rest is kept, so
+    /// the flush can be retried. If the flush is cancelled by dropping its
+    /// future, the buffered data is lost.
+    ///
+    /// Flushing does not sync the file to disk, see
+    /// [`File::sync_data`](crate::fs::File::sync_data).
+    pub async fn flush(&mut self) -> io::Result<()> {
+        let mut buf = std::mem::take(&mut self.buf);
+        let mut written = 0;
+
+        let res = loop {
+            if written == buf.len() {
+                break Ok(());
+            }
+
+            let (res, slice) = self.file.write_at(buf.slice(written..), self.offset).await;
+            buf = slice.into_inner();
+
+            match res {
+                Ok(0) => {
+                    break Err(io::Error::new(
+                        io::ErrorKind::WriteZero,
+                        "failed to write the buffered data",
+                    ))
+                }
+                Ok(n) => {
+                    written += n;
+                    self.offset += n as u64;
+                }
+                Err(e) => break Err(e),
+            }
+        };
+
+        buf.drain(..written);
+        self.buf = buf;
+        res
+    }
+
+    /// Flushes the buffered data, then moves the writer to a new position in
+    /// the file, returning it. Seeking from the end queries the size of the
+    /// file.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the flush fails, or with
+    /// [`InvalidInput`](io::ErrorKind::InvalidInput) if the new position
+    /// would be before the beginning of the file.
+    pub async fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.flush().await?;
+
+        let target = match pos {
+            SeekFrom::Start(off) => Some(off),
+            SeekFrom::Current(off) => self.offset.checked_add_signed(off),
+            SeekFrom::End(off) => self.file.statx().await?.len().checked_add_signed(off),
+        };
+        self.offset = target.ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            )
+        })?;
+
+        Ok(self.offset)
+    }
+}
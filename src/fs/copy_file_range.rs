@@ -0,0 +1,68 @@
+use crate::driver;
+use crate::fs::File;
+
+use std::io;
+
+/// Copies up to `len` bytes from `src`, starting at `src_off`, to `dst`,
+/// starting at `dst_off`, without copying them through user space. Neither
+/// file position is used or changed.
+///
+/// On success, returns the number of bytes copied. It may be less than
+/// `len`, either because the end of `src` was reached, or because a single
+/// call copies at most one pipe buffer of data, so callers loop, advancing
+/// the offsets, until all the data is copied. A return value of `0` means the
+/// end of `src`.
+///
+/// # Implementation
+///
+/// io-uring has no opcode for `copy_file_range(2)`, so the data moves
+/// through a pipe with two `IORING_OP_SPLICE` operations, as in
+/// [`splice`](crate::splice), which describes the pipe buffer sizing. The
+/// data stays in the kernel, but is copied through the page cache: unlike
+/// `copy_file_range(2)`, this never shares extents on filesystems supporting
+/// reflinks, nor offloads the copy to the server on network filesystems. For
+/// those, call [`std::fs::copy`] or `copy_file_range(2)` on a blocking thread
+/// instead.
+///
+/// If writing to `dst` fails after part of a chunk was written, the bytes
+/// written so far are returned.
+///
+/// # Examples
+///
+/// ```no_run
+/// use tokio_uring::fs::{self, File};
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     tokio_uring::start(async {
+///         let src = File::open("foo.txt").await?;
+///         let dst = File::create("bar.txt").await?;
+///
+///         let mut off = 0;
+///         loop {
+///             let n = fs::copy_file_range(&src, off, &dst, off, 1024 * 1024).await?;
+///             if n == 0 {
+///                 break;
+///             }
+///             off += n as u64;
+///         }
+///
+///         Ok(())
+///     })
+/// }
+/// ```
+pub async fn copy_file_range(
+    src: &File,
+    src_off: u64,
+    dst: &File,
+    dst_off: u64,
+    len: usize,
+) -> io::Result<usize> {
+    driver::splice(
+        src.as_shared_fd(),
+        Some(src_off),
+        dst.as_shared_fd(),
+        Some(dst_off),
+        len,
+    )
+    .await
+}
@@ -1,8 +1,51 @@
 use crate::driver::Op;
+use crate::fs::File;
 
 use std::io;
 use std::path::Path;
 
+/// Creates a new, empty directory at the provided path.
+///
+/// The directory is created with permissions `0o777`, before the process
+/// umask is applied, as with [`std::fs::create_dir`].
+///
+/// # Errors
+///
+/// Fails with [`AlreadyExists`] if `path` already exists, and with
+/// [`NotFound`] if its parent does not exist.
+///
+/// [`AlreadyExists`]: io::ErrorKind::AlreadyExists
+/// [`NotFound`]: io::ErrorKind::NotFound
+///
+/// # Examples
+///
+/// ```no_run
+/// use tokio_uring::fs::create_dir;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     tokio_uring::start(async {
+///         create_dir("/some/dir").await?;
+///         Ok::<(), std::io::Error>(())
+///     })?;
+///     Ok(())
+/// }
+/// ```
+pub async fn create_dir<P: AsRef<Path>>(path: P) -> io::Result<()> {
+    Op::mkdir_at(None, path.as_ref(), 0o777)?.await.result?;
+    Ok(())
+}
+
+/// Creates a new, empty directory at `path`, relative to the directory
+/// `dir`. See [`create_dir`].
+///
+/// An absolute `path` ignores `dir`.
+pub async fn create_dir_at<P: AsRef<Path>>(dir: &File, path: P) -> io::Result<()> {
+    Op::mkdir_at(Some(dir.as_shared_fd()), path.as_ref(), 0o777)?
+        .await
+        .result?;
+    Ok(())
+}
+
 /// Removes an empty directory.
 ///
 /// # Examples
@@ -19,9 +62,18 @@ use std::path::Path;
 /// }
 /// ```
 pub async fn remove_dir<P: AsRef<Path>>(path: P) -> io::Result<()> {
-    let op = Op::unlink_dir(path.as_ref())?;
+    let op = Op::unlink_dir(None, path.as_ref())?;
     let completion = op.await;
     completion.result?;
 
     Ok(())
 }
+
+/// Removes an empty directory at `path`, relative to the directory `dir`.
+/// See [`remove_dir`].
+pub async fn remove_dir_at<P: AsRef<Path>>(dir: &File, path: P) -> io::Result<()> {
+    Op::unlink_dir(Some(dir.as_shared_fd()), path.as_ref())?
+        .await
+        .result?;
+    Ok(())
+}
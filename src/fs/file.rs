@@ -1,18 +1,25 @@
-use crate::buf::{IoBuf, IoBufMut};
+use crate::buf::{self, IoBuf, IoBufMut, IoVectoredBuf, IoVectoredBufMut};
 use crate::driver::{Op, SharedFd};
-use crate::fs::OpenOptions;
+use crate::fs::{statx, Advice, OpenOptions, Statx};
 
 use std::fmt;
 use std::io;
-use std::os::unix::io::{AsRawFd, RawFd};
+use std::os::unix::io::{AsRawFd, BorrowedFd, RawFd};
 use std::path::Path;
 
 /// A reference to an open file on the filesystem.
 ///
 /// An instance of a `File` can be read and/or written depending on what options
 /// it was opened with. The `File` type provides **positional** read and write
-/// operations. The file does not maintain an internal cursor. The caller is
-/// required to specify an offset when issuing an operation.
+/// operations, like [`read_at`], to which the caller specifies an offset.
+/// They neither use nor move the file position.
+///
+/// For sequential access, [`read`] and [`write`] use and advance the file
+/// position kept by the kernel, as `read(2)` and `write(2)` do. Since several
+/// operations can be in flight at once, and the kernel does not serialize
+/// their updates of the position, such operations must not be interleaved:
+/// each must complete before the next one is submitted. [`BufReader`] and
+/// [`BufWriter`] track their own offset instead.
 ///
 /// While files are automatically closed when they go out of scope, the
 /// operation happens asynchronously in the background. It is recommended to
@@ -22,6 +29,11 @@ use std::path::Path;
 /// the filesystem.
 ///
 /// [`sync_all`]: File::sync_all
+/// [`read_at`]: File::read_at
+/// [`read`]: File::read
+/// [`write`]: File::write
+/// [`BufReader`]: crate::fs::BufReader
+/// [`BufWriter`]: crate::fs::BufWriter
 ///
 /// # Examples
 ///
@@ -56,6 +68,21 @@ pub struct File {
     fd: SharedFd,
 }
 
+/// The offset of operations at the file position.
+const CURRENT_POS: u64 = u64::MAX;
+
+/// Rejects the offset the kernel takes as the file position, so positional
+/// operations never move it.
+fn check_pos(pos: u64) -> io::Result<()> {
+    if pos == CURRENT_POS {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "offset is out of range",
+        ));
+    }
+    Ok(())
+}
+
 impl File {
     /// Attempts to open a file in read-only mode.
     ///
@@ -85,6 +112,31 @@ impl File {
         OpenOptions::new().read(true).open(path).await
     }
 
+    /// Attempts to open a file in read-only mode, at `path` relative to the
+    /// directory `dir`.
+    ///
+    /// See the [`OpenOptions::open_at`] method for more details, and for
+    /// restricting the file to be within `dir`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_uring::fs::File;
+    ///
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     tokio_uring::start(async {
+    ///         let dir = File::open("/etc").await?;
+    ///         let f = File::open_at(&dir, "hostname").await?;
+    ///
+    ///         f.close().await?;
+    ///         Ok(())
+    ///     })
+    /// }
+    /// ```
+    pub async fn open_at(dir: &File, path: impl AsRef<Path>) -> io::Result<File> {
+        OpenOptions::new().read(true).open_at(dir, path).await
+    }
+
     /// Opens a file in write-only mode.
     ///
     /// This function will create a file if it does not exist,
@@ -120,6 +172,10 @@ impl File {
         File { fd }
     }
 
+    pub(crate) fn as_shared_fd(&self) -> &SharedFd {
+        &self.fd
+    }
+
     /// Read some bytes at the specified offset from the file into the specified
     /// buffer, returning how many bytes were read.
     ///
@@ -143,6 +199,10 @@ impl File {
     /// If this function encounters any form of I/O or other error, an error
     /// variant will be returned. The buffer is returned on error.
     ///
+    /// An offset of `u64::MAX`, which the kernel would take as the file
+    /// position, fails with [`InvalidInput`](io::ErrorKind::InvalidInput).
+    /// Use [`read`](File::read) to read at the file position.
+    ///
     /// # Examples
     ///
     /// ```no_run
@@ -166,6 +226,10 @@ impl File {
     /// }
     /// ```
     pub async fn read_at<T: IoBufMut>(&self, buf: T, pos: u64) -> crate::BufResult<usize, T> {
+        if let Err(e) = check_pos(pos) {
+            return (Err(e), buf);
+        }
+
         // Submit the read operation
         let op = Op::read_at(&self.fd, buf, pos).unwrap();
         op.read().await
@@ -194,6 +258,10 @@ impl File {
     /// It is **not** considered an error if the entire buffer could not be
     /// written to this writer.
     ///
+    /// An offset of `u64::MAX`, which the kernel would take as the file
+    /// position, fails with [`InvalidInput`](io::ErrorKind::InvalidInput).
+    /// Use [`write`](File::write) to write at the file position.
+    ///
     /// # Examples
     ///
     /// ```no_run
@@ -218,10 +286,162 @@ impl File {
     ///
     /// [`Ok(n)`]: Ok
     pub async fn write_at<T: IoBuf>(&self, buf: T, pos: u64) -> crate::BufResult<usize, T> {
+        if let Err(e) = check_pos(pos) {
+            return (Err(e), buf);
+        }
+
         let op = Op::write_at(&self.fd, buf, pos).unwrap();
         op.write().await
     }
 
+    /// Read some bytes from the file at the file position into the specified
+    /// buffer, returning how many bytes were read, and advancing the position
+    /// past them.
+    ///
+    /// This is the counterpart of `read(2)`: the read is submitted with an
+    /// offset of `-1`, which has the kernel use the file position, shared
+    /// with duplicates of the descriptor (Linux 5.6). Return values are as
+    /// for [`read_at`](File::read_at).
+    ///
+    /// The kernel reads the position when the read starts, and updates it
+    /// when it completes, so reads and writes at the file position must not
+    /// be in flight at once: each must complete before the next one is
+    /// submitted, or they may use the same position. Positional operations
+    /// can be interleaved freely.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_uring::fs::File;
+    ///
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     tokio_uring::start(async {
+    ///         let f = File::open("foo.txt").await?;
+    ///
+    ///         // Stream the file, chunk after chunk
+    ///         let mut buf = Vec::with_capacity(4096);
+    ///         loop {
+    ///             let (res, b) = f.read(buf).await;
+    ///             if res? == 0 {
+    ///                 break;
+    ///             }
+    ///             println!("The bytes: {:?}", b);
+    ///             buf = b;
+    ///             buf.clear();
+    ///         }
+    ///
+    ///         Ok(())
+    ///     })
+    /// }
+    /// ```
+    pub async fn read<T: IoBufMut>(&self, buf: T) -> crate::BufResult<usize, T> {
+        let op = Op::read_at(&self.fd, buf, CURRENT_POS).unwrap();
+        op.read().await
+    }
+
+    /// Write a buffer into this file at the file position, returning how
+    /// many bytes were written, and advancing the position past them.
+    ///
+    /// This is the counterpart of `write(2)`, see [`read`](File::read): the
+    /// same restrictions on operations in flight apply. Files opened in
+    /// append mode are written at their end. Return values are as for
+    /// [`write_at`](File::write_at).
+    pub async fn write<T: IoBuf>(&self, buf: T) -> crate::BufResult<usize, T> {
+        let op = Op::write_at(&self.fd, buf, CURRENT_POS).unwrap();
+        op.write().await
+    }
+
+    /// Read data from the file at the specified offset into several buffers,
+    /// filling them in order, with a single `readv` operation. Returns the
+    /// original buffers and the total quantity of data read.
+    ///
+    /// Each buffer is filled up to its capacity before the next one is
+    /// written to. The buffers can be any [`IoVectoredBufMut`], such as a
+    /// `Vec` or an array of buffers.
+    ///
+    /// # Errors
+    ///
+    /// Fails with [`InvalidInput`](io::ErrorKind::InvalidInput) if there are
+    /// more than `IOV_MAX` (1024) buffers, or if `pos` is `u64::MAX`, without
+    /// reading anything.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_uring::fs::File;
+    ///
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     tokio_uring::start(async {
+    ///         let f = File::open("foo.db").await?;
+    ///
+    ///         // Read a 16 byte header and the payload following it
+    ///         let bufs = [Vec::with_capacity(16), Vec::with_capacity(4096)];
+    ///         let (res, [header, payload]) = f.readv_at(bufs, 4096).await;
+    ///         let n = res?;
+    ///
+    ///         println!("read {} bytes: {:?} {:?}", n, header, payload);
+    ///         Ok(())
+    ///     })
+    /// }
+    /// ```
+    pub async fn readv_at<T: IoVectoredBufMut>(
+        &self,
+        bufs: T,
+        pos: u64,
+    ) -> crate::BufResult<usize, T> {
+        if let Err(e) = check_pos(pos).and_then(|_| buf::check_iov_max(&bufs)) {
+            return (Err(e), bufs);
+        }
+
+        let op = Op::readv_at(&self.fd, bufs, pos).unwrap();
+        op.readv().await
+    }
+
+    /// Write data from several buffers to the file at the specified offset,
+    /// in order, with a single `writev` operation. Returns the original
+    /// buffers and the total quantity of data written.
+    ///
+    /// The write may be short, in which case only the first bytes, across
+    /// the buffers, were written. The buffers can be any [`IoVectoredBuf`],
+    /// such as a `Vec` or an array of buffers.
+    ///
+    /// # Errors
+    ///
+    /// Fails with [`InvalidInput`](io::ErrorKind::InvalidInput) if there are
+    /// more than `IOV_MAX` (1024) buffers, or if `pos` is `u64::MAX`, without
+    /// writing anything.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_uring::fs::File;
+    ///
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     tokio_uring::start(async {
+    ///         let f = File::create("foo.db").await?;
+    ///
+    ///         // Write a header and its payload without concatenating them
+    ///         let bufs = [b"len:5\n".to_vec(), b"hello".to_vec()];
+    ///         let (res, _) = f.writev_at(bufs, 4096).await;
+    ///         assert_eq!(res?, 11);
+    ///
+    ///         Ok(())
+    ///     })
+    /// }
+    /// ```
+    pub async fn writev_at<T: IoVectoredBuf>(
+        &self,
+        bufs: T,
+        pos: u64,
+    ) -> crate::BufResult<usize, T> {
+        if let Err(e) = check_pos(pos).and_then(|_| buf::check_iov_max(&bufs)) {
+            return (Err(e), bufs);
+        }
+
+        let op = Op::writev_at(&self.fd, bufs, pos).unwrap();
+        op.writev().await
+    }
+
     /// Attempts to sync all OS-internal metadata to disk.
     ///
     /// This function will attempt to ensure that all in-memory data reaches the
@@ -299,6 +519,223 @@ impl File {
         Ok(())
     }
 
+    /// Initiates or waits for writeback of the range of `nbytes` bytes
+    /// starting at `offset`, with the semantics of `sync_file_range(2)`.
+    ///
+    /// `flags` is a combination of `SYNC_FILE_RANGE_WAIT_BEFORE`,
+    /// `SYNC_FILE_RANGE_WRITE` and `SYNC_FILE_RANGE_WAIT_AFTER`, from the
+    /// `libc` crate. An `nbytes` of zero extends the range to the end of the
+    /// file.
+    ///
+    /// Unlike [`sync_all`] and [`sync_data`], this neither flushes the file
+    /// metadata nor the disk write cache, so it does not guarantee
+    /// durability. It is meant to start the writeback of data written so far
+    /// early, making a later [`sync_data`] cheaper.
+    ///
+    /// [`sync_all`]: File::sync_all
+    /// [`sync_data`]: File::sync_data
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_uring::fs::File;
+    ///
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     tokio_uring::start(async {
+    ///         let f = File::create("foo.txt").await?;
+    ///         let (res, _) = f.write_at(vec![0; 4096], 0).await;
+    ///         res?;
+    ///
+    ///         // Start writing the first page back
+    ///         f.sync_file_range(0, 4096, libc::SYNC_FILE_RANGE_WRITE).await?;
+    ///
+    ///         f.close().await?;
+    ///         Ok(())
+    ///     })
+    /// }
+    /// ```
+    pub async fn sync_file_range(&self, offset: u64, nbytes: u32, flags: u32) -> io::Result<()> {
+        let op = Op::sync_file_range(&self.fd, offset, nbytes, flags).unwrap();
+        let completion = op.await;
+
+        completion.result?;
+        Ok(())
+    }
+
+    /// Manipulates the disk space allocated to the range of `len` bytes
+    /// starting at `offset`, with the semantics of `fallocate(2)`.
+    ///
+    /// With a `mode` of zero, the range is allocated, and the file extended
+    /// if the range ends past its end. `mode` is a combination of the
+    /// `FALLOC_FL_*` flags from the `libc` crate, most commonly:
+    ///
+    /// * `FALLOC_FL_KEEP_SIZE`: allocate without changing the file size.
+    /// * `FALLOC_FL_PUNCH_HOLE | FALLOC_FL_KEEP_SIZE`: deallocate the range,
+    ///   which then reads as zeros.
+    ///
+    /// # Errors
+    ///
+    /// Fails with [`InvalidInput`] if `len` is zero, or if
+    /// `FALLOC_FL_PUNCH_HOLE` is not combined with `FALLOC_FL_KEEP_SIZE`.
+    /// Filesystems lacking support for a mode fail with `EOPNOTSUPP`,
+    /// reported as [`Unsupported`].
+    ///
+    /// [`InvalidInput`]: io::ErrorKind::InvalidInput
+    /// [`Unsupported`]: io::ErrorKind::Unsupported
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_uring::fs::File;
+    ///
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     tokio_uring::start(async {
+    ///         let f = File::create("foo.txt").await?;
+    ///
+    ///         // Reserve 1 MiB up front
+    ///         f.fallocate(0, 1024 * 1024, libc::FALLOC_FL_KEEP_SIZE).await?;
+    ///
+    ///         f.close().await?;
+    ///         Ok(())
+    ///     })
+    /// }
+    /// ```
+    pub async fn fallocate(&self, offset: u64, len: u64, mode: i32) -> io::Result<()> {
+        if len == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "fallocate length must be non-zero",
+            ));
+        }
+
+        if mode & libc::FALLOC_FL_PUNCH_HOLE != 0 && mode & libc::FALLOC_FL_KEEP_SIZE == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "FALLOC_FL_PUNCH_HOLE must be combined with FALLOC_FL_KEEP_SIZE",
+            ));
+        }
+
+        let op = Op::fallocate(&self.fd, offset, len, mode).unwrap();
+        let completion = op.await;
+
+        completion.result?;
+        Ok(())
+    }
+
+    /// Truncates or extends the file to `size` bytes, as with
+    /// [`std::fs::File::set_len`].
+    ///
+    /// Shrinking the file discards the data past `size`. Extending it leaves
+    /// a hole which reads as zeros and takes no disk space; call
+    /// [`fallocate`](File::fallocate) to allocate it. The file position is
+    /// unchanged.
+    ///
+    /// # Implementation
+    ///
+    /// The io-uring versions this crate builds on have no `ftruncate(2)`
+    /// opcode, so the call runs on a thread of the Tokio blocking pool,
+    /// through a duplicate of the file descriptor. Dropping the future does
+    /// not cancel the call.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_uring::fs::File;
+    ///
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     tokio_uring::start(async {
+    ///         let f = File::create("foo.txt").await?;
+    ///         f.set_len(1024).await?;
+    ///
+    ///         f.close().await?;
+    ///         Ok(())
+    ///     })
+    /// }
+    /// ```
+    pub async fn set_len(&self, size: u64) -> io::Result<()> {
+        // Safety: the fd is open for as long as `self` is borrowed.
+        let fd = unsafe { BorrowedFd::borrow_raw(self.fd.raw_fd()) };
+        let file = std::fs::File::from(fd.try_clone_to_owned()?);
+
+        tokio::task::spawn_blocking(move || file.set_len(size))
+            .await
+            .map_err(io::Error::other)?
+    }
+
+    /// Announces the expected access pattern of the range of `len` bytes
+    /// starting at `offset`, with `posix_fadvise(2)`. A `len` of zero
+    /// extends the range to the end of the file.
+    ///
+    /// # Examples
+    ///
+    /// Scan a file without keeping it in the page cache:
+    ///
+    /// ```no_run
+    /// use tokio_uring::fs::{Advice, File};
+    ///
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     tokio_uring::start(async {
+    ///         let f = File::open("foo.log").await?;
+    ///         f.fadvise(0, 0, Advice::Sequential).await?;
+    ///
+    ///         let mut pos = 0;
+    ///         let mut buf = vec![0; 1024 * 1024];
+    ///         loop {
+    ///             let (res, b) = f.read_at(buf, pos).await;
+    ///             buf = b;
+    ///             match res? {
+    ///                 0 => break,
+    ///                 n => pos += n as u64,
+    ///             }
+    ///         }
+    ///
+    ///         f.fadvise(0, 0, Advice::DontNeed).await?;
+    ///         Ok(())
+    ///     })
+    /// }
+    /// ```
+    pub async fn fadvise(&self, offset: u64, len: u64, advice: Advice) -> io::Result<()> {
+        let op = Op::fadvise(&self.fd, offset, len, advice.as_raw()).unwrap();
+        let completion = op.await;
+
+        completion.result?;
+        Ok(())
+    }
+
+    /// Retrieves the metadata of the file with `statx(2)`, using the file
+    /// descriptor and `AT_EMPTY_PATH` rather than a path.
+    ///
+    /// This is the asynchronous counterpart of [`std::fs::File::metadata`].
+    /// The size, block counts, mode, ownership and timestamps are requested;
+    /// use [`statx_with_mask`](File::statx_with_mask) to select the fields.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_uring::fs::File;
+    ///
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     tokio_uring::start(async {
+    ///         let f = File::open("foo.txt").await?;
+    ///         let statx = f.statx().await?;
+    ///         println!("{} bytes, modified {:?}", statx.len(), statx.modified());
+    ///
+    ///         f.close().await?;
+    ///         Ok(())
+    ///     })
+    /// }
+    /// ```
+    pub async fn statx(&self) -> io::Result<Statx> {
+        self.statx_with_mask(statx::DEFAULT_MASK).await
+    }
+
+    /// Retrieves the fields of the metadata of the file selected by `mask`,
+    /// a combination of the `STATX_*` flags from the `libc` crate. See
+    /// [`fs::statx_with_mask`](crate::fs::statx_with_mask).
+    pub async fn statx_with_mask(&self, mask: u32) -> io::Result<Statx> {
+        Statx::from_op(Op::statx_fd(&self.fd, mask)?).await
+    }
+
     /// Closes the file.
     ///
     /// The method completes once the close operation has completed,
@@ -306,7 +743,13 @@ impl File {
     ///
     /// If `close` is not called before dropping the file, the file is closed in
     /// the background, but there is no guarantee as to **when** the close
-    /// operation will complete.
+    /// operation will complete, and its errors are lost.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the close. Network file systems may report failed
+    /// writes only then, so the data of the file cannot be relied upon on
+    /// error.
     ///
     /// # Examples
     ///
@@ -325,8 +768,7 @@ impl File {
     /// }
     /// ```
     pub async fn close(self) -> io::Result<()> {
-        self.fd.close().await;
-        Ok(())
+        self.fd.close().await
     }
 }
 
@@ -360,5 +802,76 @@ impl fmt::Debug for File {
 /// }
 /// ```
 pub async fn remove_file<P: AsRef<Path>>(path: P) -> io::Result<()> {
-    Op::unlink_file(path.as_ref())?.await.result.map(|_| ())
+    Op::unlink_file(None, path.as_ref())?
+        .await
+        .result
+        .map(|_| ())
+}
+
+/// Removes the file at `path`, relative to the directory `dir`. See
+/// [`remove_file`].
+///
+/// An absolute `path` ignores `dir`.
+pub async fn remove_file_at<P: AsRef<Path>>(dir: &File, path: P) -> io::Result<()> {
+    Op::unlink_file(Some(dir.as_shared_fd()), path.as_ref())?
+        .await
+        .result
+        .map(|_| ())
+}
+
+/// Renames a file or directory to a new name, replacing the destination if
+/// it exists, as with [`std::fs::rename`].
+///
+/// The rename is atomic: other processes see either the old or the new
+/// file at `to`, which makes writing a temporary file then renaming it into
+/// place a safe way to update a file.
+///
+/// # Errors
+///
+/// Fails with [`NotFound`] if `from` does not exist. `from` and `to` must be
+/// on the same filesystem.
+///
+/// [`NotFound`]: io::ErrorKind::NotFound
+///
+/// # Examples
+///
+/// ```no_run
+/// use tokio_uring::fs::{self, File};
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     tokio_uring::start(async {
+///         let f = File::create("upload.tmp").await?;
+///         let (res, _) = f.write_at(&b"contents"[..], 0).await;
+///         res?;
+///         f.sync_all().await?;
+///         f.close().await?;
+///
+///         fs::rename("upload.tmp", "upload").await?;
+///         Ok::<(), std::io::Error>(())
+///     })?;
+///     Ok(())
+/// }
+/// ```
+pub async fn rename<P: AsRef<Path>, Q: AsRef<Path>>(from: P, to: Q) -> io::Result<()> {
+    Op::rename_at(None, from.as_ref(), None, to.as_ref(), 0)?
+        .await
+        .result
+        .map(|_| ())
+}
+
+/// Renames `from`, relative to the directory `from_dir`, to `to`, relative to
+/// the directory `to_dir`. See [`rename`].
+///
+/// Absolute paths ignore their directory.
+pub async fn rename_at<P: AsRef<Path>, Q: AsRef<Path>>(
+    from_dir: &File,
+    from: P,
+    to_dir: &File,
+    to: Q,
+) -> io::Result<()> {
+    let (from_dir, to_dir) = (from_dir.as_shared_fd(), to_dir.as_shared_fd());
+    Op::rename_at(Some(from_dir), from.as_ref(), Some(to_dir), to.as_ref(), 0)?
+        .await
+        .result
+        .map(|_| ())
 }
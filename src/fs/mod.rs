@@ -1,11 +1,29 @@
 //! Filesystem manipulation operations.
 
+mod advice;
+pub use advice::Advice;
+
+mod buf_reader;
+pub use buf_reader::BufReader;
+
+mod buf_writer;
+pub use buf_writer::BufWriter;
+
+mod copy_file_range;
+pub use copy_file_range::copy_file_range;
+
 mod directory;
-pub use directory::remove_dir;
+pub use directory::{create_dir, create_dir_at, remove_dir, remove_dir_at};
 
 mod file;
-pub use file::remove_file;
 pub use file::File;
+pub use file::{remove_file, remove_file_at, rename, rename_at};
 
 mod open_options;
 pub use open_options::OpenOptions;
+
+mod resolve;
+pub use resolve::ResolveFlags;
+
+pub(crate) mod statx;
+pub use statx::{statx, statx_with_mask, Statx};
@@ -1,5 +1,5 @@
 use crate::driver::{Op, SharedFd};
-use crate::fs::File;
+use crate::fs::{File, ResolveFlags};
 
 use std::io;
 use std::path::Path;
@@ -62,6 +62,7 @@ pub struct OpenOptions {
     create: bool,
     create_new: bool,
     pub(crate) mode: libc::mode_t,
+    pub(crate) resolve: ResolveFlags,
 }
 
 impl OpenOptions {
@@ -94,6 +95,7 @@ impl OpenOptions {
             create: false,
             create_new: false,
             mode: 0o666,
+            resolve: ResolveFlags::empty(),
         }
     }
 
@@ -279,6 +281,60 @@ impl OpenOptions {
         self
     }
 
+    /// Restricts how the path of the file is resolved when opening it, with
+    /// `openat2(2)` (Linux 5.6). See [`ResolveFlags`].
+    ///
+    /// Combined with [`open_at`](OpenOptions::open_at) and
+    /// [`ResolveFlags::BENEATH`], this guarantees the opened file is within
+    /// a directory, even if the path or symbolic links on the way contain
+    /// `..` components: opening a file outside fails with `EXDEV`.
+    ///
+    /// On kernels lacking `IORING_OP_OPENAT2`, opening with resolve flags
+    /// fails with [`Unsupported`](std::io::ErrorKind::Unsupported) rather than
+    /// resolving the path without restrictions. See
+    /// [`Probe::is_supported`] to check for it beforehand.
+    ///
+    /// [`Probe::is_supported`]: crate::Probe::is_supported
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_uring::fs::{File, OpenOptions, ResolveFlags};
+    ///
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     tokio_uring::start(async {
+    ///         let root = File::open("/srv/www").await?;
+    ///
+    ///         // Fails instead of serving /etc/passwd
+    ///         let res = OpenOptions::new()
+    ///             .read(true)
+    ///             .resolve(ResolveFlags::BENEATH)
+    ///             .open_at(&root, "../../etc/passwd")
+    ///             .await;
+    ///         assert!(res.is_err());
+    ///         Ok(())
+    ///     })
+    /// }
+    /// ```
+    pub fn resolve(&mut self, flags: ResolveFlags) -> &mut OpenOptions {
+        self.resolve = flags;
+        self
+    }
+
+    /// Opens a file at `path`, relative to the directory `dir`, with the
+    /// options specified by `self`, using `dir` as the `dirfd` of
+    /// `openat(2)`.
+    ///
+    /// An absolute `path` ignores `dir`, unless restricted with
+    /// [`resolve`](OpenOptions::resolve). See [`open`](OpenOptions::open)
+    /// for the errors.
+    pub async fn open_at(&self, dir: &File, path: impl AsRef<Path>) -> io::Result<File> {
+        let op = Op::open(Some(dir.as_shared_fd()), path.as_ref(), self)?;
+        let completion = op.await;
+
+        Ok(File::from_shared_fd(SharedFd::new(completion.result? as _)))
+    }
+
     /// Opens a file at `path` with the options specified by `self`.
     ///
     /// # Errors
@@ -330,7 +386,7 @@ impl OpenOptions {
     /// [`Other`]: io::ErrorKind::Other
     /// [`PermissionDenied`]: io::ErrorKind::PermissionDenied
     pub async fn open(&self, path: impl AsRef<Path>) -> io::Result<File> {
-        let op = Op::open(path.as_ref(), self)?;
+        let op = Op::open(None, path.as_ref(), self)?;
 
         // Await the completion of the event
         let completion = op.await;
@@ -374,3 +430,9 @@ impl OpenOptions {
         })
     }
 }
+
+impl Default for OpenOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,74 @@
+use std::ops;
+
+/// Restrictions on the resolution of the path of a file being opened, the
+/// `RESOLVE_*` flags of `openat2(2)`.
+///
+/// Set with [`OpenOptions::resolve`]. Flags are combined with `|`.
+///
+/// # Examples
+///
+/// ```
+/// use tokio_uring::fs::ResolveFlags;
+///
+/// let flags = ResolveFlags::BENEATH | ResolveFlags::NO_SYMLINKS;
+/// assert!(flags.contains(ResolveFlags::BENEATH));
+/// ```
+///
+/// [`OpenOptions::resolve`]: crate::fs::OpenOptions::resolve
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ResolveFlags(u64);
+
+impl ResolveFlags {
+    /// Do not cross mount points (`RESOLVE_NO_XDEV`).
+    pub const NO_XDEV: ResolveFlags = ResolveFlags(libc::RESOLVE_NO_XDEV);
+
+    /// Do not follow "magic links" such as `/proc/self/fd/N`
+    /// (`RESOLVE_NO_MAGICLINKS`).
+    pub const NO_MAGICLINKS: ResolveFlags = ResolveFlags(libc::RESOLVE_NO_MAGICLINKS);
+
+    /// Do not follow symbolic links (`RESOLVE_NO_SYMLINKS`).
+    pub const NO_SYMLINKS: ResolveFlags = ResolveFlags(libc::RESOLVE_NO_SYMLINKS);
+
+    /// Fail if the path, including symbolic links and `..` components,
+    /// resolves outside of the directory it is relative to
+    /// (`RESOLVE_BENEATH`). Absolute paths are rejected.
+    pub const BENEATH: ResolveFlags = ResolveFlags(libc::RESOLVE_BENEATH);
+
+    /// Resolve the path as if the directory it is relative to was the root
+    /// of the filesystem, as with `chroot(2)` (`RESOLVE_IN_ROOT`).
+    pub const IN_ROOT: ResolveFlags = ResolveFlags(libc::RESOLVE_IN_ROOT);
+
+    /// Returns no flags: paths resolve as with `openat(2)`.
+    pub const fn empty() -> ResolveFlags {
+        ResolveFlags(0)
+    }
+
+    /// Returns the raw `RESOLVE_*` bits.
+    pub const fn bits(self) -> u64 {
+        self.0
+    }
+
+    /// Returns `true` if no flags are set.
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Returns `true` if all the flags of `other` are set.
+    pub const fn contains(self, other: ResolveFlags) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl ops::BitOr for ResolveFlags {
+    type Output = ResolveFlags;
+
+    fn bitor(self, rhs: ResolveFlags) -> ResolveFlags {
+        ResolveFlags(self.0 | rhs.0)
+    }
+}
+
+impl ops::BitOrAssign for ResolveFlags {
+    fn bitor_assign(&mut self, rhs: ResolveFlags) {
+        self.0 |= rhs.0;
+    }
+}
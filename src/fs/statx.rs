@@ -0,0 +1,186 @@
+use crate::driver::Op;
+
+use std::fmt;
+use std::io;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+/// Metadata of a file, as returned by `statx(2)`.
+///
+/// Metadata is retrieved with [`File::statx`] for open files, and with
+/// [`statx`] for paths. The kernel may return fewer fields than requested,
+/// e.g. when the filesystem does not record the creation time: accessors of
+/// fields that may be missing return `None` then. [`mask`](Statx::mask)
+/// returns the `STATX_*` flags of the fields the kernel returned.
+///
+/// [`File::statx`]: crate::fs::File::statx
+pub struct Statx {
+    raw: libc::statx,
+}
+
+/// Fields retrieved by [`statx`] and [`File::statx`]: the fields of
+/// `STATX_BASIC_STATS` and the creation time.
+///
+/// [`File::statx`]: crate::fs::File::statx
+pub(crate) const DEFAULT_MASK: u32 = libc::STATX_BASIC_STATS | libc::STATX_BTIME;
+
+/// Retrieves the metadata of the file at `path`, following symbolic links.
+///
+/// This is the asynchronous counterpart of [`std::fs::metadata`], submitted
+/// as an `IORING_OP_STATX` operation.
+///
+/// # Examples
+///
+/// ```no_run
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     tokio_uring::start(async {
+///         let statx = tokio_uring::fs::statx("foo.txt").await?;
+///         println!("{} bytes", statx.len());
+///         Ok(())
+///     })
+/// }
+/// ```
+pub async fn statx<P: AsRef<Path>>(path: P) -> io::Result<Statx> {
+    statx_with_mask(path, DEFAULT_MASK).await
+}
+
+/// Retrieves the fields of the metadata of the file at `path` selected by
+/// `mask`, a combination of the `STATX_*` flags from the `libc` crate.
+///
+/// Requesting fewer fields may avoid work on some filesystems, e.g. network
+/// filesystems fetching attributes from the server. The kernel may still
+/// return more fields than requested. See [`statx`].
+pub async fn statx_with_mask<P: AsRef<Path>>(path: P, mask: u32) -> io::Result<Statx> {
+    Statx::from_op(Op::statx_path(path.as_ref(), mask)?).await
+}
+
+impl Statx {
+    pub(crate) async fn from_op(op: Op<crate::driver::Statx>) -> io::Result<Statx> {
+        let completion = op.await;
+        completion.result?;
+
+        Ok(Statx {
+            raw: *completion.data.statx,
+        })
+    }
+
+    /// Returns the `STATX_*` flags of the fields returned by the kernel.
+    pub fn mask(&self) -> u32 {
+        self.raw.stx_mask
+    }
+
+    fn has(&self, mask: u32) -> bool {
+        self.raw.stx_mask & mask == mask
+    }
+
+    /// Returns the size of the file, in bytes.
+    pub fn len(&self) -> u64 {
+        self.raw.stx_size
+    }
+
+    /// Returns `true` if the file is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of 512 byte blocks allocated to the file.
+    pub fn blocks(&self) -> u64 {
+        self.raw.stx_blocks
+    }
+
+    /// Returns the preferred block size for I/O on the file.
+    pub fn blksize(&self) -> u32 {
+        self.raw.stx_blksize
+    }
+
+    /// Returns the file type and permissions, as in `st_mode`.
+    pub fn mode(&self) -> u32 {
+        self.raw.stx_mode as u32
+    }
+
+    /// Returns `true` if the file is a regular file.
+    pub fn is_file(&self) -> bool {
+        self.file_type() == libc::S_IFREG
+    }
+
+    /// Returns `true` if the file is a directory.
+    pub fn is_dir(&self) -> bool {
+        self.file_type() == libc::S_IFDIR
+    }
+
+    /// Returns `true` if the file is a symbolic link. Only possible for
+    /// metadata retrieved without following links.
+    pub fn is_symlink(&self) -> bool {
+        self.file_type() == libc::S_IFLNK
+    }
+
+    fn file_type(&self) -> u32 {
+        self.mode() & libc::S_IFMT
+    }
+
+    /// Returns the number of hard links to the file.
+    pub fn nlink(&self) -> u32 {
+        self.raw.stx_nlink
+    }
+
+    /// Returns the inode number of the file.
+    pub fn ino(&self) -> u64 {
+        self.raw.stx_ino
+    }
+
+    /// Returns the user ID of the owner of the file.
+    pub fn uid(&self) -> u32 {
+        self.raw.stx_uid
+    }
+
+    /// Returns the group ID of the owner of the file.
+    pub fn gid(&self) -> u32 {
+        self.raw.stx_gid
+    }
+
+    /// Returns the last modification time, if returned by the kernel.
+    pub fn modified(&self) -> Option<SystemTime> {
+        self.time(libc::STATX_MTIME, &self.raw.stx_mtime)
+    }
+
+    /// Returns the last access time, if returned by the kernel.
+    pub fn accessed(&self) -> Option<SystemTime> {
+        self.time(libc::STATX_ATIME, &self.raw.stx_atime)
+    }
+
+    /// Returns the last status change time, if returned by the kernel.
+    pub fn changed(&self) -> Option<SystemTime> {
+        self.time(libc::STATX_CTIME, &self.raw.stx_ctime)
+    }
+
+    /// Returns the creation time, if returned by the kernel. Not all
+    /// filesystems record it.
+    pub fn created(&self) -> Option<SystemTime> {
+        self.time(libc::STATX_BTIME, &self.raw.stx_btime)
+    }
+
+    fn time(&self, mask: u32, ts: &libc::statx_timestamp) -> Option<SystemTime> {
+        if !self.has(mask) {
+            return None;
+        }
+
+        let nanos = Duration::from_nanos(ts.tv_nsec as u64);
+        Some(if ts.tv_sec >= 0 {
+            SystemTime::UNIX_EPOCH + Duration::from_secs(ts.tv_sec as u64) + nanos
+        } else {
+            SystemTime::UNIX_EPOCH - Duration::from_secs(ts.tv_sec.unsigned_abs()) + nanos
+        })
+    }
+}
+
+impl fmt::Debug for Statx {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Statx")
+            .field("mask", &self.mask())
+            .field("len", &self.len())
+            .field("mode", &self.mode())
+            .field("ino", &self.ino())
+            .field("modified", &self.modified())
+            .finish()
+    }
+}
@@ -0,0 +1,101 @@
+//! Helpers built on the I/O operations of the crate.
+
+use crate::net::TcpStream;
+
+use std::future::Future;
+use std::io;
+use std::net::Shutdown;
+use std::pin::pin;
+use std::task::Poll;
+
+/// Size of the buffer of each direction of [`copy_bidirectional`].
+const COPY_BUF_SIZE: usize = 64 * 1024;
+
+/// Copies data both ways between two TCP streams, until both directions
+/// reached end-of-file, and returns the number of bytes copied from `a` to
+/// `b` and from `b` to `a`.
+///
+/// Each direction has its own buffer and runs concurrently with the other:
+/// a read is in flight on each stream while the data of the other direction
+/// is written. When one stream reaches end-of-file, the write half of the
+/// other one is shut down, forwarding the half-close, and the opposite
+/// direction keeps running until it reaches end-of-file too.
+///
+/// This is the loop of a TCP proxy. The streams are left open once the copy
+/// completes.
+///
+/// # Errors
+///
+/// The first error of either direction is returned, and cancels the
+/// operations of the other one. The bytes copied before are not reported.
+///
+/// # Examples
+///
+/// ```no_run
+/// use tokio_uring::net::{TcpListener, TcpStream};
+///
+/// fn main() -> std::io::Result<()> {
+///     tokio_uring::start(async {
+///         let listener = TcpListener::bind("127.0.0.1:8080".parse().unwrap())?;
+///
+///         loop {
+///             let (client, _) = listener.accept().await?;
+///
+///             tokio_uring::spawn(async move {
+///                 let upstream = TcpStream::connect("127.0.0.1:9090".parse().unwrap()).await?;
+///                 let (sent, received) =
+///                     tokio_uring::io::copy_bidirectional(&client, &upstream).await?;
+///                 println!("sent {} bytes, received {} bytes", sent, received);
+///                 Ok::<_, std::io::Error>(())
+///             });
+///         }
+///     })
+/// }
+/// ```
+pub async fn copy_bidirectional(a: &TcpStream, b: &TcpStream) -> io::Result<(u64, u64)> {
+    let mut a_to_b = pin!(copy_one(a, b));
+    let mut b_to_a = pin!(copy_one(b, a));
+    let mut copied = (None, None);
+
+    crate::future::poll_fn(|cx| {
+        if copied.0.is_none() {
+            if let Poll::Ready(n) = a_to_b.as_mut().poll(cx) {
+                copied.0 = Some(n?);
+            }
+        }
+        if copied.1.is_none() {
+            if let Poll::Ready(n) = b_to_a.as_mut().poll(cx) {
+                copied.1 = Some(n?);
+            }
+        }
+
+        match copied {
+            (Some(a_to_b), Some(b_to_a)) => Poll::Ready(Ok((a_to_b, b_to_a))),
+            _ => Poll::Pending,
+        }
+    })
+    .await
+}
+
+/// Copies data from `from` to `to` until end-of-file, then shuts the write
+/// half of `to` down.
+async fn copy_one(from: &TcpStream, to: &TcpStream) -> io::Result<u64> {
+    let mut buf = Vec::with_capacity(COPY_BUF_SIZE);
+    let mut copied = 0;
+
+    loop {
+        let (res, read) = from.read(buf).await;
+        let n = res?;
+        if n == 0 {
+            to.shutdown(Shutdown::Write)?;
+            return Ok(copied);
+        }
+
+        let (res, mut written) = to.write_all(read).await;
+        res?;
+        copied += n as u64;
+
+        written.clear();
+        buf = written;
+    }
+}
@@ -71,14 +71,34 @@ macro_rules! syscall {
 
 #[macro_use]
 mod future;
+mod buf_result;
+mod builder;
 mod driver;
+mod eventfd;
+mod multi_thread;
+mod pipe;
+mod poll;
 mod runtime;
+mod splice;
 
 pub mod buf;
 pub mod fs;
+pub mod io;
 pub mod net;
+pub mod time;
 
-pub use runtime::spawn;
+pub use buf_result::BufResultExt;
+pub use builder::{builder, Builder};
+pub use driver::{
+    batch, metrics, probe, register_buf_ring, register_fd, reserve_fd, set_submit_wait_nr,
+    submit_raw, unregister_fd, with_timeout, CancelHandle, Chain, ChainOutput, Metrics, Probe,
+};
+pub use eventfd::{EventFd, EventFdWriter};
+pub use multi_thread::{MultiThreadRuntime, WorkerJoinError, WorkerJoinHandle};
+pub use pipe::{pipe, PipeReader, PipeWriter};
+pub use poll::{poll, poll_multishot, poll_readable, poll_writable};
+pub use runtime::{spawn, Runtime};
+pub use splice::{splice, splice_to_file};
 
 use std::future::Future;
 
@@ -93,7 +113,7 @@ use std::future::Future;
 /// A `tokio-uring` runtime consists of a Tokio `current_thread` runtime and an
 /// `io-uring` driver. All tasks spawned on the `tokio-uring` runtime are
 /// executed on the current thread. To add concurrency, spawn multiple threads,
-/// each with a `tokio-uring` runtime.
+/// each with a `tokio-uring` runtime, e.g. with a [`MultiThreadRuntime`].
 ///
 /// # Examples
 ///
@@ -140,8 +160,7 @@ use std::future::Future;
 /// }
 /// ```
 pub fn start<F: Future>(future: F) -> F::Output {
-    let mut rt = runtime::Runtime::new().unwrap();
-    rt.block_on(future)
+    builder().start(future)
 }
 
 /// A specialized `Result` type for `io-uring` operations with buffers.
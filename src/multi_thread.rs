@@ -0,0 +1,403 @@
+use crate::{Builder, EventFd, EventFdWriter, Runtime};
+
+use std::{
+    any::Any,
+    error, fmt,
+    future::Future,
+    io,
+    pin::Pin,
+    sync::{
+        mpsc::{self, TryRecvError},
+        Arc, Mutex,
+    },
+    task::{Context, Poll, Wake, Waker},
+    thread,
+};
+use tokio::task::{JoinError, JoinHandle};
+
+/// A job sent to a worker, spawning a task on its runtime.
+type Job = Box<dyn FnOnce() + Send>;
+
+/// A set of worker threads, each running a [`Runtime`] with its own io-uring
+/// instance, created by [`Runtime::new_multi_thread`] or
+/// [`Builder::build_multi_thread`].
+///
+/// Tasks are spawned onto a given worker with
+/// [`spawn_on`](MultiThreadRuntime::spawn_on), and run on that worker only.
+/// Since the resources of the crate are bound to the runtime that created
+/// them, and are not `Send`, a connection accepted by a worker stays on its
+/// ring, along with the buffers of its operations.
+///
+/// To share the connections of a port between the workers, each of them can
+/// bind its own [`TcpListener`] to the port: listeners are bound with
+/// `SO_REUSEPORT`, so the kernel distributes the incoming connections between
+/// them.
+///
+/// Dropping the runtime stops the workers once they spawned the tasks sent
+/// to them, and waits for them to shut down, as [`Runtime`] does.
+///
+/// [`TcpListener`]: crate::net::TcpListener
+///
+/// # Examples
+///
+/// ```no_run
+/// use tokio_uring::net::TcpListener;
+/// use tokio_uring::Runtime;
+///
+/// fn main() -> std::io::Result<()> {
+///     let rt = Runtime::new_multi_thread(4)?;
+///
+///     let acceptors: Vec<_> = (0..rt.workers())
+///         .map(|worker| {
+///             rt.spawn_on(worker, || async {
+///                 let listener = TcpListener::bind("127.0.0.1:8080".parse().unwrap())?;
+///                 loop {
+///                     let (stream, _) = listener.accept().await?;
+///                     tokio_uring::spawn(async move {
+///                         let (_, _) = stream.write(b"hello".as_slice()).await;
+///                     });
+///                 }
+///             })
+///         })
+///         .collect();
+///
+///     for acceptor in acceptors {
+///         let res: std::io::Result<()> = acceptor.join().unwrap();
+///         res?;
+///     }
+///
+///     Ok(())
+/// }
+/// ```
+pub struct MultiThreadRuntime {
+    workers: Vec<Worker>,
+}
+
+struct Worker {
+    /// Jobs to spawn, taken on drop to disconnect the worker.
+    jobs: Option<mpsc::Sender<Job>>,
+
+    /// Wakes the worker up to receive jobs.
+    wake: EventFdWriter,
+
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+impl MultiThreadRuntime {
+    pub(crate) fn new(builder: &Builder, workers: usize) -> io::Result<MultiThreadRuntime> {
+        if workers == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "a runtime needs at least one worker",
+            ));
+        }
+
+        // Workers started before a failure are stopped by the drop
+        let mut runtime = MultiThreadRuntime {
+            workers: Vec::with_capacity(workers),
+        };
+
+        for index in 0..workers {
+            let (jobs, receiver) = mpsc::channel();
+            let (ready, started) = mpsc::channel();
+            let builder = builder.clone();
+
+            let thread = thread::Builder::new()
+                .name(format!("tokio-uring-worker-{}", index))
+                .spawn(move || run(&builder, receiver, ready))?;
+
+            match started.recv() {
+                Ok(Ok(wake)) => runtime.workers.push(Worker {
+                    jobs: Some(jobs),
+                    wake,
+                    thread: Some(thread),
+                }),
+                Ok(Err(e)) => {
+                    let _ = thread.join();
+                    return Err(e);
+                }
+                Err(_) => {
+                    let _ = thread.join();
+                    return Err(io::Error::other("worker thread panicked"));
+                }
+            }
+        }
+
+        Ok(runtime)
+    }
+
+    /// Returns the number of workers. They are numbered from 0.
+    pub fn workers(&self) -> usize {
+        self.workers.len()
+    }
+
+    /// Spawns the future returned by `f` as a task of worker `worker`,
+    /// returning a [`WorkerJoinHandle`] for it.
+    ///
+    /// `f` is sent to the worker, which calls it on its thread, so the future
+    /// itself need not be `Send`, and can own resources of the worker. The
+    /// task is spawned with [`spawn`](crate::spawn), and can spawn other
+    /// tasks on its worker the same way.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `worker` is out of bounds, or if the worker thread stopped
+    /// after a panic of its runtime.
+    pub fn spawn_on<F, Fut>(&self, worker: usize, f: F) -> WorkerJoinHandle<Fut::Output>
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future + 'static,
+        Fut::Output: Send + 'static,
+    {
+        let worker = &self.workers[worker];
+        let slot = Arc::new(Mutex::new(Slot {
+            handle: None,
+            waker: None,
+            dropped: false,
+        }));
+
+        let filler = SlotFiller { slot: slot.clone() };
+        let job: Job = Box::new(move || filler.fill(crate::spawn(f())));
+
+        worker
+            .jobs
+            .as_ref()
+            .unwrap()
+            .send(job)
+            .expect("worker thread stopped");
+        worker.wake.write(1).expect("failed to wake the worker");
+
+        WorkerJoinHandle { slot }
+    }
+}
+
+impl Drop for MultiThreadRuntime {
+    fn drop(&mut self) {
+        for worker in &mut self.workers {
+            // The worker stops once it received the jobs sent before
+            drop(worker.jobs.take());
+            let _ = worker.wake.write(1);
+        }
+
+        for worker in &mut self.workers {
+            if let Some(thread) = worker.thread.take() {
+                let _ = thread.join();
+            }
+        }
+    }
+}
+
+impl fmt::Debug for MultiThreadRuntime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MultiThreadRuntime")
+            .field("workers", &self.workers.len())
+            .finish()
+    }
+}
+
+/// Runs a worker: spawns the jobs it receives, until the runtime is dropped.
+fn run(
+    builder: &Builder,
+    jobs: mpsc::Receiver<Job>,
+    ready: mpsc::Sender<io::Result<EventFdWriter>>,
+) {
+    let mut rt = match Runtime::new(builder) {
+        Ok(rt) => rt,
+        Err(e) => {
+            let _ = ready.send(Err(e));
+            return;
+        }
+    };
+
+    rt.block_on(async move {
+        let eventfd = match EventFd::new(0).and_then(|eventfd| {
+            let writer = eventfd.writer()?;
+            Ok((eventfd, writer))
+        }) {
+            Ok((eventfd, writer)) => {
+                let _ = ready.send(Ok(writer));
+                eventfd
+            }
+            Err(e) => {
+                let _ = ready.send(Err(e));
+                return;
+            }
+        };
+
+        loop {
+            loop {
+                match jobs.try_recv() {
+                    Ok(job) => job(),
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Disconnected) => return,
+                }
+            }
+
+            // Jobs sent since the queue was drained left the counter
+            // non-zero, so they are not missed
+            if eventfd.read().await.is_err() {
+                return;
+            }
+        }
+    });
+}
+
+/// A handle to a task spawned by [`MultiThreadRuntime::spawn_on`].
+///
+/// Awaiting the handle, from any thread, returns the output of the task, or
+/// a [`WorkerJoinError`] if it panicked, was dropped when its worker shut
+/// down, or was never spawned because its worker stopped first. Threads
+/// outside of a runtime can wait for it with
+/// [`join`](WorkerJoinHandle::join). Dropping the handle detaches the task.
+pub struct WorkerJoinHandle<T> {
+    slot: Arc<Mutex<Slot<T>>>,
+}
+
+struct Slot<T> {
+    /// Set once the worker spawned the task.
+    handle: Option<JoinHandle<T>>,
+
+    waker: Option<Waker>,
+
+    /// Set if the job was dropped without spawning the task.
+    dropped: bool,
+}
+
+impl<T> WorkerJoinHandle<T> {
+    /// Blocks the current thread until the task completes, and returns its
+    /// output.
+    ///
+    /// This must not be called from a worker, which would block its runtime.
+    pub fn join(mut self) -> Result<T, WorkerJoinError> {
+        let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+        let mut cx = Context::from_waker(&waker);
+
+        loop {
+            if let Poll::Ready(output) = Pin::new(&mut self).poll(&mut cx) {
+                return output;
+            }
+            thread::park();
+        }
+    }
+}
+
+impl<T> Future for WorkerJoinHandle<T> {
+    type Output = Result<T, WorkerJoinError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut slot = self.slot.lock().unwrap();
+        let slot = &mut *slot;
+
+        match slot.handle.as_mut() {
+            Some(handle) => Pin::new(handle)
+                .poll(cx)
+                .map_err(|e| WorkerJoinError(Repr::Join(e))),
+            None if slot.dropped => Poll::Ready(Err(WorkerJoinError(Repr::NotSpawned))),
+            None => {
+                slot.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl<T> fmt::Debug for WorkerJoinHandle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WorkerJoinHandle").finish()
+    }
+}
+
+/// The error of a task spawned by [`MultiThreadRuntime::spawn_on`] that did
+/// not complete, returned by its [`WorkerJoinHandle`].
+pub struct WorkerJoinError(Repr);
+
+enum Repr {
+    /// The task panicked, or was dropped when its worker shut down.
+    Join(JoinError),
+
+    /// The worker stopped before it spawned the task, e.g. because the
+    /// closure creating the task panicked.
+    NotSpawned,
+}
+
+impl WorkerJoinError {
+    /// Returns `true` if the task was dropped before completing, either when
+    /// its worker shut down, or because its worker stopped before spawning
+    /// it.
+    pub fn is_cancelled(&self) -> bool {
+        match &self.0 {
+            Repr::Join(e) => e.is_cancelled(),
+            Repr::NotSpawned => true,
+        }
+    }
+
+    /// Returns `true` if the task panicked.
+    pub fn is_panic(&self) -> bool {
+        match &self.0 {
+            Repr::Join(e) => e.is_panic(),
+            Repr::NotSpawned => false,
+        }
+    }
+
+    /// Returns the payload of the panic of the task, or the error itself if
+    /// the task did not panic.
+    pub fn try_into_panic(self) -> Result<Box<dyn Any + Send + 'static>, WorkerJoinError> {
+        match self.0 {
+            Repr::Join(e) => e
+                .try_into_panic()
+                .map_err(|e| WorkerJoinError(Repr::Join(e))),
+            Repr::NotSpawned => Err(self),
+        }
+    }
+}
+
+impl fmt::Display for WorkerJoinError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.0 {
+            Repr::Join(e) => e.fmt(f),
+            Repr::NotSpawned => f.write_str("worker stopped before spawning the task"),
+        }
+    }
+}
+
+impl fmt::Debug for WorkerJoinError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.0 {
+            Repr::Join(e) => e.fmt(f),
+            Repr::NotSpawned => f.write_str("WorkerJoinError::NotSpawned"),
+        }
+    }
+}
+
+impl error::Error for WorkerJoinError {}
+
+/// Hands the handle of the task over to its [`WorkerJoinHandle`].
+struct SlotFiller<T> {
+    slot: Arc<Mutex<Slot<T>>>,
+}
+
+impl<T> SlotFiller<T> {
+    fn fill(self, handle: JoinHandle<T>) {
+        self.slot.lock().unwrap().handle = Some(handle);
+    }
+}
+
+impl<T> Drop for SlotFiller<T> {
+    fn drop(&mut self) {
+        let mut slot = self.slot.lock().unwrap();
+        if slot.handle.is_none() {
+            slot.dropped = true;
+        }
+        if let Some(waker) = slot.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+struct ThreadWaker(thread::Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
@@ -7,16 +7,25 @@
 //!
 //! * [`TcpListener`] and [`TcpStream`] provide functionality for communication over TCP
 //! * [`UdpSocket`] provides functionality for communication over UDP
+//! * [`UnixListener`] and [`UnixStream`] provide functionality for communication over Unix
+//!   domain stream sockets, and [`UnixDatagram`] over Unix domain datagram sockets
 
 //!
 //! [`TcpListener`]: TcpListener
 //! [`TcpStream`]: TcpStream
 //! [`UdpSocket`]: UdpSocket
+//! [`UnixListener`]: UnixListener
+//! [`UnixStream`]: UnixStream
+//! [`UnixDatagram`]: UnixDatagram
 
+mod resolve;
 mod tcp;
 mod udp;
 mod unix;
 
-pub use tcp::{TcpListener, TcpStream};
-pub use udp::UdpSocket;
-pub use unix::{UnixListener, UnixStream};
+pub use crate::driver::{CancelableRecv, RecvGuard};
+pub use tcp::{
+    OwnedReadHalf, OwnedWriteHalf, ReadHalf, TcpKeepalive, TcpListener, TcpStream, WriteHalf,
+};
+pub use udp::{PacketInfo, RecvSlot, SendMmsgResult, UdpSocket};
+pub use unix::{UCred, UnixDatagram, UnixListener, UnixStream};
@@ -0,0 +1,39 @@
+use std::future::Future;
+use std::io;
+use std::net::{SocketAddr, ToSocketAddrs};
+
+/// Resolves `addrs` on the blocking pool of the Tokio runtime, so a slow
+/// `getaddrinfo(3)` does not hold up the ring.
+pub(crate) async fn resolve<A>(addrs: A) -> io::Result<Vec<SocketAddr>>
+where
+    A: ToSocketAddrs + Send + 'static,
+{
+    tokio::task::spawn_blocking(move || addrs.to_socket_addrs().map(Iterator::collect))
+        .await
+        .map_err(io::Error::other)?
+}
+
+/// Resolves `addrs`, then calls `f` with each address in turn, until a call
+/// succeeds. Returns the error of the last call if none did.
+pub(crate) async fn try_each<A, T, F, Fut>(addrs: A, mut f: F) -> io::Result<T>
+where
+    A: ToSocketAddrs + Send + 'static,
+    F: FnMut(SocketAddr) -> Fut,
+    Fut: Future<Output = io::Result<T>>,
+{
+    let mut last_err = None;
+
+    for addr in resolve(addrs).await? {
+        match f(addr).await {
+            Ok(value) => return Ok(value),
+            Err(e) => last_err = Some(e),
+        }
+    }
+
+    Err(last_err.unwrap_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "could not resolve to any addresses",
+        )
+    }))
+}
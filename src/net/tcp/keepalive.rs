@@ -0,0 +1,62 @@
+use std::time::Duration;
+
+/// Parameters of TCP keepalive probes, applied with
+/// [`TcpStream::set_keepalive`].
+///
+/// Parameters left unset keep their system default.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use tokio_uring::net::TcpKeepalive;
+///
+/// // Probe after a minute of inactivity, every ten seconds, and give up on
+/// // the peer after three unanswered probes.
+/// let keepalive = TcpKeepalive::new()
+///     .with_time(Duration::from_secs(60))
+///     .with_interval(Duration::from_secs(10))
+///     .with_retries(3);
+/// ```
+///
+/// [`TcpStream::set_keepalive`]: crate::net::TcpStream::set_keepalive
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TcpKeepalive {
+    pub(super) time: Option<Duration>,
+    pub(super) interval: Option<Duration>,
+    pub(super) retries: Option<u32>,
+}
+
+impl TcpKeepalive {
+    /// Returns keepalive parameters using the system defaults.
+    pub fn new() -> TcpKeepalive {
+        TcpKeepalive::default()
+    }
+
+    /// Sets how long the connection must stay idle before the first probe is
+    /// sent, using `TCP_KEEPIDLE`. It is rounded down to whole seconds.
+    pub fn with_time(self, time: Duration) -> TcpKeepalive {
+        TcpKeepalive {
+            time: Some(time),
+            ..self
+        }
+    }
+
+    /// Sets the delay between unanswered probes, using `TCP_KEEPINTVL`. It is
+    /// rounded down to whole seconds.
+    pub fn with_interval(self, interval: Duration) -> TcpKeepalive {
+        TcpKeepalive {
+            interval: Some(interval),
+            ..self
+        }
+    }
+
+    /// Sets how many unanswered probes are sent before the connection is
+    /// dropped, using `TCP_KEEPCNT`.
+    pub fn with_retries(self, retries: u32) -> TcpKeepalive {
+        TcpKeepalive {
+            retries: Some(retries),
+            ..self
+        }
+    }
+}
@@ -1,6 +1,12 @@
 use super::TcpStream;
-use crate::driver::Socket;
-use std::{io, net::SocketAddr};
+use crate::driver::{AcceptMultiStream, BindOptions, Socket};
+use futures_core::Stream;
+use std::{
+    io,
+    net::SocketAddr,
+    pin::Pin,
+    task::{Context, Poll},
+};
 
 /// A TCP socket server, listening for connections.
 ///
@@ -41,14 +47,196 @@ impl TcpListener {
     /// The returned listener is ready for accepting connections.
     ///
     /// Binding with a port number of 0 will request that the OS assigns a port
-    /// to this listener.
+    /// to this listener. The port allocated can be queried via the
+    /// [`local_addr`](TcpListener::local_addr) method.
     ///
-    /// In the future, the port allocated can be queried via a (blocking) `local_addr`
-    /// method.
+    /// The listen backlog is 1024 connections, see
+    /// [`bind_with_backlog`](TcpListener::bind_with_backlog) to change it.
     pub fn bind(addr: SocketAddr) -> io::Result<Self> {
+        TcpListener::bind_with_backlog(addr, 1024)
+    }
+
+    /// Creates a new TcpListener bound to the specified address, with a listen
+    /// backlog of `backlog` connections.
+    ///
+    /// The backlog bounds the connections the kernel completes before they
+    /// are accepted. Once it is full, new connection attempts are dropped, so
+    /// a larger backlog absorbs bursts of connections. The kernel silently
+    /// caps it to `/proc/sys/net/core/somaxconn`.
+    ///
+    /// Returns an error of kind [`InvalidInput`](io::ErrorKind::InvalidInput)
+    /// if `backlog` is not positive.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_uring::net::TcpListener;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     let listener = TcpListener::bind_with_backlog("127.0.0.1:0".parse().unwrap(), 4096)?;
+    ///     println!("listening on {}", listener.local_addr()?);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn bind_with_backlog(addr: SocketAddr, backlog: i32) -> io::Result<Self> {
+        if backlog <= 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "listen backlog must be positive",
+            ));
+        }
+
         let socket = Socket::bind(addr, libc::SOCK_STREAM)?;
+        socket.listen(backlog)?;
+        Ok(TcpListener { inner: socket })
+    }
+
+    /// Creates a new TcpListener for a transparent proxy, bound to the
+    /// specified address.
+    ///
+    /// `IP_TRANSPARENT` and `IP_FREEBIND` are set before binding, so the
+    /// address need not be local to the host. Along with `TPROXY` rules of
+    /// the firewall, the listener accepts connections addressed to other
+    /// hosts, whose original destination is the
+    /// [`local_addr`](TcpStream::local_addr) of the accepted streams.
+    ///
+    /// Setting `IP_TRANSPARENT` requires `CAP_NET_ADMIN` or `CAP_NET_RAW`,
+    /// binding fails with `EPERM` without them. The listen backlog is 1024
+    /// connections.
+    pub fn bind_transparent(addr: SocketAddr) -> io::Result<Self> {
+        let options = BindOptions {
+            transparent: true,
+            ..BindOptions::default()
+        };
+        let socket = Socket::bind_with_options(addr, libc::SOCK_STREAM, options)?;
         socket.listen(1024)?;
-        return Ok(TcpListener { inner: socket });
+        Ok(TcpListener { inner: socket })
+    }
+
+    /// Creates a new `TcpListener` from a listening `std::net::TcpListener`,
+    /// taking ownership of its file descriptor.
+    ///
+    /// This allows using listeners created elsewhere, like the descriptors
+    /// passed by systemd socket activation. The listener is switched to
+    /// non-blocking mode, so it behaves the same whether or not the caller
+    /// configured it. Returns an error if it is not a listening stream socket.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_uring::net::TcpListener;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     tokio_uring::start(async {
+    ///         let std_listener = std::net::TcpListener::bind("127.0.0.1:0")?;
+    ///         let addr = std_listener.local_addr()?;
+    ///
+    ///         let listener = TcpListener::from_std(std_listener)?;
+    ///         assert_eq!(listener.local_addr()?, addr);
+    ///
+    ///         Ok(())
+    ///     })
+    /// }
+    /// ```
+    pub fn from_std(listener: std::net::TcpListener) -> io::Result<TcpListener> {
+        listener.set_nonblocking(true)?;
+
+        let listener = TcpListener {
+            inner: Socket::from_std(listener),
+        };
+        let listening: libc::c_int = listener
+            .inner
+            .getsockopt(libc::SOL_SOCKET, libc::SO_ACCEPTCONN)?;
+        if listening == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "not a listening socket",
+            ));
+        }
+
+        Ok(listener)
+    }
+
+    /// Converts the listener into a `std::net::TcpListener`, relinquishing
+    /// its file descriptor. The blocking mode of the descriptor is left as
+    /// is.
+    ///
+    /// No accept may be in-flight on the listener, including ones left
+    /// behind by dropped futures. Otherwise, an error is returned and the
+    /// listener is closed once the operations complete.
+    pub fn into_std(self) -> io::Result<std::net::TcpListener> {
+        self.inner.into_std()
+    }
+
+    /// Registers the listener in the file table of the runtime, as
+    /// [`TcpStream::use_fixed_file`] does for streams. The slot is released
+    /// when the listener is closed. Accepted streams are not registered,
+    /// call [`TcpStream::use_fixed_file`] on them.
+    pub fn use_fixed_file(&self) -> io::Result<()> {
+        self.inner.use_fixed_file()
+    }
+
+    /// Returns the local address that this listener is bound to.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.inner.local_addr()
+    }
+
+    /// Sets `IP_TRANSPARENT` on this listener, or `IPV6_TRANSPARENT` on IPv6
+    /// ones. It only takes effect for binds made after it is set, see
+    /// [`bind_transparent`](TcpListener::bind_transparent).
+    ///
+    /// Requires `CAP_NET_ADMIN` or `CAP_NET_RAW`, fails with `EPERM`
+    /// otherwise.
+    pub fn set_transparent(&self, transparent: bool) -> io::Result<()> {
+        self.inner.set_transparent(transparent)
+    }
+
+    /// Gets the value of `IP_TRANSPARENT` on this listener.
+    pub fn transparent(&self) -> io::Result<bool> {
+        self.inner.transparent()
+    }
+
+    /// Sets `IP_FREEBIND` on this listener, or `IPV6_FREEBIND` on IPv6 ones,
+    /// letting it bind to addresses not configured on the host. Unlike
+    /// `IP_TRANSPARENT`, it requires no privileges.
+    pub fn set_freebind(&self, freebind: bool) -> io::Result<()> {
+        self.inner.set_freebind(freebind)
+    }
+
+    /// Gets the value of `IP_FREEBIND` on this listener.
+    pub fn freebind(&self) -> io::Result<bool> {
+        self.inner.freebind()
+    }
+
+    /// Sets the socket option `name` at `level` to the raw bytes `value`,
+    /// with `setsockopt(2)`.
+    ///
+    /// This reaches the options without a dedicated method, see
+    /// [`TcpStream::set_sockopt`](crate::net::TcpStream::set_sockopt).
+    pub fn set_sockopt(&self, level: i32, name: i32, value: &[u8]) -> io::Result<()> {
+        self.inner.set_sockopt_bytes(level, name, value)
+    }
+
+    /// Reads the socket option `name` at `level` into `buf`, with
+    /// `getsockopt(2)`, and returns the length of its value.
+    ///
+    /// Values longer than `buf` are truncated by the kernel. See
+    /// [`set_sockopt`](TcpListener::set_sockopt).
+    pub fn get_sockopt(&self, level: i32, name: i32, buf: &mut [u8]) -> io::Result<usize> {
+        self.inner.get_sockopt_bytes(level, name, buf)
+    }
+
+    /// Returns the number of connections established by the kernel and
+    /// waiting to be accepted, along with the listen backlog bounding it.
+    ///
+    /// Both are read from `TCP_INFO`. A queue length close to the backlog
+    /// means connections are accepted too slowly, and new ones are about to
+    /// be dropped.
+    pub fn pending_connections(&self) -> io::Result<(u32, u32)> {
+        let info: libc::tcp_info = self.inner.getsockopt(libc::IPPROTO_TCP, libc::TCP_INFO)?;
+
+        // Listening sockets report the queue in these fields
+        Ok((info.tcpi_unacked, info.tcpi_sacked))
     }
 
     /// Accepts a new incoming connection from this listener.
@@ -57,13 +245,119 @@ impl TcpListener {
     /// established, the corresponding [`TcpStream`] and the remote peer's
     /// address will be returned.
     ///
+    /// The accepted socket is created with `SOCK_CLOEXEC`, so it is not
+    /// inherited by programs the process executes. See
+    /// [`accept_with_flags`](TcpListener::accept_with_flags) to opt out.
+    ///
     /// [`TcpStream`]: struct@crate::net::TcpStream
     pub async fn accept(&self) -> io::Result<(TcpStream, SocketAddr)> {
-        let (socket, socket_addr) = self.inner.accept().await?;
+        self.accept_with_flags(libc::SOCK_CLOEXEC).await
+    }
+
+    /// Accepts a new incoming connection from this listener, setting `flags`
+    /// on the accepted socket, as `accept4(2)` does.
+    ///
+    /// `flags` is a combination of `SOCK_CLOEXEC` and `SOCK_NONBLOCK` from
+    /// the `libc` crate, any other flag fails the accept with `EINVAL`.
+    /// Leaving `SOCK_CLOEXEC` out lets executed programs inherit the socket,
+    /// e.g. to hand a connection over to a helper process.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::os::unix::io::AsRawFd;
+    /// use tokio_uring::net::TcpListener;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     let listener = TcpListener::bind("127.0.0.1:8080".parse().unwrap())?;
+    ///
+    ///     tokio_uring::start(async {
+    ///         // The connection is inherited by a child process
+    ///         let (stream, _) = listener.accept_with_flags(0).await?;
+    ///         let fd = stream.as_raw_fd();
+    ///
+    ///         std::process::Command::new("handler")
+    ///             .env("CONNECTION_FD", fd.to_string())
+    ///             .spawn()?;
+    ///
+    ///         Ok(())
+    ///     })
+    /// }
+    /// ```
+    pub async fn accept_with_flags(&self, flags: i32) -> io::Result<(TcpStream, SocketAddr)> {
+        let (socket, socket_addr) = self.inner.accept_with_flags(flags).await?;
         let stream = TcpStream { inner: socket };
-        let socket_addr = socket_addr.ok_or_else(|| {
-            io::Error::new(io::ErrorKind::Other, "Could not get socket IP address")
-        })?;
+        let socket_addr =
+            socket_addr.ok_or_else(|| io::Error::other("Could not get socket IP address"))?;
         Ok((stream, socket_addr))
     }
+
+    /// Accepts incoming connections with a multishot accept, yielding each
+    /// of them along with the remote peer's address.
+    ///
+    /// A single accept operation stays armed in the kernel and posts a
+    /// completion for every new connection, saving the submission of an
+    /// accept per connection. If the kernel terminates the operation, the
+    /// stream submits a new one.
+    ///
+    /// Errors caused by a lack of resources, like running out of file
+    /// descriptors (`EMFILE`), are yielded without ending the stream, so
+    /// accepting resumes once resources were freed. Any other error ends the
+    /// stream. Dropping the stream cancels the accept.
+    ///
+    /// Multishot accepts require Linux 5.19 or newer.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_uring::net::TcpListener;
+    /// use std::future::poll_fn;
+    /// use std::pin::pin;
+    /// use futures_core::Stream;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     tokio_uring::start(async {
+    ///         let listener = TcpListener::bind("127.0.0.1:8080".parse().unwrap())?;
+    ///
+    ///         let mut incoming = pin!(listener.accept_multishot());
+    ///         while let Some(conn) = poll_fn(|cx| incoming.as_mut().poll_next(cx)).await {
+    ///             let (stream, peer) = conn?;
+    ///             println!("connection from {}", peer);
+    ///             tokio_uring::spawn(async move {
+    ///                 let (_, _) = stream.write(b"hello".as_slice()).await;
+    ///             });
+    ///         }
+    ///
+    ///         Ok(())
+    ///     })
+    /// }
+    /// ```
+    pub fn accept_multishot(&self) -> impl Stream<Item = io::Result<(TcpStream, SocketAddr)>> {
+        Incoming {
+            inner: self.inner.accept_multishot(),
+        }
+    }
+}
+
+/// Connections accepted by [`TcpListener::accept_multishot`].
+struct Incoming {
+    inner: AcceptMultiStream,
+}
+
+impl Stream for Incoming {
+    type Item = io::Result<(TcpStream, SocketAddr)>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let socket = match ready!(Pin::new(&mut self.inner).poll_next(cx)) {
+            Some(Ok(socket)) => socket,
+            Some(Err(e)) => return Poll::Ready(Some(Err(e))),
+            None => return Poll::Ready(None),
+        };
+
+        // Multishot accepts do not report the peer address
+        let result = socket
+            .peer_addr()
+            .map(|socket_addr| (TcpStream { inner: socket }, socket_addr));
+        Poll::Ready(Some(result))
+    }
 }
@@ -1,5 +1,11 @@
+mod keepalive;
+pub use keepalive::TcpKeepalive;
+
 mod listener;
 pub use listener::TcpListener;
 
+mod split;
+pub use split::{OwnedReadHalf, OwnedWriteHalf, ReadHalf, WriteHalf};
+
 mod stream;
 pub use stream::TcpStream;
@@ -0,0 +1,208 @@
+use super::TcpStream;
+use crate::buf::{BufRing, BufSlot, FixedBuf, IoBuf, IoBufMut, IoVectoredBuf, IoVectoredBufMut};
+use crate::net::{CancelableRecv, RecvGuard};
+
+use std::{
+    fmt, io,
+    net::Shutdown,
+    os::unix::io::{AsRawFd, RawFd},
+};
+
+/// The reading operations of a [`TcpStream`], delegated to `self.stream()`.
+macro_rules! read_methods {
+    () => {
+        /// Read some data from the stream into the buffer, see
+        /// [`TcpStream::read`].
+        pub async fn read<T: IoBufMut>(&self, buf: T) -> crate::BufResult<usize, T> {
+            self.stream().read(buf).await
+        }
+
+        /// Read data from the stream until the buffer is filled up to its
+        /// capacity, see [`TcpStream::read_exact`].
+        pub async fn read_exact<T: IoBufMut>(&self, buf: T) -> crate::BufResult<(), T> {
+            self.stream().read_exact(buf).await
+        }
+
+        /// Read data from the stream until the buffer is filled up to its
+        /// capacity with a single receive, see [`TcpStream::read_waitall`].
+        pub async fn read_waitall<T: IoBufMut>(&self, buf: T) -> crate::BufResult<usize, T> {
+            self.stream().read_waitall(buf).await
+        }
+
+        /// Read some data from the stream without removing it from the
+        /// receive queue, see [`TcpStream::peek`].
+        pub async fn peek<T: IoBufMut>(&self, buf: T) -> crate::BufResult<usize, T> {
+            self.stream().peek(buf).await
+        }
+
+        /// Receives data from the stream, handing the buffer to a
+        /// [`RecvGuard`] if the receive is canceled, see
+        /// [`TcpStream::recv_cancelable`].
+        pub fn recv_cancelable<T: IoBufMut>(&self, buf: T) -> (CancelableRecv<T>, RecvGuard<T>) {
+            self.stream().recv_cancelable(buf)
+        }
+
+        /// Read some data from the stream into a buffer picked from
+        /// `buf_ring`, see [`TcpStream::recv_ring`].
+        pub async fn recv_ring(&self, buf_ring: &BufRing) -> io::Result<Option<BufSlot>> {
+            self.stream().recv_ring(buf_ring).await
+        }
+
+        /// Read some data from the stream into a fixed buffer, see
+        /// [`TcpStream::read_fixed`].
+        pub async fn read_fixed(&self, buf: FixedBuf) -> crate::BufResult<usize, FixedBuf> {
+            self.stream().read_fixed(buf).await
+        }
+
+        /// Read some data from the stream into several buffers, see
+        /// [`TcpStream::readv`].
+        pub async fn readv<T: IoVectoredBufMut>(&self, bufs: T) -> crate::BufResult<usize, T> {
+            self.stream().readv(bufs).await
+        }
+    };
+}
+
+/// The writing operations of a [`TcpStream`], delegated to `self.stream()`.
+macro_rules! write_methods {
+    () => {
+        /// Write some data to the stream from the buffer, see
+        /// [`TcpStream::write`].
+        pub async fn write<T: IoBuf>(&self, buf: T) -> crate::BufResult<usize, T> {
+            self.stream().write(buf).await
+        }
+
+        /// Write the whole buffer to the stream, see
+        /// [`TcpStream::write_all`].
+        pub async fn write_all<T: IoBuf>(&self, buf: T) -> crate::BufResult<(), T> {
+            self.stream().write_all(buf).await
+        }
+
+        /// Write some data to the stream, letting the kernel know more data
+        /// follows, see [`TcpStream::send_more`].
+        pub async fn send_more<T: IoBuf>(&self, buf: T) -> crate::BufResult<usize, T> {
+            self.stream().send_more(buf).await
+        }
+
+        /// Write some data to the stream without copying it, see
+        /// [`TcpStream::send_zc`].
+        pub async fn send_zc<T: IoBuf>(&self, buf: T) -> crate::BufResult<usize, T> {
+            self.stream().send_zc(buf).await
+        }
+
+        /// Write the initialized bytes of a fixed buffer to the stream, see
+        /// [`TcpStream::write_fixed`].
+        pub async fn write_fixed(&self, buf: FixedBuf) -> crate::BufResult<usize, FixedBuf> {
+            self.stream().write_fixed(buf).await
+        }
+
+        /// Write data from several buffers to the stream, see
+        /// [`TcpStream::writev`].
+        pub async fn writev<T: IoVectoredBuf>(&self, bufs: T) -> crate::BufResult<usize, T> {
+            self.stream().writev(bufs).await
+        }
+
+        /// Shuts down the write half of the connection, sending a FIN to the
+        /// peer once the data written before was sent. The read half keeps
+        /// receiving data.
+        pub fn shutdown(&self) -> io::Result<()> {
+            self.stream().shutdown(Shutdown::Write)
+        }
+    };
+}
+
+/// The read half of a [`TcpStream`], borrowed by [`TcpStream::split`].
+pub struct ReadHalf<'a> {
+    stream: &'a TcpStream,
+}
+
+/// The write half of a [`TcpStream`], borrowed by [`TcpStream::split`].
+pub struct WriteHalf<'a> {
+    stream: &'a TcpStream,
+}
+
+/// The read half of a [`TcpStream`], owned after [`TcpStream::into_split`].
+///
+/// The socket is closed once both halves are dropped.
+pub struct OwnedReadHalf {
+    stream: TcpStream,
+}
+
+/// The write half of a [`TcpStream`], owned after [`TcpStream::into_split`].
+///
+/// The socket is closed once both halves are dropped. Dropping the write
+/// half alone does not shut the connection down, see
+/// [`shutdown`](OwnedWriteHalf::shutdown).
+pub struct OwnedWriteHalf {
+    stream: TcpStream,
+}
+
+pub(super) fn split(stream: &TcpStream) -> (ReadHalf<'_>, WriteHalf<'_>) {
+    (ReadHalf { stream }, WriteHalf { stream })
+}
+
+pub(super) fn into_split(stream: TcpStream) -> (OwnedReadHalf, OwnedWriteHalf) {
+    // Both halves hold a reference to the file descriptor
+    let write = TcpStream {
+        inner: stream.inner.clone(),
+    };
+    (OwnedReadHalf { stream }, OwnedWriteHalf { stream: write })
+}
+
+impl ReadHalf<'_> {
+    fn stream(&self) -> &TcpStream {
+        self.stream
+    }
+
+    read_methods!();
+}
+
+impl WriteHalf<'_> {
+    fn stream(&self) -> &TcpStream {
+        self.stream
+    }
+
+    write_methods!();
+}
+
+impl OwnedReadHalf {
+    fn stream(&self) -> &TcpStream {
+        &self.stream
+    }
+
+    read_methods!();
+}
+
+impl OwnedWriteHalf {
+    fn stream(&self) -> &TcpStream {
+        &self.stream
+    }
+
+    write_methods!();
+}
+
+macro_rules! impl_common {
+    ($($half:ty => $name:literal,)*) => {
+        $(
+            impl AsRawFd for $half {
+                fn as_raw_fd(&self) -> RawFd {
+                    self.stream().as_raw_fd()
+                }
+            }
+
+            impl fmt::Debug for $half {
+                fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                    f.debug_struct($name)
+                        .field("fd", &self.as_raw_fd())
+                        .finish()
+                }
+            }
+        )*
+    };
+}
+
+impl_common! {
+    ReadHalf<'_> => "ReadHalf",
+    WriteHalf<'_> => "WriteHalf",
+    OwnedReadHalf => "OwnedReadHalf",
+    OwnedWriteHalf => "OwnedWriteHalf",
+}
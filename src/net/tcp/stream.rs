@@ -1,8 +1,16 @@
-use std::{io, net::SocketAddr};
+use std::{
+    convert::TryFrom,
+    io,
+    net::{Shutdown, SocketAddr, ToSocketAddrs},
+    os::unix::io::{AsRawFd, RawFd},
+    time::Duration,
+};
 
+use super::{split, OwnedReadHalf, OwnedWriteHalf, ReadHalf, TcpKeepalive, WriteHalf};
 use crate::{
-    buf::{IoBuf, IoBufMut},
+    buf::{BufRing, BufSlot, FixedBuf, IoBuf, IoBufMut, IoVectoredBuf, IoVectoredBufMut},
     driver::Socket,
+    net::{resolve, CancelableRecv, RecvGuard},
 };
 
 /// A TCP stream between a local and a remote socket.
@@ -34,16 +42,177 @@ use crate::{
 /// [`accepting`]: crate::net::TcpListener::accept
 /// [`listener`]: crate::net::TcpListener
 pub struct TcpStream {
-    pub(super) inner: Socket,
+    pub(crate) inner: Socket,
 }
 
 impl TcpStream {
-    /// Opens a TCP connection to a remote host at the given `SocketAddr`
+    /// Opens a TCP connection to a remote host at the given `SocketAddr`.
+    ///
+    /// The connection is made with an io-uring `connect` operation, which
+    /// resolves once the connection is established. If the connection fails,
+    /// for instance because no one listens on `addr`, the error of the
+    /// connect is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_uring::net::{TcpListener, TcpStream};
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     tokio_uring::start(async {
+    ///         let listener = TcpListener::bind("127.0.0.1:2421".parse().unwrap())?;
+    ///
+    ///         let (stream, accepted) = tokio::try_join!(
+    ///             TcpStream::connect("127.0.0.1:2421".parse().unwrap()),
+    ///             listener.accept(),
+    ///         )?;
+    ///
+    ///         let (result, _) = stream.write(b"hello".as_slice()).await;
+    ///         result?;
+    ///
+    ///         let (result, buf) = accepted.0.read(vec![0; 5]).await;
+    ///         assert_eq!(result?, 5);
+    ///         assert_eq!(buf, b"hello");
+    ///
+    ///         Ok(())
+    ///     })
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// The error the kernel completes the connect with is returned as is, so
+    /// callers can tell the causes apart, e.g. to decide whether to retry:
+    /// nothing listening on the port gives
+    /// [`ConnectionRefused`](io::ErrorKind::ConnectionRefused)
+    /// (`ECONNREFUSED`), a peer that never answers gives
+    /// [`TimedOut`](io::ErrorKind::TimedOut) (`ETIMEDOUT`), and an
+    /// unreachable host gives
+    /// [`HostUnreachable`](io::ErrorKind::HostUnreachable) (`EHOSTUNREACH`).
     pub async fn connect(addr: SocketAddr) -> io::Result<TcpStream> {
         let socket = Socket::new(addr, libc::SOCK_STREAM)?;
         socket.connect(socket2::SockAddr::from(addr)).await?;
         let tcp_stream = TcpStream { inner: socket };
-        return Ok(tcp_stream);
+        Ok(tcp_stream)
+    }
+
+    /// Opens a TCP connection to `remote` from the local address `local`.
+    ///
+    /// The socket is bound to `local` before the connect is submitted, which
+    /// pins the source address of the connection, e.g. to steer traffic
+    /// through one of the uplinks of a multi-homed host. With a port of 0,
+    /// the port is picked when connecting (`IP_BIND_ADDRESS_NO_PORT`), so a
+    /// local port can be shared by connections to different peers.
+    ///
+    /// Returns an error of kind [`InvalidInput`](io::ErrorKind::InvalidInput)
+    /// if `local` and `remote` are of different address families. Errors of
+    /// the connect are returned as by [`connect`](TcpStream::connect).
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_uring::net::TcpStream;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     tokio_uring::start(async {
+    ///         let stream = TcpStream::bind_and_connect(
+    ///             "192.0.2.10:0".parse().unwrap(),
+    ///             "198.51.100.1:80".parse().unwrap(),
+    ///         )
+    ///         .await?;
+    ///
+    ///         let (res, _) = stream.write_all(b"hello".as_slice()).await;
+    ///         res?;
+    ///
+    ///         Ok(())
+    ///     })
+    /// }
+    /// ```
+    pub async fn bind_and_connect(local: SocketAddr, remote: SocketAddr) -> io::Result<TcpStream> {
+        let socket = Socket::new_bound(local, remote, libc::SOCK_STREAM, false)?;
+        socket.connect(socket2::SockAddr::from(remote)).await?;
+        Ok(TcpStream { inner: socket })
+    }
+
+    /// Opens a TCP connection to `remote` from `local`, as a transparent
+    /// proxy does.
+    ///
+    /// `IP_TRANSPARENT` and `IP_FREEBIND` are set before binding, so `local`
+    /// may be an address of another host, typically that of the client whose
+    /// connection is proxied. The replies only reach the socket if the
+    /// routing and the firewall steer them back to the host. Otherwise, this
+    /// behaves like [`bind_and_connect`](TcpStream::bind_and_connect).
+    ///
+    /// Setting `IP_TRANSPARENT` requires `CAP_NET_ADMIN` or `CAP_NET_RAW`,
+    /// this fails with `EPERM` without them.
+    pub async fn connect_transparent(
+        local: SocketAddr,
+        remote: SocketAddr,
+    ) -> io::Result<TcpStream> {
+        let socket = Socket::new_bound(local, remote, libc::SOCK_STREAM, true)?;
+        socket.connect(socket2::SockAddr::from(remote)).await?;
+        Ok(TcpStream { inner: socket })
+    }
+
+    /// Opens a TCP connection to the first address `addrs` resolves to that
+    /// accepts it.
+    ///
+    /// Names are resolved with `getaddrinfo(3)` on the blocking thread pool
+    /// of the runtime, so the ring keeps running meanwhile. The addresses are
+    /// tried one after the other, in the order they were resolved, and the
+    /// error of the last one is returned if none accepts the connection.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_uring::net::TcpStream;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     tokio_uring::start(async {
+    ///         let stream = TcpStream::connect_host("example.com:80").await?;
+    ///
+    ///         let request = b"GET / HTTP/1.0\r\nHost: example.com\r\n\r\n";
+    ///         let (res, _) = stream.write_all(request.as_slice()).await;
+    ///         res?;
+    ///
+    ///         Ok(())
+    ///     })
+    /// }
+    /// ```
+    pub async fn connect_host<A>(addrs: A) -> io::Result<TcpStream>
+    where
+        A: ToSocketAddrs + Send + 'static,
+    {
+        resolve::try_each(addrs, TcpStream::connect).await
+    }
+
+    /// Opens a TCP connection to a remote host at the given `SocketAddr`,
+    /// waiting at most `timeout` for it to be established.
+    ///
+    /// The connect is linked to an `IORING_OP_LINK_TIMEOUT`, so the kernel
+    /// cancels it when the timeout expires, and an error of kind
+    /// [`TimedOut`](io::ErrorKind::TimedOut) is returned. Linked timeouts
+    /// require Linux 5.5 or later. Other errors are returned as by
+    /// [`connect`](TcpStream::connect).
+    pub async fn connect_timeout(addr: SocketAddr, timeout: Duration) -> io::Result<TcpStream> {
+        let socket = Socket::new(addr, libc::SOCK_STREAM)?;
+        socket
+            .connect_timeout(socket2::SockAddr::from(addr), timeout)
+            .await?;
+        let tcp_stream = TcpStream { inner: socket };
+        Ok(tcp_stream)
+    }
+
+    /// Registers the stream in the file table of the runtime, so that its
+    /// operations name it by slot with `IOSQE_FIXED_FILE` instead of by file
+    /// descriptor. This saves the kernel looking the file up for every
+    /// operation.
+    ///
+    /// Calling this again does nothing. The slot is released when the
+    /// stream is closed. See [`register_fd`](crate::register_fd) for the
+    /// limits of the table.
+    pub fn use_fixed_file(&self) -> io::Result<()> {
+        self.inner.use_fixed_file()
     }
 
     /// Read some data from the stream into the buffer, returning the original buffer and
@@ -52,9 +221,766 @@ impl TcpStream {
         self.inner.read(buf).await
     }
 
+    /// Read data from the stream until the buffer is filled up to its
+    /// capacity, returning the original buffer.
+    ///
+    /// Reads are submitted until the whole capacity of the buffer was
+    /// filled, each resuming where the previous one stopped. If the peer
+    /// closes its side of the stream first, an error of kind
+    /// [`ErrorKind::UnexpectedEof`] is returned. On any error, the buffer is
+    /// returned holding the data read so far, as its initialized bytes.
+    ///
+    /// This is the owned buffer counterpart of `AsyncReadExt::read_exact`.
+    ///
+    /// [`ErrorKind::UnexpectedEof`]: std::io::ErrorKind::UnexpectedEof
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::convert::TryInto;
+    /// use tokio_uring::net::TcpStream;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     tokio_uring::start(async {
+    ///         let stream = TcpStream::connect("127.0.0.1:8080".parse().unwrap()).await?;
+    ///
+    ///         // A 4 byte length prefix, then the body
+    ///         let (res, header) = stream.read_exact(Vec::with_capacity(4)).await;
+    ///         res?;
+    ///         let len = u32::from_be_bytes(header[..].try_into().unwrap());
+    ///
+    ///         let (res, body) = stream.read_exact(Vec::with_capacity(len as usize)).await;
+    ///         res?;
+    ///         println!("received {} bytes", body.len());
+    ///
+    ///         Ok(())
+    ///     })
+    /// }
+    /// ```
+    pub async fn read_exact<T: IoBufMut>(&self, buf: T) -> crate::BufResult<(), T> {
+        self.inner.read_exact(buf).await
+    }
+
+    /// Read data from the stream until the buffer is filled up to its
+    /// capacity, with a single receive setting `MSG_WAITALL`. Returns the
+    /// original buffer and quantity of data read.
+    ///
+    /// Instead of completing with the first data received, the kernel keeps
+    /// receiving into the buffer until it is full, saving the submission of
+    /// a read per chunk that [`read_exact`](TcpStream::read_exact) makes. The
+    /// read is short only if the peer closed its side of the stream, or if an
+    /// error occurred after some data was received: the quantity of data
+    /// actually received is returned, and the buffer holds that data.
+    ///
+    /// Since nothing is returned before the whole buffer was received, this
+    /// can increase latency when the peer sends its data in pieces.
+    pub async fn read_waitall<T: IoBufMut>(&self, buf: T) -> crate::BufResult<usize, T> {
+        self.inner.recv_with_flags(buf, libc::MSG_WAITALL).await
+    }
+
+    /// Read some data from the stream into the buffer without removing it
+    /// from the receive queue, returning the original buffer and quantity of
+    /// data read.
+    ///
+    /// The peeked bytes are returned again by the next [`read`]. This is
+    /// useful to sniff the start of a protocol before dispatching the stream.
+    /// Like `read`, this waits for data if none is available, and may return
+    /// fewer bytes than were already received.
+    ///
+    /// [`read`]: TcpStream::read
+    pub async fn peek<T: IoBufMut>(&self, buf: T) -> crate::BufResult<usize, T> {
+        self.inner.recv_with_flags(buf, libc::MSG_PEEK).await
+    }
+
+    /// Receives data from the stream into the buffer, handing the buffer to
+    /// a [`RecvGuard`] if the receive is canceled.
+    ///
+    /// The receive is submitted right away. Awaiting the returned
+    /// [`CancelableRecv`] returns the result along with the buffer, as
+    /// [`read`] does. Dropping it, e.g. when another branch of a
+    /// `tokio::select!` completed first, cancels the receive, and the guard
+    /// returns the buffer once the kernel released it, so large buffers can
+    /// be reused across iterations of a loop instead of being reallocated.
+    ///
+    /// If the receive completed before it was canceled, the data it received
+    /// is lost with the cancellation. See [`RecvGuard`] for the details.
+    ///
+    /// [`read`]: TcpStream::read
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    /// use tokio_uring::net::TcpStream;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     tokio_uring::start(async {
+    ///         let stream = TcpStream::connect("127.0.0.1:8080".parse().unwrap()).await?;
+    ///         let mut buf = vec![0; 64 * 1024];
+    ///
+    ///         loop {
+    ///             let (recv, guard) = stream.recv_cancelable(buf);
+    ///
+    ///             tokio::select! {
+    ///                 (res, b) = recv => {
+    ///                     let n = res?;
+    ///                     println!("received {} bytes", n);
+    ///                     buf = b;
+    ///                 }
+    ///                 _ = tokio_uring::time::sleep(Duration::from_secs(1)) => {
+    ///                     println!("idle");
+    ///                     buf = guard.await.unwrap();
+    ///                 }
+    ///             }
+    ///         }
+    ///     })
+    /// }
+    /// ```
+    pub fn recv_cancelable<T: IoBufMut>(&self, buf: T) -> (CancelableRecv<T>, RecvGuard<T>) {
+        self.inner.recv_cancelable(buf)
+    }
+
     /// Write some data to the stream from the buffer, returning the original buffer and
     /// quantity of data written.
     pub async fn write<T: IoBuf>(&self, buf: T) -> crate::BufResult<usize, T> {
         self.inner.write(buf).await
     }
+
+    /// Write some data to the stream from the buffer, setting `MSG_MORE` to
+    /// let the kernel know more data follows. Returns the original buffer and
+    /// quantity of data written.
+    ///
+    /// The kernel holds the data back instead of sending it right away, so
+    /// that it goes out in the same segment as the data of the next write,
+    /// e.g. a small header followed by its body. The data is sent once a
+    /// write without `MSG_MORE` follows, once a full segment was buffered,
+    /// or after 200ms, whichever comes first.
+    ///
+    /// `MSG_MORE` overrides [`TCP_NODELAY`](TcpStream::set_nodelay) for this
+    /// write: the data is held back even when Nagle's algorithm is disabled.
+    /// See [`set_cork`](TcpStream::set_cork) to hold back a series of writes.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_uring::net::TcpStream;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     tokio_uring::start(async {
+    ///         let stream = TcpStream::connect("127.0.0.1:8080".parse().unwrap()).await?;
+    ///         stream.set_nodelay(true)?;
+    ///
+    ///         // The header and the body go out in a single segment
+    ///         let (res, _) = stream.send_more(b"len:5\n".as_slice()).await;
+    ///         res?;
+    ///         let (res, _) = stream.write_all(b"hello".as_slice()).await;
+    ///         res?;
+    ///
+    ///         Ok(())
+    ///     })
+    /// }
+    /// ```
+    pub async fn send_more<T: IoBuf>(&self, buf: T) -> crate::BufResult<usize, T> {
+        self.inner.send_with_flags(buf, libc::MSG_MORE).await
+    }
+
+    /// Write some data to the stream from the buffer without copying it into
+    /// the kernel (`IORING_OP_SEND_ZC`), returning the original buffer and
+    /// quantity of data written.
+    ///
+    /// The network stack reads the data straight from the buffer, so the
+    /// buffer is only handed back once the kernel notifies it released it,
+    /// which may be after the data was acknowledged by the peer. Saving the
+    /// copy pays off for large buffers. For small ones, say below 10 KiB, the
+    /// extra completion and the wait for the notification outweigh the copy,
+    /// and [`write`] is faster.
+    ///
+    /// Zero copy sends require Linux 6.0. On older kernels, this falls back
+    /// to a regular [`write`].
+    ///
+    /// [`write`]: TcpStream::write
+    pub async fn send_zc<T: IoBuf>(&self, buf: T) -> crate::BufResult<usize, T> {
+        self.inner.send_zc(buf).await
+    }
+
+    /// Write all the data of the buffer to the stream, returning the
+    /// original buffer.
+    ///
+    /// Writes are submitted until every initialized byte of the buffer was
+    /// written, each resuming where the previous one stopped. An empty buffer
+    /// succeeds without writing anything. If a write fails, the error is
+    /// returned, and how much of the buffer was written is unspecified. A
+    /// write of zero bytes fails with [`ErrorKind::WriteZero`].
+    ///
+    /// This is the owned buffer counterpart of `AsyncWriteExt::write_all`.
+    ///
+    /// [`ErrorKind::WriteZero`]: std::io::ErrorKind::WriteZero
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_uring::net::TcpStream;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     tokio_uring::start(async {
+    ///         let stream = TcpStream::connect("127.0.0.1:8080".parse().unwrap()).await?;
+    ///
+    ///         let (res, buf) = stream.write_all(vec![0u8; 1024 * 1024]).await;
+    ///         res?;
+    ///         assert_eq!(buf.len(), 1024 * 1024);
+    ///
+    ///         Ok(())
+    ///     })
+    /// }
+    /// ```
+    pub async fn write_all<T: IoBuf>(&self, buf: T) -> crate::BufResult<(), T> {
+        self.inner.write_all(buf).await
+    }
+
+    /// Receives some data from the stream into a buffer picked by the kernel
+    /// from `buf_ring`, once data arrives. Returns `None` once the peer
+    /// closed its side of the stream.
+    ///
+    /// No buffer is tied up while waiting for data, so many streams can share
+    /// the memory of one ring. Fails with `ENOBUFS` when all the buffers of
+    /// the ring are in use.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_uring::net::TcpStream;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     tokio_uring::start(async {
+    ///         let ring = tokio_uring::register_buf_ring(64, 4096)?;
+    ///
+    ///         let stream = TcpStream::connect("127.0.0.1:8080".parse().unwrap()).await?;
+    ///         while let Some(slot) = stream.recv_ring(&ring).await? {
+    ///             println!("{:?}", &slot[..]);
+    ///             // Dropping the slot hands the buffer back to the ring
+    ///         }
+    ///
+    ///         Ok(())
+    ///     })
+    /// }
+    /// ```
+    pub async fn recv_ring(&self, buf_ring: &BufRing) -> io::Result<Option<BufSlot>> {
+        self.inner.recv_ring(buf_ring).await
+    }
+
+    /// Read some data from the stream into a fixed buffer, returning the
+    /// original buffer and quantity of data read.
+    ///
+    /// Like [`read`](TcpStream::read), the data is read at the start of the
+    /// buffer, up to its capacity. Fixed buffers are registered with the
+    /// kernel up front, which saves mapping the buffer for every read.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_uring::buf::FixedBufPool;
+    /// use tokio_uring::net::TcpStream;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     tokio_uring::start(async {
+    ///         let bufs = (0..4).map(|_| Vec::with_capacity(64 * 1024)).collect();
+    ///         let pool = FixedBufPool::register(bufs)?;
+    ///
+    ///         let stream = TcpStream::connect("127.0.0.1:8080".parse().unwrap()).await?;
+    ///         let buf = pool.try_next().unwrap();
+    ///         let (res, buf) = stream.read_fixed(buf).await;
+    ///         println!("{:?}", &buf[..res?]);
+    ///
+    ///         Ok(())
+    ///     })
+    /// }
+    /// ```
+    pub async fn read_fixed(&self, buf: FixedBuf) -> crate::BufResult<usize, FixedBuf> {
+        self.inner.read_fixed(buf).await
+    }
+
+    /// Write the initialized bytes of a fixed buffer to the stream,
+    /// returning the original buffer and quantity of data written.
+    pub async fn write_fixed(&self, buf: FixedBuf) -> crate::BufResult<usize, FixedBuf> {
+        self.inner.write_fixed(buf).await
+    }
+
+    /// Read some data from the stream into several buffers, filling them in
+    /// order, with a single `readv` operation. Returns the original buffers
+    /// and the total quantity of data read.
+    ///
+    /// Each buffer is filled up to its capacity before the next one is
+    /// written to. The buffers can be any [`IoVectoredBufMut`], such as a
+    /// `Vec` or an array of buffers, of at most `IOV_MAX` (1024) buffers. The
+    /// read fails with [`InvalidInput`](io::ErrorKind::InvalidInput) beyond
+    /// that.
+    pub async fn readv<T: IoVectoredBufMut>(&self, bufs: T) -> crate::BufResult<usize, T> {
+        self.inner.readv(bufs).await
+    }
+
+    /// Write data from several buffers to the stream, in order, with a single
+    /// `writev` operation. Returns the original buffers and the total quantity
+    /// of data written.
+    ///
+    /// The write may be short, in which case only the first bytes, across
+    /// the buffers, were written. It is up to the caller to write the rest.
+    ///
+    /// The buffers can be any [`IoVectoredBuf`], such as a `Vec` or an array
+    /// of buffers, of at most `IOV_MAX` (1024) buffers. The write fails
+    /// with [`InvalidInput`](io::ErrorKind::InvalidInput) beyond that.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_uring::net::{TcpListener, TcpStream};
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     tokio_uring::start(async {
+    ///         let listener = TcpListener::bind("127.0.0.1:2422".parse().unwrap())?;
+    ///
+    ///         let (stream, (peer, _)) = tokio::try_join!(
+    ///             TcpStream::connect("127.0.0.1:2422".parse().unwrap()),
+    ///             listener.accept(),
+    ///         )?;
+    ///
+    ///         // Write a header and a body without concatenating them
+    ///         let frame = [b"len:5\n".to_vec(), b"hello".to_vec()];
+    ///         let (result, frame) = stream.writev(frame).await;
+    ///         assert_eq!(result?, 11);
+    ///         assert_eq!(frame.len(), 2);
+    ///
+    ///         let (result, bufs) = peer.readv(vec![vec![0; 6], vec![0; 5]]).await;
+    ///         assert_eq!(result?, 11);
+    ///         assert_eq!(bufs[1], b"hello");
+    ///
+    ///         Ok(())
+    ///     })
+    /// }
+    /// ```
+    pub async fn writev<T: IoVectoredBuf>(&self, bufs: T) -> crate::BufResult<usize, T> {
+        self.inner.writev(bufs).await
+    }
+
+    /// Shuts down the read, write, or both halves of this connection.
+    ///
+    /// Shutting down the write half sends a FIN to the peer, which reads
+    /// end-of-file once it received everything written before, while this
+    /// side can keep reading the response. Shutting down the read half makes
+    /// further reads return end-of-file.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::net::Shutdown;
+    /// use tokio_uring::net::{TcpListener, TcpStream};
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     tokio_uring::start(async {
+    ///         let listener = TcpListener::bind("127.0.0.1:2423".parse().unwrap())?;
+    ///
+    ///         let (stream, (peer, _)) = tokio::try_join!(
+    ///             TcpStream::connect("127.0.0.1:2423".parse().unwrap()),
+    ///             listener.accept(),
+    ///         )?;
+    ///
+    ///         let (result, _) = stream.write(b"request".as_slice()).await;
+    ///         result?;
+    ///         stream.shutdown(Shutdown::Write)?;
+    ///
+    ///         // The peer reads the request, then end-of-file
+    ///         let (result, _) = peer.read(vec![0; 32]).await;
+    ///         assert_eq!(result?, 7);
+    ///         let (result, _) = peer.read(vec![0; 32]).await;
+    ///         assert_eq!(result?, 0);
+    ///
+    ///         // The response still goes through
+    ///         let (result, _) = peer.write(b"response".as_slice()).await;
+    ///         result?;
+    ///         let (result, _) = stream.read(vec![0; 32]).await;
+    ///         assert_eq!(result?, 8);
+    ///
+    ///         Ok(())
+    ///     })
+    /// }
+    /// ```
+    pub fn shutdown(&self, how: Shutdown) -> io::Result<()> {
+        self.inner.shutdown(how)
+    }
+
+    /// Borrows the stream as a read half and a write half.
+    ///
+    /// The halves give the reading and the writing operations to different
+    /// parts of a program. Since operations take the stream by reference,
+    /// they proceed concurrently, without locking. See
+    /// [`into_split`](TcpStream::into_split) for halves that can be moved to
+    /// different tasks.
+    pub fn split(&self) -> (ReadHalf<'_>, WriteHalf<'_>) {
+        split::split(self)
+    }
+
+    /// Splits the stream into an owned read half and an owned write half,
+    /// which can be moved to different tasks.
+    ///
+    /// Each half holds a reference-counted handle to the socket, which is
+    /// closed once both halves are dropped. A read and a write can be in
+    /// flight on the socket at once: each operation owns its buffer, and the
+    /// kernel orders the data of each direction on its own.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_uring::net::{TcpListener, TcpStream};
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     tokio_uring::start(async {
+    ///         let listener = TcpListener::bind("127.0.0.1:8080".parse().unwrap())?;
+    ///         let (client, _) = listener.accept().await?;
+    ///         let upstream = TcpStream::connect("127.0.0.1:9090".parse().unwrap()).await?;
+    ///
+    ///         let (client_read, client_write) = client.into_split();
+    ///         let (upstream_read, upstream_write) = upstream.into_split();
+    ///
+    ///         // Forward the upstream responses in their own task
+    ///         let responses = tokio_uring::spawn(async move {
+    ///             let mut buf = Vec::with_capacity(4096);
+    ///             loop {
+    ///                 let (res, b) = upstream_read.read(buf).await;
+    ///                 if res? == 0 {
+    ///                     return client_write.shutdown();
+    ///                 }
+    ///                 let (res, b) = client_write.write_all(b).await;
+    ///                 res?;
+    ///                 buf = b;
+    ///                 buf.clear();
+    ///             }
+    ///         });
+    ///
+    ///         let mut buf = Vec::with_capacity(4096);
+    ///         loop {
+    ///             let (res, b) = client_read.read(buf).await;
+    ///             if res? == 0 {
+    ///                 upstream_write.shutdown()?;
+    ///                 break;
+    ///             }
+    ///             let (res, b) = upstream_write.write_all(b).await;
+    ///             res?;
+    ///             buf = b;
+    ///             buf.clear();
+    ///         }
+    ///
+    ///         responses.await.unwrap()
+    ///     })
+    /// }
+    /// ```
+    pub fn into_split(self) -> (OwnedReadHalf, OwnedWriteHalf) {
+        split::into_split(self)
+    }
+
+    /// Closes the stream, returning the result of the close.
+    ///
+    /// The close is submitted once the operations in flight on the stream
+    /// complete, and the method completes along with it. Dropping the stream
+    /// instead closes it in the background, and loses the error. To let the
+    /// peer know no more data is coming while still reading, see
+    /// [`shutdown`](TcpStream::shutdown).
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_uring::net::TcpStream;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     tokio_uring::start(async {
+    ///         let stream = TcpStream::connect("127.0.0.1:8080".parse().unwrap()).await?;
+    ///         let (res, _) = stream.write_all(b"bye".as_slice()).await;
+    ///         res?;
+    ///
+    ///         stream.close().await
+    ///     })
+    /// }
+    /// ```
+    pub async fn close(self) -> io::Result<()> {
+        self.inner.close().await
+    }
+
+    /// Sets the value of the `TCP_NODELAY` option on this socket.
+    ///
+    /// When enabled, Nagle's algorithm is disabled: small writes are sent
+    /// right away instead of being coalesced, trading bandwidth for latency.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_uring::net::{TcpListener, TcpStream};
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     tokio_uring::start(async {
+    ///         let _listener = TcpListener::bind("127.0.0.1:2424".parse().unwrap())?;
+    ///         let stream = TcpStream::connect("127.0.0.1:2424".parse().unwrap()).await?;
+    ///
+    ///         stream.set_nodelay(true)?;
+    ///         assert!(stream.nodelay()?);
+    ///
+    ///         Ok(())
+    ///     })
+    /// }
+    /// ```
+    pub fn set_nodelay(&self, on: bool) -> io::Result<()> {
+        self.inner
+            .setsockopt(libc::IPPROTO_TCP, libc::TCP_NODELAY, on as libc::c_int)
+    }
+
+    /// Gets the value of the `TCP_NODELAY` option on this socket.
+    ///
+    /// For more information about this option, see
+    /// [`set_nodelay`](`TcpStream::set_nodelay`).
+    pub fn nodelay(&self) -> io::Result<bool> {
+        let on: libc::c_int = self
+            .inner
+            .getsockopt(libc::IPPROTO_TCP, libc::TCP_NODELAY)?;
+        Ok(on != 0)
+    }
+
+    /// Sets the value of the `TCP_CORK` option on this socket.
+    ///
+    /// While the socket is corked, the kernel only sends full segments, and
+    /// holds partial ones back, so data written in small pieces goes out in
+    /// as few segments as possible. Uncorking sends the data held back right
+    /// away. Data is never held back for more than 200ms.
+    ///
+    /// Corking takes precedence over [`TCP_NODELAY`](TcpStream::set_nodelay):
+    /// a partial segment is held back even when Nagle's algorithm is
+    /// disabled. Setting `TCP_NODELAY` while corked sends the pending data,
+    /// but data keeps being held back until the socket is uncorked.
+    pub fn set_cork(&self, on: bool) -> io::Result<()> {
+        self.inner
+            .setsockopt(libc::IPPROTO_TCP, libc::TCP_CORK, on as libc::c_int)
+    }
+
+    /// Gets the value of the `TCP_CORK` option on this socket.
+    ///
+    /// For more information about this option, see
+    /// [`set_cork`](`TcpStream::set_cork`).
+    pub fn cork(&self) -> io::Result<bool> {
+        let on: libc::c_int = self.inner.getsockopt(libc::IPPROTO_TCP, libc::TCP_CORK)?;
+        Ok(on != 0)
+    }
+
+    /// Sets the value of the `SO_LINGER` option on this socket, which
+    /// controls how closing the stream handles data not yet sent.
+    ///
+    /// * With `None`, the default, closing returns right away, and the
+    ///   kernel keeps sending the remaining data in the background before
+    ///   shutting the connection down gracefully.
+    /// * With a linger of zero, closing discards the remaining data and
+    ///   resets the connection with an RST, so the peer sees
+    ///   `ECONNRESET` and the connection skips the `TIME_WAIT` state.
+    /// * With a positive linger, closing waits for the remaining data to be
+    ///   acknowledged by the peer, for up to that long. Once the linger time
+    ///   runs out, the close completes, and the kernel keeps sending the
+    ///   remaining data in the background as without a linger: only a linger
+    ///   of zero resets the connection.
+    ///
+    /// The stream is usually closed with an `IORING_OP_CLOSE`, so a lingering
+    /// close waits on an io-wq worker thread of the kernel, see
+    /// [`Runtime::set_iowq_max_workers`](crate::Runtime::set_iowq_max_workers),
+    /// rather than blocking the runtime. The stream is closed with a
+    /// blocking `close(2)` instead, which lingers on the thread dropping it,
+    /// when the operation cannot be submitted: when the stream is dropped
+    /// off runtime, when its last reference is released by an operation
+    /// completing as the runtime reaps completions, or when the kernel lacks
+    /// `IORING_OP_CLOSE`.
+    ///
+    /// The kernel counts the linger in whole seconds, so a fractional
+    /// duration is rounded up.
+    pub fn set_linger(&self, dur: Option<Duration>) -> io::Result<()> {
+        let linger = match dur {
+            Some(dur) => {
+                let secs = dur.as_secs() + u64::from(dur.subsec_nanos() > 0);
+                libc::linger {
+                    l_onoff: 1,
+                    l_linger: libc::c_int::try_from(secs).map_err(|_| {
+                        io::Error::new(io::ErrorKind::InvalidInput, "linger is too long")
+                    })?,
+                }
+            }
+            None => libc::linger {
+                l_onoff: 0,
+                l_linger: 0,
+            },
+        };
+
+        self.inner
+            .setsockopt(libc::SOL_SOCKET, libc::SO_LINGER, linger)
+    }
+
+    /// Gets the value of the `SO_LINGER` option on this socket.
+    ///
+    /// For more information about this option, see
+    /// [`set_linger`](`TcpStream::set_linger`).
+    pub fn linger(&self) -> io::Result<Option<Duration>> {
+        let linger: libc::linger = self.inner.getsockopt(libc::SOL_SOCKET, libc::SO_LINGER)?;
+        Ok((linger.l_onoff != 0).then(|| Duration::from_secs(linger.l_linger as u64)))
+    }
+
+    /// Returns the local address of this stream.
+    ///
+    /// For a connection accepted by a transparent listener, see
+    /// [`TcpListener::bind_transparent`](crate::net::TcpListener::bind_transparent),
+    /// this is the original destination of the connection.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.inner.local_addr()
+    }
+
+    /// Returns the address of the remote peer of this stream.
+    pub fn peer_addr(&self) -> io::Result<SocketAddr> {
+        self.inner.peer_addr()
+    }
+
+    /// Takes the pending error of the socket (`SO_ERROR`), returning `None`
+    /// if there is none. Reading the error clears it.
+    ///
+    /// The pending error records asynchronous failures of the connection,
+    /// like a reset by the peer or a keepalive timeout, until an operation on
+    /// the stream fails with it, or until it is taken here.
+    pub fn so_error(&self) -> io::Result<Option<io::Error>> {
+        self.inner.so_error()
+    }
+
+    /// Sets the socket option `name` at `level` to the raw bytes `value`,
+    /// with `setsockopt(2)`.
+    ///
+    /// This reaches the options without a dedicated method, like `SO_MARK`
+    /// or `IP_TOS`. `level` and `name` are the constants of the `libc` crate,
+    /// and `value` holds the option in the layout the kernel expects, most
+    /// often a native-endian `c_int`. Errors of the kernel are returned as
+    /// is, e.g. `ENOPROTOOPT` for an unknown option, or `EPERM` for options
+    /// needing privileges.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_uring::net::TcpStream;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     tokio_uring::start(async {
+    ///         let stream = TcpStream::connect("127.0.0.1:8080".parse().unwrap()).await?;
+    ///
+    ///         // Mark the traffic of the connection as low delay
+    ///         let tos: libc::c_int = 0x10;
+    ///         stream.set_sockopt(libc::IPPROTO_IP, libc::IP_TOS, &tos.to_ne_bytes())?;
+    ///
+    ///         let mut buf = [0; 4];
+    ///         let len = stream.get_sockopt(libc::IPPROTO_IP, libc::IP_TOS, &mut buf)?;
+    ///         assert_eq!(libc::c_int::from_ne_bytes(buf), tos);
+    ///         assert_eq!(len, 4);
+    ///
+    ///         Ok(())
+    ///     })
+    /// }
+    /// ```
+    pub fn set_sockopt(&self, level: i32, name: i32, value: &[u8]) -> io::Result<()> {
+        self.inner.set_sockopt_bytes(level, name, value)
+    }
+
+    /// Reads the socket option `name` at `level` into `buf`, with
+    /// `getsockopt(2)`, and returns the length of its value.
+    ///
+    /// Values longer than `buf` are truncated by the kernel. See
+    /// [`set_sockopt`](TcpStream::set_sockopt).
+    pub fn get_sockopt(&self, level: i32, name: i32, buf: &mut [u8]) -> io::Result<usize> {
+        self.inner.get_sockopt_bytes(level, name, buf)
+    }
+
+    /// Sets the `SO_MARK` of this socket, tagging the packets it sends for
+    /// routing rules and packet filters.
+    ///
+    /// Setting the mark requires `CAP_NET_ADMIN` or `CAP_NET_RAW`, and fails
+    /// with `EPERM` otherwise.
+    pub fn set_mark(&self, mark: u32) -> io::Result<()> {
+        self.inner.setsockopt(libc::SOL_SOCKET, libc::SO_MARK, mark)
+    }
+
+    /// Gets the `SO_MARK` of this socket, see [`set_mark`](TcpStream::set_mark).
+    pub fn mark(&self) -> io::Result<u32> {
+        self.inner.getsockopt(libc::SOL_SOCKET, libc::SO_MARK)
+    }
+
+    /// Enables keepalive probes on this socket with the `SO_KEEPALIVE`
+    /// option, and configures them according to `params`.
+    ///
+    /// Probes are sent once the connection has been idle for a while, so a
+    /// dead peer is detected even if nothing is written to the connection.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use tokio_uring::net::{TcpKeepalive, TcpListener, TcpStream};
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     tokio_uring::start(async {
+    ///         let _listener = TcpListener::bind("127.0.0.1:2425".parse().unwrap())?;
+    ///         let stream = TcpStream::connect("127.0.0.1:2425".parse().unwrap()).await?;
+    ///
+    ///         let keepalive = TcpKeepalive::new()
+    ///             .with_time(Duration::from_secs(60))
+    ///             .with_interval(Duration::from_secs(10))
+    ///             .with_retries(3);
+    ///         stream.set_keepalive(keepalive)?;
+    ///         assert!(stream.keepalive()?);
+    ///
+    ///         Ok(())
+    ///     })
+    /// }
+    /// ```
+    pub fn set_keepalive(&self, params: TcpKeepalive) -> io::Result<()> {
+        // Validate every parameter before changing any option
+        let time = params.time.map(secs).transpose()?;
+        let interval = params.interval.map(secs).transpose()?;
+        let retries = params
+            .retries
+            .map(|retries| {
+                libc::c_int::try_from(retries).map_err(|_| {
+                    io::Error::new(io::ErrorKind::InvalidInput, "too many keepalive retries")
+                })
+            })
+            .transpose()?;
+
+        if let Some(time) = time {
+            self.inner
+                .setsockopt(libc::IPPROTO_TCP, libc::TCP_KEEPIDLE, time)?;
+        }
+        if let Some(interval) = interval {
+            self.inner
+                .setsockopt(libc::IPPROTO_TCP, libc::TCP_KEEPINTVL, interval)?;
+        }
+        if let Some(retries) = retries {
+            self.inner
+                .setsockopt(libc::IPPROTO_TCP, libc::TCP_KEEPCNT, retries)?;
+        }
+
+        self.inner
+            .setsockopt(libc::SOL_SOCKET, libc::SO_KEEPALIVE, 1 as libc::c_int)
+    }
+
+    /// Gets the value of the `SO_KEEPALIVE` option on this socket.
+    pub fn keepalive(&self) -> io::Result<bool> {
+        let on: libc::c_int = self
+            .inner
+            .getsockopt(libc::SOL_SOCKET, libc::SO_KEEPALIVE)?;
+        Ok(on != 0)
+    }
+}
+
+impl AsRawFd for TcpStream {
+    fn as_raw_fd(&self) -> RawFd {
+        self.inner.as_raw_fd()
+    }
+}
+
+/// Converts a keepalive delay to the whole seconds the kernel expects.
+fn secs(duration: Duration) -> io::Result<libc::c_int> {
+    libc::c_int::try_from(duration.as_secs())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "keepalive delay is too long"))
 }
@@ -1,11 +1,22 @@
 use crate::{
-    buf::{IoBuf, IoBufMut},
-    driver::Socket,
+    buf::{BufRing, IoBuf, IoBufMut},
+    driver::{BindOptions, CmsgBuf, Op, Read, Socket, Write, Writev},
+    net::{resolve, CancelableRecv, RecvGuard},
 };
-use libc::socket;
+use futures_core::Stream;
 use socket2::SockAddr;
-use std::{future::ready, io, net::SocketAddr, os::unix::prelude::AsRawFd};
-use tokio::io::{AsyncRead, AsyncWrite};
+use std::{
+    convert::TryFrom,
+    fmt, io,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, Shutdown, SocketAddr, SocketAddrV6, ToSocketAddrs},
+    ops::Deref,
+    os::unix::io::{AsRawFd, RawFd},
+    pin::Pin,
+    sync::OnceLock,
+    task::{Context, Poll},
+    time::Duration,
+};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
 
 /// A UDP socket.
 ///
@@ -14,8 +25,9 @@ use tokio::io::{AsyncRead, AsyncWrite};
 ///
 /// * one to many: [`bind`](`UdpSocket::bind`) and use [`send_to`](`UdpSocket::send_to`)
 ///   and [`recv_from`](`UdpSocket::recv_from`) to communicate with many different addresses
-/// * one to one: [`connect`](`UdpSocket::connect`) and associate with a single address, using [`write`](`UdpSocket::write`)
-///   and [`read`](`UdpSocket::read`) to communicate only with that remote address
+/// * one to one: [`connect`](`UdpSocket::connect`) and associate with a single address, using [`send`](`UdpSocket::send`)
+///   and [`recv`](`UdpSocket::recv`) (or [`write`](`UdpSocket::write`) and [`read`](`UdpSocket::read`))
+///   to communicate only with that remote address
 ///
 /// # Examples
 /// Bind and connect a pair of sockets and send a packet:
@@ -83,29 +95,420 @@ use tokio::io::{AsyncRead, AsyncWrite};
 ///     })
 /// }
 /// ```
+///
+/// # AsyncRead and AsyncWrite
+///
+/// A connected `UdpSocket` implements Tokio's [`AsyncRead`] and
+/// [`AsyncWrite`] traits, so it can be handed to code that is generic over
+/// those traits. Each call to `poll_read` receives at most one datagram. If
+/// the datagram does not fit in the provided [`ReadBuf`], the excess bytes are
+/// discarded, as they would be with `recv(2)`. Each call to `poll_write` or
+/// `poll_write_vectored` sends a single datagram. Flushing is a no-op, and
+/// shutting down shuts down the write half of the socket.
 pub struct UdpSocket {
     pub(super) inner: Socket,
+
+    /// In-flight read driven by `AsyncRead::poll_read`.
+    read: Option<Op<Read<Vec<u8>>>>,
+
+    /// In-flight write driven by `AsyncWrite::poll_write`.
+    write: Option<Op<Write<Vec<u8>>>>,
+
+    /// In-flight write driven by `AsyncWrite::poll_write_vectored`.
+    writev: Option<Op<Writev<Vec<Vec<u8>>>>>,
 }
 
 impl From<std::net::UdpSocket> for UdpSocket {
+    /// Takes ownership of the std socket, leaving its blocking mode as is.
+    /// See [`UdpSocket::from_std`] for a conversion that validates the
+    /// socket.
     fn from(sock: std::net::UdpSocket) -> UdpSocket {
-        let socket = Socket::from_raw_fd(sock.as_raw_fd());
-        UdpSocket {
-            inner: socket.expect("Unable to create from std::net::UdpSocket"),
-        }
+        UdpSocket::new(Socket::from_std(sock))
+    }
+}
+
+impl AsRawFd for UdpSocket {
+    fn as_raw_fd(&self) -> RawFd {
+        self.inner.as_raw_fd()
     }
 }
 
 impl UdpSocket {
+    fn new(inner: Socket) -> UdpSocket {
+        UdpSocket {
+            inner,
+            read: None,
+            write: None,
+            writev: None,
+        }
+    }
+
     /// Creates a new UDP socket and attempt to bind it to the addr provided.
+    ///
+    /// The scope id of an IPv6 address is passed on to the kernel, so a
+    /// link-local address like `[fe80::1%2]:5353` binds to interface 2.
     pub async fn bind(socket_addr: SocketAddr) -> io::Result<UdpSocket> {
         let socket = Socket::bind(socket_addr, libc::SOCK_DGRAM)?;
-        Ok(UdpSocket { inner: socket })
+        Ok(UdpSocket::new(socket))
+    }
+
+    /// Creates a new UDP socket bound to `local`, and connects it to
+    /// `remote`.
+    ///
+    /// The socket is bound before it is connected, which pins the source
+    /// address of the datagrams it sends, e.g. to steer traffic through one
+    /// of the uplinks of a multi-homed host. Unlike [`bind`](UdpSocket::bind),
+    /// the socket is bound without `SO_REUSEPORT`.
+    ///
+    /// Returns an error of kind [`InvalidInput`](io::ErrorKind::InvalidInput)
+    /// if `local` and `remote` are of different address families. See
+    /// [`connect`](UdpSocket::connect) for what connecting implies.
+    pub async fn bind_and_connect(local: SocketAddr, remote: SocketAddr) -> io::Result<UdpSocket> {
+        let socket = Socket::new_bound(local, remote, libc::SOCK_DGRAM, false)?;
+        socket.connect(SockAddr::from(remote)).await?;
+        Ok(UdpSocket::new(socket))
+    }
+
+    /// Creates a new UDP socket bound to the first address `addrs` resolves
+    /// to that can be bound.
+    ///
+    /// Names are resolved with `getaddrinfo(3)` on the blocking thread pool
+    /// of the runtime, so the ring keeps running meanwhile. The addresses are
+    /// tried in the order they were resolved, and the error of the last one
+    /// is returned if none can be bound.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_uring::net::UdpSocket;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     tokio_uring::start(async {
+    ///         let socket = UdpSocket::bind_host("localhost:0").await?;
+    ///         assert!(socket.local_addr()?.ip().is_loopback());
+    ///
+    ///         Ok(())
+    ///     })
+    /// }
+    /// ```
+    pub async fn bind_host<A>(addrs: A) -> io::Result<UdpSocket>
+    where
+        A: ToSocketAddrs + Send + 'static,
+    {
+        resolve::try_each(addrs, UdpSocket::bind).await
+    }
+
+    /// Creates a new UDP socket and binds it to the addr provided, with
+    /// `SO_REUSEADDR` and `SO_REUSEPORT` set according to `reuseport`.
+    ///
+    /// With `reuseport` set, several sockets can bind to the same address,
+    /// for instance one per worker thread, and the kernel distributes
+    /// incoming datagrams between them. Without it, binding to an address
+    /// that is already in use fails.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_uring::net::UdpSocket;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     tokio_uring::start(async {
+    ///         let addr = "127.0.0.1:2408".parse().unwrap();
+    ///
+    ///         let _first = UdpSocket::bind_reuseport(addr, true).await?;
+    ///         let _second = UdpSocket::bind_reuseport(addr, true).await?;
+    ///
+    ///         Ok(())
+    ///     })
+    /// }
+    /// ```
+    pub async fn bind_reuseport(socket_addr: SocketAddr, reuseport: bool) -> io::Result<UdpSocket> {
+        let options = BindOptions {
+            reuse_address: reuseport,
+            reuse_port: reuseport,
+            ..BindOptions::default()
+        };
+        let socket = Socket::bind_with_options(socket_addr, libc::SOCK_DGRAM, options)?;
+        Ok(UdpSocket::new(socket))
+    }
+
+    /// Creates a new IPv6 UDP socket that also accepts IPv4 traffic, and
+    /// binds it to the addr provided.
+    ///
+    /// `IPV6_V6ONLY` is cleared before binding, so IPv4 peers can reach the
+    /// socket through IPv4-mapped addresses like `::ffff:127.0.0.1`. Their
+    /// datagrams are reported from, and replies must be sent to, such mapped
+    /// addresses. Bind to the unspecified address `::` to receive from both
+    /// families.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_uring::net::UdpSocket;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     tokio_uring::start(async {
+    ///         let socket = UdpSocket::bind_v6_dualstack("[::]:2416".parse().unwrap()).await?;
+    ///
+    ///         let peer = std::net::UdpSocket::bind("127.0.0.1:0")?;
+    ///         peer.send_to(b"hello", "127.0.0.1:2416")?;
+    ///
+    ///         let (result, buf) = socket.recv_from(vec![0; 32]).await;
+    ///         let (n, addr) = result?;
+    ///         assert_eq!(&buf[..n], b"hello");
+    ///         assert_eq!(addr.ip().to_string(), "::ffff:127.0.0.1");
+    ///
+    ///         Ok(())
+    ///     })
+    /// }
+    /// ```
+    pub async fn bind_v6_dualstack(socket_addr: SocketAddrV6) -> io::Result<UdpSocket> {
+        let options = BindOptions {
+            only_v6: Some(false),
+            ..BindOptions::default()
+        };
+        let socket = Socket::bind_with_options(socket_addr.into(), libc::SOCK_DGRAM, options)?;
+        Ok(UdpSocket::new(socket))
+    }
+
+    /// Creates a new UDP socket for a transparent proxy, and binds it to the
+    /// addr provided.
+    ///
+    /// `IP_TRANSPARENT` and `IP_FREEBIND` are set before binding, so the
+    /// address need not be local to the host. Along with `TPROXY` rules of
+    /// the firewall, this lets the socket receive datagrams addressed to
+    /// other hosts, and send datagrams from their addresses. See
+    /// [`set_transparent`](UdpSocket::set_transparent).
+    ///
+    /// Setting `IP_TRANSPARENT` requires `CAP_NET_ADMIN` or `CAP_NET_RAW`,
+    /// binding fails with `EPERM` without them.
+    pub async fn bind_transparent(socket_addr: SocketAddr) -> io::Result<UdpSocket> {
+        let options = BindOptions {
+            transparent: true,
+            ..BindOptions::default()
+        };
+        let socket = Socket::bind_with_options(socket_addr, libc::SOCK_DGRAM, options)?;
+        Ok(UdpSocket::new(socket))
+    }
+
+    /// Creates a new `UdpSocket` from a previously bound
+    /// `std::net::UdpSocket`, taking ownership of its file descriptor.
+    ///
+    /// The socket is switched to non-blocking mode, so it behaves the same
+    /// whether or not the caller configured it. Returns an error if it is
+    /// not a datagram socket.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_uring::net::UdpSocket;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     tokio_uring::start(async {
+    ///         let std_socket = std::net::UdpSocket::bind("127.0.0.1:0")?;
+    ///         let addr = std_socket.local_addr()?;
+    ///
+    ///         let socket = UdpSocket::from_std(std_socket)?;
+    ///         assert_eq!(socket.local_addr()?, addr);
+    ///
+    ///         Ok(())
+    ///     })
+    /// }
+    /// ```
+    pub fn from_std(socket: std::net::UdpSocket) -> io::Result<UdpSocket> {
+        socket.set_nonblocking(true)?;
+
+        let socket = UdpSocket::new(Socket::from_std(socket));
+        let ty: libc::c_int = socket.inner.getsockopt(libc::SOL_SOCKET, libc::SO_TYPE)?;
+        if ty != libc::SOCK_DGRAM {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "not a datagram socket",
+            ));
+        }
+
+        Ok(socket)
+    }
+
+    /// Converts the socket into a `std::net::UdpSocket`, relinquishing its
+    /// file descriptor. The blocking mode of the descriptor is left as is.
+    ///
+    /// No operation may be in-flight on the socket, including ones left
+    /// behind by dropped futures, so the driver no longer references the
+    /// descriptor once it is handed over. Otherwise, an error is returned and
+    /// the socket is closed once the operations complete.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_uring::net::UdpSocket;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     tokio_uring::start(async {
+    ///         let socket = UdpSocket::bind("127.0.0.1:2418".parse().unwrap()).await?;
+    ///
+    ///         let std_socket = socket.into_std()?;
+    ///         assert_eq!(std_socket.local_addr()?, "127.0.0.1:2418".parse().unwrap());
+    ///
+    ///         Ok(())
+    ///     })
+    /// }
+    /// ```
+    pub fn into_std(self) -> io::Result<std::net::UdpSocket> {
+        // Operations started through `AsyncRead` and `AsyncWrite` are
+        // canceled, but keep the descriptor until they complete.
+        let UdpSocket {
+            inner,
+            read,
+            write,
+            writev,
+        } = self;
+        drop((read, write, writev));
+
+        inner.into_std()
+    }
+
+    /// Closes the socket, returning the result of the close.
+    ///
+    /// The close is submitted once the operations in flight on the socket
+    /// complete, and the method completes along with it. Dropping the socket
+    /// instead closes it in the background, and loses the error.
+    pub async fn close(self) -> io::Result<()> {
+        let UdpSocket {
+            inner,
+            read,
+            write,
+            writev,
+        } = self;
+        drop((read, write, writev));
+
+        inner.close().await
+    }
+
+    /// Registers the socket in the file table of the runtime, as
+    /// [`TcpStream::use_fixed_file`](crate::net::TcpStream::use_fixed_file)
+    /// does for streams. The slot is released when the socket is closed.
+    pub fn use_fixed_file(&self) -> io::Result<()> {
+        self.inner.use_fixed_file()
     }
 
+    /// Creates a new UDP socket bound to the network device named
+    /// `device_name`, using `SO_BINDTODEVICE`.
     pub async fn bind_todevice(device_name: &str) -> io::Result<UdpSocket> {
         let socket = Socket::bind_todevice(device_name, libc::SOCK_DGRAM)?;
-        Ok(UdpSocket { inner: socket })
+        Ok(UdpSocket::new(socket))
+    }
+
+    /// Returns the local address that this socket is bound to.
+    ///
+    /// This is useful to find out the port the kernel picked when binding to
+    /// port 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_uring::net::UdpSocket;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     tokio_uring::start(async {
+    ///         let socket = UdpSocket::bind("127.0.0.1:0".parse().unwrap()).await?;
+    ///         let addr = socket.local_addr()?;
+    ///
+    ///         assert_ne!(addr.port(), 0);
+    ///
+    ///         Ok(())
+    ///     })
+    /// }
+    /// ```
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.inner.local_addr()
+    }
+
+    /// Returns the address of the remote peer this socket was connected to.
+    ///
+    /// If the socket is not connected, an error of kind
+    /// [`NotConnected`](io::ErrorKind::NotConnected) is returned.
+    pub fn peer_addr(&self) -> io::Result<SocketAddr> {
+        self.inner.peer_addr()
+    }
+
+    /// Takes the pending error of the socket (`SO_ERROR`), returning `None`
+    /// if there is none. Reading the error clears it.
+    ///
+    /// Errors reported asynchronously by the network, like the ICMP port
+    /// unreachable message answering a datagram sent to a closed port, are
+    /// held as the pending error until the next operation on the socket
+    /// fails with them, or until they are taken here.
+    pub fn so_error(&self) -> io::Result<Option<io::Error>> {
+        self.inner.so_error()
+    }
+
+    /// Sets the socket option `name` at `level` to the raw bytes `value`,
+    /// with `setsockopt(2)`.
+    ///
+    /// This reaches the options without a dedicated method, see
+    /// [`TcpStream::set_sockopt`](crate::net::TcpStream::set_sockopt).
+    pub fn set_sockopt(&self, level: i32, name: i32, value: &[u8]) -> io::Result<()> {
+        self.inner.set_sockopt_bytes(level, name, value)
+    }
+
+    /// Reads the socket option `name` at `level` into `buf`, with
+    /// `getsockopt(2)`, and returns the length of its value.
+    ///
+    /// Values longer than `buf` are truncated by the kernel. See
+    /// [`set_sockopt`](UdpSocket::set_sockopt).
+    pub fn get_sockopt(&self, level: i32, name: i32, buf: &mut [u8]) -> io::Result<usize> {
+        self.inner.get_sockopt_bytes(level, name, buf)
+    }
+
+    /// Sets the `SO_MARK` of this socket, tagging the datagrams it sends for
+    /// routing rules and packet filters.
+    ///
+    /// Setting the mark requires `CAP_NET_ADMIN` or `CAP_NET_RAW`, and fails
+    /// with `EPERM` otherwise.
+    pub fn set_mark(&self, mark: u32) -> io::Result<()> {
+        self.inner.setsockopt(libc::SOL_SOCKET, libc::SO_MARK, mark)
+    }
+
+    /// Gets the `SO_MARK` of this socket, see [`set_mark`](UdpSocket::set_mark).
+    pub fn mark(&self) -> io::Result<u32> {
+        self.inner.getsockopt(libc::SOL_SOCKET, libc::SO_MARK)
+    }
+
+    /// Sets `IP_TRANSPARENT` on this socket, or `IPV6_TRANSPARENT` on IPv6
+    /// ones.
+    ///
+    /// A transparent socket can be bound to, and send from, addresses that
+    /// are not local to the host, as transparent proxies do. It only takes
+    /// effect for binds made after it is set, see
+    /// [`bind_transparent`](UdpSocket::bind_transparent) to set it before
+    /// binding.
+    ///
+    /// Requires `CAP_NET_ADMIN` or `CAP_NET_RAW`, fails with `EPERM`
+    /// otherwise.
+    pub fn set_transparent(&self, transparent: bool) -> io::Result<()> {
+        self.inner.set_transparent(transparent)
+    }
+
+    /// Gets the value of `IP_TRANSPARENT` on this socket, see
+    /// [`set_transparent`](UdpSocket::set_transparent).
+    pub fn transparent(&self) -> io::Result<bool> {
+        self.inner.transparent()
+    }
+
+    /// Sets `IP_FREEBIND` on this socket, or `IPV6_FREEBIND` on IPv6 ones.
+    ///
+    /// It lets the socket bind to addresses that are not, or not yet,
+    /// configured on the host, e.g. to start a service before its address
+    /// comes up. Unlike `IP_TRANSPARENT`, it requires no privileges.
+    pub fn set_freebind(&self, freebind: bool) -> io::Result<()> {
+        self.inner.set_freebind(freebind)
+    }
+
+    /// Gets the value of `IP_FREEBIND` on this socket, see
+    /// [`set_freebind`](UdpSocket::set_freebind).
+    pub fn freebind(&self) -> io::Result<bool> {
+        self.inner.freebind()
     }
 
     /// Connects this UDP socket to a remote address, allowing the `write` and
@@ -114,11 +517,47 @@ impl UdpSocket {
     ///
     /// Note that usually, a successful `connect` call does not specify
     /// that there is a remote server listening on the port, rather, such an
-    /// error would only be detected after the first send.
+    /// error would only be detected after the first send. Once the peer
+    /// answers a datagram with an ICMP port unreachable message, the next
+    /// operation on the socket fails with
+    /// [`ConnectionRefused`](io::ErrorKind::ConnectionRefused).
     pub async fn connect(&self, socket_addr: SocketAddr) -> io::Result<()> {
         self.inner.connect(SockAddr::from(socket_addr)).await
     }
 
+    /// Connects this UDP socket to the first address `addrs` resolves to
+    /// that it can connect to, and returns that address.
+    ///
+    /// Names are resolved as by [`bind_host`](UdpSocket::bind_host).
+    /// Addresses of the other family than the socket fail to connect, and
+    /// are skipped. As with [`connect`](UdpSocket::connect), a successful
+    /// connect does not mean that the peer is reachable.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_uring::net::UdpSocket;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     tokio_uring::start(async {
+    ///         let socket = UdpSocket::bind("[::]:0".parse().unwrap()).await?;
+    ///         let peer = socket.connect_host("example.com:443").await?;
+    ///         println!("connected to {}", peer);
+    ///
+    ///         Ok(())
+    ///     })
+    /// }
+    /// ```
+    pub async fn connect_host<A>(&self, addrs: A) -> io::Result<SocketAddr>
+    where
+        A: ToSocketAddrs + Send + 'static,
+    {
+        resolve::try_each(addrs, |addr| async move {
+            self.connect(addr).await.map(|()| addr)
+        })
+        .await
+    }
+
     /// Sends data on the socket to the given address. On success, returns the
     /// number of bytes written.
     pub async fn send_to<T: IoBuf>(
@@ -129,12 +568,1063 @@ impl UdpSocket {
         self.inner.send_to(buf, socket_addr).await
     }
 
+    /// Sends `buf` to the given address as a series of datagrams of
+    /// `segment_size` bytes each, using UDP Generic Segmentation Offload. On
+    /// success, returns the number of bytes written.
+    ///
+    /// The kernel, or the network card, splits the buffer into datagrams. The
+    /// last datagram is shorter if the length of `buf` is not a multiple of
+    /// `segment_size`. A single call produces at most 64 datagrams, and each
+    /// of them must fit in the MTU of the route.
+    ///
+    /// UDP GSO requires Linux 4.18 or newer. If the kernel does not support
+    /// it, an error of kind [`Unsupported`](io::ErrorKind::Unsupported) is
+    /// returned. A `segment_size` of zero is rejected with
+    /// [`InvalidInput`](io::ErrorKind::InvalidInput).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_uring::net::UdpSocket;
+    /// use std::net::SocketAddr;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     tokio_uring::start(async {
+    ///         let socket = UdpSocket::bind("127.0.0.1:2411".parse().unwrap()).await?;
+    ///         let peer: SocketAddr = "127.0.0.1:8086".parse().unwrap();
+    ///
+    ///         // Three datagrams of 1000 bytes, and a last one of 500 bytes
+    ///         let (result, _) = socket.send_to_segmented(vec![0; 3500], peer, 1000).await;
+    ///         assert_eq!(result?, 3500);
+    ///
+    ///         Ok(())
+    ///     })
+    /// }
+    /// ```
+    pub async fn send_to_segmented<T: IoBuf>(
+        &self,
+        buf: T,
+        socket_addr: SocketAddr,
+        segment_size: u16,
+    ) -> crate::BufResult<usize, T> {
+        if segment_size == 0 {
+            let err = io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "segment size must be greater than zero",
+            );
+            return (Err(err), buf);
+        }
+
+        // Kernels without UDP GSO silently ignore the control message, and
+        // would send a single large datagram.
+        match self.supports_gso() {
+            Ok(true) => {}
+            Ok(false) => {
+                let err = io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    "UDP GSO is not supported by the kernel",
+                );
+                return (Err(err), buf);
+            }
+            Err(e) => return (Err(e), buf),
+        }
+
+        let mut control = CmsgBuf::with_space(CmsgBuf::space(std::mem::size_of::<u16>()));
+        control.push(libc::SOL_UDP, libc::UDP_SEGMENT, segment_size);
+
+        self.inner
+            .send_to_with_control(buf, socket_addr, control)
+            .await
+    }
+
+    /// Returns whether the kernel supports UDP GSO. The kernel is only
+    /// asked once, the answer holds for every socket.
+    fn supports_gso(&self) -> io::Result<bool> {
+        static GSO: OnceLock<bool> = OnceLock::new();
+
+        if let Some(&supported) = GSO.get() {
+            return Ok(supported);
+        }
+
+        let supported = match self
+            .inner
+            .getsockopt::<libc::c_int>(libc::SOL_UDP, libc::UDP_SEGMENT)
+        {
+            Ok(_) => true,
+            Err(e) if e.raw_os_error() == Some(libc::ENOPROTOOPT) => false,
+            Err(e) => return Err(e),
+        };
+        Ok(*GSO.get_or_init(|| supported))
+    }
+
+    /// Sends a batch of independent datagrams, each to its own destination.
+    /// Returns the result of each send along with its buffer, in order.
+    ///
+    /// All sends are queued before the socket is polled, and reach the kernel
+    /// in a single submission when the batch fits in the submission queue.
+    /// Unlike [`send_mmsg`](`UdpSocket::send_mmsg`), a failed send does not
+    /// prevent the following ones, and larger batches are split over several
+    /// submissions.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_uring::net::UdpSocket;
+    /// use std::net::SocketAddr;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     tokio_uring::start(async {
+    ///         let socket = UdpSocket::bind("127.0.0.1:2419".parse().unwrap()).await?;
+    ///         let peers: Vec<SocketAddr> = vec![
+    ///             "127.0.0.1:8089".parse().unwrap(),
+    ///             "127.0.0.1:8090".parse().unwrap(),
+    ///         ];
+    ///
+    ///         let results = socket
+    ///             .send_to_batch(peers.into_iter().map(|peer| (b"hello".to_vec(), peer)))
+    ///             .await;
+    ///
+    ///         for (result, _buf) in results {
+    ///             assert_eq!(result?, 5);
+    ///         }
+    ///
+    ///         Ok(())
+    ///     })
+    /// }
+    /// ```
+    pub async fn send_to_batch<T, I>(&self, packets: I) -> Vec<crate::BufResult<usize, T>>
+    where
+        T: IoBuf,
+        I: IntoIterator<Item = (T, SocketAddr)>,
+    {
+        self.inner
+            .send_to_batch(packets.into_iter().collect())
+            .await
+    }
+
+    /// Sends a batch of datagrams, each to its own destination. On success,
+    /// returns the number of datagrams sent. All buffers are returned, in
+    /// order.
+    ///
+    /// The batch is submitted at once as a chain of linked `sendmsg(2)`
+    /// operations. If a send fails, the remaining datagrams are not sent: a
+    /// result of `n` means the first `n` datagrams were sent. If the very
+    /// first send fails, its error is returned. The error of a later send is
+    /// not recorded by the socket, so it is lost: see
+    /// [`send_mmsg_detailed`](`UdpSocket::send_mmsg_detailed`) to get it.
+    /// Buffers are only returned once the kernel is done with all of them.
+    ///
+    /// As with [`recv_mmsg`](`UdpSocket::recv_mmsg`), the batch must fit in
+    /// the submission queue of the runtime.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_uring::net::UdpSocket;
+    /// use std::net::SocketAddr;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     tokio_uring::start(async {
+    ///         let socket = UdpSocket::bind("127.0.0.1:2410".parse().unwrap()).await?;
+    ///         let first_peer: SocketAddr = "127.0.0.1:8084".parse().unwrap();
+    ///         let second_peer: SocketAddr = "127.0.0.1:8085".parse().unwrap();
+    ///
+    ///         let packets = vec![(b"hello".to_vec(), first_peer), (b"world".to_vec(), second_peer)];
+    ///         let (result, bufs) = socket.send_mmsg(packets).await;
+    ///
+    ///         assert_eq!(result?, 2);
+    ///         assert_eq!(bufs.len(), 2);
+    ///
+    ///         Ok(())
+    ///     })
+    /// }
+    /// ```
+    pub async fn send_mmsg<T: IoBuf>(
+        &self,
+        packets: Vec<(T, SocketAddr)>,
+    ) -> crate::BufResult<usize, Vec<T>> {
+        let (result, bufs) = self.send_mmsg_detailed(packets).await;
+
+        match result.error {
+            Some(e) if result.sent == 0 => (Err(e), bufs),
+            _ => (Ok(result.sent), bufs),
+        }
+    }
+
+    /// Sends a batch of datagrams as [`send_mmsg`](`UdpSocket::send_mmsg`)
+    /// does, and reports the error of the send that cut the batch short
+    /// along with the number of datagrams sent before it, e.g. `EMSGSIZE` or
+    /// `ENETUNREACH` for datagram `n` after `n` were sent.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_uring::net::UdpSocket;
+    /// use std::net::SocketAddr;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     tokio_uring::start(async {
+    ///         let socket = UdpSocket::bind("127.0.0.1:2426".parse().unwrap()).await?;
+    ///         let peer: SocketAddr = "127.0.0.1:8091".parse().unwrap();
+    ///
+    ///         let packets = vec![(b"hello".to_vec(), peer), (b"world".to_vec(), peer)];
+    ///         let (result, _bufs) = socket.send_mmsg_detailed(packets).await;
+    ///
+    ///         assert_eq!(result.sent(), 2);
+    ///         assert!(result.error().is_none());
+    ///
+    ///         Ok(())
+    ///     })
+    /// }
+    /// ```
+    pub async fn send_mmsg_detailed<T: IoBuf>(
+        &self,
+        packets: Vec<(T, SocketAddr)>,
+    ) -> (SendMmsgResult, Vec<T>) {
+        let (sent, error, bufs) = self.inner.send_mmsg(packets).await;
+        (SendMmsgResult { sent, error }, bufs)
+    }
+
+    /// Sends data on a connected socket. On success, returns the number of
+    /// bytes written.
+    ///
+    /// The datagram is sent to the address the socket was connected to with
+    /// [`connect`](`UdpSocket::connect`). If the socket is not connected, an
+    /// error of kind [`NotConnected`] is returned. The buffer is returned on
+    /// both success and error.
+    ///
+    /// [`NotConnected`]: io::ErrorKind::NotConnected
+    pub async fn send<T: IoBuf>(&self, buf: T) -> crate::BufResult<usize, T> {
+        let (res, buf) = self.inner.send(buf).await;
+
+        // Linux reports a missing destination, rather than a missing
+        // connection, for unconnected datagram sockets.
+        let res = res.map_err(|e| match e.raw_os_error() {
+            Some(libc::EDESTADDRREQ) => io::Error::from_raw_os_error(libc::ENOTCONN),
+            _ => e,
+        });
+
+        (res, buf)
+    }
+
     /// Receives a single datagram message on the socket. On success, returns
     /// the number of bytes read and the origin.
     pub async fn recv_from<T: IoBufMut>(&self, buf: T) -> crate::BufResult<(usize, SocketAddr), T> {
         self.inner.recv_from(buf).await
     }
 
+    /// Receives a single datagram message on the socket, waiting at most
+    /// `timeout` for it to arrive. On success, returns the number of bytes
+    /// read and the origin.
+    ///
+    /// The receive is linked to an `IORING_OP_LINK_TIMEOUT`, so the kernel
+    /// cancels it when the timeout expires, and an error of kind
+    /// [`TimedOut`](io::ErrorKind::TimedOut) is returned. Nothing is left
+    /// in-flight afterwards, unlike when wrapping
+    /// [`recv_from`](`UdpSocket::recv_from`) in a runtime timer. The buffer
+    /// is returned in all cases.
+    ///
+    /// Linked timeouts require Linux 5.5 or later.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use tokio_uring::net::UdpSocket;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     tokio_uring::start(async {
+    ///         let socket = UdpSocket::bind("127.0.0.1:2417".parse().unwrap()).await?;
+    ///
+    ///         let (result, _buf) = socket
+    ///             .recv_from_timeout(vec![0; 32], Duration::from_millis(10))
+    ///             .await;
+    ///         assert_eq!(result.unwrap_err().kind(), std::io::ErrorKind::TimedOut);
+    ///
+    ///         Ok(())
+    ///     })
+    /// }
+    /// ```
+    pub async fn recv_from_timeout<T: IoBufMut>(
+        &self,
+        buf: T,
+        timeout: Duration,
+    ) -> crate::BufResult<(usize, SocketAddr), T> {
+        self.inner.recv_from_timeout(buf, timeout).await
+    }
+
+    /// Receives a batch of datagrams, one per buffer in `bufs`. On success,
+    /// returns the number of datagrams received, along with the length and
+    /// origin of each of them. All buffers are returned, in order.
+    ///
+    /// io-uring has no `recvmmsg(2)` operation, so the batch is submitted as a
+    /// chain of linked `recvmsg(2)` operations. The first one waits for a
+    /// datagram; the following ones only pick up datagrams that are already
+    /// queued on the socket. If fewer datagrams than buffers are queued,
+    /// the batch is partial: the first `n` buffers hold the received
+    /// datagrams and the remaining buffers are untouched.
+    ///
+    /// The whole batch must fit in the submission queue of the runtime, see
+    /// [`Builder::entries`](crate::Builder::entries): larger batches fail with
+    /// [`InvalidInput`](io::ErrorKind::InvalidInput).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_uring::net::UdpSocket;
+    /// use std::net::SocketAddr;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     tokio_uring::start(async {
+    ///         let first_addr: SocketAddr = "127.0.0.1:2409".parse().unwrap();
+    ///         let second_addr: SocketAddr = "127.0.0.1:8083".parse().unwrap();
+    ///
+    ///         let socket = UdpSocket::bind(first_addr).await?;
+    ///         let other_socket = UdpSocket::bind(second_addr).await?;
+    ///
+    ///         for payload in [b"one", b"two"] {
+    ///             let (result, _) = other_socket.send_to(payload.as_slice(), first_addr).await;
+    ///             result?;
+    ///         }
+    ///
+    ///         let bufs = vec![vec![0; 32]; 8];
+    ///         let (result, datagrams, bufs) = socket.recv_mmsg(bufs).await;
+    ///         assert_eq!(result?, 2);
+    ///
+    ///         for ((len, addr), buf) in datagrams.iter().zip(&bufs) {
+    ///             assert_eq!(*addr, second_addr);
+    ///             println!("{:?}", &buf[..*len]);
+    ///         }
+    ///
+    ///         Ok(())
+    ///     })
+    /// }
+    /// ```
+    pub async fn recv_mmsg<T: IoBufMut>(
+        &self,
+        bufs: Vec<T>,
+    ) -> (io::Result<usize>, Vec<(usize, SocketAddr)>, Vec<T>) {
+        self.inner.recv_mmsg(bufs).await
+    }
+
+    /// Receives a single datagram message on the socket, along with the
+    /// segment size if the kernel coalesced several datagrams into it. On
+    /// success, returns the number of bytes read, the origin and the segment
+    /// size.
+    ///
+    /// With [`set_gro`](`UdpSocket::set_gro`) enabled, the kernel may merge
+    /// consecutive datagrams from the same origin into a single receive. The
+    /// buffer then holds the datagrams back to back, each of them the
+    /// returned segment size long, except for the last one which may be
+    /// shorter. The segment size is `None` when the receive holds a single
+    /// datagram.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_uring::net::UdpSocket;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     tokio_uring::start(async {
+    ///         let socket = UdpSocket::bind("127.0.0.1:2412".parse().unwrap()).await?;
+    ///         let other_socket = UdpSocket::bind("127.0.0.1:8087".parse().unwrap()).await?;
+    ///         socket.set_gro(true)?;
+    ///
+    ///         let (result, _) = other_socket
+    ///             .send_to_segmented(vec![1; 3000], socket.local_addr()?, 1000)
+    ///             .await;
+    ///         result?;
+    ///
+    ///         let (result, buf) = socket.recv_from_gro(vec![0; 65536]).await;
+    ///         let (n_bytes, _, segment_size) = result?;
+    ///
+    ///         // Split the receive back into datagrams
+    ///         let segment_size = segment_size.map_or(n_bytes, usize::from);
+    ///         for datagram in buf[..n_bytes].chunks(segment_size) {
+    ///             assert_eq!(datagram.len(), 1000);
+    ///         }
+    ///
+    ///         Ok(())
+    ///     })
+    /// }
+    /// ```
+    pub async fn recv_from_gro<T: IoBufMut>(
+        &self,
+        buf: T,
+    ) -> crate::BufResult<(usize, SocketAddr, Option<u16>), T> {
+        let control = CmsgBuf::with_space(CmsgBuf::space(std::mem::size_of::<libc::c_int>()));
+        let (res, buf, control) = self.inner.recv_from_with_control(buf, control).await;
+
+        let res = res.map(|(n, addr)| {
+            let segment_size = control
+                .iter()
+                .find(|cmsg| cmsg.level == libc::SOL_UDP && cmsg.ty == libc::UDP_GRO)
+                .and_then(|cmsg| cmsg.read::<libc::c_int>())
+                .map(|size| size as u16);
+            (n, addr, segment_size)
+        });
+
+        (res, buf)
+    }
+
+    /// Receives a single datagram message on the socket, along with the
+    /// local address it was sent to. On success, returns the number of bytes
+    /// read, the origin and the packet information.
+    ///
+    /// The packet information is only available once enabled with
+    /// [`set_recv_pktinfo`](`UdpSocket::set_recv_pktinfo`), and is `None`
+    /// otherwise. It tells which local address and interface received the
+    /// datagram, which is useful to reply from the same address when the
+    /// socket is bound to an unspecified address.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_uring::net::UdpSocket;
+    /// use std::net::Ipv4Addr;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     tokio_uring::start(async {
+    ///         let socket = UdpSocket::bind("0.0.0.0:2413".parse().unwrap()).await?;
+    ///         let other_socket = UdpSocket::bind("127.0.0.1:8088".parse().unwrap()).await?;
+    ///         socket.set_recv_pktinfo(true)?;
+    ///
+    ///         let (result, _) = other_socket
+    ///             .send_to(b"hello".as_slice(), "127.0.0.1:2413".parse().unwrap())
+    ///             .await;
+    ///         result?;
+    ///
+    ///         let (result, _) = socket.recv_from_with_info(vec![0; 32]).await;
+    ///         let (_, _, info) = result?;
+    ///         assert_eq!(info.unwrap().destination(), Ipv4Addr::LOCALHOST);
+    ///
+    ///         Ok(())
+    ///     })
+    /// }
+    /// ```
+    pub async fn recv_from_with_info<T: IoBufMut>(
+        &self,
+        buf: T,
+    ) -> crate::BufResult<(usize, SocketAddr, Option<PacketInfo>), T> {
+        let space = CmsgBuf::space(std::mem::size_of::<libc::in_pktinfo>())
+            + CmsgBuf::space(std::mem::size_of::<libc::in6_pktinfo>());
+        let control = CmsgBuf::with_space(space);
+        let (res, buf, control) = self.inner.recv_from_with_control(buf, control).await;
+
+        let res = res.map(|(n, addr)| {
+            let info = control.iter().find_map(|cmsg| match (cmsg.level, cmsg.ty) {
+                (libc::IPPROTO_IP, libc::IP_PKTINFO) => {
+                    cmsg.read::<libc::in_pktinfo>().map(|info| PacketInfo {
+                        destination: Ipv4Addr::from(u32::from_be(info.ipi_addr.s_addr)).into(),
+                        interface: info.ipi_ifindex as u32,
+                    })
+                }
+                (libc::IPPROTO_IPV6, libc::IPV6_PKTINFO) => {
+                    cmsg.read::<libc::in6_pktinfo>().map(|info| PacketInfo {
+                        destination: Ipv6Addr::from(info.ipi6_addr.s6_addr).into(),
+                        interface: info.ipi6_ifindex,
+                    })
+                }
+                _ => None,
+            });
+            (n, addr, info)
+        });
+
+        (res, buf)
+    }
+
+    /// Receives a single datagram message on the socket without removing it
+    /// from the receive queue. On success, returns the number of bytes read
+    /// and the origin.
+    ///
+    /// The next receive returns the same datagram again. If the datagram
+    /// does not fit in `buf`, only the first bytes are copied and the
+    /// returned length is the length of `buf`. Use
+    /// [`peek_from_trunc`](`UdpSocket::peek_from_trunc`) to learn the
+    /// real size of the datagram.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_uring::net::UdpSocket;
+    /// use std::net::SocketAddr;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     tokio_uring::start(async {
+    ///         let first_addr: SocketAddr = "127.0.0.1:2404".parse().unwrap();
+    ///         let second_addr: SocketAddr = "127.0.0.1:8082".parse().unwrap();
+    ///
+    ///         let socket = UdpSocket::bind(first_addr).await?;
+    ///         let other_socket = UdpSocket::bind(second_addr).await?;
+    ///
+    ///         let (result, _) = other_socket.send_to(b"hello world".as_slice(), first_addr).await;
+    ///         result?;
+    ///
+    ///         // Look at the header without consuming the datagram
+    ///         let (result, header) = socket.peek_from(vec![0; 5]).await;
+    ///         let (n_bytes, addr) = result?;
+    ///         assert_eq!(addr, second_addr);
+    ///         assert_eq!(b"hello", &header[..n_bytes]);
+    ///
+    ///         let (result, buf) = socket.recv_from(vec![0; 32]).await;
+    ///         let (n_bytes, _) = result?;
+    ///         assert_eq!(b"hello world", &buf[..n_bytes]);
+    ///
+    ///         Ok(())
+    ///     })
+    /// }
+    /// ```
+    pub async fn peek_from<T: IoBufMut>(&self, buf: T) -> crate::BufResult<(usize, SocketAddr), T> {
+        self.inner.recv_from_with_flags(buf, libc::MSG_PEEK).await
+    }
+
+    /// Like [`peek_from`](`UdpSocket::peek_from`), but returns the real
+    /// size of the datagram, even when it is larger than `buf`.
+    ///
+    /// Only the first bytes of the datagram that fit in `buf` are copied, as
+    /// with `MSG_TRUNC`.
+    pub async fn peek_from_trunc<T: IoBufMut>(
+        &self,
+        buf: T,
+    ) -> crate::BufResult<(usize, SocketAddr), T> {
+        self.inner
+            .recv_from_with_flags(buf, libc::MSG_PEEK | libc::MSG_TRUNC)
+            .await
+    }
+
+    /// Receives a single datagram message on a connected socket without
+    /// removing it from the receive queue. On success, returns the number of
+    /// bytes read.
+    ///
+    /// This is the connected counterpart of
+    /// [`peek_from`](`UdpSocket::peek_from`).
+    pub async fn peek<T: IoBufMut>(&self, buf: T) -> crate::BufResult<usize, T> {
+        self.inner.recv_with_flags(buf, libc::MSG_PEEK).await
+    }
+
+    /// Like [`peek`](`UdpSocket::peek`), but returns the real size of the
+    /// datagram, even when it is larger than `buf`.
+    pub async fn peek_trunc<T: IoBufMut>(&self, buf: T) -> crate::BufResult<usize, T> {
+        self.inner
+            .recv_with_flags(buf, libc::MSG_PEEK | libc::MSG_TRUNC)
+            .await
+    }
+
+    /// Receives datagrams with a single multishot operation, using buffers
+    /// picked by the kernel from `buf_ring`.
+    ///
+    /// Each datagram is yielded as a [`RecvSlot`], which holds on to its
+    /// buffer until dropped. Datagrams larger than a buffer are truncated;
+    /// the source address is stored in front of the payload, so each buffer
+    /// holds up to 44 bytes less than [`BufRing::buf_len`].
+    ///
+    /// When all buffers are in use, the stream yields an error with raw OS
+    /// error `ENOBUFS`. The stream keeps going after that error; polling it
+    /// again re-arms the receive once buffers were returned to the ring. Any
+    /// other error ends the stream. Dropping the stream cancels the receive.
+    ///
+    /// Multishot receives require Linux 6.0 or newer.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_uring::buf::BufRing;
+    /// use tokio_uring::net::UdpSocket;
+    /// use std::future::poll_fn;
+    /// use std::pin::pin;
+    /// use futures_core::Stream;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     tokio_uring::start(async {
+    ///         let socket = UdpSocket::bind("127.0.0.1:2403".parse().unwrap()).await?;
+    ///         let ring = BufRing::register(64, 2048)?;
+    ///
+    ///         let mut datagrams = pin!(socket.recv_multishot(ring));
+    ///         while let Some(slot) = poll_fn(|cx| datagrams.as_mut().poll_next(cx)).await {
+    ///             let slot = slot?;
+    ///             println!("{} bytes from {}", slot.len(), slot.addr());
+    ///         }
+    ///
+    ///         Ok(())
+    ///     })
+    /// }
+    /// ```
+    pub fn recv_multishot(&self, buf_ring: BufRing) -> impl Stream<Item = io::Result<RecvSlot>> {
+        self.inner.recv_multishot(buf_ring)
+    }
+
+    /// Receives a single datagram message on a connected socket. On success,
+    /// returns the number of bytes read.
+    ///
+    /// Unlike [`recv_from`](`UdpSocket::recv_from`), the origin of the
+    /// datagram is not returned: the socket must have been connected with
+    /// [`connect`](`UdpSocket::connect`), which filters out datagrams from any
+    /// other address. The buffer is returned on both success and error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_uring::net::UdpSocket;
+    /// use std::net::SocketAddr;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     tokio_uring::start(async {
+    ///         let first_addr: SocketAddr = "127.0.0.1:2402".parse().unwrap();
+    ///         let second_addr: SocketAddr = "127.0.0.1:8081".parse().unwrap();
+    ///
+    ///         let socket = UdpSocket::bind(first_addr).await?;
+    ///         let other_socket = UdpSocket::bind(second_addr).await?;
+    ///
+    ///         socket.connect(second_addr).await?;
+    ///         other_socket.connect(first_addr).await?;
+    ///
+    ///         let (result, _) = other_socket.write(b"hello world".as_slice()).await;
+    ///         result?;
+    ///
+    ///         let (result, buf) = socket.recv(vec![0; 32]).await;
+    ///         let n_bytes = result?;
+    ///
+    ///         assert_eq!(b"hello world", &buf[..n_bytes]);
+    ///
+    ///         Ok(())
+    ///     })
+    /// }
+    /// ```
+    pub async fn recv<T: IoBufMut>(&self, buf: T) -> crate::BufResult<usize, T> {
+        self.inner.recv(buf).await
+    }
+
+    /// Receives a single datagram from the connected address into the
+    /// buffer, handing the buffer to a [`RecvGuard`] if the receive is
+    /// canceled. See
+    /// [`TcpStream::recv_cancelable`](crate::net::TcpStream::recv_cancelable).
+    pub fn recv_cancelable<T: IoBufMut>(&self, buf: T) -> (CancelableRecv<T>, RecvGuard<T>) {
+        self.inner.recv_cancelable(buf)
+    }
+
+    /// Sets the value of the `UDP_GRO` option for this socket.
+    ///
+    /// When enabled, the kernel may coalesce datagrams into a single receive.
+    /// Use [`recv_from_gro`](`UdpSocket::recv_from_gro`) to learn how to split
+    /// them again. Requires Linux 5.0 or newer.
+    pub fn set_gro(&self, on: bool) -> io::Result<()> {
+        self.inner
+            .setsockopt(libc::SOL_UDP, libc::UDP_GRO, on as libc::c_int)
+    }
+
+    /// Gets the value of the `UDP_GRO` option for this socket.
+    ///
+    /// For more information about this option, see
+    /// [`set_gro`](`UdpSocket::set_gro`).
+    pub fn gro(&self) -> io::Result<bool> {
+        let on: libc::c_int = self.inner.getsockopt(libc::SOL_UDP, libc::UDP_GRO)?;
+        Ok(on != 0)
+    }
+
+    /// Enables or disables receiving packet information, using
+    /// `IP_PKTINFO` on IPv4 sockets and `IPV6_RECVPKTINFO` on IPv6 sockets.
+    ///
+    /// When enabled, [`recv_from_with_info`](`UdpSocket::recv_from_with_info`)
+    /// returns the local address and interface each datagram arrived on.
+    pub fn set_recv_pktinfo(&self, on: bool) -> io::Result<()> {
+        let (level, name) = self.pktinfo_option()?;
+        self.inner.setsockopt(level, name, on as libc::c_int)
+    }
+
+    /// Returns whether receiving packet information is enabled.
+    ///
+    /// For more information about this option, see
+    /// [`set_recv_pktinfo`](`UdpSocket::set_recv_pktinfo`).
+    pub fn recv_pktinfo(&self) -> io::Result<bool> {
+        let (level, name) = self.pktinfo_option()?;
+        let on: libc::c_int = self.inner.getsockopt(level, name)?;
+        Ok(on != 0)
+    }
+
+    // Level and name of the option enabling packet information
+    fn pktinfo_option(&self) -> io::Result<(libc::c_int, libc::c_int)> {
+        if self.is_ipv6()? {
+            Ok((libc::IPPROTO_IPV6, libc::IPV6_RECVPKTINFO))
+        } else {
+            Ok((libc::IPPROTO_IP, libc::IP_PKTINFO))
+        }
+    }
+
+    fn is_ipv6(&self) -> io::Result<bool> {
+        let domain: libc::c_int = self.inner.getsockopt(libc::SOL_SOCKET, libc::SO_DOMAIN)?;
+        Ok(domain == libc::AF_INET6)
+    }
+
+    /// Sets the value for the `IP_TTL` option on this socket, or
+    /// `IPV6_UNICAST_HOPS` on an IPv6 socket.
+    ///
+    /// This value sets the time-to-live field that is used in every packet
+    /// sent from this socket. It must be between 1 and 255, otherwise an
+    /// error of kind [`InvalidInput`](io::ErrorKind::InvalidInput) is
+    /// returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_uring::net::UdpSocket;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     tokio_uring::start(async {
+    ///         let socket = UdpSocket::bind("127.0.0.1:2414".parse().unwrap()).await?;
+    ///
+    ///         socket.set_ttl(60)?;
+    ///         assert_eq!(socket.ttl()?, 60);
+    ///
+    ///         Ok(())
+    ///     })
+    /// }
+    /// ```
+    pub fn set_ttl(&self, ttl: u32) -> io::Result<()> {
+        if !(1..=255).contains(&ttl) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "ttl must be between 1 and 255",
+            ));
+        }
+
+        let (level, name) = self.ttl_option()?;
+        self.inner.setsockopt(level, name, ttl as libc::c_int)
+    }
+
+    /// Gets the value of the `IP_TTL` option for this socket, or
+    /// `IPV6_UNICAST_HOPS` on an IPv6 socket.
+    ///
+    /// For more information about this option, see
+    /// [`set_ttl`](`UdpSocket::set_ttl`).
+    pub fn ttl(&self) -> io::Result<u32> {
+        let (level, name) = self.ttl_option()?;
+        let ttl: libc::c_int = self.inner.getsockopt(level, name)?;
+        Ok(ttl as u32)
+    }
+
+    fn ttl_option(&self) -> io::Result<(libc::c_int, libc::c_int)> {
+        if self.is_ipv6()? {
+            Ok((libc::IPPROTO_IPV6, libc::IPV6_UNICAST_HOPS))
+        } else {
+            Ok((libc::IPPROTO_IP, libc::IP_TTL))
+        }
+    }
+
+    /// Sets the value for the `IP_TOS` option on this socket, or
+    /// `IPV6_TCLASS` on an IPv6 socket.
+    ///
+    /// This value sets the type-of-service field, or traffic class, that is
+    /// used in every packet sent from this socket. The upper six bits hold
+    /// the DSCP, so DSCP class `dscp` is set with `dscp << 2`. It must be at
+    /// most 255, otherwise an error of kind
+    /// [`InvalidInput`](io::ErrorKind::InvalidInput) is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_uring::net::UdpSocket;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     tokio_uring::start(async {
+    ///         let socket = UdpSocket::bind("127.0.0.1:2415".parse().unwrap()).await?;
+    ///
+    ///         // Expedited forwarding
+    ///         socket.set_tos(46 << 2)?;
+    ///         assert_eq!(socket.tos()?, 46 << 2);
+    ///
+    ///         Ok(())
+    ///     })
+    /// }
+    /// ```
+    pub fn set_tos(&self, tos: u32) -> io::Result<()> {
+        if tos > 255 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "tos must be at most 255",
+            ));
+        }
+
+        let (level, name) = self.tos_option()?;
+        self.inner.setsockopt(level, name, tos as libc::c_int)
+    }
+
+    /// Gets the value of the `IP_TOS` option for this socket, or
+    /// `IPV6_TCLASS` on an IPv6 socket.
+    ///
+    /// For more information about this option, see
+    /// [`set_tos`](`UdpSocket::set_tos`).
+    pub fn tos(&self) -> io::Result<u32> {
+        let (level, name) = self.tos_option()?;
+        let tos: libc::c_int = self.inner.getsockopt(level, name)?;
+        Ok(tos as u32)
+    }
+
+    fn tos_option(&self) -> io::Result<(libc::c_int, libc::c_int)> {
+        if self.is_ipv6()? {
+            Ok((libc::IPPROTO_IPV6, libc::IPV6_TCLASS))
+        } else {
+            Ok((libc::IPPROTO_IP, libc::IP_TOS))
+        }
+    }
+
+    /// Sets the value of the `SO_BROADCAST` option for this socket.
+    ///
+    /// When enabled, this socket is allowed to send packets to a broadcast
+    /// address. Without it, sending to a broadcast address fails with
+    /// [`PermissionDenied`](io::ErrorKind::PermissionDenied).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_uring::net::UdpSocket;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     tokio_uring::start(async {
+    ///         let socket = UdpSocket::bind("0.0.0.0:2407".parse().unwrap()).await?;
+    ///
+    ///         socket.set_broadcast(true)?;
+    ///         assert!(socket.broadcast()?);
+    ///
+    ///         Ok(())
+    ///     })
+    /// }
+    /// ```
+    pub fn set_broadcast(&self, on: bool) -> io::Result<()> {
+        self.inner
+            .setsockopt(libc::SOL_SOCKET, libc::SO_BROADCAST, on as libc::c_int)
+    }
+
+    /// Gets the value of the `SO_BROADCAST` option for this socket.
+    ///
+    /// For more information about this option, see
+    /// [`set_broadcast`](`UdpSocket::set_broadcast`).
+    pub fn broadcast(&self) -> io::Result<bool> {
+        let on: libc::c_int = self
+            .inner
+            .getsockopt(libc::SOL_SOCKET, libc::SO_BROADCAST)?;
+        Ok(on != 0)
+    }
+
+    /// Sets the size of the kernel receive buffer of this socket, using the
+    /// `SO_RCVBUF` option.
+    ///
+    /// Datagrams arriving while the buffer is full are dropped, so
+    /// high-rate receivers may need a larger buffer than the default. The
+    /// kernel doubles the value to account for its own bookkeeping, and caps
+    /// it to `net.core.rmem_max`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_uring::net::UdpSocket;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     tokio_uring::start(async {
+    ///         let socket = UdpSocket::bind("127.0.0.1:2420".parse().unwrap()).await?;
+    ///
+    ///         socket.set_recv_buffer_size(64 * 1024)?;
+    ///         // The kernel doubled the value
+    ///         assert_eq!(socket.recv_buffer_size()?, 128 * 1024);
+    ///
+    ///         Ok(())
+    ///     })
+    /// }
+    /// ```
+    pub fn set_recv_buffer_size(&self, bytes: usize) -> io::Result<()> {
+        self.set_buffer_size(libc::SO_RCVBUF, bytes)
+    }
+
+    /// Returns the size of the kernel receive buffer of this socket, from
+    /// the `SO_RCVBUF` option.
+    ///
+    /// This reflects the value doubled by the kernel, see
+    /// [`set_recv_buffer_size`](`UdpSocket::set_recv_buffer_size`).
+    pub fn recv_buffer_size(&self) -> io::Result<usize> {
+        self.buffer_size(libc::SO_RCVBUF)
+    }
+
+    /// Sets the size of the kernel send buffer of this socket, using the
+    /// `SO_SNDBUF` option.
+    ///
+    /// The kernel doubles the value to account for its own bookkeeping, and
+    /// caps it to `net.core.wmem_max`.
+    pub fn set_send_buffer_size(&self, bytes: usize) -> io::Result<()> {
+        self.set_buffer_size(libc::SO_SNDBUF, bytes)
+    }
+
+    /// Returns the size of the kernel send buffer of this socket, from the
+    /// `SO_SNDBUF` option.
+    ///
+    /// This reflects the value doubled by the kernel, see
+    /// [`set_send_buffer_size`](`UdpSocket::set_send_buffer_size`).
+    pub fn send_buffer_size(&self) -> io::Result<usize> {
+        self.buffer_size(libc::SO_SNDBUF)
+    }
+
+    fn set_buffer_size(&self, name: libc::c_int, bytes: usize) -> io::Result<()> {
+        let bytes = libc::c_int::try_from(bytes)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "buffer size is too large"))?;
+        self.inner.setsockopt(libc::SOL_SOCKET, name, bytes)
+    }
+
+    fn buffer_size(&self, name: libc::c_int) -> io::Result<usize> {
+        let bytes: libc::c_int = self.inner.getsockopt(libc::SOL_SOCKET, name)?;
+        Ok(bytes as usize)
+    }
+
+    /// Executes an operation of the `IP_ADD_MEMBERSHIP` type.
+    ///
+    /// This function specifies a new multicast group for this socket to join.
+    /// The address must be a valid multicast address, and `interface` is the
+    /// address of the local interface with which the system should join the
+    /// multicast group. If it's equal to `INADDR_ANY` then an appropriate
+    /// interface is chosen by the system.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_uring::net::UdpSocket;
+    /// use std::net::Ipv4Addr;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     tokio_uring::start(async {
+    ///         let socket = UdpSocket::bind("0.0.0.0:2405".parse().unwrap()).await?;
+    ///
+    ///         socket.join_multicast_v4(Ipv4Addr::new(239, 1, 2, 3), Ipv4Addr::LOCALHOST)?;
+    ///         socket.leave_multicast_v4(Ipv4Addr::new(239, 1, 2, 3), Ipv4Addr::LOCALHOST)?;
+    ///
+    ///         Ok(())
+    ///     })
+    /// }
+    /// ```
+    pub fn join_multicast_v4(&self, multiaddr: Ipv4Addr, interface: Ipv4Addr) -> io::Result<()> {
+        self.inner.setsockopt(
+            libc::IPPROTO_IP,
+            libc::IP_ADD_MEMBERSHIP,
+            ip_mreq(multiaddr, interface),
+        )
+    }
+
+    /// Executes an operation of the `IP_DROP_MEMBERSHIP` type.
+    ///
+    /// For more information about this option, see
+    /// [`join_multicast_v4`](`UdpSocket::join_multicast_v4`).
+    pub fn leave_multicast_v4(&self, multiaddr: Ipv4Addr, interface: Ipv4Addr) -> io::Result<()> {
+        self.inner.setsockopt(
+            libc::IPPROTO_IP,
+            libc::IP_DROP_MEMBERSHIP,
+            ip_mreq(multiaddr, interface),
+        )
+    }
+
+    /// Sets the value of the `IP_MULTICAST_LOOP` option for this socket.
+    ///
+    /// If enabled, multicast packets will be looped back to the local socket.
+    pub fn set_multicast_loop_v4(&self, on: bool) -> io::Result<()> {
+        self.inner
+            .setsockopt(libc::IPPROTO_IP, libc::IP_MULTICAST_LOOP, on as libc::c_int)
+    }
+
+    /// Gets the value of the `IP_MULTICAST_LOOP` option for this socket.
+    ///
+    /// For more information about this option, see
+    /// [`set_multicast_loop_v4`](`UdpSocket::set_multicast_loop_v4`).
+    pub fn multicast_loop_v4(&self) -> io::Result<bool> {
+        let on: libc::c_int = self
+            .inner
+            .getsockopt(libc::IPPROTO_IP, libc::IP_MULTICAST_LOOP)?;
+        Ok(on != 0)
+    }
+
+    /// Sets the value of the `IP_MULTICAST_TTL` option for this socket.
+    ///
+    /// Indicates the time-to-live value of outgoing multicast packets for
+    /// this socket. The default value is 1 which means that multicast packets
+    /// don't leave the local network unless explicitly requested.
+    pub fn set_multicast_ttl_v4(&self, ttl: u32) -> io::Result<()> {
+        self.inner
+            .setsockopt(libc::IPPROTO_IP, libc::IP_MULTICAST_TTL, ttl as libc::c_int)
+    }
+
+    /// Gets the value of the `IP_MULTICAST_TTL` option for this socket.
+    ///
+    /// For more information about this option, see
+    /// [`set_multicast_ttl_v4`](`UdpSocket::set_multicast_ttl_v4`).
+    pub fn multicast_ttl_v4(&self) -> io::Result<u32> {
+        let ttl: libc::c_int = self
+            .inner
+            .getsockopt(libc::IPPROTO_IP, libc::IP_MULTICAST_TTL)?;
+        Ok(ttl as u32)
+    }
+
+    /// Executes an operation of the `IPV6_ADD_MEMBERSHIP` type.
+    ///
+    /// This function specifies a new multicast group for this socket to join.
+    /// The address must be a valid multicast address, and `interface` is the
+    /// index of the interface to join/leave (or 0 to indicate any interface).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_uring::net::UdpSocket;
+    /// use std::net::Ipv6Addr;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     tokio_uring::start(async {
+    ///         let socket = UdpSocket::bind("[::]:2406".parse().unwrap()).await?;
+    ///         let all_nodes: Ipv6Addr = "ff02::1".parse().unwrap();
+    ///
+    ///         // Join on the loopback interface
+    ///         socket.join_multicast_v6(&all_nodes, 1)?;
+    ///         socket.leave_multicast_v6(&all_nodes, 1)?;
+    ///
+    ///         Ok(())
+    ///     })
+    /// }
+    /// ```
+    pub fn join_multicast_v6(&self, multiaddr: &Ipv6Addr, interface: u32) -> io::Result<()> {
+        self.inner.setsockopt(
+            libc::IPPROTO_IPV6,
+            libc::IPV6_ADD_MEMBERSHIP,
+            ipv6_mreq(multiaddr, interface),
+        )
+    }
+
+    /// Executes an operation of the `IPV6_DROP_MEMBERSHIP` type.
+    ///
+    /// For more information about this option, see
+    /// [`join_multicast_v6`](`UdpSocket::join_multicast_v6`).
+    pub fn leave_multicast_v6(&self, multiaddr: &Ipv6Addr, interface: u32) -> io::Result<()> {
+        self.inner.setsockopt(
+            libc::IPPROTO_IPV6,
+            libc::IPV6_DROP_MEMBERSHIP,
+            ipv6_mreq(multiaddr, interface),
+        )
+    }
+
+    /// Sets the value of the `IPV6_MULTICAST_LOOP` option for this socket.
+    ///
+    /// Controls whether this socket sees the multicast packets it sends
+    /// itself.
+    pub fn set_multicast_loop_v6(&self, on: bool) -> io::Result<()> {
+        self.inner.setsockopt(
+            libc::IPPROTO_IPV6,
+            libc::IPV6_MULTICAST_LOOP,
+            on as libc::c_int,
+        )
+    }
+
+    /// Gets the value of the `IPV6_MULTICAST_LOOP` option for this socket.
+    ///
+    /// For more information about this option, see
+    /// [`set_multicast_loop_v6`](`UdpSocket::set_multicast_loop_v6`).
+    pub fn multicast_loop_v6(&self) -> io::Result<bool> {
+        let on: libc::c_int = self
+            .inner
+            .getsockopt(libc::IPPROTO_IPV6, libc::IPV6_MULTICAST_LOOP)?;
+        Ok(on != 0)
+    }
+
     /// Read a packet of data from the socket into the buffer, returning the original buffer and
     /// quantity of data read.
     pub async fn read<T: IoBufMut>(&self, buf: T) -> crate::BufResult<usize, T> {
@@ -148,43 +1638,180 @@ impl UdpSocket {
     }
 }
 
+fn ip_mreq(multiaddr: Ipv4Addr, interface: Ipv4Addr) -> libc::ip_mreq {
+    libc::ip_mreq {
+        imr_multiaddr: libc::in_addr {
+            s_addr: u32::from(multiaddr).to_be(),
+        },
+        imr_interface: libc::in_addr {
+            s_addr: u32::from(interface).to_be(),
+        },
+    }
+}
+
+fn ipv6_mreq(multiaddr: &Ipv6Addr, interface: u32) -> libc::ipv6_mreq {
+    libc::ipv6_mreq {
+        ipv6mr_multiaddr: libc::in6_addr {
+            s6_addr: multiaddr.octets(),
+        },
+        ipv6mr_interface: interface,
+    }
+}
+
 impl AsyncRead for UdpSocket {
     fn poll_read(
-        self: std::pin::Pin<&mut Self>,
-        cx: &mut std::task::Context<'_>,
-        buf: &mut tokio::io::ReadBuf<'_>,
-    ) -> std::task::Poll<io::Result<()>> {
-        loop {
-            todo!();
-        }
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let me = self.get_mut();
+        me.inner.poll_read(&mut me.read, cx, buf)
     }
 }
 
 impl AsyncWrite for UdpSocket {
     fn poll_write(
-        self: std::pin::Pin<&mut Self>,
-        cx: &mut std::task::Context<'_>,
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
         buf: &[u8],
-    ) -> std::task::Poll<Result<usize, io::Error>> {
-        todo!();
+    ) -> Poll<io::Result<usize>> {
+        let me = self.get_mut();
+        me.inner.poll_write(&mut me.write, cx, buf)
     }
-    fn poll_flush(
-        self: std::pin::Pin<&mut Self>,
-        cx: &mut std::task::Context<'_>,
-    ) -> std::task::Poll<Result<(), io::Error>> {
-        todo!();
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        // Datagrams are not buffered, there is nothing to flush.
+        Poll::Ready(Ok(()))
     }
-    fn poll_shutdown(
-        self: std::pin::Pin<&mut Self>,
-        cx: &mut std::task::Context<'_>,
-    ) -> std::task::Poll<Result<(), io::Error>> {
-        todo!();
+
+    fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(self.inner.shutdown(Shutdown::Write))
     }
+
     fn poll_write_vectored(
-        self: std::pin::Pin<&mut Self>,
-        cx: &mut std::task::Context<'_>,
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
         bufs: &[io::IoSlice<'_>],
-    ) -> std::task::Poll<Result<usize, io::Error>> {
-        todo!();
+    ) -> Poll<io::Result<usize>> {
+        let me = self.get_mut();
+        me.inner.poll_write_vectored(&mut me.writev, cx, bufs)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        true
+    }
+}
+
+/// Information about the local end of a received datagram.
+///
+/// See [`UdpSocket::recv_from_with_info`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PacketInfo {
+    destination: IpAddr,
+    interface: u32,
+}
+
+impl PacketInfo {
+    /// Returns the local address the datagram was sent to.
+    pub fn destination(&self) -> IpAddr {
+        self.destination
+    }
+
+    /// Returns the index of the interface the datagram arrived on.
+    pub fn interface(&self) -> u32 {
+        self.interface
+    }
+}
+
+/// Outcome of sending a batch of datagrams.
+///
+/// See [`UdpSocket::send_mmsg_detailed`].
+#[derive(Debug)]
+pub struct SendMmsgResult {
+    sent: usize,
+    error: Option<io::Error>,
+}
+
+impl SendMmsgResult {
+    /// Returns the number of datagrams sent, from the start of the batch.
+    pub fn sent(&self) -> usize {
+        self.sent
+    }
+
+    /// Returns the error of the send that cut the batch short, if any. The
+    /// error belongs to the datagram following the last one sent.
+    pub fn error(&self) -> Option<&io::Error> {
+        self.error.as_ref()
+    }
+
+    /// Consumes the result, returning the error of the send that cut the
+    /// batch short, if any.
+    pub fn into_error(self) -> Option<io::Error> {
+        self.error
+    }
+}
+
+/// A datagram received into a [`BufRing`] buffer.
+///
+/// Dereferences to the datagram payload. The buffer is handed back to the
+/// ring when the slot is dropped.
+///
+/// See [`UdpSocket::recv_multishot`].
+pub struct RecvSlot {
+    buf_ring: BufRing,
+    bid: u16,
+
+    /// Location of the payload in the buffer.
+    offset: usize,
+    len: usize,
+
+    addr: SocketAddr,
+}
+
+impl RecvSlot {
+    pub(crate) fn new(
+        buf_ring: BufRing,
+        bid: u16,
+        offset: usize,
+        len: usize,
+        addr: SocketAddr,
+    ) -> RecvSlot {
+        RecvSlot {
+            buf_ring,
+            bid,
+            offset,
+            len,
+            addr,
+        }
+    }
+
+    /// Returns the address the datagram was received from.
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+}
+
+impl Deref for RecvSlot {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        // Safety: the buffer belongs to the slot until it is dropped, and the
+        // kernel wrote the payload to it.
+        unsafe { &self.buf_ring.buf(self.bid, self.offset + self.len)[self.offset..] }
+    }
+}
+
+impl fmt::Debug for RecvSlot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RecvSlot")
+            .field("addr", &self.addr)
+            .field("len", &self.len)
+            .finish()
+    }
+}
+
+impl Drop for RecvSlot {
+    fn drop(&mut self) {
+        self.buf_ring.recycle(self.bid);
     }
 }
@@ -0,0 +1,177 @@
+use crate::{
+    buf::{IoBuf, IoBufMut},
+    driver::Socket,
+};
+use socket2::SockAddr;
+use std::{
+    ffi::OsStr,
+    io,
+    os::{
+        linux::net::SocketAddrExt,
+        unix::{ffi::OsStrExt, net::SocketAddr},
+    },
+    path::Path,
+    sync::OnceLock,
+};
+
+/// A Unix datagram socket.
+///
+/// Datagrams are sent between local sockets named by a path, or by a name in
+/// the Linux abstract namespace. Abstract names are given as paths starting
+/// with a NUL byte, like `"\0metrics"`, and do not appear on the file system.
+///
+/// As with [`UdpSocket`], a socket can either [`send_to`] and [`recv_from`]
+/// many peers, or be [`connect`]ed to a single peer and use [`send`] and
+/// [`recv`].
+///
+/// # Examples
+///
+/// ```
+/// use tokio_uring::net::UnixDatagram;
+///
+/// fn main() -> std::io::Result<()> {
+///     tokio_uring::start(async {
+///         let server = UnixDatagram::bind("\0tokio-uring-datagram-example")?;
+///         let client = UnixDatagram::unbound()?;
+///
+///         let (result, _) = client
+///             .send_to(b"hello".as_slice(), "\0tokio-uring-datagram-example")
+///             .await;
+///         result?;
+///
+///         let (result, buf) = server.recv_from(vec![0; 32]).await;
+///         let (n, addr) = result?;
+///         assert_eq!(&buf[..n], b"hello");
+///         assert!(addr.is_unnamed());
+///
+///         Ok(())
+///     })
+/// }
+/// ```
+///
+/// [`UdpSocket`]: crate::net::UdpSocket
+/// [`send_to`]: UnixDatagram::send_to
+/// [`recv_from`]: UnixDatagram::recv_from
+/// [`connect`]: UnixDatagram::connect
+/// [`send`]: UnixDatagram::send
+/// [`recv`]: UnixDatagram::recv
+pub struct UnixDatagram {
+    inner: Socket,
+}
+
+impl UnixDatagram {
+    /// Creates a new Unix datagram socket bound to the specified path. The
+    /// path cannot yet exist. A path starting with a NUL byte binds to a name
+    /// in the abstract namespace instead.
+    pub fn bind<P: AsRef<Path>>(path: P) -> io::Result<UnixDatagram> {
+        let socket = Socket::bind_unix(path, libc::SOCK_DGRAM)?;
+        Ok(UnixDatagram { inner: socket })
+    }
+
+    /// Creates a new Unix datagram socket which is not bound to any address.
+    ///
+    /// Peers see datagrams sent from an unbound socket as coming from an
+    /// unnamed address, and cannot reply to them.
+    pub fn unbound() -> io::Result<UnixDatagram> {
+        let socket = Socket::new_unix(libc::SOCK_DGRAM)?;
+        Ok(UnixDatagram { inner: socket })
+    }
+
+    /// Connects the socket to the specified path, or abstract name.
+    ///
+    /// Once connected, [`send`](UnixDatagram::send) sends to this peer, and
+    /// only datagrams from this peer are received.
+    pub async fn connect<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        self.inner.connect(SockAddr::unix(path)?).await
+    }
+
+    /// Sends a datagram to the socket at the specified path, or abstract
+    /// name. On success, returns the number of bytes written.
+    pub async fn send_to<T: IoBuf, P: AsRef<Path>>(
+        &self,
+        buf: T,
+        path: P,
+    ) -> crate::BufResult<usize, T> {
+        let socket_addr = match SockAddr::unix(path) {
+            Ok(socket_addr) => socket_addr,
+            Err(e) => return (Err(e), buf),
+        };
+        self.inner.send_to_sockaddr(buf, socket_addr).await
+    }
+
+    /// Receives a single datagram on the socket. On success, returns the
+    /// number of bytes read and the address of the sender.
+    pub async fn recv_from<T: IoBufMut>(&self, buf: T) -> crate::BufResult<(usize, SocketAddr), T> {
+        let (res, buf) = self.inner.recv_from_sockaddr(buf).await;
+        let res = res.and_then(|(n, socket_addr)| Ok((n, to_unix_addr(&socket_addr)?)));
+        (res, buf)
+    }
+
+    /// Sends a datagram to the connected peer. On success, returns the number
+    /// of bytes written.
+    pub async fn send<T: IoBuf>(&self, buf: T) -> crate::BufResult<usize, T> {
+        self.inner.send(buf).await
+    }
+
+    /// Receives a single datagram from the connected peer. On success,
+    /// returns the number of bytes read.
+    pub async fn recv<T: IoBufMut>(&self, buf: T) -> crate::BufResult<usize, T> {
+        self.inner.recv(buf).await
+    }
+
+    /// Returns the address this socket is bound to.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        to_unix_addr(&self.inner.local_sockaddr()?)
+    }
+
+    /// Returns the address of the peer this socket is connected to.
+    pub fn peer_addr(&self) -> io::Result<SocketAddr> {
+        to_unix_addr(&self.inner.peer_sockaddr()?)
+    }
+}
+
+/// Converts a `sockaddr_un` into its standard library counterpart.
+pub(super) fn to_unix_addr(socket_addr: &SockAddr) -> io::Result<SocketAddr> {
+    // An unnamed sender leaves the address empty, family included
+    if socket_addr.len() == 0 {
+        return unnamed_addr();
+    }
+
+    if socket_addr.family() != libc::AF_UNIX as libc::sa_family_t {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "socket address is not a Unix address",
+        ));
+    }
+
+    // Safety: the address is a `sockaddr_un` of `len` bytes.
+    let storage = unsafe { &*(socket_addr.as_ptr() as *const libc::sockaddr_un) };
+    let offset = storage.sun_path.as_ptr() as usize - storage as *const _ as usize;
+    let len = (socket_addr.len() as usize)
+        .saturating_sub(offset)
+        .min(storage.sun_path.len());
+    let path = unsafe { std::slice::from_raw_parts(storage.sun_path.as_ptr() as *const u8, len) };
+
+    match path {
+        [] => unnamed_addr(),
+        [0, name @ ..] => SocketAddr::from_abstract_name(name),
+        path => {
+            // Path names may be followed by their NUL terminator
+            let end = path.iter().position(|&b| b == 0).unwrap_or(path.len());
+            SocketAddr::from_pathname(OsStr::from_bytes(&path[..end]))
+        }
+    }
+}
+
+/// The standard library cannot build an unnamed address, so borrow one from
+/// an unbound socket, once.
+fn unnamed_addr() -> io::Result<SocketAddr> {
+    static UNNAMED: OnceLock<SocketAddr> = OnceLock::new();
+
+    if let Some(socket_addr) = UNNAMED.get() {
+        return Ok(socket_addr.clone());
+    }
+
+    let socket_addr = std::os::unix::net::UnixDatagram::unbound()?.local_addr()?;
+    Ok(UNNAMED.get_or_init(|| socket_addr).clone())
+}
@@ -1,12 +1,21 @@
-use super::UnixStream;
+use super::{datagram::to_unix_addr, UnixStream};
 use crate::driver::Socket;
-use std::{io, path::Path};
+use std::{
+    io,
+    os::unix::net::SocketAddr,
+    path::{Path, PathBuf},
+};
 
 /// A Unix socket server, listening for connections.
 ///
 /// You can accept a new connection by using the [`accept`](`UnixListener::accept`)
 /// method.
 ///
+/// The listener is named by a path, or by a name in the Linux abstract
+/// namespace, given as a path starting with a NUL byte, like `"\0control"`.
+/// The file created for a path is left behind when the listener is dropped,
+/// unless [`set_unlink_on_drop`](UnixListener::set_unlink_on_drop) is used.
+///
 /// # Examples
 ///
 /// ```
@@ -35,15 +44,60 @@ use std::{io, path::Path};
 /// ```
 pub struct UnixListener {
     inner: Socket,
+    // Path removed when the listener is dropped, if opted in
+    unlink: Option<PathBuf>,
 }
 
 impl UnixListener {
     /// Creates a new UnixListener, which will be bound to the specified file path.
-    /// The file path cannnot yet exist, and will be cleaned up upon dropping `UnixListener`
+    /// The file path cannot yet exist. A path starting with a NUL byte binds
+    /// to a name in the abstract namespace instead.
     pub fn bind<P: AsRef<Path>>(path: P) -> io::Result<UnixListener> {
         let socket = Socket::bind_unix(path, libc::SOCK_STREAM)?;
         socket.listen(1024)?;
-        Ok(UnixListener { inner: socket })
+        Ok(UnixListener {
+            inner: socket,
+            unlink: None,
+        })
+    }
+
+    /// Returns the address this listener is bound to.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        to_unix_addr(&self.inner.local_sockaddr()?)
+    }
+
+    /// Sets whether the socket file is removed when the listener is dropped,
+    /// so the path can be bound again. Off by default.
+    ///
+    /// The path is the one the listener is bound to when this is called. It
+    /// is removed even if another file replaced the socket in the meantime.
+    /// Listeners in the abstract namespace have no file, this does nothing
+    /// for them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_uring::net::UnixListener;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     let path = std::env::temp_dir().join("tokio-uring-unlink-example.sock");
+    ///
+    ///     let mut listener = UnixListener::bind(&path)?;
+    ///     listener.set_unlink_on_drop(true)?;
+    ///     drop(listener);
+    ///
+    ///     assert!(!path.exists());
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn set_unlink_on_drop(&mut self, unlink: bool) -> io::Result<()> {
+        self.unlink = if unlink {
+            self.local_addr()?.as_pathname().map(Path::to_path_buf)
+        } else {
+            None
+        };
+
+        Ok(())
     }
 
     /// Accepts a new incoming connection from this listener.
@@ -52,10 +106,28 @@ impl UnixListener {
     /// is established. When established, the corresponding [`UnixStream`] and
     /// will be returned.
     ///
+    /// The accepted socket is created with `SOCK_CLOEXEC`, see
+    /// [`accept_with_flags`](UnixListener::accept_with_flags) to opt out.
+    ///
     /// [`UnixStream`]: struct@crate::net::UnixStream
     pub async fn accept(&self) -> io::Result<UnixStream> {
-        let (socket, _) = self.inner.accept().await?;
+        self.accept_with_flags(libc::SOCK_CLOEXEC).await
+    }
+
+    /// Accepts a new incoming connection from this listener, setting `flags`
+    /// on the accepted socket. See
+    /// [`TcpListener::accept_with_flags`](crate::net::TcpListener::accept_with_flags).
+    pub async fn accept_with_flags(&self, flags: i32) -> io::Result<UnixStream> {
+        let (socket, _) = self.inner.accept_with_flags(flags).await?;
         let stream = UnixStream { inner: socket };
         Ok(stream)
     }
 }
+
+impl Drop for UnixListener {
+    fn drop(&mut self) {
+        if let Some(path) = &self.unlink {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
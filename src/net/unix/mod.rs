@@ -1,5 +1,11 @@
+mod datagram;
+pub use datagram::UnixDatagram;
+
 mod listener;
 pub use listener::UnixListener;
 
 mod stream;
 pub use stream::UnixStream;
+
+mod ucred;
+pub use ucred::UCred;
@@ -1,9 +1,17 @@
+use super::{datagram::to_unix_addr, UCred};
 use crate::{
     buf::{IoBuf, IoBufMut},
-    driver::Socket,
+    driver::{CmsgBuf, Socket},
 };
 use socket2::SockAddr;
-use std::{io, path::Path};
+use std::{
+    io,
+    os::unix::{
+        io::{FromRawFd, OwnedFd, RawFd},
+        net::SocketAddr,
+    },
+    path::Path,
+};
 
 /// A Unix stream between two local sockets on a Unix OS.
 ///
@@ -41,6 +49,9 @@ impl UnixStream {
     /// Opens a Unix connection to the specified file path. There must be a
     /// `UnixListener` or equivalent listening on the corresponding Unix domain socket
     /// to successfully connect and return a `UnixStream`.
+    ///
+    /// A path starting with a NUL byte connects to a name in the abstract
+    /// namespace instead.
     pub async fn connect<P: AsRef<Path>>(path: P) -> io::Result<UnixStream> {
         let socket = Socket::new_unix(libc::SOCK_STREAM)?;
         socket.connect(SockAddr::unix(path)?).await?;
@@ -59,4 +70,112 @@ impl UnixStream {
     pub async fn write<T: IoBuf>(&self, buf: T) -> crate::BufResult<usize, T> {
         self.inner.write(buf).await
     }
+
+    /// Returns the address this stream is bound to, usually unnamed for the
+    /// connecting side.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        to_unix_addr(&self.inner.local_sockaddr()?)
+    }
+
+    /// Returns the address of the peer this stream is connected to.
+    pub fn peer_addr(&self) -> io::Result<SocketAddr> {
+        to_unix_addr(&self.inner.peer_sockaddr()?)
+    }
+
+    /// Returns the credentials of the peer process, read with `SO_PEERCRED`.
+    ///
+    /// The kernel records them when the connection is established, so they
+    /// cannot be forged by the peer, and can be used to authorize it.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_uring::net::UnixListener;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     let listener = UnixListener::bind("/run/my-daemon.sock")?;
+    ///
+    ///     tokio_uring::start(async {
+    ///         let stream = listener.accept().await?;
+    ///
+    ///         // Only serve processes of the same user
+    ///         if stream.peer_cred()?.uid() != unsafe { libc::getuid() } {
+    ///             return Ok(());
+    ///         }
+    ///
+    ///         Ok(())
+    ///     })
+    /// }
+    /// ```
+    pub fn peer_cred(&self) -> io::Result<UCred> {
+        UCred::from_socket(&self.inner)
+    }
+
+    /// Write some data to the stream from the buffer, passing the file
+    /// descriptors `fds` along in an `SCM_RIGHTS` control message.
+    ///
+    /// The peer receives duplicates of the descriptors with [`recv_fds`],
+    /// with the first byte of the data; the caller keeps ownership of `fds`.
+    /// Returns the original buffer and quantity of data written.
+    ///
+    /// [`recv_fds`]: UnixStream::recv_fds
+    pub async fn send_fds<T: IoBuf>(&self, buf: T, fds: &[RawFd]) -> crate::BufResult<usize, T> {
+        let control = if fds.is_empty() {
+            None
+        } else {
+            let mut control = CmsgBuf::with_space(CmsgBuf::space(std::mem::size_of_val(fds)));
+            control.push_slice(libc::SOL_SOCKET, libc::SCM_RIGHTS, fds);
+            Some(control)
+        };
+
+        let (res, [buf], _) = self.inner.send_msg([buf], None, control, 0).await;
+        (res, buf)
+    }
+
+    /// Read some data from the stream into the buffer, along with the file
+    /// descriptors passed by the peer with [`send_fds`], with room for at
+    /// least `max_fds` of them.
+    ///
+    /// The received descriptors are owned by the caller, and closed when
+    /// dropped. They are opened with `O_CLOEXEC`. Returns the original
+    /// buffer, the quantity of data read, the descriptors, and whether
+    /// descriptors were truncated.
+    ///
+    /// If the peer passed more descriptors than there is room for, the
+    /// kernel closes the ones which did not fit, and reports the truncation with
+    /// `MSG_CTRUNC`. The data and the descriptors which fit are still
+    /// received, and the truncation flag is set.
+    ///
+    /// [`send_fds`]: UnixStream::send_fds
+    pub async fn recv_fds<T: IoBufMut>(
+        &self,
+        buf: T,
+        max_fds: usize,
+    ) -> crate::BufResult<(usize, Vec<OwnedFd>, bool), T> {
+        let control = CmsgBuf::with_space(CmsgBuf::space(max_fds * std::mem::size_of::<RawFd>()));
+
+        let (res, [buf], control) = self
+            .inner
+            .recv_msg([buf], false, Some(control), libc::MSG_CMSG_CLOEXEC)
+            .await;
+
+        let out = match res {
+            Ok(out) => out,
+            Err(e) => return (Err(e), buf),
+        };
+
+        // Take ownership of every descriptor, so they are closed if the
+        // caller drops them
+        let fds: Vec<OwnedFd> = control
+            .iter()
+            .flat_map(|control| control.iter())
+            .filter(|cmsg| (cmsg.level, cmsg.ty) == (libc::SOL_SOCKET, libc::SCM_RIGHTS))
+            .flat_map(|cmsg| cmsg.read_slice::<RawFd>())
+            // Safety: the kernel installed the descriptors for this process.
+            .map(|fd| unsafe { OwnedFd::from_raw_fd(fd) })
+            .collect();
+
+        let truncated = out.flags & libc::MSG_CTRUNC != 0;
+        (Ok((out.len, fds, truncated)), buf)
+    }
 }
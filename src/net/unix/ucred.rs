@@ -0,0 +1,43 @@
+use crate::driver::Socket;
+use std::io;
+
+/// Credentials of the process on the other end of a Unix socket.
+///
+/// Returned by [`UnixStream::peer_cred`](crate::net::UnixStream::peer_cred).
+/// The credentials are those of the peer when it connected, or created the
+/// socket pair, not when they are queried.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct UCred {
+    pid: Option<libc::pid_t>,
+    uid: libc::uid_t,
+    gid: libc::gid_t,
+}
+
+impl UCred {
+    /// Returns the user ID of the peer process.
+    pub fn uid(&self) -> libc::uid_t {
+        self.uid
+    }
+
+    /// Returns the group ID of the peer process.
+    pub fn gid(&self) -> libc::gid_t {
+        self.gid
+    }
+
+    /// Returns the process ID of the peer process, if it is known. It is not
+    /// when the peer lives in another PID namespace.
+    pub fn pid(&self) -> Option<libc::pid_t> {
+        self.pid
+    }
+
+    /// Reads the credentials of the peer of `socket` with `SO_PEERCRED`.
+    pub(super) fn from_socket(socket: &Socket) -> io::Result<UCred> {
+        let cred: libc::ucred = socket.getsockopt(libc::SOL_SOCKET, libc::SO_PEERCRED)?;
+
+        Ok(UCred {
+            pid: Some(cred.pid).filter(|&pid| pid != 0),
+            uid: cred.uid,
+            gid: cred.gid,
+        })
+    }
+}
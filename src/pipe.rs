@@ -0,0 +1,170 @@
+use crate::buf::{IoBuf, IoBufMut};
+use crate::driver::{Op, SharedFd};
+
+use std::{
+    convert::TryFrom,
+    fmt, io,
+    os::unix::io::{AsRawFd, RawFd},
+};
+
+/// Creates an anonymous pipe, returning its read and write ends.
+///
+/// Both ends are opened with `O_NONBLOCK` and `O_CLOEXEC`, and are read and
+/// written through the driver, with the owned buffer convention of the rest
+/// of the crate. Reading blocks until data is written, or until the write end
+/// is closed, at which point reads return `0`.
+///
+/// Pipes are useful to bridge a producer running on a blocking thread, which
+/// writes to the raw descriptor of the [`PipeWriter`], and a task reading the
+/// [`PipeReader`]. The descriptor is non-blocking, also in duplicates, so
+/// such a producer gets `EAGAIN` when the pipe is full and must retry. Pipes
+/// are also the required intermediary of `splice(2)`.
+///
+/// # Examples
+///
+/// ```
+/// fn main() -> std::io::Result<()> {
+///     tokio_uring::start(async {
+///         let (reader, writer) = tokio_uring::pipe()?;
+///
+///         let (res, _) = writer.write(b"hello".to_vec()).await;
+///         res?;
+///         writer.close().await?;
+///
+///         let (res, buf) = reader.read(Vec::with_capacity(16)).await;
+///         assert_eq!(&buf[..res?], b"hello");
+///
+///         Ok(())
+///     })
+/// }
+/// ```
+pub fn pipe() -> io::Result<(PipeReader, PipeWriter)> {
+    let mut fds = [0; 2];
+    syscall!(pipe2(fds.as_mut_ptr(), libc::O_CLOEXEC | libc::O_NONBLOCK))?;
+
+    Ok((
+        PipeReader {
+            fd: SharedFd::new(fds[0]),
+        },
+        PipeWriter {
+            fd: SharedFd::new(fds[1]),
+        },
+    ))
+}
+
+/// The read end of a pipe, created by [`pipe`].
+pub struct PipeReader {
+    fd: SharedFd,
+}
+
+/// The write end of a pipe, created by [`pipe`].
+pub struct PipeWriter {
+    fd: SharedFd,
+}
+
+impl PipeReader {
+    /// Read some data from the pipe into the buffer, returning the original
+    /// buffer and quantity of data read. A return value of `0` means the
+    /// write end of the pipe was closed.
+    pub async fn read<T: IoBufMut>(&self, buf: T) -> crate::BufResult<usize, T> {
+        let op = Op::read_at(&self.fd, buf, 0).unwrap();
+        op.read().await
+    }
+
+    /// Returns the capacity of the pipe in bytes. See
+    /// [`PipeWriter::set_capacity`].
+    pub fn capacity(&self) -> io::Result<usize> {
+        capacity(&self.fd)
+    }
+
+    /// Sets the capacity of the pipe. See [`PipeWriter::set_capacity`].
+    pub fn set_capacity(&self, size: usize) -> io::Result<usize> {
+        set_capacity(&self.fd, size)
+    }
+
+    /// Closes the read end of the pipe.
+    ///
+    /// The method completes once the close operation has completed.
+    pub async fn close(self) -> io::Result<()> {
+        self.fd.close().await
+    }
+}
+
+impl PipeWriter {
+    /// Write some data to the pipe from the buffer, returning the original
+    /// buffer and quantity of data written.
+    ///
+    /// Writing to a pipe whose read end was closed fails with
+    /// [`BrokenPipe`](io::ErrorKind::BrokenPipe).
+    pub async fn write<T: IoBuf>(&self, buf: T) -> crate::BufResult<usize, T> {
+        let op = Op::write_at(&self.fd, buf, 0).unwrap();
+        op.write().await
+    }
+
+    /// Returns the capacity of the pipe in bytes, 64 KiB by default.
+    pub fn capacity(&self) -> io::Result<usize> {
+        capacity(&self.fd)
+    }
+
+    /// Sets the capacity of the pipe to at least `size` bytes, with
+    /// `fcntl(F_SETPIPE_SZ)`, returning the capacity the kernel settled on.
+    /// The capacity is shared by both ends of the pipe.
+    ///
+    /// The kernel rounds the capacity up to a power of two number of pages.
+    /// A larger capacity lets a single `splice(2)` move more data.
+    ///
+    /// # Errors
+    ///
+    /// Fails with `EPERM` if `size` exceeds `/proc/sys/fs/pipe-max-size`,
+    /// 1 MiB by default, for unprivileged processes, and with `EBUSY` if
+    /// the pipe holds more data than fits the new capacity.
+    pub fn set_capacity(&self, size: usize) -> io::Result<usize> {
+        set_capacity(&self.fd, size)
+    }
+
+    /// Closes the write end of the pipe. Reads from the read end return `0`
+    /// once the data in the pipe is consumed.
+    ///
+    /// The method completes once the close operation has completed.
+    pub async fn close(self) -> io::Result<()> {
+        self.fd.close().await
+    }
+}
+
+fn capacity(fd: &SharedFd) -> io::Result<usize> {
+    syscall!(fcntl(fd.raw_fd(), libc::F_GETPIPE_SZ)).map(|size| size as usize)
+}
+
+fn set_capacity(fd: &SharedFd, size: usize) -> io::Result<usize> {
+    let size = libc::c_int::try_from(size)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "pipe capacity is too large"))?;
+    syscall!(fcntl(fd.raw_fd(), libc::F_SETPIPE_SZ, size)).map(|size| size as usize)
+}
+
+impl AsRawFd for PipeReader {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd.raw_fd()
+    }
+}
+
+impl AsRawFd for PipeWriter {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd.raw_fd()
+    }
+}
+
+impl fmt::Debug for PipeReader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PipeReader")
+            .field("fd", &self.fd.raw_fd())
+            .finish()
+    }
+}
+
+impl fmt::Debug for PipeWriter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PipeWriter")
+            .field("fd", &self.fd.raw_fd())
+            .finish()
+    }
+}
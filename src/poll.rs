@@ -0,0 +1,115 @@
+use crate::driver::{Op, PollMultiStream};
+use futures_core::Stream;
+use std::{
+    io,
+    os::unix::io::RawFd,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// Waits until `fd` is readable, with an `IORING_OP_POLL_ADD`.
+///
+/// This integrates descriptors owned by other libraries, like an inotify
+/// descriptor, into the runtime: the poll only reports readiness, and the
+/// caller then reads from `fd` itself, e.g. with a non-blocking `read(2)`.
+/// Errors and hang-ups of `fd` count as readiness, as with `poll(2)`, so the
+/// following read reports them.
+///
+/// `fd` is not owned by the poll, and must stay open until it completes.
+/// Dropping the future removes the poll with `IORING_OP_POLL_REMOVE`.
+///
+/// # Examples
+///
+/// ```
+/// use std::io::Write;
+/// use std::os::unix::io::AsRawFd;
+/// use std::os::unix::net::UnixStream;
+///
+/// fn main() -> std::io::Result<()> {
+///     tokio_uring::start(async {
+///         let (rx, mut tx) = UnixStream::pair()?;
+///
+///         tx.write_all(b"ready")?;
+///         tokio_uring::poll_readable(rx.as_raw_fd()).await?;
+///
+///         Ok(())
+///     })
+/// }
+/// ```
+pub async fn poll_readable(fd: RawFd) -> io::Result<()> {
+    poll(fd, libc::POLLIN).await.map(|_| ())
+}
+
+/// Waits until `fd` is writable, with an `IORING_OP_POLL_ADD`. See
+/// [`poll_readable`].
+pub async fn poll_writable(fd: RawFd) -> io::Result<()> {
+    poll(fd, libc::POLLOUT).await.map(|_| ())
+}
+
+/// Waits until one of `events` occurs on `fd`, and returns the events that
+/// occurred.
+///
+/// `events` is a mask of `POLL*` flags of the `libc` crate, as passed to
+/// `poll(2)`. `POLLERR` and `POLLHUP` are always reported. See
+/// [`poll_readable`].
+pub async fn poll(fd: RawFd, events: libc::c_short) -> io::Result<libc::c_short> {
+    let op = Op::poll_add(fd, events as u16 as u32, false)?;
+    let revents = op.ready().await?;
+    Ok(revents as u16 as libc::c_short)
+}
+
+/// Polls `fd` for `events` with a multishot `IORING_OP_POLL_ADD`, yielding
+/// the events that occurred each time `fd` becomes ready.
+///
+/// A single poll stays armed in the kernel, saving a submission per
+/// wake-up. Like an edge-triggered `epoll(7)`, the stream yields when `fd`
+/// becomes ready, so the caller drains `fd` before waiting for the next
+/// item. If the kernel terminates the poll, the stream submits a new one. An
+/// error ends the stream, and dropping it removes the poll with
+/// `IORING_OP_POLL_REMOVE`.
+///
+/// Multishot polls require Linux 5.13 or newer.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::future::poll_fn;
+/// use std::os::unix::io::RawFd;
+/// use std::pin::pin;
+/// use futures_core::Stream;
+///
+/// fn watch(fd: RawFd) -> std::io::Result<()> {
+///     tokio_uring::start(async {
+///         let mut ready = pin!(tokio_uring::poll_multishot(fd, libc::POLLIN));
+///
+///         while let Some(revents) = poll_fn(|cx| ready.as_mut().poll_next(cx)).await {
+///             let _revents = revents?;
+///             // Read from `fd` until it would block
+///         }
+///
+///         Ok(())
+///     })
+/// }
+/// ```
+pub fn poll_multishot(
+    fd: RawFd,
+    events: libc::c_short,
+) -> impl Stream<Item = io::Result<libc::c_short>> {
+    Revents {
+        inner: PollMultiStream::new(fd, events as u16 as u32),
+    }
+}
+
+/// Readiness events yielded by [`poll_multishot`].
+struct Revents {
+    inner: PollMultiStream,
+}
+
+impl Stream for Revents {
+    type Item = io::Result<libc::c_short>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let item = ready!(Pin::new(&mut self.inner).poll_next(cx));
+        Poll::Ready(item.map(|res| res.map(|revents| revents as u16 as libc::c_short)))
+    }
+}
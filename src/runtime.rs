@@ -1,17 +1,55 @@
-use crate::driver::Driver;
+use crate::driver::{BufRing, Driver, FixedBufPool, Metrics};
+use crate::{Builder, EventFd, MultiThreadRuntime};
 
 use std::future::Future;
 use std::io;
+use std::os::unix::io::{AsRawFd, RawFd};
 use tokio::io::unix::AsyncFd;
 use tokio::task::LocalSet;
 
-pub(crate) struct Runtime {
-    /// io-uring driver
-    driver: AsyncFd<Driver>,
-
+/// A `tokio-uring` runtime, built with a [`Builder`].
+///
+/// The runtime consists of a Tokio `current_thread` runtime and an io-uring
+/// driver. [`block_on`](Runtime::block_on) runs a future to completion on the
+/// current thread. Dropping the runtime drops the tasks spawned on it, and
+/// waits for their in-flight operations to complete.
+///
+/// [`start`](crate::start) creates a runtime and runs a single future on it,
+/// which covers most uses.
+///
+/// # Shutdown
+///
+/// When the runtime is dropped, e.g. once the future passed to `start`
+/// returned, the tasks still running are dropped, which cancels their
+/// in-flight operations. The kernel may still be using the buffers of these
+/// operations, so the runtime then waits for every operation to complete
+/// before closing the io-uring instance and releasing the buffers. This
+/// wait is unbounded, unless a [`shutdown_timeout`] is set.
+///
+/// [`shutdown_timeout`]: Runtime::shutdown_timeout
+///
+/// # Examples
+///
+/// ```
+/// fn main() -> std::io::Result<()> {
+///     let mut rt = tokio_uring::builder().entries(64).build()?;
+///
+///     let n = rt.block_on(async { 1 + 1 });
+///     assert_eq!(n, 2);
+///
+///     Ok(())
+/// }
+/// ```
+pub struct Runtime {
     /// LocalSet for !Send tasks
+    ///
+    /// Declared before the driver so the tasks, and the operations they own,
+    /// are dropped before the driver waits for in-flight operations.
     local: LocalSet,
 
+    /// io-uring driver
+    driver: AsyncFd<Driver>,
+
     /// Tokio runtime, always current-thread
     rt: tokio::runtime::Runtime,
 }
@@ -23,25 +61,54 @@ pub(crate) struct Runtime {
 /// runtime is shutdown, all outstanding tasks are dropped, regardless of the
 /// lifecycle of that task.
 ///
+/// Awaiting the handle returns the output of the task. If the task panicked,
+/// the panic is caught and the handle returns a [`JoinError`] for which
+/// [`is_panic`] is true, so the other tasks keep running.
+/// [`JoinHandle::abort`] drops the task the next time it yields, canceling
+/// the operations it has in flight, and the handle then returns a
+/// [`JoinError`] for which [`is_cancelled`] is true. Dropping the handle
+/// detaches the task, which keeps running.
+///
 /// This function must be called from the context of a `tokio-uring` runtime.
 ///
 /// [`JoinHandle`]: tokio::task::JoinHandle
+/// [`JoinHandle::abort`]: tokio::task::JoinHandle::abort
+/// [`JoinError`]: tokio::task::JoinError
+/// [`is_panic`]: tokio::task::JoinError::is_panic
+/// [`is_cancelled`]: tokio::task::JoinError::is_cancelled
 ///
 /// # Examples
 ///
-/// In this example, a server is started and `spawn` is used to start a new task
-/// that processes each received connection.
+/// In this example, a server spawns a task per connection, and waits for the
+/// tasks to complete before shutting down.
 ///
 /// ```no_run
-/// fn main() {
+/// use tokio_uring::net::TcpListener;
+///
+/// fn main() -> std::io::Result<()> {
 ///     tokio_uring::start(async {
-///         let handle = tokio_uring::spawn(async {
-///             println!("hello from a background task");
-///         });
+///         let listener = TcpListener::bind("127.0.0.1:8080".parse().unwrap())?;
+///
+///         let mut connections = Vec::new();
+///         for _ in 0..16 {
+///             let (stream, _) = listener.accept().await?;
+///             connections.push(tokio_uring::spawn(async move {
+///                 let (res, _) = stream.write(b"hello".as_slice()).await;
+///                 res
+///             }));
+///         }
+///
+///         // Drain the connections
+///         for connection in connections {
+///             match connection.await {
+///                 Ok(res) => drop(res),
+///                 Err(e) if e.is_panic() => eprintln!("connection task panicked"),
+///                 Err(e) => return Err(e.into()),
+///             }
+///         }
 ///
-///         // Let the task complete
-///         handle.await.unwrap();
-///     });
+///         Ok(())
+///     })
 /// }
 /// ```
 pub fn spawn<T: std::future::Future + 'static>(task: T) -> tokio::task::JoinHandle<T::Output> {
@@ -49,7 +116,8 @@ pub fn spawn<T: std::future::Future + 'static>(task: T) -> tokio::task::JoinHand
 }
 
 impl Runtime {
-    pub(crate) fn new() -> io::Result<Runtime> {
+    /// Creates a runtime with the settings of `builder`.
+    pub fn new(builder: &Builder) -> io::Result<Runtime> {
         let rt = tokio::runtime::Builder::new_current_thread()
             .enable_all()
             .build()?;
@@ -58,19 +126,260 @@ impl Runtime {
 
         let driver = {
             let _guard = rt.enter();
-            AsyncFd::new(Driver::new()?)?
+            AsyncFd::new(Driver::new(builder)?)?
         };
 
         Ok(Runtime { driver, local, rt })
     }
 
-    pub(crate) fn block_on<F>(&mut self, future: F) -> F::Output
+    /// Creates a runtime of `workers` threads with the default settings,
+    /// each running its own runtime, with its own io-uring instance. See
+    /// [`MultiThreadRuntime`].
+    ///
+    /// Returns an error of kind [`InvalidInput`](io::ErrorKind::InvalidInput)
+    /// if `workers` is zero.
+    pub fn new_multi_thread(workers: usize) -> io::Result<MultiThreadRuntime> {
+        Builder::new().build_multi_thread(workers)
+    }
+
+    /// Shuts the runtime down, waiting at most `timeout` for in-flight
+    /// operations to complete.
+    ///
+    /// Operations still in flight after the timeout are abandoned: their
+    /// buffers are leaked, as the kernel may still access them, and closing
+    /// the io-uring instance cancels them. Operations the kernel cannot
+    /// interrupt, or futures of operations moved out of the runtime, would
+    /// otherwise hold the shutdown forever.
+    pub fn shutdown_timeout(mut self, timeout: std::time::Duration) {
+        self.driver.get_mut().set_shutdown_timeout(Some(timeout));
+    }
+
+    /// Sets how many completions the runtime waits for before reaping them.
+    /// See [`set_submit_wait_nr`](crate::set_submit_wait_nr).
+    pub fn set_submit_wait_nr(&self, n: u32) {
+        self.driver.get_ref().set_submit_wait_nr(n)
+    }
+
+    /// Returns a snapshot of the counters of the runtime.
+    pub fn metrics(&self) -> Metrics {
+        self.driver.get_ref().metrics()
+    }
+
+    /// Enables NAPI busy polling (`IORING_REGISTER_NAPI`, Linux 6.9).
+    ///
+    /// When the runtime waits for completions, the kernel busy polls the
+    /// receive queues of the network devices the sockets of the runtime
+    /// receive from, for up to `busy_poll_usecs` microseconds, instead of
+    /// waiting for an interrupt. This lowers receive latency at the cost of
+    /// CPU time. With `prefer_busy_poll`, the device also defers interrupts
+    /// while busy polling is active (`SO_PREFER_BUSY_POLL`).
+    ///
+    /// Busy polling only helps with network devices whose driver supports
+    /// NAPI busy polling, and is a no-op otherwise, e.g. over loopback.
+    /// Fails with [`Unsupported`] on kernels lacking the feature, see
+    /// [`Probe::supports_napi`](crate::Probe::supports_napi).
+    ///
+    /// [`Unsupported`]: io::ErrorKind::Unsupported
+    pub fn register_napi(&self, busy_poll_usecs: u32, prefer_busy_poll: bool) -> io::Result<()> {
+        self.driver
+            .get_ref()
+            .register_napi(busy_poll_usecs, prefer_busy_poll)
+    }
+
+    /// Disables NAPI busy polling enabled by
+    /// [`register_napi`](Runtime::register_napi).
+    pub fn unregister_napi(&self) -> io::Result<()> {
+        self.driver.get_ref().unregister_napi()
+    }
+
+    /// Caps the number of io-wq worker threads of the runtime
+    /// (`IORING_REGISTER_IOWQ_MAX_WORKERS`, Linux 5.15), returning the
+    /// previous limits as `(bounded, unbounded)`.
+    ///
+    /// Operations the kernel cannot complete right away, nor arm to retry
+    /// once their file is ready, are handed to the io-wq, a pool of kernel
+    /// threads attached to the io-uring instance. Its workers are split in
+    /// two classes:
+    ///
+    /// * Bounded workers run work that completes in bounded time: I/O on
+    ///   regular files and block devices, like buffered reads and writes,
+    ///   `fsync`, `fallocate`, and opening or `statx`-ing files. They are
+    ///   limited by default to the smaller of the submission queue size and
+    ///   four times the number of CPUs.
+    /// * Unbounded workers run work that may wait forever, e.g. on sockets,
+    ///   pipes and character devices. They are only limited by
+    ///   `RLIMIT_NPROC` by default.
+    ///
+    /// Once a limit is reached, further work waits for a worker of its class
+    /// to be free. A limit of 0 leaves the current one unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// fn main() -> std::io::Result<()> {
+    ///     let rt = tokio_uring::builder().build()?;
+    ///
+    ///     let (bounded, unbounded) = rt.set_iowq_max_workers(8, 32)?;
+    ///     println!("previous limits: {} bounded, {} unbounded", bounded, unbounded);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn set_iowq_max_workers(&self, bounded: u32, unbounded: u32) -> io::Result<(u32, u32)> {
+        let [bounded, unbounded] = self
+            .driver
+            .get_ref()
+            .register_iowq_max_workers([bounded, unbounded])?;
+        Ok((bounded, unbounded))
+    }
+
+    /// Returns the limits of the io-wq worker threads of the runtime, as
+    /// `(bounded, unbounded)`. See
+    /// [`set_iowq_max_workers`](Runtime::set_iowq_max_workers).
+    pub fn iowq_max_workers(&self) -> io::Result<(u32, u32)> {
+        self.set_iowq_max_workers(0, 0)
+    }
+
+    /// Creates an eventfd signaled whenever the runtime's ring posts
+    /// completions (`IORING_REGISTER_EVENTFD`).
+    ///
+    /// This lets another event loop, e.g. an `epoll` loop running alongside
+    /// the runtime, watch the ring through the eventfd, and only drive the
+    /// runtime once completions are available. Each completion adds to the
+    /// counter of the eventfd, though the kernel may coalesce them.
+    ///
+    /// The eventfd must be read with `read(2)` on its
+    /// [raw descriptor](std::os::unix::io::AsRawFd), not with
+    /// [`EventFd::read`]: a read through the ring posts a completion of its
+    /// own, signaling the eventfd again. Its descriptor is non-blocking.
+    ///
+    /// A ring signals a single eventfd: registering another fails with
+    /// `EBUSY` until [`unregister_cq_eventfd`](Runtime::unregister_cq_eventfd)
+    /// is called. The eventfd is unregistered when the runtime shuts down.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::os::unix::io::AsRawFd;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     let mut rt = tokio_uring::builder().build()?;
+    ///     let eventfd = rt.register_cq_eventfd()?;
+    ///
+    ///     // Hand the descriptor to the other event loop
+    ///     let fd = eventfd.as_raw_fd();
+    ///     println!("watch fd {} for completions", fd);
+    ///
+    ///     rt.block_on(async {
+    ///         tokio_uring::time::sleep(std::time::Duration::from_millis(1)).await;
+    ///     });
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn register_cq_eventfd(&self) -> io::Result<EventFd> {
+        self.register_eventfd(false)
+    }
+
+    /// Creates an eventfd signaled when the runtime's ring posts the
+    /// completions of operations run by io-wq workers
+    /// (`IORING_REGISTER_EVENTFD_ASYNC`, Linux 5.6).
+    ///
+    /// Operations the kernel cannot complete right away, nor arm to retry
+    /// once their file is ready, run on the io-wq threads, see
+    /// [`set_iowq_max_workers`](Runtime::set_iowq_max_workers), e.g. `fsync`
+    /// or buffered file I/O. Other completions are not signaled. See
+    /// [`register_cq_eventfd`](Runtime::register_cq_eventfd).
+    pub fn register_cq_eventfd_async(&self) -> io::Result<EventFd> {
+        self.register_eventfd(true)
+    }
+
+    fn register_eventfd(&self, async_only: bool) -> io::Result<EventFd> {
+        let eventfd = EventFd::new(0)?;
+        self.driver
+            .get_ref()
+            .register_eventfd(eventfd.as_raw_fd(), async_only)?;
+        Ok(eventfd)
+    }
+
+    /// Stops signaling the eventfd registered by
+    /// [`register_cq_eventfd`](Runtime::register_cq_eventfd) or
+    /// [`register_cq_eventfd_async`](Runtime::register_cq_eventfd_async).
+    pub fn unregister_cq_eventfd(&self) -> io::Result<()> {
+        self.driver.get_ref().unregister_eventfd()
+    }
+
+    /// Registers `bufs` as the fixed buffers of the runtime, see
+    /// [`FixedBufPool::register`].
+    ///
+    /// Unlike [`FixedBufPool::register`], this can be called outside of
+    /// [`block_on`](Runtime::block_on), e.g. to register the buffers once
+    /// when setting up the runtime.
+    pub fn register_buffers(&self, bufs: Vec<Vec<u8>>) -> io::Result<FixedBufPool> {
+        self.driver.get_ref().with(|| FixedBufPool::register(bufs))
+    }
+
+    /// Registers `fd` in the file table of the runtime, returning its slot,
+    /// see [`register_fd`](crate::register_fd).
+    ///
+    /// Unlike [`register_fd`](crate::register_fd), this can be called
+    /// outside of [`block_on`](Runtime::block_on).
+    pub fn register_fd(&self, fd: RawFd) -> io::Result<u32> {
+        self.driver.get_ref().with(|| crate::register_fd(fd))
+    }
+
+    /// Releases a slot returned by [`register_fd`](Runtime::register_fd),
+    /// see [`unregister_fd`](crate::unregister_fd).
+    pub fn unregister_fd(&self, slot: u32) -> io::Result<()> {
+        self.driver.get_ref().with(|| crate::unregister_fd(slot))
+    }
+
+    /// Registers a ring of `entries` buffers of `buf_len` bytes each with the
+    /// runtime, see [`BufRing::register`].
+    ///
+    /// Unlike [`register_buf_ring`](crate::register_buf_ring), this can be
+    /// called outside of [`block_on`](Runtime::block_on).
+    pub fn register_buf_ring(&self, entries: u16, buf_len: usize) -> io::Result<BufRing> {
+        self.driver
+            .get_ref()
+            .with(|| BufRing::register(entries, buf_len))
+    }
+
+    /// Submits a submission queue entry prepared by `prep` to the runtime,
+    /// and returns a future resolving to the `res` field of its completion.
+    /// See [`submit_raw`](crate::submit_raw).
+    ///
+    /// Unlike [`submit_raw`](crate::submit_raw), this can be called outside
+    /// of [`block_on`](Runtime::block_on). The entry is submitted right away,
+    /// and the future must be awaited on this runtime.
+    ///
+    /// # Safety
+    ///
+    /// The same as for [`submit_raw`](crate::submit_raw).
+    pub unsafe fn submit_raw<F>(&self, prep: F) -> impl Future<Output = i32>
+    where
+        F: FnOnce(&mut io_uring::squeue::Entry),
+    {
+        self.driver.get_ref().with(|| crate::submit_raw(prep))
+    }
+
+    /// Runs `future` to completion on the runtime, along with the tasks
+    /// spawned on it, and returns its output.
+    pub fn block_on<F>(&mut self, future: F) -> F::Output
     where
         F: Future,
     {
         self.driver.get_ref().with(|| {
             let drive = async {
                 loop {
+                    if self.driver.get_ref().has_backlog() {
+                        // Nothing in flight may complete to wake the driver
+                        // up, retry submitting the backlog on the next poll
+                        tokio::task::yield_now().await;
+                        self.driver.get_ref().tick();
+                        continue;
+                    }
+
                     // Wait for read-readiness
                     let mut guard = self.driver.readable().await.unwrap();
                     self.driver.get_ref().tick();
@@ -0,0 +1,73 @@
+use crate::{driver, fs::File, net::TcpStream};
+use std::io;
+
+/// Moves up to `len` bytes from a file to a TCP stream, without copying them
+/// through user space.
+///
+/// The file is read from its current position, which is advanced by the
+/// number of bytes moved. On success, returns that number. It may be less
+/// than `len`, either because the end of the file was reached, or because a
+/// single call moves at most one pipe buffer of data, so callers loop until
+/// all the data is sent. A return value of `0` means the end of the file.
+///
+/// # Pipe buffer
+///
+/// `splice(2)` needs a pipe on one side, so the data goes through a pipe
+/// created for the call: file to pipe, then pipe to socket. The pipe is grown
+/// to fit `len`, up to 1 MiB or `/proc/sys/fs/pipe-max-size` for unprivileged
+/// processes, and keeps its 64 KiB default if growing fails. A larger `len`
+/// means fewer operations and round trips per byte, at the cost of pinning
+/// more kernel memory per call in flight. Chunks of 64 KiB to 1 MiB work well
+/// for serving files.
+///
+/// If the socket fails after part of a chunk was sent, the bytes sent so far
+/// are returned and the rest of the chunk is lost. The file position is past
+/// the lost bytes, so the stream should not be used further.
+///
+/// # Examples
+///
+/// ```no_run
+/// use tokio_uring::{fs::File, net::TcpListener};
+///
+/// fn main() -> std::io::Result<()> {
+///     tokio_uring::start(async {
+///         let listener = TcpListener::bind("127.0.0.1:8080".parse().unwrap())?;
+///         let (stream, _) = listener.accept().await?;
+///         let file = File::open("index.html").await?;
+///
+///         while tokio_uring::splice(&file, &stream, 1024 * 1024).await? > 0 {}
+///
+///         Ok(())
+///     })
+/// }
+/// ```
+pub async fn splice(src: &File, dst: &TcpStream, len: usize) -> io::Result<usize> {
+    driver::splice(
+        src.as_shared_fd(),
+        None,
+        dst.inner.as_shared_fd(),
+        None,
+        len,
+    )
+    .await
+}
+
+/// Moves up to `len` bytes from a TCP stream to a file, without copying them
+/// through user space.
+///
+/// This is the reverse of [`splice`]. The file is written at its current
+/// position, which is advanced by the number of bytes moved. On success,
+/// returns that number, which may be less than `len`. A return value of `0`
+/// means the peer closed its side of the stream.
+///
+/// The notes on pipe buffer sizing of [`splice`] apply here too.
+pub async fn splice_to_file(src: &TcpStream, dst: &File, len: usize) -> io::Result<usize> {
+    driver::splice(
+        src.inner.as_shared_fd(),
+        None,
+        dst.as_shared_fd(),
+        None,
+        len,
+    )
+    .await
+}
@@ -0,0 +1,115 @@
+//! Timers running on the ring.
+//!
+//! The timers of this module are `IORING_OP_TIMEOUT` operations, completed by
+//! the kernel, so they do not depend on the timer of the tokio runtime.
+//! Dropping a timer before it expires cancels its operation.
+
+use crate::driver::Op;
+use crate::future::poll_fn;
+
+use std::fmt;
+use std::future::Future;
+use std::io;
+use std::pin::pin;
+use std::task::Poll;
+use std::time::{Duration, Instant};
+
+/// Waits until `duration` has elapsed.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::{Duration, Instant};
+///
+/// tokio_uring::start(async {
+///     let start = Instant::now();
+///     tokio_uring::time::sleep(Duration::from_millis(10)).await;
+///     assert!(start.elapsed() >= Duration::from_millis(10));
+/// });
+/// ```
+pub async fn sleep(duration: Duration) {
+    wait(Op::sleep(duration)).await
+}
+
+/// Waits until `deadline` is reached.
+///
+/// The deadline is passed to the kernel as an absolute time
+/// (`IORING_TIMEOUT_ABS`), so it is not delayed by the time spent submitting
+/// the timer. A deadline in the past completes right away.
+pub async fn sleep_until(deadline: Instant) {
+    wait(Op::sleep_until(deadline)).await
+}
+
+async fn wait(op: io::Result<Op<crate::driver::Sleep>>) {
+    // The timer is only cut short when the runtime shuts down
+    let _ = op.unwrap().wait().await;
+}
+
+/// Runs `future`, giving up if it does not complete within `duration`.
+///
+/// On expiry, `future` is dropped and [`Elapsed`] is returned. Operations the
+/// future submitted are canceled by the drop, but may still run to completion
+/// in the background. To have the kernel cancel each operation on time, and
+/// get its buffer back along with the error, see
+/// [`with_timeout`](crate::with_timeout).
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use tokio_uring::net::TcpListener;
+///
+/// fn main() -> std::io::Result<()> {
+///     tokio_uring::start(async {
+///         let listener = TcpListener::bind("127.0.0.1:0".parse().unwrap())?;
+///
+///         let res = tokio_uring::time::timeout(listener.accept(), Duration::from_millis(10)).await;
+///         assert!(res.is_err());
+///
+///         Ok(())
+///     })
+/// }
+/// ```
+pub async fn timeout<F: Future>(future: F, duration: Duration) -> Result<F::Output, Elapsed> {
+    race(future, sleep(duration)).await
+}
+
+/// Runs `future`, giving up if it does not complete by `deadline`. See
+/// [`timeout`] and [`sleep_until`].
+pub async fn timeout_at<F: Future>(future: F, deadline: Instant) -> Result<F::Output, Elapsed> {
+    race(future, sleep_until(deadline)).await
+}
+
+async fn race<F: Future>(future: F, timer: impl Future<Output = ()>) -> Result<F::Output, Elapsed> {
+    let mut future = pin!(future);
+    let mut timer = pin!(timer);
+
+    poll_fn(|cx| {
+        // The future wins a tie
+        if let Poll::Ready(output) = future.as_mut().poll(cx) {
+            return Poll::Ready(Ok(output));
+        }
+
+        timer.as_mut().poll(cx).map(|()| Err(Elapsed(())))
+    })
+    .await
+}
+
+/// Error returned by [`timeout`] and [`timeout_at`] when the future did not
+/// complete in time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Elapsed(());
+
+impl fmt::Display for Elapsed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("deadline has elapsed")
+    }
+}
+
+impl std::error::Error for Elapsed {}
+
+impl From<Elapsed> for io::Error {
+    fn from(_: Elapsed) -> io::Error {
+        io::ErrorKind::TimedOut.into()
+    }
+}
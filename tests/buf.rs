@@ -1,4 +1,4 @@
-use tokio_uring::buf::{IoBuf, IoBufMut};
+use tokio_uring::buf::{BufPool, IoBuf, IoBufMut};
 
 #[test]
 fn test_vec() {
@@ -53,6 +53,28 @@ fn test_slice() {
     assert_eq!(v.bytes_total(), 5);
 }
 
+#[test]
+fn test_slice_past_init() {
+    let mut v = Vec::with_capacity(16);
+    v.extend(b"hello");
+
+    // The slice covers uninitialized capacity
+    let mut slice = v.slice(2..12);
+    assert_eq!(slice.bytes_init(), 3);
+    assert_eq!(slice.bytes_total(), 10);
+    assert_eq!(&slice[..], b"llo");
+    assert_eq!(slice.stable_mut_ptr(), unsafe {
+        slice.get_mut().as_mut_ptr().add(2)
+    });
+
+    unsafe {
+        std::ptr::copy(DATA.as_ptr(), slice.stable_mut_ptr().add(3), 4);
+        slice.set_init(7);
+    }
+    assert_eq!(&slice[..], b"lloabcd");
+    assert_eq!(slice.into_inner(), b"helloabcd");
+}
+
 const DATA: &[u8] = b"abcdefghijklmnopqrstuvwxyz0123456789!?";
 
 macro_rules! test_slice {
@@ -117,3 +139,60 @@ test_slice! {
     vec => Vec::from(DATA);
     slice => DATA;
 }
+
+#[test]
+fn buf_pool_reuse() {
+    let pool = BufPool::new(64);
+
+    let mut buf = pool.try_get().unwrap();
+    assert_eq!(buf.bytes_init(), 0);
+    assert_eq!(buf.bytes_total(), 64);
+
+    unsafe {
+        std::ptr::copy(DATA.as_ptr(), buf.stable_mut_ptr(), 10);
+        buf.set_init(10);
+    }
+    assert_eq!(&buf[..], &DATA[..10]);
+
+    let ptr = buf.stable_ptr();
+    drop(buf);
+    assert_eq!(pool.available(), 1);
+
+    // The allocation is handed out again, cleared
+    let buf = pool.try_get().unwrap();
+    assert_eq!(buf.stable_ptr(), ptr);
+    assert_eq!(buf.bytes_init(), 0);
+    assert_eq!(pool.allocated(), 1);
+
+    // Detached buffers are replaced
+    let vec = buf.into_vec();
+    assert_eq!(vec.as_ptr(), ptr);
+    assert_eq!(pool.allocated(), 0);
+    assert_eq!(pool.available(), 0);
+}
+
+#[test]
+fn buf_pool_bounded() {
+    tokio_uring::start(async {
+        let pool = BufPool::bounded(16, 2);
+
+        let first = pool.get().await;
+        let second = pool.try_get().unwrap();
+        assert!(pool.try_get().is_none());
+
+        let waiter = tokio_uring::spawn({
+            let pool = pool.clone();
+            async move { pool.get().await.stable_ptr() }
+        });
+        tokio::task::yield_now().await;
+        assert!(!waiter.is_finished());
+
+        let ptr = first.stable_ptr();
+        drop(first);
+        assert_eq!(waiter.await.unwrap(), ptr);
+
+        drop(second);
+        assert_eq!(pool.allocated(), 2);
+        assert_eq!(pool.available(), 2);
+    });
+}
@@ -6,3 +6,58 @@ fn basic_remove_dir() {
         assert!(std::fs::metadata(temp_dir.path()).is_err());
     });
 }
+
+#[test]
+fn create_rename_remove() {
+    use std::io::ErrorKind;
+    use tokio_uring::fs;
+
+    tokio_uring::start(async {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("dir");
+
+        fs::create_dir(&path).await.unwrap();
+        assert!(std::fs::metadata(&path).unwrap().is_dir());
+        let err = fs::create_dir(&path).await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::AlreadyExists);
+
+        std::fs::write(path.join("upload.tmp"), b"contents").unwrap();
+        fs::rename(path.join("upload.tmp"), path.join("upload"))
+            .await
+            .unwrap();
+        assert_eq!(std::fs::read(path.join("upload")).unwrap(), b"contents");
+        let err = fs::rename(path.join("upload.tmp"), path.join("upload"))
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+
+        fs::remove_file(path.join("upload")).await.unwrap();
+        fs::remove_dir(&path).await.unwrap();
+        assert!(std::fs::metadata(&path).is_err());
+    });
+}
+
+#[test]
+fn relative_to_dir() {
+    use std::io::ErrorKind;
+    use tokio_uring::fs::{self, File};
+
+    tokio_uring::start(async {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let dir = File::open(temp_dir.path()).await.unwrap();
+
+        fs::create_dir_at(&dir, "sub").await.unwrap();
+        assert!(temp_dir.path().join("sub").is_dir());
+        let sub = File::open(temp_dir.path().join("sub")).await.unwrap();
+
+        std::fs::write(temp_dir.path().join("a"), b"a").unwrap();
+        fs::rename_at(&dir, "a", &sub, "b").await.unwrap();
+        assert!(temp_dir.path().join("sub/b").is_file());
+
+        let err = fs::remove_file_at(&dir, "b").await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+        fs::remove_file_at(&sub, "b").await.unwrap();
+        fs::remove_dir_at(&dir, "sub").await.unwrap();
+        assert!(std::fs::read_dir(temp_dir.path()).unwrap().next().is_none());
+    });
+}
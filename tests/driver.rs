@@ -107,3 +107,255 @@ async fn poll_once(future: impl std::future::Future) {
     })
     .await;
 }
+
+#[test]
+fn close_released_while_reaping() {
+    use tokio_uring::net::UdpSocket;
+
+    tokio_uring::start(async {
+        let socket = UdpSocket::bind("127.0.0.1:0".parse().unwrap())
+            .await
+            .unwrap();
+        let addr = socket.local_addr().unwrap();
+
+        // Once the task is dropped, its canceled receive holds the last
+        // reference to the socket, released while the driver reaps its
+        // completion. The socket is then closed without re-entering the
+        // driver.
+        let recv = tokio_uring::spawn(async move { socket.recv_from(vec![0; 1]).await });
+        tokio::task::yield_now().await;
+        recv.abort();
+        assert!(recv.await.unwrap_err().is_cancelled());
+
+        // The address is free once the socket is closed
+        while std::net::UdpSocket::bind(addr).is_err() {
+            tokio::task::yield_now().await;
+        }
+    });
+}
+
+#[test]
+fn register_fd_reuses_slots() {
+    use std::os::unix::io::AsRawFd;
+
+    tokio_uring::start(async {
+        let file = tempfile::tempfile().unwrap();
+
+        let slot = tokio_uring::register_fd(file.as_raw_fd()).unwrap();
+        let other = tokio_uring::register_fd(file.as_raw_fd()).unwrap();
+        assert_ne!(slot, other);
+
+        tokio_uring::unregister_fd(slot).unwrap();
+        assert!(tokio_uring::unregister_fd(slot).is_err());
+        assert_eq!(tokio_uring::register_fd(file.as_raw_fd()).unwrap(), slot);
+    });
+}
+
+#[test]
+fn probe() {
+    use io_uring::opcode;
+
+    // Probing does not need a runtime
+    let probe = tokio_uring::probe().unwrap();
+    assert!(probe.is_supported(opcode::Read::CODE));
+    assert!(probe.is_supported(opcode::Write::CODE));
+    assert!(probe.has_nodrop());
+
+    // Buffer rings are used by other tests
+    assert!(probe.supports_buf_ring());
+    assert!(probe.supports_multishot_accept());
+
+    // The result is cached
+    tokio_uring::start(async {
+        let cached = tokio_uring::probe().unwrap();
+        assert_eq!(cached.supports_send_zc(), probe.supports_send_zc());
+    });
+}
+
+#[test]
+fn submit_interrupted_by_signals() {
+    use std::rc::Rc;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+
+    static SIGNALS: AtomicUsize = AtomicUsize::new(0);
+
+    extern "C" fn count(_: libc::c_int) {
+        SIGNALS.fetch_add(1, Ordering::Relaxed);
+    }
+
+    // Without SA_RESTART, so blocking calls fail with EINTR
+    unsafe {
+        let mut action: libc::sigaction = std::mem::zeroed();
+        action.sa_sigaction = count as *const () as usize;
+        assert_eq!(
+            libc::sigaction(libc::SIGUSR1, &action, std::ptr::null_mut()),
+            0
+        );
+    }
+
+    let tempfile = tempfile();
+    let target = unsafe { libc::pthread_self() };
+    let done = Arc::new(AtomicBool::new(false));
+
+    let signaler = std::thread::spawn({
+        let done = done.clone();
+        move || {
+            while !done.load(Ordering::Relaxed) {
+                unsafe { libc::pthread_kill(target, libc::SIGUSR1) };
+                std::thread::sleep(Duration::from_micros(50));
+            }
+        }
+    });
+
+    // A small ring, so submissions regularly flush a full queue, and
+    // batches of completions, so the driver waits in the kernel
+    let mut builder = tokio_uring::builder();
+    builder.entries(4).submit_wait_nr(8);
+    builder.start(async {
+        let file = Rc::new(File::create(tempfile.path()).await.unwrap());
+
+        for _ in 0..20 {
+            let writes: Vec<_> = (0..64u64)
+                .map(|i| {
+                    let file = file.clone();
+                    tokio_uring::spawn(async move {
+                        let (res, _) = file.write_at(vec![i as u8; 16], i * 16).await;
+                        res.unwrap()
+                    })
+                })
+                .collect();
+
+            for write in writes {
+                assert_eq!(write.await.unwrap(), 16);
+            }
+        }
+
+        // The sleeps outlast the wait for the batch, which the signals
+        // interrupt
+        for _ in 0..20 {
+            let start = Instant::now();
+            let sleeps: Vec<_> = (0..8)
+                .map(|_| tokio_uring::spawn(tokio_uring::time::sleep(Duration::from_millis(2))))
+                .collect();
+
+            for sleep in sleeps {
+                sleep.await.unwrap();
+            }
+            assert!(start.elapsed() >= Duration::from_millis(2));
+        }
+    });
+
+    done.store(true, Ordering::Relaxed);
+    signaler.join().unwrap();
+    assert!(SIGNALS.load(Ordering::Relaxed) > 0);
+
+    let contents = std::fs::read(tempfile.path()).unwrap();
+    assert_eq!(contents.len(), 64 * 16);
+    assert!(contents
+        .chunks(16)
+        .enumerate()
+        .all(|(i, chunk)| chunk.iter().all(|&b| b == i as u8)));
+}
+
+#[test]
+fn submissions_outpacing_completions() {
+    use std::rc::Rc;
+
+    let tempfile = tempfile();
+    std::fs::write(tempfile.path(), [7; 4096]).unwrap();
+
+    // Far more operations in flight than submission queue entries
+    tokio_uring::builder().entries(2).start(async {
+        let file = Rc::new(File::open(tempfile.path()).await.unwrap());
+
+        let reads: Vec<_> = (0..4096u64)
+            .map(|i| {
+                let file = file.clone();
+                tokio_uring::spawn(async move {
+                    let (res, buf) = file.read_at(Vec::with_capacity(1), i).await;
+                    assert_eq!(res.unwrap(), 1);
+                    buf[0]
+                })
+            })
+            .collect();
+
+        // Chains wait in the backlog as a whole
+        let chains: Vec<_> = (0..256u64)
+            .map(|i| {
+                let file = file.clone();
+                tokio_uring::spawn(async move {
+                    let mut chain = tokio_uring::Chain::new();
+                    chain.read_at(&file, Vec::with_capacity(1), i).read_at(
+                        &file,
+                        Vec::with_capacity(1),
+                        i + 1,
+                    );
+                    let output = chain.submit().await;
+                    assert!(output.results().iter().all(|r| *r.as_ref().unwrap() == 1));
+                })
+            })
+            .collect();
+
+        // Each entry of a batch waits in the backlog on its own
+        let peer = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        let peer_addr = peer.local_addr().unwrap();
+        let socket = tokio_uring::net::UdpSocket::bind("127.0.0.1:0".parse().unwrap())
+            .await
+            .unwrap();
+        let batch = tokio_uring::spawn(async move {
+            socket
+                .send_to_batch((0..64).map(|_| (b"batch".to_vec(), peer_addr)))
+                .await
+        });
+
+        for read in reads {
+            assert_eq!(read.await.unwrap(), 7);
+        }
+        for chain in chains {
+            chain.await.unwrap();
+        }
+        for (res, _) in batch.await.unwrap() {
+            assert_eq!(res.unwrap(), 5);
+        }
+    });
+}
+
+#[test]
+fn submit_raw() {
+    use io_uring::{opcode, types};
+    use std::io::Write;
+    use std::os::unix::io::AsRawFd;
+
+    let mut rt = tokio_uring::builder().build().unwrap();
+
+    // Submitted before the runtime runs
+    let nop = unsafe { rt.submit_raw(|sqe| *sqe = opcode::Nop::new().build()) };
+
+    rt.block_on(async {
+        assert_eq!(nop.await, 0);
+
+        let mut tempfile = tempfile();
+        tempfile.write_all(b"hello raw").unwrap();
+        let fd = tempfile.as_raw_fd();
+
+        let mut buf = vec![0u8; 16];
+        let ptr = buf.as_mut_ptr();
+        let res = unsafe {
+            tokio_uring::submit_raw(|sqe| {
+                *sqe = opcode::Read::new(types::Fd(fd), ptr, 16).offset(0).build()
+            })
+        }
+        .await;
+        assert_eq!(res, 9);
+        assert_eq!(&buf[..9], b"hello raw");
+
+        // Errors are reported as negated errno values
+        let res = unsafe {
+            tokio_uring::submit_raw(|sqe| *sqe = opcode::Read::new(types::Fd(-1), ptr, 16).build())
+        }
+        .await;
+        assert_eq!(res, -libc::EBADF);
+    });
+}
@@ -0,0 +1,49 @@
+use tokio_uring::EventFd;
+
+#[test]
+fn wake_from_thread() {
+    tokio_uring::start(async {
+        let eventfd = EventFd::new(0).unwrap();
+        let writer = eventfd.writer().unwrap();
+
+        let producer = std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(20));
+            writer.write(2).unwrap();
+            writer.try_clone().unwrap().write(3).unwrap();
+        });
+
+        // The writes may be coalesced into a single read
+        let mut total = eventfd.read().await.unwrap();
+        if total < 5 {
+            total += eventfd.read().await.unwrap();
+        }
+        assert_eq!(total, 5);
+        producer.join().unwrap();
+    });
+}
+
+#[test]
+fn semaphore() {
+    tokio_uring::start(async {
+        let eventfd = EventFd::new_semaphore(2).unwrap();
+        eventfd.write(1).await.unwrap();
+
+        for _ in 0..3 {
+            assert_eq!(eventfd.read().await.unwrap(), 1);
+        }
+    });
+}
+
+#[test]
+fn overflow() {
+    tokio_uring::start(async {
+        let eventfd = EventFd::new(0).unwrap();
+        let err = eventfd.write(u64::MAX).await.unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+
+        let writer = eventfd.writer().unwrap();
+        writer.write(u64::MAX - 1).unwrap();
+        let err = writer.write(1).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::WouldBlock);
+    });
+}
@@ -1,11 +1,11 @@
 use std::{
-    io::prelude::*,
+    io::{prelude::*, SeekFrom},
     os::unix::io::{AsRawFd, FromRawFd, RawFd},
 };
 
 use tempfile::NamedTempFile;
 
-use tokio_uring::fs::File;
+use tokio_uring::fs::{BufReader, BufWriter, File, OpenOptions};
 
 #[path = "../src/future.rs"]
 #[allow(warnings)]
@@ -81,7 +81,7 @@ fn explicit_close() {
 fn drop_open() {
     tokio_uring::start(async {
         let tempfile = tempfile();
-        let _ = File::create(tempfile.path());
+        drop(File::create(tempfile.path()));
 
         // Do something else
         let file = File::create(tempfile.path()).await.unwrap();
@@ -142,7 +142,8 @@ async fn poll_once(future: impl std::future::Future) {
 fn assert_invalid_fd(fd: RawFd) {
     use std::fs::File;
 
-    let mut f = unsafe { File::from_raw_fd(fd) };
+    // The fd is already closed, make sure dropping `f` does not close it again.
+    let mut f = std::mem::ManuallyDrop::new(unsafe { File::from_raw_fd(fd) });
     let mut buf = vec![];
 
     match f.read_to_end(&mut buf) {
@@ -150,3 +151,523 @@ fn assert_invalid_fd(fd: RawFd) {
         res => panic!("{:?}", res),
     }
 }
+
+#[test]
+fn chain_write_sync_read() {
+    tokio_uring::start(async {
+        let tempfile = tempfile();
+        let file = File::create(tempfile.path()).await.unwrap();
+
+        let mut chain = tokio_uring::Chain::new();
+        chain
+            .write_at(&file, HELLO.to_vec(), 0)
+            .sync_all(&file)
+            .write_at(&file, HELLO.to_vec(), HELLO.len() as u64);
+        let mut output = chain.submit().await;
+
+        let results: Vec<usize> = output
+            .results()
+            .iter()
+            .map(|r| *r.as_ref().unwrap())
+            .collect();
+        assert_eq!(results, [HELLO.len(), 0, HELLO.len()]);
+        assert!(output.take_buf::<String>(0).is_none());
+        assert_eq!(output.take_buf::<Vec<u8>>(0).unwrap(), HELLO);
+        assert!(output.take_buf::<Vec<u8>>(1).is_none());
+
+        let file = std::fs::read(tempfile.path()).unwrap();
+        assert_eq!(file, [HELLO, HELLO].concat());
+    });
+}
+
+#[test]
+fn chain_links() {
+    tokio_uring::start(async {
+        let mut tempfile = tempfile();
+        tempfile.write_all(HELLO).unwrap();
+        let file = File::open(tempfile.path()).await.unwrap();
+
+        // Writing to a read-only file breaks a soft link
+        let mut chain = tokio_uring::Chain::new();
+        chain
+            .write_at(&file, HELLO.to_vec(), 0)
+            .read_at(&file, Vec::with_capacity(HELLO.len()), 0);
+        let mut output = chain.submit().await;
+        assert_eq!(
+            output.results()[0].as_ref().unwrap_err().raw_os_error(),
+            Some(libc::EBADF)
+        );
+        assert_eq!(
+            output.results()[1].as_ref().unwrap_err().raw_os_error(),
+            Some(libc::ECANCELED)
+        );
+        assert!(output.take_buf::<Vec<u8>>(1).unwrap().is_empty());
+
+        // A hard link keeps going
+        let mut chain = tokio_uring::Chain::new();
+        chain
+            .hard_links()
+            .write_at(&file, HELLO.to_vec(), 0)
+            .read_at(&file, Vec::with_capacity(HELLO.len()), 0);
+        let mut output = chain.submit().await;
+        assert!(output.results()[0].is_err());
+        assert_eq!(*output.results()[1].as_ref().unwrap(), HELLO.len());
+        assert_eq!(output.take_buf::<Vec<u8>>(1).unwrap(), HELLO);
+    });
+}
+
+#[test]
+fn chain_open_at() {
+    tokio_uring::start(async {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("header");
+        let slot = tokio_uring::reserve_fd().unwrap();
+
+        // Open, write a header, and sync, in a single submission
+        let mut chain = tokio_uring::Chain::new();
+        chain
+            .open_at(
+                &path,
+                OpenOptions::new().read(true).write(true).create(true),
+                slot,
+            )
+            .write_at_slot(slot, HELLO.to_vec(), 0)
+            .sync_all_slot(slot)
+            .read_at_slot(slot, Vec::with_capacity(HELLO.len()), 0);
+        let mut output = chain.submit().await;
+
+        let results: Vec<usize> = output
+            .results()
+            .iter()
+            .map(|r| *r.as_ref().unwrap())
+            .collect();
+        assert_eq!(results, [0, HELLO.len(), 0, HELLO.len()]);
+        assert_eq!(output.take_buf::<Vec<u8>>(3).unwrap(), HELLO);
+        assert_eq!(std::fs::read(&path).unwrap(), HELLO);
+
+        tokio_uring::unregister_fd(slot).unwrap();
+
+        // An open failing to be prepared fails the chain
+        let mut chain = tokio_uring::Chain::new();
+        chain
+            .open_at("nul\0byte", OpenOptions::new().read(true), slot)
+            .sync_all_slot(slot);
+        let output = chain.submit().await;
+        assert_eq!(output.results().len(), 2);
+        assert_eq!(
+            output.results()[0].as_ref().unwrap_err().kind(),
+            std::io::ErrorKind::InvalidInput
+        );
+        assert_eq!(
+            output.results()[1].as_ref().unwrap_err().raw_os_error(),
+            Some(libc::ECANCELED)
+        );
+    });
+}
+
+#[cfg(feature = "bytes")]
+#[test]
+fn bytes_write_read() {
+    use bytes::{Bytes, BytesMut};
+    use tokio_uring::buf::IoBuf;
+
+    tokio_uring::start(async {
+        let tempfile = tempfile();
+        let file = File::create(tempfile.path()).await.unwrap();
+
+        let (res, buf) = file.write_at(Bytes::from_static(HELLO), 0).await;
+        assert_eq!(res.unwrap(), HELLO.len());
+        assert_eq!(buf, HELLO);
+
+        // Read into the spare capacity of a partially filled buffer
+        let file = File::open(tempfile.path()).await.unwrap();
+        let mut buf = BytesMut::with_capacity(64);
+        buf.extend_from_slice(b">> ");
+        let (res, buf) = file.read_at(buf.slice(3..), 0).await;
+        assert_eq!(res.unwrap(), HELLO.len());
+
+        let buf = buf.into_inner();
+        assert_eq!(&buf[..3], b">> ");
+        assert_eq!(&buf[3..], HELLO);
+    });
+}
+
+#[test]
+fn buf_result_combinators() {
+    use tokio_uring::buf::{IoBuf, Slice};
+    use tokio_uring::BufResultExt;
+
+    tokio_uring::start(async {
+        let mut tempfile = tempfile();
+        tempfile.write_all(HELLO).unwrap();
+        let file = File::open(tempfile.path()).await.unwrap();
+
+        let (res, buf) = file
+            .read_at(vec![0; 5].slice(..), 0)
+            .await
+            .map_buf(Slice::into_inner)
+            .and_then_buf(|n, buf| (Ok(buf[..n].to_vec()), buf))
+            .map_ok(String::from_utf8);
+        assert_eq!(res.unwrap().unwrap(), "hello");
+        assert_eq!(buf, b"hello");
+
+        let res = file
+            .read_at(Vec::with_capacity(4), 100)
+            .await
+            .into_result_dropping_buf();
+        assert_eq!(res.unwrap(), 0);
+    });
+}
+
+#[test]
+fn sync_file_range() {
+    tokio_uring::start(async {
+        let tempfile = tempfile();
+        let file = File::create(tempfile.path()).await.unwrap();
+
+        let (res, _) = file.write_at(vec![1; 8192], 0).await;
+        res.unwrap();
+
+        let flags = libc::SYNC_FILE_RANGE_WAIT_BEFORE
+            | libc::SYNC_FILE_RANGE_WRITE
+            | libc::SYNC_FILE_RANGE_WAIT_AFTER;
+        file.sync_file_range(0, 4096, flags).await.unwrap();
+        file.sync_file_range(4096, 0, flags).await.unwrap();
+
+        // Invalid flags are reported from the completion
+        let err = file.sync_file_range(0, 0, 1 << 8).await.unwrap_err();
+        assert_eq!(err.raw_os_error(), Some(libc::EINVAL));
+    });
+}
+
+#[test]
+fn fallocate() {
+    tokio_uring::start(async {
+        let tempfile = tempfile();
+        let file = File::create(tempfile.path()).await.unwrap();
+
+        file.fallocate(0, 16384, 0).await.unwrap();
+        assert_eq!(tempfile.as_file().metadata().unwrap().len(), 16384);
+
+        let (res, _) = file.write_at(vec![1; 16384], 0).await;
+        res.unwrap();
+
+        // Invalid combinations are rejected up front
+        let err = file.fallocate(0, 0, 0).await.unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+        let err = file
+            .fallocate(0, 4096, libc::FALLOC_FL_PUNCH_HOLE)
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+
+        // Punching a hole zeroes the range, keeping the size
+        let mode = libc::FALLOC_FL_PUNCH_HOLE | libc::FALLOC_FL_KEEP_SIZE;
+        match file.fallocate(4096, 4096, mode).await {
+            Err(e) if e.kind() == std::io::ErrorKind::Unsupported => return,
+            res => res.unwrap(),
+        }
+        let contents = std::fs::read(tempfile.path()).unwrap();
+        assert_eq!(contents.len(), 16384);
+        assert!(contents[..4096].iter().all(|&b| b == 1));
+        assert!(contents[4096..8192].iter().all(|&b| b == 0));
+        assert!(contents[8192..].iter().all(|&b| b == 1));
+    });
+}
+
+#[test]
+fn statx() {
+    tokio_uring::start(async {
+        let mut tempfile = tempfile();
+        tempfile.write_all(HELLO).unwrap();
+        let std_metadata = tempfile.as_file().metadata().unwrap();
+
+        let file = File::open(tempfile.path()).await.unwrap();
+        let statx = file.statx().await.unwrap();
+        assert_eq!(statx.len(), HELLO.len() as u64);
+        assert!(statx.is_file());
+        assert!(!statx.is_dir());
+        assert_eq!(statx.modified().unwrap(), std_metadata.modified().unwrap());
+
+        let by_path = tokio_uring::fs::statx(tempfile.path()).await.unwrap();
+        assert_eq!(by_path.ino(), statx.ino());
+        assert_eq!(by_path.blocks(), statx.blocks());
+
+        let dir = tokio_uring::fs::statx(std::env::temp_dir()).await.unwrap();
+        assert!(dir.is_dir());
+
+        // Only the requested fields are guaranteed
+        let size = file.statx_with_mask(libc::STATX_SIZE).await.unwrap();
+        assert_ne!(size.mask() & libc::STATX_SIZE, 0);
+        assert_eq!(size.len(), HELLO.len() as u64);
+
+        let err = tokio_uring::fs::statx("/does/not/exist").await.unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+    });
+}
+
+#[test]
+fn copy_file_range() {
+    use tokio_uring::fs;
+
+    tokio_uring::start(async {
+        let data: Vec<u8> = (0..256 * 1024).map(|i| i as u8).collect();
+        let mut src_file = tempfile();
+        src_file.write_all(&data).unwrap();
+        let dst_file = tempfile();
+
+        let src = File::open(src_file.path()).await.unwrap();
+        let dst = File::create(dst_file.path()).await.unwrap();
+
+        // Copy all but the first 100 bytes, in chunks, past a 10 byte gap
+        let mut off = 0;
+        loop {
+            let n = fs::copy_file_range(&src, 100 + off, &dst, 10 + off, 64 * 1024)
+                .await
+                .unwrap();
+            if n == 0 {
+                break;
+            }
+            off += n as u64;
+        }
+        assert_eq!(off, data.len() as u64 - 100);
+
+        let copied = std::fs::read(dst_file.path()).unwrap();
+        assert_eq!(&copied[..10], &[0; 10]);
+        assert_eq!(&copied[10..], &data[100..]);
+    });
+}
+
+#[test]
+fn fadvise() {
+    use tokio_uring::fs::Advice;
+
+    tokio_uring::start(async {
+        let mut tempfile = tempfile();
+        tempfile.write_all(HELLO).unwrap();
+        let file = File::open(tempfile.path()).await.unwrap();
+
+        for advice in [
+            Advice::Sequential,
+            Advice::WillNeed,
+            Advice::Random,
+            Advice::NoReuse,
+            Advice::DontNeed,
+            Advice::Normal,
+        ] {
+            file.fadvise(0, 0, advice).await.unwrap();
+        }
+        read_hello(&file).await;
+    });
+}
+
+#[test]
+fn open_at_beneath() {
+    use tokio_uring::fs::{OpenOptions, ResolveFlags};
+
+    tokio_uring::start(async {
+        let root = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir(root.path().join("sub")).unwrap();
+        std::fs::write(root.path().join("sub/inner"), HELLO).unwrap();
+        std::os::unix::fs::symlink("/etc/hostname", root.path().join("escape")).unwrap();
+
+        let dir = File::open(root.path()).await.unwrap();
+        let file = File::open_at(&dir, "sub/inner").await.unwrap();
+        read_hello(&file).await;
+
+        let mut beneath = OpenOptions::new();
+        beneath.read(true).resolve(ResolveFlags::BENEATH);
+        let file = beneath.open_at(&dir, "sub/../sub/inner").await.unwrap();
+        read_hello(&file).await;
+
+        // Paths resolving outside of the directory are rejected
+        for path in ["../etc/hostname", "escape", "/etc/hostname"] {
+            let err = beneath.open_at(&dir, path).await.unwrap_err();
+            assert_eq!(err.raw_os_error(), Some(libc::EXDEV), "{}", path);
+        }
+
+        // Files can be created beneath the directory
+        let mut create = OpenOptions::new();
+        create
+            .write(true)
+            .create_new(true)
+            .resolve(ResolveFlags::BENEATH);
+        create.open_at(&dir, "sub/new").await.unwrap();
+        assert!(root.path().join("sub/new").exists());
+    });
+}
+
+#[test]
+fn set_len() {
+    tokio_uring::start(async {
+        let mut tempfile = tempfile();
+        tempfile.write_all(HELLO).unwrap();
+        let file = OpenOptions::new()
+            .write(true)
+            .open(tempfile.path())
+            .await
+            .unwrap();
+
+        // Growing leaves zeros past the data
+        file.set_len(64).await.unwrap();
+        let contents = std::fs::read(tempfile.path()).unwrap();
+        assert_eq!(contents.len(), 64);
+        assert_eq!(&contents[..HELLO.len()], HELLO);
+        assert!(contents[HELLO.len()..].iter().all(|&b| b == 0));
+
+        // Shrinking discards the data
+        file.set_len(5).await.unwrap();
+        assert_eq!(std::fs::read(tempfile.path()).unwrap(), b"hello");
+
+        let read_only = File::open(tempfile.path()).await.unwrap();
+        assert!(read_only.set_len(0).await.is_err());
+    });
+}
+
+#[test]
+fn writev_readv_at() {
+    tokio_uring::start(async {
+        let tempfile = tempfile();
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(tempfile.path())
+            .await
+            .unwrap();
+
+        let (res, bufs) = file
+            .writev_at(vec![b"hello ".to_vec(), b"world...".to_vec()], 4)
+            .await;
+        assert_eq!(res.unwrap(), 14);
+        assert_eq!(bufs.len(), 2);
+
+        let bufs = [
+            Vec::with_capacity(4),
+            Vec::with_capacity(6),
+            Vec::with_capacity(64),
+        ];
+        let (res, [a, b, c]) = file.readv_at(bufs, 0).await;
+        assert_eq!(res.unwrap(), 18);
+        assert_eq!(a, [0; 4]);
+        assert_eq!(b, b"hello ");
+        assert_eq!(c, b"world...");
+
+        // Too many buffers fail without writing
+        let bufs: Vec<_> = (0..1025).map(|_| b"x".as_slice()).collect();
+        let (res, bufs) = file.writev_at(bufs, 0).await;
+        assert_eq!(res.unwrap_err().kind(), std::io::ErrorKind::InvalidInput);
+        assert_eq!(bufs.len(), 1025);
+        assert_eq!(tempfile.as_file().metadata().unwrap().len(), 18);
+    });
+}
+
+#[test]
+fn buf_reader_lines_and_seek() {
+    tokio_uring::start(async {
+        let mut tempfile = tempfile();
+        tempfile.write_all(b"first\nsecond line\nlast").unwrap();
+
+        let file = File::open(tempfile.path()).await.unwrap();
+        let mut reader = BufReader::with_capacity(4, file);
+
+        let mut line = String::new();
+        assert_eq!(reader.read_line(&mut line).await.unwrap(), 6);
+        assert_eq!(line, "first\n");
+        assert_eq!(reader.position(), 6);
+
+        let mut record = Vec::new();
+        reader.read_until(b' ', &mut record).await.unwrap();
+        assert_eq!(record, b"second ");
+
+        let mut buf = [0; 8];
+        assert_eq!(reader.read(&mut buf).await.unwrap(), 3);
+        assert_eq!(&buf[..3], b"lin");
+        assert_eq!(reader.fill_buf().await.unwrap(), b"e\nla");
+
+        // Seeking within the buffer keeps it
+        assert_eq!(reader.seek(SeekFrom::Current(1)).await.unwrap(), 17);
+        assert_eq!(reader.buffer(), b"\nla");
+
+        assert_eq!(reader.seek(SeekFrom::End(-4)).await.unwrap(), 18);
+        line.clear();
+        assert_eq!(reader.read_line(&mut line).await.unwrap(), 4);
+        assert_eq!(line, "last");
+        assert_eq!(reader.read_line(&mut line).await.unwrap(), 0);
+
+        let err = reader.seek(SeekFrom::Current(-100)).await.unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    });
+}
+
+#[test]
+fn buf_writer_flush_and_seek() {
+    tokio_uring::start(async {
+        let tempfile = tempfile();
+        let file = File::create(tempfile.path()).await.unwrap();
+        let mut writer = BufWriter::with_capacity(8, file);
+
+        writer.write_all(b"hello").await.unwrap();
+        assert_eq!(writer.buffer(), b"hello");
+        assert_eq!(std::fs::read(tempfile.path()).unwrap(), b"");
+
+        writer.write_all(b" world, and more").await.unwrap();
+        assert_eq!(writer.position(), 21);
+        assert_eq!(std::fs::read(tempfile.path()).unwrap(), b"hello world, and");
+
+        writer.seek(SeekFrom::Start(0)).await.unwrap();
+        writer.write_all(b"H").await.unwrap();
+        assert_eq!(writer.seek(SeekFrom::End(0)).await.unwrap(), 21);
+        writer.write_all(b"!").await.unwrap();
+
+        let file = writer.into_inner().await.unwrap();
+        file.close().await.unwrap();
+        assert_eq!(
+            std::fs::read(tempfile.path()).unwrap(),
+            b"Hello world, and more!"
+        );
+    });
+}
+
+#[test]
+fn read_write_at_file_position() {
+    tokio_uring::start(async {
+        let tempfile = tempfile();
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(tempfile.path())
+            .await
+            .unwrap();
+
+        let (res, _) = file.write(b"hello ".as_slice()).await;
+        assert_eq!(res.unwrap(), 6);
+
+        // Positional writes leave the position alone
+        let (res, _) = file.write_at(b"!".as_slice(), 11).await;
+        res.unwrap();
+
+        let (res, _) = file.write(b"world".as_slice()).await;
+        assert_eq!(res.unwrap(), 5);
+        assert_eq!(std::fs::read(tempfile.path()).unwrap(), b"hello world!");
+
+        let pos = unsafe { libc::lseek(file.as_raw_fd(), 0, libc::SEEK_CUR) };
+        assert_eq!(pos, 11);
+        unsafe { libc::lseek(file.as_raw_fd(), 0, libc::SEEK_SET) };
+
+        // Sequential reads resume where the previous one stopped
+        let (res, buf) = file.read(Vec::with_capacity(6)).await;
+        assert_eq!(res.unwrap(), 6);
+        assert_eq!(buf, b"hello ");
+        let (res, buf) = file.read(Vec::with_capacity(16)).await;
+        assert_eq!(res.unwrap(), 6);
+        assert_eq!(buf, b"world!");
+        let (res, _) = file.read(Vec::with_capacity(16)).await;
+        assert_eq!(res.unwrap(), 0);
+
+        // The offset standing for the file position is rejected
+        let (res, _) = file.read_at(Vec::with_capacity(4), u64::MAX).await;
+        assert_eq!(res.unwrap_err().kind(), std::io::ErrorKind::InvalidInput);
+        let (res, _) = file.write_at(b"x".as_slice(), u64::MAX).await;
+        assert_eq!(res.unwrap_err().kind(), std::io::ErrorKind::InvalidInput);
+    });
+}
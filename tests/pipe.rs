@@ -0,0 +1,67 @@
+use std::{io::Write, os::unix::io::AsRawFd};
+
+#[test]
+fn read_before_write() {
+    tokio_uring::start(async {
+        let (reader, writer) = tokio_uring::pipe().unwrap();
+
+        // The read is pending until the data is written by another task
+        let read = tokio_uring::spawn(async move {
+            let (res, buf) = reader.read(Vec::with_capacity(16)).await;
+            let n = res.unwrap();
+            (buf, n, reader)
+        });
+
+        let (res, _) = writer.write(b"hello".to_vec()).await;
+        assert_eq!(res.unwrap(), 5);
+
+        let (buf, n, reader) = read.await.unwrap();
+        assert_eq!(&buf[..n], b"hello");
+
+        // Closing the write end makes reads return 0
+        writer.close().await.unwrap();
+        let (res, _) = reader.read(Vec::with_capacity(16)).await;
+        assert_eq!(res.unwrap(), 0);
+    });
+}
+
+#[test]
+fn blocking_producer() {
+    tokio_uring::start(async {
+        let (reader, writer) = tokio_uring::pipe().unwrap();
+
+        // A blocking thread writes to a duplicate of the write end
+        let fd = unsafe { libc::dup(writer.as_raw_fd()) };
+        writer.close().await.unwrap();
+        let producer = std::thread::spawn(move || {
+            let mut file =
+                unsafe { <std::fs::File as std::os::unix::io::FromRawFd>::from_raw_fd(fd) };
+            for _ in 0..4 {
+                file.write_all(&[7; 1000]).unwrap();
+            }
+        });
+
+        let mut total = 0;
+        loop {
+            let (res, _) = reader.read(vec![0; 512]).await;
+            match res.unwrap() {
+                0 => break,
+                n => total += n,
+            }
+        }
+        assert_eq!(total, 4000);
+        producer.join().unwrap();
+    });
+}
+
+#[test]
+fn set_capacity() {
+    tokio_uring::start(async {
+        let (reader, writer) = tokio_uring::pipe().unwrap();
+        assert_eq!(writer.capacity().unwrap(), 64 * 1024);
+
+        let capacity = writer.set_capacity(100 * 1024).unwrap();
+        assert!(capacity >= 100 * 1024);
+        assert_eq!(reader.capacity().unwrap(), capacity);
+    });
+}
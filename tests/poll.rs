@@ -0,0 +1,59 @@
+use std::future::poll_fn;
+use std::io::{Read, Write};
+use std::os::unix::io::AsRawFd;
+use std::os::unix::net::UnixStream;
+
+use futures_core::Stream;
+
+#[test]
+fn poll_readable() {
+    tokio_uring::start(async {
+        let (rx, mut tx) = UnixStream::pair().unwrap();
+
+        // Writable right away, readable once data arrives
+        tokio_uring::poll_writable(tx.as_raw_fd()).await.unwrap();
+
+        let readable = tokio_uring::spawn({
+            let fd = rx.as_raw_fd();
+            async move { tokio_uring::poll_readable(fd).await }
+        });
+        tokio::task::yield_now().await;
+        assert!(!readable.is_finished());
+
+        tx.write_all(b"ping").unwrap();
+        readable.await.unwrap().unwrap();
+
+        let revents = tokio_uring::poll(rx.as_raw_fd(), libc::POLLIN | libc::POLLOUT)
+            .await
+            .unwrap();
+        assert_ne!(revents & libc::POLLIN, 0);
+    });
+}
+
+#[test]
+fn poll_multishot() {
+    tokio_uring::start(async {
+        let (rx, mut tx) = UnixStream::pair().unwrap();
+        rx.set_nonblocking(true).unwrap();
+
+        let mut ready = Box::pin(tokio_uring::poll_multishot(rx.as_raw_fd(), libc::POLLIN));
+
+        for _ in 0..3 {
+            tx.write_all(b"ping").unwrap();
+            let revents = poll_fn(|cx| ready.as_mut().poll_next(cx))
+                .await
+                .unwrap()
+                .unwrap();
+            assert_ne!(revents & libc::POLLIN, 0);
+
+            // Drain the socket before waiting again
+            let mut buf = [0; 4];
+            assert_eq!((&rx).read(&mut buf).unwrap(), 4);
+        }
+
+        // Dropping the stream removes the poll
+        drop(ready);
+        tokio_uring::time::sleep(std::time::Duration::from_millis(10)).await;
+        assert_eq!(tokio_uring::metrics().ops_in_flight(), 0);
+    });
+}
@@ -34,3 +34,517 @@ fn spawn_a_task() {
         assert_eq!(2, *cell.borrow());
     });
 }
+
+#[test]
+fn join_handle() {
+    use tokio_uring::net::UdpSocket;
+
+    tokio_uring::start(async {
+        let handle = tokio_uring::spawn(async { 42 });
+        assert_eq!(handle.await.unwrap(), 42);
+
+        // Panics are caught
+        let handle = tokio_uring::spawn(async { panic!("task panicked") });
+        assert!(handle.await.unwrap_err().is_panic());
+
+        // Aborting drops the task and its in-flight receive
+        let socket = UdpSocket::bind("127.0.0.1:0".parse().unwrap())
+            .await
+            .unwrap();
+        let handle = tokio_uring::spawn(async move { socket.recv_from(vec![0; 1]).await });
+        tokio::task::yield_now().await;
+        handle.abort();
+        assert!(handle.await.unwrap_err().is_cancelled());
+    });
+}
+
+#[test]
+fn sqpoll() {
+    use std::time::Duration;
+    use tokio_uring::net::UdpSocket;
+
+    tokio_uring::builder()
+        .with_sqpoll(Duration::from_millis(10), None)
+        .start(async {
+            let socket = UdpSocket::bind("127.0.0.1:0".parse().unwrap())
+                .await
+                .unwrap();
+            let addr = socket.local_addr().unwrap();
+
+            // Let the polling thread fall asleep, so the next submission
+            // needs to wake it
+            for _ in 0..3 {
+                tokio::time::sleep(Duration::from_millis(20)).await;
+
+                let (res, _) = socket.send_to(b"ping".as_slice(), addr).await;
+                res.unwrap();
+                let (res, buf) = socket.recv_from(vec![0; 4]).await;
+                assert_eq!(&buf[..res.unwrap().0], b"ping");
+            }
+        });
+}
+
+#[test]
+fn builder_ring_sizes() {
+    use tokio_uring::fs::File;
+
+    // Rounded up to powers of two
+    let mut rt = tokio_uring::builder()
+        .entries(1000)
+        .cq_entries(5000)
+        .build()
+        .unwrap();
+
+    rt.block_on(async {
+        let file = std::rc::Rc::new(File::open("Cargo.toml").await.unwrap());
+
+        // More operations in flight than submission queue entries
+        let reads: Vec<_> = (0..2048)
+            .map(|_| {
+                let file = file.clone();
+                tokio_uring::spawn(async move { file.read_at(vec![0; 16], 0).await })
+            })
+            .collect();
+
+        for read in reads {
+            let (res, _) = read.await.unwrap();
+            assert_eq!(res.unwrap(), 16);
+        }
+    });
+
+    // Too large without clamping
+    assert!(tokio_uring::builder().entries(1 << 20).build().is_err());
+    tokio_uring::builder()
+        .entries(1 << 20)
+        .cq_entries(1 << 24)
+        .clamp()
+        .build()
+        .unwrap();
+}
+
+#[test]
+fn cq_overflow() {
+    use tokio_uring::fs::File;
+
+    let mut rt = tokio_uring::builder()
+        .entries(16)
+        .cq_entries(16)
+        .build()
+        .unwrap();
+
+    rt.block_on(async {
+        let file = std::rc::Rc::new(File::open("Cargo.toml").await.unwrap());
+
+        // Submitted before any completion is reaped
+        let reads: Vec<_> = (0..512)
+            .map(|_| {
+                let file = file.clone();
+                tokio_uring::spawn(async move { file.read_at(vec![0; 16], 0).await })
+            })
+            .collect();
+
+        for read in reads {
+            let (res, _) = read.await.unwrap();
+            assert_eq!(res.unwrap(), 16);
+        }
+
+        let metrics = tokio_uring::metrics();
+        assert!(metrics.cq_overflow_flushes() > 0);
+        assert_eq!(metrics.cq_dropped(), 0);
+    });
+
+    assert!(rt.metrics().cq_overflow_flushes() > 0);
+}
+
+#[test]
+fn taskrun_flags() {
+    use tokio_uring::net::{TcpListener, TcpStream};
+
+    let mut rt = tokio_uring::builder()
+        .coop_taskrun()
+        .defer_taskrun()
+        .build()
+        .unwrap();
+
+    rt.block_on(async {
+        let listener = TcpListener::bind("127.0.0.1:0".parse().unwrap()).unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        // Completions completing in the background are still picked up
+        let (stream, (mut accepted, _)) =
+            tokio::try_join!(TcpStream::connect(addr), listener.accept()).unwrap();
+
+        for _ in 0..3 {
+            let read = tokio_uring::spawn(async move {
+                let (res, buf) = accepted.read(vec![0; 4]).await;
+                assert_eq!(&buf[..res.unwrap()], b"ping");
+                accepted
+            });
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+
+            let (res, _) = stream.write(b"ping".as_slice()).await;
+            res.unwrap();
+            accepted = read.await.unwrap();
+        }
+    });
+}
+
+#[test]
+fn shutdown_drains_tasks() {
+    use std::rc::Rc;
+    use std::time::{Duration, Instant};
+    use tokio_uring::net::{TcpListener, TcpStream};
+
+    let buf = Rc::new(());
+
+    let mut rt = tokio_uring::builder()
+        .shutdown_timeout(Duration::from_secs(5))
+        .build()
+        .unwrap();
+
+    let stream = rt.block_on({
+        let buf = buf.clone();
+        async move {
+            let listener = TcpListener::bind("127.0.0.1:0".parse().unwrap()).unwrap();
+            let addr = listener.local_addr().unwrap();
+            let (stream, (accepted, _)) =
+                tokio::try_join!(TcpStream::connect(addr), listener.accept()).unwrap();
+
+            // Never completes on its own, the peer stays open
+            tokio_uring::spawn(async move {
+                let _buf = buf;
+                let _ = accepted.read(vec![0; 8]).await;
+            });
+            tokio::task::yield_now().await;
+            stream
+        }
+    });
+
+    // Shutting down cancels the read instead of waiting out the timeout
+    let start = Instant::now();
+    drop(rt);
+    assert!(start.elapsed() < Duration::from_secs(1));
+    assert_eq!(Rc::strong_count(&buf), 1);
+
+    drop(stream);
+}
+
+#[test]
+fn shutdown_timeout() {
+    use std::future::Future;
+    use std::task::{Context, Waker};
+    use std::time::{Duration, Instant};
+    use tokio_uring::fs::File;
+
+    let mut rt = tokio_uring::builder().build().unwrap();
+
+    rt.block_on(async {
+        let file = File::open("Cargo.toml").await.unwrap();
+
+        // A read whose future outlives the runtime holds the shutdown
+        let mut read = Box::pin(async move { file.read_at(vec![0; 8], 0).await });
+        let mut cx = Context::from_waker(Waker::noop());
+        assert!(read.as_mut().poll(&mut cx).is_pending());
+        std::mem::forget(read);
+    });
+
+    let start = Instant::now();
+    rt.shutdown_timeout(Duration::from_millis(50));
+    assert!(start.elapsed() >= Duration::from_millis(50));
+    assert!(start.elapsed() < Duration::from_secs(5));
+}
+
+#[test]
+fn register_napi() {
+    let mut rt = tokio_uring::builder().build().unwrap();
+
+    if !tokio_uring::probe().unwrap().supports_napi() {
+        let err = rt.register_napi(50, false).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::Unsupported);
+        return;
+    }
+
+    rt.register_napi(50, true).unwrap();
+    rt.block_on(async {
+        let socket = tokio_uring::net::UdpSocket::bind("127.0.0.1:0".parse().unwrap())
+            .await
+            .unwrap();
+        let addr = socket.local_addr().unwrap();
+
+        let (res, _) = socket.send_to(b"ping".as_slice(), addr).await;
+        res.unwrap();
+        let (res, buf) = socket.recv_from(vec![0; 4]).await;
+        assert_eq!(&buf[..res.unwrap().0], b"ping");
+    });
+    rt.unregister_napi().unwrap();
+}
+
+#[test]
+fn submission_metrics() {
+    use tokio_uring::net::UdpSocket;
+
+    let mut rt = tokio_uring::builder().build().unwrap();
+
+    rt.block_on(async {
+        let socket = UdpSocket::bind("127.0.0.1:0".parse().unwrap())
+            .await
+            .unwrap();
+        let addr = socket.local_addr().unwrap();
+        let before = tokio_uring::metrics();
+
+        let sends = tokio_uring::batch(async {
+            tokio::join!(
+                socket.send_to(b"a".as_slice(), addr),
+                socket.send_to(b"b".as_slice(), addr),
+                socket.send_to(b"c".as_slice(), addr),
+            )
+        });
+        let (a, b, c) = sends.await;
+        for (res, _) in [a, b, c] {
+            res.unwrap();
+        }
+
+        let after = tokio_uring::metrics();
+        assert_eq!(after.sqes_submitted() - before.sqes_submitted(), 3);
+        assert_eq!(after.submit_syscalls() - before.submit_syscalls(), 1);
+        assert!(after.cqes_reaped() - before.cqes_reaped() >= 3);
+        assert_eq!(after.ops_in_flight(), 0);
+
+        // A pending receive is in flight
+        let idle = UdpSocket::bind("127.0.0.1:0".parse().unwrap())
+            .await
+            .unwrap();
+        let recv = tokio_uring::spawn(async move { idle.recv_from(vec![0; 1]).await });
+        tokio::task::yield_now().await;
+        assert_eq!(tokio_uring::metrics().ops_in_flight(), 1);
+        recv.abort();
+    });
+}
+
+#[test]
+fn register_buffers() {
+    use tokio_uring::net::{TcpListener, TcpStream};
+
+    let mut rt = tokio_uring::builder().build().unwrap();
+    let bufs = (0..4).map(|_| Vec::with_capacity(1024 * 1024)).collect();
+    let pool = rt.register_buffers(bufs).unwrap();
+    assert_eq!(pool.len(), 4);
+
+    let pool = rt.block_on(async move {
+        let listener = TcpListener::bind("127.0.0.1:0".parse().unwrap()).unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (stream, (peer, _)) =
+            tokio::try_join!(TcpStream::connect(addr), listener.accept()).unwrap();
+
+        let (res, _) = stream.write(b"ping".as_slice()).await;
+        res.unwrap();
+        let (res, buf) = peer.read_fixed(pool.try_next().unwrap()).await;
+        assert_eq!(&buf[..res.unwrap()], b"ping");
+
+        // A checked out buffer keeps the buffers registered
+        let err = pool.clone().unregister().unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::ResourceBusy);
+        drop(buf);
+        pool
+    });
+
+    let bufs = pool.unregister().unwrap();
+    assert_eq!(bufs.len(), 4);
+    assert_eq!(&bufs[0][..], b"ping");
+
+    // The buffers can be registered again
+    rt.register_buffers(bufs).unwrap();
+}
+
+#[test]
+fn register_fd() {
+    use std::os::unix::io::AsRawFd;
+
+    let mut rt = tokio_uring::builder().build().unwrap();
+    let file = tempfile::tempfile().unwrap();
+
+    let slot = rt.register_fd(file.as_raw_fd()).unwrap();
+    let other = rt.register_fd(file.as_raw_fd()).unwrap();
+    rt.unregister_fd(other).unwrap();
+    assert!(rt.unregister_fd(other).is_err());
+
+    // Slots registered outside of `block_on` belong to the runtime
+    rt.block_on(async move {
+        tokio_uring::unregister_fd(slot).unwrap();
+    });
+    assert!(rt.unregister_fd(slot).is_err());
+}
+
+#[test]
+fn register_buf_ring() {
+    use tokio_uring::net::{TcpListener, TcpStream};
+
+    let mut rt = tokio_uring::builder().build().unwrap();
+    let ring = rt.register_buf_ring(4, 16).unwrap();
+    assert_eq!(ring.entries(), 4);
+    assert_eq!(ring.buf_len(), 16);
+
+    rt.block_on(async move {
+        let listener = TcpListener::bind("127.0.0.1:0".parse().unwrap()).unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (stream, (peer, _)) =
+            tokio::try_join!(TcpStream::connect(addr), listener.accept()).unwrap();
+
+        let (res, _) = stream.write(b"ping".as_slice()).await;
+        res.unwrap();
+        let slot = peer.recv_ring(&ring).await.unwrap().unwrap();
+        assert_eq!(&slot[..], b"ping");
+    });
+}
+
+#[test]
+fn submit_wait_nr() {
+    use std::time::{Duration, Instant};
+    use tokio_uring::time::{sleep, timeout};
+
+    let mut rt = tokio_uring::builder().submit_wait_nr(16).build().unwrap();
+    rt.set_submit_wait_nr(8);
+
+    rt.block_on(async {
+        // Completions are reaped in batches
+        let sleeps: Vec<_> = (0..10)
+            .map(|_| tokio_uring::spawn(sleep(Duration::from_millis(5))))
+            .collect();
+        for sleep in sleeps {
+            sleep.await.unwrap();
+        }
+
+        // Waiting for a batch does not hold a lone completion back for long
+        let start = Instant::now();
+        let res = timeout(sleep(Duration::from_secs(10)), Duration::from_millis(10)).await;
+        assert!(res.is_err());
+        assert!(start.elapsed() < Duration::from_secs(1));
+
+        // Idle operations do not hold back the completions of later ones
+        for _ in 0..8 {
+            tokio_uring::spawn(sleep(Duration::from_secs(10)));
+        }
+        sleep(Duration::from_millis(1)).await;
+
+        let before = tokio_uring::metrics();
+        for _ in 0..10 {
+            sleep(Duration::from_millis(1)).await;
+        }
+        let after = tokio_uring::metrics();
+        assert_eq!(after.cqes_reaped() - before.cqes_reaped(), 10);
+        // One call submitting each sleep, none waiting for a batch
+        assert_eq!(after.submit_syscalls() - before.submit_syscalls(), 10);
+
+        tokio_uring::set_submit_wait_nr(1);
+        sleep(Duration::from_millis(1)).await;
+    });
+}
+
+#[test]
+fn multi_thread() {
+    let rt = tokio_uring::Runtime::new_multi_thread(2).unwrap();
+    assert_eq!(rt.workers(), 2);
+
+    let names: Vec<_> = (0..rt.workers())
+        .map(|worker| {
+            rt.spawn_on(worker, || async {
+                // Each worker drives its own ring
+                let (reader, writer) = tokio_uring::pipe().unwrap();
+                let (res, _) = writer.write(b"ping".as_slice()).await;
+                res.unwrap();
+                let (res, buf) = reader.read(vec![0; 4]).await;
+                assert_eq!(&buf[..res.unwrap()], b"ping");
+
+                std::thread::current().name().unwrap().to_string()
+            })
+        })
+        .map(|handle| handle.join().unwrap())
+        .collect();
+    assert_eq!(names, ["tokio-uring-worker-0", "tokio-uring-worker-1"]);
+
+    // Handles can be awaited from another runtime
+    let handle = rt.spawn_on(1, || async { 42 });
+    assert_eq!(tokio_uring::start(handle).unwrap(), 42);
+
+    // Panics surface as join errors
+    let handle = rt.spawn_on(0, || async { panic!("task panicked") });
+    assert!(handle.join().unwrap_err().is_panic());
+
+    // A worker stopping before it spawned a task fails its handle
+    let handle = rt.spawn_on(1, || -> std::future::Ready<()> {
+        panic!("worker panicked")
+    });
+    let err = handle.join().unwrap_err();
+    assert!(err.is_cancelled());
+    assert!(!err.is_panic());
+
+    assert_eq!(
+        tokio_uring::Runtime::new_multi_thread(0)
+            .unwrap_err()
+            .kind(),
+        std::io::ErrorKind::InvalidInput
+    );
+}
+
+#[test]
+fn iowq_max_workers() {
+    let rt = tokio_uring::builder().build().unwrap();
+
+    let (bounded, unbounded) = rt.iowq_max_workers().unwrap();
+    assert!(bounded > 0 && unbounded > 0);
+
+    assert_eq!(rt.set_iowq_max_workers(2, 4).unwrap(), (bounded, unbounded));
+    assert_eq!(rt.iowq_max_workers().unwrap(), (2, 4));
+
+    // Zero leaves a limit unchanged
+    rt.set_iowq_max_workers(0, 8).unwrap();
+    assert_eq!(rt.iowq_max_workers().unwrap(), (2, 8));
+}
+
+#[test]
+fn cq_eventfd() {
+    use std::os::unix::io::AsRawFd;
+    use std::time::Duration;
+
+    fn read_counter(fd: i32) -> Option<u64> {
+        let mut value = 0u64;
+        let n = unsafe { libc::read(fd, (&mut value as *mut u64).cast(), 8) };
+        if n == 8 {
+            Some(value)
+        } else {
+            None
+        }
+    }
+
+    let mut rt = tokio_uring::builder().build().unwrap();
+    let eventfd = rt.register_cq_eventfd().unwrap();
+    let fd = eventfd.as_raw_fd();
+
+    // A single eventfd is signaled
+    let err = rt.register_cq_eventfd().unwrap_err();
+    assert_eq!(err.raw_os_error(), Some(libc::EBUSY));
+
+    rt.block_on(tokio_uring::time::sleep(Duration::from_millis(1)));
+    assert!(read_counter(fd).unwrap() > 0);
+
+    rt.unregister_cq_eventfd().unwrap();
+    rt.block_on(tokio_uring::time::sleep(Duration::from_millis(1)));
+    assert_eq!(read_counter(fd), None);
+
+    // Another eventfd can be registered in its place
+    let eventfd = rt.register_cq_eventfd_async().unwrap();
+    let fd = eventfd.as_raw_fd();
+
+    // Only the completions of io-wq workers are signaled
+    rt.block_on(tokio_uring::time::sleep(Duration::from_millis(1)));
+    assert_eq!(read_counter(fd), None);
+
+    let tempfile = tempfile::NamedTempFile::new().unwrap();
+    rt.block_on(async {
+        let file = tokio_uring::fs::File::create(tempfile.path())
+            .await
+            .unwrap();
+        file.sync_all().await.unwrap();
+    });
+    assert!(read_counter(fd).unwrap() > 0);
+}
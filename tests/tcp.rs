@@ -0,0 +1,949 @@
+use std::future::poll_fn;
+use std::net::SocketAddr;
+use std::pin::pin;
+use std::time::Duration;
+
+use futures_core::Stream;
+
+use tokio_uring::buf::IoBuf;
+use tokio_uring::net::{TcpListener, TcpStream};
+
+#[test]
+fn connect() {
+    tokio_uring::start(async {
+        let listener = TcpListener::bind("127.0.0.1:30200".parse().unwrap()).unwrap();
+
+        let (stream, (accepted, peer)) = tokio::try_join!(
+            TcpStream::connect("127.0.0.1:30200".parse().unwrap()),
+            listener.accept(),
+        )
+        .unwrap();
+        assert_eq!(peer.ip(), std::net::Ipv4Addr::LOCALHOST);
+
+        let (res, _) = stream.write(b"ping".as_slice()).await;
+        assert_eq!(res.unwrap(), 4);
+
+        let (res, buf) = accepted.read(vec![0; 32]).await;
+        let n = res.unwrap();
+        assert_eq!(&buf[..n], b"ping");
+    });
+}
+
+#[test]
+fn connect_refused() {
+    tokio_uring::start(async {
+        // Nothing listens on the port of a socket that is merely bound
+        let socket = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = socket.local_addr().unwrap();
+        drop(socket);
+
+        let err = TcpStream::connect(addr).await.err().unwrap();
+        assert_eq!(err.kind(), std::io::ErrorKind::ConnectionRefused);
+        assert_eq!(err.raw_os_error(), Some(libc::ECONNREFUSED));
+
+        // Not reported as a timeout when a timeout is linked
+        let err = TcpStream::connect_timeout(addr, Duration::from_secs(5))
+            .await
+            .err()
+            .unwrap();
+        assert_eq!(err.kind(), std::io::ErrorKind::ConnectionRefused);
+    });
+}
+
+#[test]
+fn connect_timeout() {
+    tokio_uring::start(async {
+        let listener = TcpListener::bind("127.0.0.1:30201".parse().unwrap()).unwrap();
+        let addr: SocketAddr = "127.0.0.1:30201".parse().unwrap();
+
+        let stream = TcpStream::connect_timeout(addr, Duration::from_secs(5))
+            .await
+            .unwrap();
+        let (accepted, _) = listener.accept().await.unwrap();
+
+        let (res, _) = stream.write(b"ping".as_slice()).await;
+        assert_eq!(res.unwrap(), 4);
+        let (res, buf) = accepted.read(vec![0; 32]).await;
+        assert_eq!(&buf[..res.unwrap()], b"ping");
+    });
+}
+
+#[test]
+fn connect_timeout_expires() {
+    tokio_uring::start(async {
+        // A listener with a full accept queue drops incoming SYNs
+        let listener =
+            socket2::Socket::new(socket2::Domain::IPV4, socket2::Type::STREAM, None).unwrap();
+        let addr: SocketAddr = "127.0.0.1:30202".parse().unwrap();
+        listener.bind(&addr.into()).unwrap();
+        listener.listen(0).unwrap();
+
+        let mut backlog = vec![];
+        let err = loop {
+            match TcpStream::connect_timeout(addr, Duration::from_millis(100)).await {
+                Ok(stream) => backlog.push(stream),
+                Err(e) => break e,
+            }
+            assert!(backlog.len() < 16, "accept queue never filled up");
+        };
+        assert_eq!(err.kind(), std::io::ErrorKind::TimedOut);
+    });
+}
+
+#[test]
+fn accept_multishot() {
+    tokio_uring::start(async {
+        let listener = TcpListener::bind("127.0.0.1:30203".parse().unwrap()).unwrap();
+        let mut incoming = pin!(listener.accept_multishot());
+
+        let clients: Vec<_> = (0..4)
+            .map(|_| std::net::TcpStream::connect("127.0.0.1:30203").unwrap())
+            .collect();
+
+        for (i, client) in clients.iter().enumerate() {
+            let (stream, peer) = poll_fn(|cx| incoming.as_mut().poll_next(cx))
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(peer, client.local_addr().unwrap());
+
+            let (res, _) = stream.write(vec![i as u8; 4]).await;
+            assert_eq!(res.unwrap(), 4);
+        }
+
+        for (i, mut client) in clients.into_iter().enumerate() {
+            let mut buf = [0; 4];
+            std::io::Read::read_exact(&mut client, &mut buf).unwrap();
+            assert_eq!(buf, [i as u8; 4]);
+        }
+    });
+}
+
+async fn connected_pair(port: u16) -> (TcpStream, TcpStream) {
+    let listener = TcpListener::bind(format!("127.0.0.1:{}", port).parse().unwrap()).unwrap();
+
+    let (stream, (accepted, _)) = tokio::try_join!(
+        TcpStream::connect(format!("127.0.0.1:{}", port).parse().unwrap()),
+        listener.accept(),
+    )
+    .unwrap();
+
+    (stream, accepted)
+}
+
+#[test]
+fn writev_readv() {
+    tokio_uring::start(async {
+        let (stream, peer) = connected_pair(30204).await;
+
+        let header = b"header".to_vec();
+        let body = vec![7; 1000];
+        let (res, bufs) = stream.writev(vec![header, body]).await;
+        assert_eq!(res.unwrap(), 1006);
+        assert_eq!(bufs[0], b"header");
+        assert_eq!(bufs[1], vec![7; 1000]);
+
+        let mut received = vec![];
+        while received.len() < 1006 {
+            let bufs = vec![Vec::with_capacity(6), Vec::with_capacity(2000)];
+            let (res, bufs) = peer.readv(bufs).await;
+            let n = res.unwrap();
+            assert!(n > 0);
+            assert_eq!(bufs[0].len() + bufs[1].len(), n);
+            received.extend(bufs.into_iter().flatten());
+        }
+
+        assert_eq!(&received[..6], b"header");
+        assert!(received[6..].iter().all(|&b| b == 7));
+    });
+}
+
+#[test]
+fn shutdown() {
+    use std::net::Shutdown;
+
+    tokio_uring::start(async {
+        let (stream, peer) = connected_pair(30205).await;
+
+        // Half-close, the peer reads end-of-file but can still reply
+        let (res, _) = stream.write(b"request".as_slice()).await;
+        assert_eq!(res.unwrap(), 7);
+        stream.shutdown(Shutdown::Write).unwrap();
+
+        let (res, buf) = peer.read(vec![0; 32]).await;
+        assert_eq!(&buf[..res.unwrap()], b"request");
+        let (res, _) = peer.read(vec![0; 32]).await;
+        assert_eq!(res.unwrap(), 0);
+
+        let (res, _) = peer.write(b"response".as_slice()).await;
+        assert_eq!(res.unwrap(), 8);
+        let (res, buf) = stream.read(vec![0; 32]).await;
+        assert_eq!(&buf[..res.unwrap()], b"response");
+
+        let (res, _) = stream.write(b"more".as_slice()).await;
+        assert_eq!(res.unwrap_err().kind(), std::io::ErrorKind::BrokenPipe);
+
+        // Shutting down the read half makes reads return end-of-file
+        peer.shutdown(Shutdown::Read).unwrap();
+        let (res, _) = peer.read(vec![0; 32]).await;
+        assert_eq!(res.unwrap(), 0);
+
+        peer.shutdown(Shutdown::Both).unwrap();
+    });
+}
+
+#[test]
+fn nodelay_and_keepalive() {
+    use tokio_uring::net::TcpKeepalive;
+
+    tokio_uring::start(async {
+        let (stream, _peer) = connected_pair(30206).await;
+
+        assert!(!stream.nodelay().unwrap());
+        stream.set_nodelay(true).unwrap();
+        assert!(stream.nodelay().unwrap());
+        stream.set_nodelay(false).unwrap();
+        assert!(!stream.nodelay().unwrap());
+
+        assert!(!stream.keepalive().unwrap());
+        let params = TcpKeepalive::new()
+            .with_time(Duration::from_secs(30))
+            .with_interval(Duration::from_secs(5))
+            .with_retries(4);
+        stream.set_keepalive(params).unwrap();
+        assert!(stream.keepalive().unwrap());
+
+        // Out of range parameters are rejected
+        let params = TcpKeepalive::new().with_time(Duration::from_secs(u64::MAX));
+        let err = stream.set_keepalive(params).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    });
+}
+
+#[test]
+fn splice_file_to_stream_and_back() {
+    use std::io::{Read, Write};
+    use tokio_uring::fs::File;
+
+    // Larger than the default pipe buffer, to take several calls
+    let data: Vec<u8> = (0..200_000u32).map(|i| i as u8).collect();
+
+    let mut src = tempfile::NamedTempFile::new().unwrap();
+    src.write_all(&data).unwrap();
+    let mut dst = tempfile::NamedTempFile::new().unwrap();
+
+    tokio_uring::start(async {
+        let (stream, peer) = connected_pair(30207).await;
+        let src_file = File::open(src.path()).await.unwrap();
+        let dst_file = File::create(dst.path()).await.unwrap();
+
+        let send = async {
+            let mut sent = 0;
+            loop {
+                let n = tokio_uring::splice(&src_file, &stream, 64 * 1024)
+                    .await
+                    .unwrap();
+                if n == 0 {
+                    break;
+                }
+                sent += n;
+            }
+            stream.shutdown(std::net::Shutdown::Write).unwrap();
+            sent
+        };
+
+        let recv = async {
+            let mut received = 0;
+            loop {
+                let n = tokio_uring::splice_to_file(&peer, &dst_file, 1024 * 1024)
+                    .await
+                    .unwrap();
+                if n == 0 {
+                    break;
+                }
+                received += n;
+            }
+            received
+        };
+
+        let (sent, received) = tokio::join!(send, recv);
+        assert_eq!(sent, data.len());
+        assert_eq!(received, data.len());
+    });
+
+    let mut copied = Vec::new();
+    dst.read_to_end(&mut copied).unwrap();
+    assert_eq!(copied, data);
+}
+
+#[test]
+fn peek() {
+    tokio_uring::start(async {
+        let (stream, peer) = connected_pair(30208).await;
+
+        let (res, _) = stream.write(b"hello".to_vec()).await;
+        assert_eq!(res.unwrap(), 5);
+
+        let (res, buf) = peer.peek(vec![0; 2]).await;
+        assert_eq!(res.unwrap(), 2);
+        assert_eq!(&buf[..], b"he");
+
+        // The peeked bytes are still there
+        let (res, buf) = peer.read(vec![0; 32]).await;
+        let n = res.unwrap();
+        assert_eq!(&buf[..n], b"hello");
+    });
+}
+
+#[test]
+fn listener_from_std() {
+    tokio_uring::start(async {
+        let std_listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = std_listener.local_addr().unwrap();
+
+        let listener = TcpListener::from_std(std_listener).unwrap();
+        assert_eq!(listener.local_addr().unwrap(), addr);
+
+        let (stream, (accepted, _)) =
+            tokio::try_join!(TcpStream::connect(addr), listener.accept()).unwrap();
+        let (res, _) = stream.write(b"ping".as_slice()).await;
+        res.unwrap();
+        let (res, buf) = accepted.read(vec![0; 4]).await;
+        assert_eq!(&buf[..res.unwrap()], b"ping");
+
+        let std_listener = listener.into_std().unwrap();
+        assert_eq!(std_listener.local_addr().unwrap(), addr);
+    });
+}
+
+#[test]
+fn listener_from_std_not_listening() {
+    tokio_uring::start(async {
+        let socket =
+            socket2::Socket::new(socket2::Domain::IPV4, socket2::Type::STREAM, None).unwrap();
+        let err = TcpListener::from_std(socket.into()).err().unwrap();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    });
+}
+
+#[test]
+fn read_write_fixed() {
+    use tokio_uring::buf::FixedBufPool;
+
+    tokio_uring::start(async {
+        let (stream, peer) = connected_pair(30209).await;
+
+        let bufs = (0..2).map(|_| Vec::with_capacity(64)).collect();
+        let pool = FixedBufPool::register(bufs).unwrap();
+        assert_eq!(pool.len(), 2);
+
+        // A buffer cannot be checked out twice
+        let buf = pool.check_out(0).unwrap();
+        assert!(pool.check_out(0).is_none());
+        assert_eq!(pool.try_next().unwrap().index(), 1);
+
+        let (res, _) = stream.write(b"hello".as_slice()).await;
+        res.unwrap();
+        let (res, buf) = peer.read_fixed(buf).await;
+        assert_eq!(res.unwrap(), 5);
+        assert_eq!(&buf[..], b"hello");
+
+        // Echo the buffer back
+        let (res, buf) = peer.write_fixed(buf).await;
+        assert_eq!(res.unwrap(), 5);
+        drop(buf);
+
+        let (res, buf) = stream.read_fixed(pool.check_out(0).unwrap()).await;
+        assert_eq!(res.unwrap(), 5);
+        assert_eq!(&buf[..], b"hello");
+    });
+}
+
+#[test]
+fn write_all() {
+    tokio_uring::start(async {
+        let (stream, peer) = connected_pair(30210).await;
+
+        // Larger than the socket buffers, so writes come back short
+        let data: Vec<u8> = (0..8 * 1024 * 1024u32).map(|i| i as u8).collect();
+
+        let send = async {
+            let (res, buf) = stream.write_all(data).await;
+            res.unwrap();
+            stream.shutdown(std::net::Shutdown::Write).unwrap();
+            buf
+        };
+
+        let recv = async {
+            let mut received = Vec::new();
+            let mut buf = vec![0; 64 * 1024];
+            loop {
+                let (res, b) = peer.read(buf).await;
+                let n = res.unwrap();
+                if n == 0 {
+                    break received;
+                }
+                received.extend_from_slice(&b[..n]);
+                buf = b;
+            }
+        };
+
+        let (sent, received) = tokio::join!(send, recv);
+        assert_eq!(sent.len(), 8 * 1024 * 1024);
+        assert!(received == sent);
+
+        // Nothing to write
+        let (res, buf) = stream.write_all(Vec::new()).await;
+        res.unwrap();
+        assert!(buf.is_empty());
+    });
+}
+
+#[test]
+fn use_fixed_file() {
+    tokio_uring::start(async {
+        let listener = TcpListener::bind("127.0.0.1:30211".parse().unwrap()).unwrap();
+        listener.use_fixed_file().unwrap();
+
+        let (stream, (accepted, _)) = tokio::try_join!(
+            TcpStream::connect("127.0.0.1:30211".parse().unwrap()),
+            listener.accept(),
+        )
+        .unwrap();
+        stream.use_fixed_file().unwrap();
+        accepted.use_fixed_file().unwrap();
+        // Registering again is a no-op
+        stream.use_fixed_file().unwrap();
+
+        let (res, _) = stream.write_all(b"ping".as_slice()).await;
+        res.unwrap();
+        let (res, buf) = accepted.read(vec![0; 4]).await;
+        assert_eq!(&buf[..res.unwrap()], b"ping");
+
+        // The slot is released, and the socket closed, on close
+        drop(accepted);
+        let (res, _) = stream.read(vec![0; 4]).await;
+        assert_eq!(res.unwrap(), 0);
+    });
+}
+
+#[test]
+fn recv_ring() {
+    tokio_uring::start(async {
+        let (stream, peer) = connected_pair(30212).await;
+        let ring = tokio_uring::register_buf_ring(2, 16).unwrap();
+
+        let (res, _) = stream.write_all(b"hello".as_slice()).await;
+        res.unwrap();
+        let first = peer.recv_ring(&ring).await.unwrap().unwrap();
+        assert_eq!(&first[..], b"hello");
+
+        let (res, _) = stream.write_all(b"world".as_slice()).await;
+        res.unwrap();
+        let second = peer.recv_ring(&ring).await.unwrap().unwrap();
+        assert_eq!(&second[..], b"world");
+        assert_ne!(first.bid(), second.bid());
+
+        // All buffers are in use
+        let (res, _) = stream.write_all(b"!".as_slice()).await;
+        res.unwrap();
+        let err = peer.recv_ring(&ring).await.unwrap_err();
+        assert_eq!(err.raw_os_error(), Some(libc::ENOBUFS));
+
+        // Dropping a slot hands its buffer back
+        drop(first);
+        let third = peer.recv_ring(&ring).await.unwrap().unwrap();
+        assert_eq!(&third[..], b"!");
+
+        // Even the end of the stream needs a free buffer
+        drop((second, third));
+        stream.shutdown(std::net::Shutdown::Write).unwrap();
+        assert!(peer.recv_ring(&ring).await.unwrap().is_none());
+    });
+}
+
+#[test]
+fn cancel_handle() {
+    tokio_uring::start(async {
+        let (stream, peer) = connected_pair(30213).await;
+        let peer = std::rc::Rc::new(peer);
+        let handle = tokio_uring::CancelHandle::new();
+
+        // Nothing to cancel yet
+        assert!(!handle.cancel().await.unwrap());
+
+        let read = tokio_uring::spawn({
+            let (handle, peer) = (handle.clone(), peer.clone());
+            async move { handle.run(peer.read(vec![0; 8])).await }
+        });
+        tokio::task::yield_now().await;
+
+        assert!(handle.cancel().await.unwrap());
+        let (res, buf) = read.await.unwrap();
+        assert_eq!(res.unwrap_err().raw_os_error(), Some(libc::ECANCELED));
+        assert_eq!(buf.capacity(), 8);
+
+        // The canceled read is no longer tracked
+        assert!(!handle.cancel().await.unwrap());
+
+        // Operations outside of the handle are untouched
+        let (res, _) = stream.write_all(b"ping".as_slice()).await;
+        res.unwrap();
+        let (res, buf) = peer.read(vec![0; 8]).await;
+        assert_eq!(&buf[..res.unwrap()], b"ping");
+    });
+}
+
+#[test]
+fn accept_with_timeout() {
+    tokio_uring::start(async {
+        let listener = TcpListener::bind("127.0.0.1:30214".parse().unwrap()).unwrap();
+
+        let res = tokio_uring::with_timeout(listener.accept(), Duration::from_millis(50)).await;
+        assert_eq!(res.err().unwrap().kind(), std::io::ErrorKind::TimedOut);
+
+        // The listener is still usable
+        let (stream, (accepted, _)) = tokio::try_join!(
+            TcpStream::connect("127.0.0.1:30214".parse().unwrap()),
+            tokio_uring::with_timeout(listener.accept(), Duration::from_secs(5)),
+        )
+        .unwrap();
+
+        // Reads hand their buffer back on timeout
+        let read = tokio_uring::with_timeout(accepted.read(vec![0; 8]), Duration::from_millis(50));
+        let (res, buf) = read.await;
+        assert_eq!(res.unwrap_err().kind(), std::io::ErrorKind::TimedOut);
+        assert_eq!(buf.capacity(), 8);
+
+        let (res, _) = stream.write_all(b"ping".as_slice()).await;
+        res.unwrap();
+        let read = tokio_uring::with_timeout(accepted.read(buf), Duration::from_secs(5));
+        let (res, buf) = read.await;
+        assert_eq!(&buf[..res.unwrap()], b"ping");
+    });
+}
+
+#[test]
+fn send_zc() {
+    tokio_uring::start(async {
+        let (stream, peer) = connected_pair(30215).await;
+        let data: Vec<u8> = (0..1024 * 1024).map(|i| i as u8).collect();
+
+        let reader = tokio_uring::spawn(async move {
+            let mut received = Vec::new();
+            let mut buf = vec![0; 64 * 1024];
+            loop {
+                let (res, b) = peer.read(buf).await;
+                buf = b;
+                match res.unwrap() {
+                    0 => break received,
+                    n => received.extend_from_slice(&buf[..n]),
+                }
+            }
+        });
+
+        let mut sent = 0;
+        let mut buf = data.clone();
+        while sent < data.len() {
+            let (res, slice) = stream.send_zc(buf.slice(sent..)).await;
+            buf = slice.into_inner();
+            sent += res.unwrap();
+        }
+        assert_eq!(buf, data);
+
+        stream.shutdown(std::net::Shutdown::Write).unwrap();
+        assert_eq!(reader.await.unwrap(), data);
+    });
+}
+
+#[test]
+fn writev_readv_arrays() {
+    tokio_uring::start(async {
+        let (stream, peer) = connected_pair(30216).await;
+
+        // Slices of a single buffer, without copying
+        let data = b"header|body".to_vec();
+        let (res, bufs) = stream
+            .writev([data.slice(..7), b"tail".to_vec().slice(..)])
+            .await;
+        assert_eq!(res.unwrap(), 11);
+        let [head, tail] = bufs;
+        assert_eq!(head.into_inner(), b"header|body");
+        assert_eq!(tail.into_inner(), b"tail");
+
+        let bufs = [Vec::with_capacity(7), Vec::with_capacity(16)];
+        let (res, [head, rest]) = peer.readv(bufs).await;
+        assert_eq!(res.unwrap(), 11);
+        assert_eq!(head, b"header|");
+        assert_eq!(rest, b"tail");
+    });
+}
+
+#[test]
+fn bind_with_backlog() {
+    let addr = "127.0.0.1:0".parse().unwrap();
+    for backlog in [0, -1] {
+        let err = TcpListener::bind_with_backlog(addr, backlog).err().unwrap();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    }
+
+    let listener = TcpListener::bind_with_backlog(addr, 100).unwrap();
+    let addr = listener.local_addr().unwrap();
+    assert_eq!(listener.pending_connections().unwrap(), (0, 100));
+
+    // Connections completed by the kernel wait in the queue until accepted
+    let _client = std::net::TcpStream::connect(addr).unwrap();
+    assert_eq!(listener.pending_connections().unwrap(), (1, 100));
+}
+
+#[test]
+fn read_exact() {
+    use std::convert::TryInto;
+
+    tokio_uring::start(async {
+        let (stream, peer) = connected_pair(30217).await;
+
+        // The data arrives in several segments
+        let writer = tokio_uring::spawn(async move {
+            for chunk in [&b"\0\0\0\x05he"[..], b"llo", b"tail"] {
+                let (res, _) = peer.write_all(chunk.to_vec()).await;
+                res.unwrap();
+                tokio::task::yield_now().await;
+            }
+            peer
+        });
+
+        let (res, header) = stream.read_exact(Vec::with_capacity(4)).await;
+        res.unwrap();
+        let len = u32::from_be_bytes(header[..].try_into().unwrap());
+        assert_eq!(len, 5);
+
+        let (res, body) = stream.read_exact(Vec::with_capacity(len as usize)).await;
+        res.unwrap();
+        assert_eq!(body, b"hello");
+
+        // The peer closes before the buffer is filled
+        drop(writer.await.unwrap());
+        let (res, buf) = stream.read_exact(Vec::with_capacity(16)).await;
+        assert_eq!(res.unwrap_err().kind(), std::io::ErrorKind::UnexpectedEof);
+        assert_eq!(buf, b"tail");
+    });
+}
+
+#[test]
+fn close() {
+    tokio_uring::start(async {
+        let (stream, peer) = connected_pair(30218).await;
+
+        stream.close().await.unwrap();
+        let (res, _) = peer.read(vec![0; 4]).await;
+        assert_eq!(res.unwrap(), 0);
+    });
+}
+
+#[test]
+fn accept_cloexec() {
+    use std::os::unix::io::AsRawFd;
+
+    fn cloexec(fd: &impl AsRawFd) -> bool {
+        let flags = unsafe { libc::fcntl(fd.as_raw_fd(), libc::F_GETFD) };
+        assert!(flags >= 0);
+        flags & libc::FD_CLOEXEC != 0
+    }
+
+    tokio_uring::start(async {
+        let listener = TcpListener::bind("127.0.0.1:0".parse().unwrap()).unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let (stream, (accepted, _)) =
+            tokio::try_join!(TcpStream::connect(addr), listener.accept()).unwrap();
+        assert!(cloexec(&stream));
+        assert!(cloexec(&accepted));
+
+        // Opting out
+        let (_, (inherited, _)) = tokio::try_join!(
+            TcpStream::connect(addr),
+            listener.accept_with_flags(libc::SOCK_NONBLOCK)
+        )
+        .unwrap();
+        assert!(!cloexec(&inherited));
+        let flags = unsafe { libc::fcntl(inherited.as_raw_fd(), libc::F_GETFL) };
+        assert_ne!(flags & libc::O_NONBLOCK, 0);
+    });
+}
+
+#[test]
+fn recv_cancelable() {
+    tokio_uring::start(async {
+        let (stream, peer) = connected_pair(30219).await;
+
+        // Canceled: the guard hands the buffer back
+        let (recv, guard) = stream.recv_cancelable(Vec::with_capacity(4096));
+        assert!(!guard.is_released());
+        drop(recv);
+        let buf = guard.await.unwrap();
+        assert_eq!(buf.capacity(), 4096);
+
+        // Completed: the future returns the buffer
+        let (recv, guard) = stream.recv_cancelable(buf);
+        let (res, _) = peer.write(b"ping".as_slice()).await;
+        res.unwrap();
+        let (res, buf) = recv.await;
+        assert_eq!(&buf[..res.unwrap()], b"ping");
+        assert!(guard.is_released());
+        assert!(guard.await.is_none());
+    });
+}
+
+#[test]
+fn connect_host() {
+    tokio_uring::start(async {
+        // `localhost` may resolve to `::1` first, on which nothing listens
+        let listener = TcpListener::bind("127.0.0.1:30220".parse().unwrap()).unwrap();
+
+        let (stream, (accepted, _)) = tokio::try_join!(
+            TcpStream::connect_host("localhost:30220"),
+            listener.accept()
+        )
+        .unwrap();
+        let (res, _) = stream.write(b"ping".as_slice()).await;
+        res.unwrap();
+        let (res, buf) = accepted.read(vec![0; 4]).await;
+        assert_eq!(&buf[..res.unwrap()], b"ping");
+
+        // Resolution errors are returned
+        let err = TcpStream::connect_host("localhost").await.err().unwrap();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    });
+}
+
+#[test]
+fn into_split() {
+    tokio_uring::start(async {
+        let (stream, peer) = connected_pair(30221).await;
+        let (read, write) = stream.into_split();
+
+        // A read is in flight while the other half writes
+        let reader = tokio_uring::spawn(async move {
+            let (res, buf) = read.read_exact(Vec::with_capacity(5)).await;
+            res.unwrap();
+            assert_eq!(buf, b"hello");
+            read
+        });
+
+        let (res, _) = write.write_all(b"ping".as_slice()).await;
+        res.unwrap();
+        let (res, buf) = peer.read_exact(Vec::with_capacity(4)).await;
+        res.unwrap();
+        assert_eq!(buf, b"ping");
+
+        let (res, _) = peer.write_all(b"hello".as_slice()).await;
+        res.unwrap();
+        let read = reader.await.unwrap();
+
+        // Dropping one half leaves the socket open
+        drop(write);
+        let (res, _) = peer.write_all(b"more".as_slice()).await;
+        res.unwrap();
+        let (res, buf) = read.read(Vec::with_capacity(4)).await;
+        assert_eq!(res.unwrap(), 4);
+        assert_eq!(buf, b"more");
+
+        // The socket is closed once both halves are dropped
+        drop(read);
+        let (res, _) = peer.read(Vec::with_capacity(4)).await;
+        assert_eq!(res.unwrap(), 0);
+    });
+}
+
+#[test]
+fn split() {
+    use std::os::unix::io::AsRawFd;
+
+    tokio_uring::start(async {
+        let (stream, peer) = connected_pair(30222).await;
+        let (read, write) = stream.split();
+        assert_eq!(read.as_raw_fd(), write.as_raw_fd());
+
+        let (res, _) = write.write_all(b"ping".as_slice()).await;
+        res.unwrap();
+        let (res, buf) = peer.read_exact(Vec::with_capacity(4)).await;
+        res.unwrap();
+        assert_eq!(buf, b"ping");
+
+        // Shutting the write half down leaves the read half working
+        write.shutdown().unwrap();
+        let (res, _) = peer.read(Vec::with_capacity(4)).await;
+        assert_eq!(res.unwrap(), 0);
+
+        let (res, _) = peer.write_all(b"pong".as_slice()).await;
+        res.unwrap();
+        let (res, buf) = read.read_exact(Vec::with_capacity(4)).await;
+        res.unwrap();
+        assert_eq!(buf, b"pong");
+    });
+}
+
+#[test]
+fn read_waitall() {
+    tokio_uring::start(async {
+        let (stream, peer) = connected_pair(30223).await;
+
+        let writer = tokio_uring::spawn(async move {
+            for chunk in [b"hel".as_slice(), b"lo ".as_slice(), b"world".as_slice()] {
+                let (res, _) = peer.write_all(chunk).await;
+                res.unwrap();
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+            peer
+        });
+
+        // A single receive gathers the chunks
+        let (res, buf) = stream.read_waitall(Vec::with_capacity(11)).await;
+        assert_eq!(res.unwrap(), 11);
+        assert_eq!(buf, b"hello world");
+
+        // Closing the stream ends the receive short
+        let peer = writer.await.unwrap();
+        let (res, _) = peer.write_all(b"bye".as_slice()).await;
+        res.unwrap();
+        peer.close().await.unwrap();
+
+        let (res, buf) = stream.read_waitall(Vec::with_capacity(8)).await;
+        assert_eq!(res.unwrap(), 3);
+        assert_eq!(buf, b"bye");
+    });
+}
+
+#[test]
+fn send_more_cork() {
+    tokio_uring::start(async {
+        let (stream, peer) = connected_pair(30224).await;
+        stream.set_nodelay(true).unwrap();
+
+        let (res, _) = stream.send_more(b"len:5\n".as_slice()).await;
+        assert_eq!(res.unwrap(), 6);
+        let (res, _) = stream.write_all(b"hello".as_slice()).await;
+        res.unwrap();
+
+        let (res, buf) = peer.read_exact(Vec::with_capacity(11)).await;
+        res.unwrap();
+        assert_eq!(buf, b"len:5\nhello");
+
+        assert!(!stream.cork().unwrap());
+        stream.set_cork(true).unwrap();
+        assert!(stream.cork().unwrap());
+
+        let (res, _) = stream.write_all(b"corked".as_slice()).await;
+        res.unwrap();
+
+        // Uncorking sends the data held back
+        stream.set_cork(false).unwrap();
+        assert!(!stream.cork().unwrap());
+        let (res, buf) = peer.read_exact(Vec::with_capacity(6)).await;
+        res.unwrap();
+        assert_eq!(buf, b"corked");
+    });
+}
+
+#[test]
+fn bind_and_connect() {
+    tokio_uring::start(async {
+        let listener = TcpListener::bind("127.0.0.1:30225".parse().unwrap()).unwrap();
+
+        let (stream, (_, peer_addr)) = tokio::try_join!(
+            TcpStream::bind_and_connect(
+                "127.0.0.2:0".parse().unwrap(),
+                "127.0.0.1:30225".parse().unwrap(),
+            ),
+            listener.accept(),
+        )
+        .unwrap();
+        assert_eq!(
+            peer_addr.ip(),
+            "127.0.0.2".parse::<std::net::IpAddr>().unwrap()
+        );
+        assert_eq!(stream.local_addr().unwrap(), peer_addr);
+        assert_eq!(
+            stream.peer_addr().unwrap(),
+            "127.0.0.1:30225".parse().unwrap()
+        );
+        drop(stream);
+
+        let listener = TcpListener::bind("[::1]:0".parse().unwrap()).unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (_, (_, peer_addr)) = tokio::try_join!(
+            TcpStream::bind_and_connect("[::1]:0".parse().unwrap(), addr),
+            listener.accept(),
+        )
+        .unwrap();
+        assert!(peer_addr.ip().is_loopback());
+
+        let remote = "127.0.0.1:30225".parse().unwrap();
+        let err = TcpStream::bind_and_connect("[::1]:0".parse().unwrap(), remote)
+            .await
+            .err()
+            .unwrap();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    });
+}
+
+#[test]
+fn copy_bidirectional() {
+    use std::net::Shutdown;
+
+    tokio_uring::start(async {
+        let (client, a) = connected_pair(30226).await;
+        let (b, server) = connected_pair(30227).await;
+
+        let proxy =
+            tokio_uring::spawn(async move { tokio_uring::io::copy_bidirectional(&a, &b).await });
+
+        let (res, _) = client.write_all(b"ping".as_slice()).await;
+        res.unwrap();
+        let (res, buf) = server.read_exact(Vec::with_capacity(4)).await;
+        res.unwrap();
+        assert_eq!(buf, b"ping");
+
+        // The half-close is forwarded, the other direction keeps running
+        client.shutdown(Shutdown::Write).unwrap();
+        let (res, _) = server.read(Vec::with_capacity(4)).await;
+        assert_eq!(res.unwrap(), 0);
+
+        let (res, _) = server.write_all(b"pong, late".as_slice()).await;
+        res.unwrap();
+        let (res, buf) = client.read_exact(Vec::with_capacity(10)).await;
+        res.unwrap();
+        assert_eq!(buf, b"pong, late");
+
+        server.shutdown(Shutdown::Write).unwrap();
+        let (res, _) = client.read(Vec::with_capacity(4)).await;
+        assert_eq!(res.unwrap(), 0);
+
+        assert_eq!(proxy.await.unwrap().unwrap(), (4, 10));
+    });
+}
+
+#[test]
+fn linger() {
+    tokio_uring::start(async {
+        let (stream, peer) = connected_pair(30228).await;
+
+        assert_eq!(stream.linger().unwrap(), None);
+        stream.set_linger(Some(Duration::from_secs(5))).unwrap();
+        assert_eq!(stream.linger().unwrap(), Some(Duration::from_secs(5)));
+        stream
+            .set_linger(Some(Duration::from_millis(1500)))
+            .unwrap();
+        assert_eq!(stream.linger().unwrap(), Some(Duration::from_secs(2)));
+        stream.set_linger(None).unwrap();
+        assert_eq!(stream.linger().unwrap(), None);
+
+        // A linger of zero resets the connection on close
+        stream.set_linger(Some(Duration::ZERO)).unwrap();
+        stream.close().await.unwrap();
+
+        let (res, _) = peer.read(vec![0; 8]).await;
+        assert_eq!(res.unwrap_err().kind(), std::io::ErrorKind::ConnectionReset);
+    });
+}
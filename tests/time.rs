@@ -0,0 +1,58 @@
+use std::time::{Duration, Instant};
+
+use tokio_uring::time;
+
+#[test]
+fn sleep() {
+    tokio_uring::start(async {
+        let start = Instant::now();
+        time::sleep(Duration::from_millis(20)).await;
+        assert!(start.elapsed() >= Duration::from_millis(20));
+
+        let deadline = Instant::now() + Duration::from_millis(20);
+        time::sleep_until(deadline).await;
+        assert!(Instant::now() >= deadline);
+
+        // A deadline in the past completes right away
+        time::sleep_until(start).await;
+    });
+}
+
+#[test]
+fn timeout() {
+    tokio_uring::start(async {
+        let res = time::timeout(std::future::pending::<()>(), Duration::from_millis(10)).await;
+        let err = res.unwrap_err();
+        assert_eq!(
+            std::io::Error::from(err).kind(),
+            std::io::ErrorKind::TimedOut
+        );
+
+        let res = time::timeout(async { 7 }, Duration::from_secs(10)).await;
+        assert_eq!(res.unwrap(), 7);
+
+        let deadline = Instant::now() + Duration::from_millis(10);
+        let sleep = time::sleep(Duration::from_secs(10));
+        assert!(time::timeout_at(sleep, deadline).await.is_err());
+    });
+}
+
+#[test]
+fn drop_cancels_timeout() {
+    tokio_uring::start(async {
+        let res = time::timeout(
+            time::sleep(Duration::from_secs(60)),
+            Duration::from_millis(5),
+        )
+        .await;
+        assert!(res.is_err());
+
+        // The dropped sleep is removed from the ring instead of lingering for
+        // a minute.
+        let start = Instant::now();
+        while tokio_uring::metrics().ops_in_flight() > 0 {
+            assert!(start.elapsed() < Duration::from_secs(1));
+            tokio::task::yield_now().await;
+        }
+    });
+}
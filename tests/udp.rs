@@ -0,0 +1,1141 @@
+use std::future::poll_fn;
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV6};
+use std::pin::Pin;
+use std::time::Duration;
+
+use futures_core::Stream;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+use tokio_uring::buf::BufRing;
+use tokio_uring::net::UdpSocket;
+
+async fn connected_pair(port: u16) -> (UdpSocket, std::net::UdpSocket) {
+    let addr: SocketAddr = format!("127.0.0.1:{}", port).parse().unwrap();
+    let socket = UdpSocket::bind(addr).await.unwrap();
+
+    let peer = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+    peer.connect(addr).unwrap();
+    socket.connect(peer.local_addr().unwrap()).await.unwrap();
+
+    (socket, peer)
+}
+
+#[test]
+fn poll_read_datagram() {
+    tokio_uring::start(async {
+        let (mut socket, peer) = connected_pair(30101).await;
+
+        peer.send(b"hello world").unwrap();
+
+        let mut buf = [0; 32];
+        let n = AsyncReadExt::read(&mut socket, &mut buf).await.unwrap();
+        assert_eq!(&buf[..n], b"hello world");
+    });
+}
+
+#[test]
+fn poll_read_truncates_datagram() {
+    tokio_uring::start(async {
+        let (mut socket, peer) = connected_pair(30102).await;
+
+        peer.send(b"hello world").unwrap();
+        peer.send(b"again").unwrap();
+
+        // The remainder of the first datagram is discarded
+        let mut buf = [0; 5];
+        let n = AsyncReadExt::read(&mut socket, &mut buf).await.unwrap();
+        assert_eq!(&buf[..n], b"hello");
+
+        let n = AsyncReadExt::read(&mut socket, &mut buf).await.unwrap();
+        assert_eq!(&buf[..n], b"again");
+    });
+}
+
+#[test]
+fn poll_read_empty_datagram() {
+    tokio_uring::start(async {
+        let (mut socket, peer) = connected_pair(30103).await;
+
+        peer.send(b"").unwrap();
+
+        let mut buf = [0; 32];
+        let n = AsyncReadExt::read(&mut socket, &mut buf).await.unwrap();
+        assert_eq!(n, 0);
+    });
+}
+
+#[test]
+fn poll_write_datagram() {
+    tokio_uring::start(async {
+        let (mut socket, peer) = connected_pair(30104).await;
+
+        let n = AsyncWriteExt::write(&mut socket, b"hello world")
+            .await
+            .unwrap();
+        assert_eq!(n, 11);
+        socket.flush().await.unwrap();
+
+        let mut buf = [0; 32];
+        let n = peer.recv(&mut buf).unwrap();
+        assert_eq!(&buf[..n], b"hello world");
+    });
+}
+
+#[test]
+fn poll_write_vectored_datagram() {
+    tokio_uring::start(async {
+        let (mut socket, peer) = connected_pair(30105).await;
+
+        let bufs = [
+            std::io::IoSlice::new(b"hello"),
+            std::io::IoSlice::new(b" world"),
+        ];
+        let n = socket.write_vectored(&bufs).await.unwrap();
+        assert_eq!(n, 11);
+
+        // Both slices are sent as a single datagram
+        let mut buf = [0; 32];
+        let n = peer.recv(&mut buf).unwrap();
+        assert_eq!(&buf[..n], b"hello world");
+
+        socket.shutdown().await.unwrap();
+    });
+}
+
+#[test]
+fn send_recv_connected() {
+    tokio_uring::start(async {
+        let (socket, peer) = connected_pair(30106).await;
+
+        let (res, _) = socket.send(b"ping".as_slice()).await;
+        assert_eq!(res.unwrap(), 4);
+
+        let mut buf = [0; 32];
+        let n = peer.recv(&mut buf).unwrap();
+        assert_eq!(&buf[..n], b"ping");
+
+        peer.send(b"pong").unwrap();
+
+        let (res, buf) = socket.recv(vec![0; 32]).await;
+        let n = res.unwrap();
+        assert_eq!(&buf[..n], b"pong");
+    });
+}
+
+#[test]
+fn send_unconnected() {
+    tokio_uring::start(async {
+        let socket = UdpSocket::bind("127.0.0.1:30107".parse().unwrap())
+            .await
+            .unwrap();
+
+        let (res, buf) = socket.send(b"ping".as_slice()).await;
+        assert_eq!(res.unwrap_err().kind(), std::io::ErrorKind::NotConnected);
+        assert_eq!(buf, b"ping");
+    });
+}
+
+#[test]
+fn peek_from_keeps_datagram() {
+    tokio_uring::start(async {
+        let socket = UdpSocket::bind("127.0.0.1:30110".parse().unwrap())
+            .await
+            .unwrap();
+        let peer = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        peer.send_to(b"hello world", "127.0.0.1:30110").unwrap();
+
+        let (res, buf) = socket.peek_from(vec![0; 5]).await;
+        let (n, addr) = res.unwrap();
+        assert_eq!(&buf[..n], b"hello");
+        assert_eq!(addr, peer.local_addr().unwrap());
+
+        // The real size is only reported when asked for
+        let (res, buf) = socket.peek_from_trunc(vec![0; 5]).await;
+        assert_eq!(res.unwrap().0, 11);
+        assert_eq!(&buf[..], b"hello");
+
+        let (res, buf) = socket.recv_from(vec![0; 32]).await;
+        let (n, _) = res.unwrap();
+        assert_eq!(&buf[..n], b"hello world");
+    });
+}
+
+#[test]
+fn peek_connected() {
+    tokio_uring::start(async {
+        let (socket, peer) = connected_pair(30111).await;
+        peer.send(b"hello world").unwrap();
+
+        let (res, buf) = socket.peek(vec![0; 5]).await;
+        assert_eq!(&buf[..res.unwrap()], b"hello");
+
+        let (res, buf) = socket.peek_trunc(Vec::with_capacity(5)).await;
+        assert_eq!(res.unwrap(), 11);
+        assert_eq!(&buf[..], b"hello");
+
+        let (res, buf) = socket.recv(vec![0; 32]).await;
+        assert_eq!(&buf[..res.unwrap()], b"hello world");
+    });
+}
+
+#[test]
+fn multicast_v4() {
+    tokio_uring::start(async {
+        let group = Ipv4Addr::new(239, 1, 2, 3);
+        let socket = UdpSocket::bind("0.0.0.0:30112".parse().unwrap())
+            .await
+            .unwrap();
+        socket
+            .join_multicast_v4(group, Ipv4Addr::LOCALHOST)
+            .unwrap();
+
+        socket.set_multicast_loop_v4(false).unwrap();
+        assert!(!socket.multicast_loop_v4().unwrap());
+        socket.set_multicast_loop_v4(true).unwrap();
+        assert!(socket.multicast_loop_v4().unwrap());
+
+        socket.set_multicast_ttl_v4(4).unwrap();
+        assert_eq!(socket.multicast_ttl_v4().unwrap(), 4);
+
+        let sender = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        sender.send_to(b"discover", (group, 30112)).unwrap();
+
+        let (res, buf) = socket.recv_from(vec![0; 32]).await;
+        let (n, addr) = res.unwrap();
+        assert_eq!(&buf[..n], b"discover");
+        assert_eq!(addr, sender.local_addr().unwrap());
+
+        socket
+            .leave_multicast_v4(group, Ipv4Addr::LOCALHOST)
+            .unwrap();
+    });
+}
+
+#[test]
+fn multicast_v6() {
+    tokio_uring::start(async {
+        let group: Ipv6Addr = "ff02::1".parse().unwrap();
+        let socket = UdpSocket::bind("[::]:30113".parse().unwrap())
+            .await
+            .unwrap();
+
+        // Interface 1 is the loopback interface
+        socket.join_multicast_v6(&group, 1).unwrap();
+
+        socket.set_multicast_loop_v6(false).unwrap();
+        assert!(!socket.multicast_loop_v6().unwrap());
+        socket.set_multicast_loop_v6(true).unwrap();
+        assert!(socket.multicast_loop_v6().unwrap());
+
+        // Loopback only routes IPv6 multicast on some setups
+        let sender = std::net::UdpSocket::bind("[::1]:0").unwrap();
+        let dest = SocketAddrV6::new(group, 30113, 0, 1);
+        if let Err(e) = sender.send_to(b"discover", dest) {
+            eprintln!("skipping the multicast round trip, no route: {}", e);
+            return;
+        }
+
+        let recv = socket.recv_from(vec![0; 32]);
+        let (res, buf) = match tokio_uring::time::timeout(recv, Duration::from_secs(1)).await {
+            Ok(received) => received,
+            Err(_) => {
+                eprintln!("skipping the multicast round trip, nothing looped back");
+                return;
+            }
+        };
+        let (n, addr) = res.unwrap();
+        assert_eq!(&buf[..n], b"discover");
+        assert_eq!(addr.port(), sender.local_addr().unwrap().port());
+
+        socket.leave_multicast_v6(&group, 1).unwrap();
+    });
+}
+
+#[test]
+fn send_recv_v6() {
+    tokio_uring::start(async {
+        let socket = UdpSocket::bind("[::1]:30114".parse().unwrap())
+            .await
+            .unwrap();
+        let peer = std::net::UdpSocket::bind("[::1]:0").unwrap();
+        peer.send_to(b"hello", "[::1]:30114").unwrap();
+
+        let (res, buf) = socket.recv_from(vec![0; 32]).await;
+        let (n, addr) = res.unwrap();
+        assert_eq!(&buf[..n], b"hello");
+        assert_eq!(addr, peer.local_addr().unwrap());
+
+        let (res, _) = socket.send_to(b"world".as_slice(), addr).await;
+        assert_eq!(res.unwrap(), 5);
+
+        let mut buf = [0; 32];
+        let n = peer.recv(&mut buf).unwrap();
+        assert_eq!(&buf[..n], b"world");
+    });
+}
+
+#[test]
+fn broadcast() {
+    tokio_uring::start(async {
+        let socket = UdpSocket::bind("0.0.0.0:30115".parse().unwrap())
+            .await
+            .unwrap();
+        assert!(!socket.broadcast().unwrap());
+
+        let (res, _) = socket
+            .send_to(
+                b"hello".as_slice(),
+                "255.255.255.255:30116".parse().unwrap(),
+            )
+            .await;
+        assert_eq!(
+            res.unwrap_err().kind(),
+            std::io::ErrorKind::PermissionDenied
+        );
+
+        socket.set_broadcast(true).unwrap();
+        assert!(socket.broadcast().unwrap());
+    });
+}
+
+#[test]
+fn local_and_peer_addr() {
+    tokio_uring::start(async {
+        let socket = UdpSocket::bind("127.0.0.1:0".parse().unwrap())
+            .await
+            .unwrap();
+        let addr = socket.local_addr().unwrap();
+        assert_eq!(addr.ip(), Ipv4Addr::LOCALHOST);
+        assert_ne!(addr.port(), 0);
+
+        assert_eq!(
+            socket.peer_addr().unwrap_err().kind(),
+            std::io::ErrorKind::NotConnected
+        );
+
+        let peer = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        socket.connect(peer.local_addr().unwrap()).await.unwrap();
+        assert_eq!(socket.peer_addr().unwrap(), peer.local_addr().unwrap());
+    });
+}
+
+#[test]
+fn bind_reuseport() {
+    tokio_uring::start(async {
+        let addr = "127.0.0.1:9000".parse().unwrap();
+
+        let mut sockets = vec![];
+        for _ in 0..4 {
+            sockets.push(UdpSocket::bind_reuseport(addr, true).await.unwrap());
+        }
+
+        for socket in &sockets {
+            assert_eq!(socket.local_addr().unwrap(), addr);
+        }
+    });
+}
+
+#[test]
+fn bind_without_reuseport() {
+    tokio_uring::start(async {
+        let addr = "127.0.0.1:30117".parse().unwrap();
+
+        let _socket = UdpSocket::bind_reuseport(addr, false).await.unwrap();
+        let err = UdpSocket::bind_reuseport(addr, false).await.err().unwrap();
+        assert_eq!(err.kind(), std::io::ErrorKind::AddrInUse);
+    });
+}
+
+#[test]
+fn recv_mmsg_batch() {
+    tokio_uring::start(async {
+        let socket = UdpSocket::bind("127.0.0.1:30118".parse().unwrap())
+            .await
+            .unwrap();
+        let peer = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+
+        for payload in [&b"one"[..], b"two", b"three"] {
+            peer.send_to(payload, "127.0.0.1:30118").unwrap();
+        }
+
+        // Fewer datagrams than buffers
+        let (res, datagrams, bufs) = socket.recv_mmsg(vec![vec![0; 16]; 5]).await;
+        assert_eq!(res.unwrap(), 3);
+        assert_eq!(bufs.len(), 5);
+
+        let payloads: Vec<&[u8]> = datagrams
+            .iter()
+            .zip(&bufs)
+            .map(|((len, addr), buf)| {
+                assert_eq!(*addr, peer.local_addr().unwrap());
+                &buf[..*len]
+            })
+            .collect();
+        assert_eq!(payloads, [&b"one"[..], b"two", b"three"]);
+
+        // Datagrams beyond the batch stay queued
+        for payload in [&b"four"[..], b"five"] {
+            peer.send_to(payload, "127.0.0.1:30118").unwrap();
+        }
+
+        let (res, datagrams, bufs) = socket.recv_mmsg(vec![vec![0; 16]; 1]).await;
+        assert_eq!(res.unwrap(), 1);
+        assert_eq!(&bufs[0][..datagrams[0].0], b"four");
+
+        let (res, buf) = socket.recv_from(vec![0; 16]).await;
+        assert_eq!(&buf[..res.unwrap().0], b"five");
+    });
+}
+
+#[test]
+fn recv_mmsg_too_large() {
+    tokio_uring::start(async {
+        let socket = UdpSocket::bind("127.0.0.1:30119".parse().unwrap())
+            .await
+            .unwrap();
+
+        let (res, datagrams, bufs) = socket.recv_mmsg(vec![vec![0; 16]; 1024]).await;
+        assert_eq!(res.unwrap_err().kind(), std::io::ErrorKind::InvalidInput);
+        assert!(datagrams.is_empty());
+        assert_eq!(bufs.len(), 1024);
+    });
+}
+
+#[test]
+fn send_mmsg_batch() {
+    tokio_uring::start(async {
+        let socket = UdpSocket::bind("127.0.0.1:0".parse().unwrap())
+            .await
+            .unwrap();
+        let first = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        let second = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+
+        let packets = vec![
+            (b"one".to_vec(), first.local_addr().unwrap()),
+            (b"two".to_vec(), second.local_addr().unwrap()),
+            (b"three".to_vec(), first.local_addr().unwrap()),
+        ];
+        let (res, bufs) = socket.send_mmsg(packets).await;
+        assert_eq!(res.unwrap(), 3);
+        assert_eq!(bufs, [&b"one"[..], b"two", b"three"]);
+
+        let mut buf = [0; 16];
+        let n = first.recv(&mut buf).unwrap();
+        assert_eq!(&buf[..n], b"one");
+        let n = first.recv(&mut buf).unwrap();
+        assert_eq!(&buf[..n], b"three");
+        let n = second.recv(&mut buf).unwrap();
+        assert_eq!(&buf[..n], b"two");
+    });
+}
+
+#[test]
+fn send_mmsg_partial() {
+    tokio_uring::start(async {
+        let socket = UdpSocket::bind("127.0.0.1:0".parse().unwrap())
+            .await
+            .unwrap();
+        let peer = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+
+        // Sending to an IPv6 address from an IPv4 socket fails, and cuts the
+        // batch short
+        let packets = vec![
+            (b"one".to_vec(), peer.local_addr().unwrap()),
+            (b"two".to_vec(), "[::1]:30120".parse().unwrap()),
+            (b"three".to_vec(), peer.local_addr().unwrap()),
+        ];
+        let (res, bufs) = socket.send_mmsg(packets).await;
+        assert_eq!(res.unwrap(), 1);
+        assert_eq!(bufs.len(), 3);
+
+        let mut buf = [0; 16];
+        let n = peer.recv(&mut buf).unwrap();
+        assert_eq!(&buf[..n], b"one");
+    });
+}
+
+#[test]
+fn send_mmsg_detailed() {
+    tokio_uring::start(async {
+        let socket = UdpSocket::bind("127.0.0.1:0".parse().unwrap())
+            .await
+            .unwrap();
+        let peer = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+
+        let packets = vec![
+            (b"one".to_vec(), peer.local_addr().unwrap()),
+            (b"two".to_vec(), "[::1]:30120".parse().unwrap()),
+            (b"three".to_vec(), peer.local_addr().unwrap()),
+        ];
+        let (result, bufs) = socket.send_mmsg_detailed(packets).await;
+        assert_eq!(result.sent(), 1);
+        assert_eq!(bufs.len(), 3);
+        let err = result.into_error().unwrap();
+        assert_eq!(err.raw_os_error(), Some(libc::EAFNOSUPPORT));
+
+        // The first send failing is reported the same
+        let packets = vec![(b"one".to_vec(), "[::1]:30120".parse().unwrap())];
+        let (result, _) = socket.send_mmsg_detailed(packets).await;
+        assert_eq!(result.sent(), 0);
+        assert!(result.error().is_some());
+    });
+}
+
+#[test]
+fn send_to_segmented() {
+    tokio_uring::start(async {
+        let socket = UdpSocket::bind("127.0.0.1:0".parse().unwrap())
+            .await
+            .unwrap();
+        let peer = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+
+        let payload: Vec<u8> = (0..2500).map(|i| i as u8).collect();
+        let (res, _) = socket
+            .send_to_segmented(payload.clone(), peer.local_addr().unwrap(), 1000)
+            .await;
+        assert_eq!(res.unwrap(), 2500);
+
+        // The peer receives one datagram per segment
+        let mut buf = [0; 4096];
+        for chunk in payload.chunks(1000) {
+            let n = peer.recv(&mut buf).unwrap();
+            assert_eq!(&buf[..n], chunk);
+        }
+
+        let (res, _) = socket
+            .send_to_segmented(payload, peer.local_addr().unwrap(), 0)
+            .await;
+        assert_eq!(res.unwrap_err().kind(), std::io::ErrorKind::InvalidInput);
+    });
+}
+
+#[test]
+fn recv_from_gro() {
+    tokio_uring::start(async {
+        let socket = UdpSocket::bind("127.0.0.1:0".parse().unwrap())
+            .await
+            .unwrap();
+        let addr = socket.local_addr().unwrap();
+        let peer = UdpSocket::bind("127.0.0.1:0".parse().unwrap())
+            .await
+            .unwrap();
+
+        assert!(!socket.gro().unwrap());
+        socket.set_gro(true).unwrap();
+        assert!(socket.gro().unwrap());
+
+        // A plain datagram is not coalesced
+        let (res, _) = peer.send_to(b"hello".as_slice(), addr).await;
+        res.unwrap();
+
+        let (res, buf) = socket.recv_from_gro(vec![0; 65536]).await;
+        let (n, from, segment_size) = res.unwrap();
+        assert_eq!(&buf[..n], b"hello");
+        assert_eq!(from, peer.local_addr().unwrap());
+        assert_eq!(segment_size, None);
+
+        // Segments sent in one go are received in one go
+        let payload: Vec<u8> = (0..2500).map(|i| i as u8).collect();
+        let (res, _) = peer.send_to_segmented(payload.clone(), addr, 1000).await;
+        res.unwrap();
+
+        let (res, buf) = socket.recv_from_gro(vec![0; 65536]).await;
+        let (n, _, segment_size) = res.unwrap();
+        assert_eq!(segment_size, Some(1000));
+        assert_eq!(&buf[..n], &payload[..]);
+    });
+}
+
+#[test]
+fn recv_from_with_info() {
+    tokio_uring::start(async {
+        let socket = UdpSocket::bind("0.0.0.0:30121".parse().unwrap())
+            .await
+            .unwrap();
+        let peer = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+
+        // Disabled by default
+        assert!(!socket.recv_pktinfo().unwrap());
+        peer.send_to(b"one", "127.0.0.1:30121").unwrap();
+        let (res, _) = socket.recv_from_with_info(vec![0; 32]).await;
+        assert_eq!(res.unwrap().2, None);
+
+        socket.set_recv_pktinfo(true).unwrap();
+        assert!(socket.recv_pktinfo().unwrap());
+
+        peer.send_to(b"two", "127.0.0.1:30121").unwrap();
+        let (res, buf) = socket.recv_from_with_info(vec![0; 32]).await;
+        let (n, addr, info) = res.unwrap();
+        assert_eq!(&buf[..n], b"two");
+        assert_eq!(addr, peer.local_addr().unwrap());
+
+        // Interface 1 is the loopback interface
+        let info = info.unwrap();
+        assert_eq!(info.destination(), Ipv4Addr::LOCALHOST);
+        assert_eq!(info.interface(), 1);
+    });
+}
+
+#[test]
+fn recv_from_with_info_v6() {
+    tokio_uring::start(async {
+        let socket = UdpSocket::bind("[::]:30122".parse().unwrap())
+            .await
+            .unwrap();
+        socket.set_recv_pktinfo(true).unwrap();
+
+        let peer = std::net::UdpSocket::bind("[::1]:0").unwrap();
+        peer.send_to(b"hello", "[::1]:30122").unwrap();
+
+        let (res, _) = socket.recv_from_with_info(vec![0; 32]).await;
+        let info = res.unwrap().2.unwrap();
+        assert_eq!(info.destination(), Ipv6Addr::LOCALHOST);
+        assert_eq!(info.interface(), 1);
+    });
+}
+
+#[test]
+fn ttl_and_tos() {
+    tokio_uring::start(async {
+        for addr in ["127.0.0.1:0", "[::1]:0"] {
+            let socket = UdpSocket::bind(addr.parse().unwrap()).await.unwrap();
+
+            socket.set_ttl(17).unwrap();
+            assert_eq!(socket.ttl().unwrap(), 17);
+
+            socket.set_tos(46 << 2).unwrap();
+            assert_eq!(socket.tos().unwrap(), 46 << 2);
+
+            for ttl in [0, 256] {
+                let err = socket.set_ttl(ttl).unwrap_err();
+                assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+            }
+            let err = socket.set_tos(256).unwrap_err();
+            assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+        }
+    });
+}
+
+#[test]
+fn cancel_recv_from() {
+    tokio_uring::start(async {
+        let socket = UdpSocket::bind("127.0.0.1:30123".parse().unwrap())
+            .await
+            .unwrap();
+        let socket = std::rc::Rc::new(socket);
+        let peer = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+
+        for i in 0..8u8 {
+            let handle = tokio_uring::spawn({
+                let socket = socket.clone();
+                async move { socket.recv_from(vec![0; 32]).await }
+            });
+
+            // Let the task submit its recv, then drop it while in-flight
+            tokio::task::yield_now().await;
+            handle.abort();
+            assert!(handle.await.unwrap_err().is_cancelled());
+
+            let datagram = [i; 16];
+            peer.send_to(&datagram, "127.0.0.1:30123").unwrap();
+
+            let (res, buf) = socket.recv_from(vec![0xff; 32]).await;
+            let (n, addr) = res.unwrap();
+            assert_eq!(addr, peer.local_addr().unwrap());
+            assert_eq!(&buf[..n], &datagram);
+            assert!(buf[n..].iter().all(|&b| b == 0xff));
+        }
+    });
+}
+
+#[test]
+fn from_std_takes_ownership() {
+    tokio_uring::start(async {
+        let std_socket = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        let addr = std_socket.local_addr().unwrap();
+
+        let socket = UdpSocket::from_std(std_socket.try_clone().unwrap()).unwrap();
+        let other = UdpSocket::from(std_socket);
+
+        // Both sockets own a descriptor of their own
+        drop(other);
+        let peer = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        peer.send_to(b"ping", addr).unwrap();
+
+        let (res, buf) = socket.recv_from(vec![0; 32]).await;
+        let (n, from) = res.unwrap();
+        assert_eq!(&buf[..n], b"ping");
+        assert_eq!(from, peer.local_addr().unwrap());
+    });
+}
+
+#[test]
+fn bind_v6_dualstack() {
+    tokio_uring::start(async {
+        let socket = UdpSocket::bind_v6_dualstack("[::]:30124".parse().unwrap())
+            .await
+            .unwrap();
+
+        let v4_peer = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        v4_peer.send_to(b"from v4", "127.0.0.1:30124").unwrap();
+
+        let (res, buf) = socket.recv_from(vec![0; 32]).await;
+        let (n, addr) = res.unwrap();
+        assert_eq!(&buf[..n], b"from v4");
+        let mapped = Ipv4Addr::LOCALHOST.to_ipv6_mapped();
+        assert_eq!(
+            addr,
+            SocketAddr::new(mapped.into(), v4_peer.local_addr().unwrap().port())
+        );
+
+        // Replies go back through the mapped address
+        let (res, _) = socket.send_to(b"reply".as_slice(), addr).await;
+        assert_eq!(res.unwrap(), 5);
+        let mut buf = [0; 32];
+        let n = v4_peer.recv(&mut buf).unwrap();
+        assert_eq!(&buf[..n], b"reply");
+
+        let v6_peer = std::net::UdpSocket::bind("[::1]:0").unwrap();
+        v6_peer.send_to(b"from v6", "[::1]:30124").unwrap();
+
+        let (res, buf) = socket.recv_from(vec![0; 32]).await;
+        let (n, addr) = res.unwrap();
+        assert_eq!(&buf[..n], b"from v6");
+        assert_eq!(addr, v6_peer.local_addr().unwrap());
+    });
+}
+
+#[test]
+fn recv_from_timeout() {
+    tokio_uring::start(async {
+        let socket = UdpSocket::bind("127.0.0.1:30125".parse().unwrap())
+            .await
+            .unwrap();
+
+        let start = std::time::Instant::now();
+        let (res, buf) = socket
+            .recv_from_timeout(vec![0; 32], Duration::from_millis(50))
+            .await;
+        assert_eq!(res.unwrap_err().kind(), std::io::ErrorKind::TimedOut);
+        assert!(start.elapsed() >= Duration::from_millis(50));
+        assert_eq!(buf.len(), 32);
+
+        // The timed out receive does not consume the next datagram
+        let peer = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        peer.send_to(b"ping", "127.0.0.1:30125").unwrap();
+
+        let (res, buf) = socket
+            .recv_from_timeout(vec![0; 32], Duration::from_secs(5))
+            .await;
+        let (n, addr) = res.unwrap();
+        assert_eq!(&buf[..n], b"ping");
+        assert_eq!(addr, peer.local_addr().unwrap());
+    });
+}
+
+#[test]
+fn into_std() {
+    use std::os::unix::io::AsRawFd;
+
+    tokio_uring::start(async {
+        let socket = UdpSocket::bind("127.0.0.1:30126".parse().unwrap())
+            .await
+            .unwrap();
+        let fd = socket.as_raw_fd();
+
+        let peer = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        peer.send_to(b"ping", "127.0.0.1:30126").unwrap();
+
+        let std_socket = socket.into_std().unwrap();
+        assert_eq!(std_socket.as_raw_fd(), fd);
+
+        let mut buf = [0; 32];
+        let (n, addr) = std_socket.recv_from(&mut buf).unwrap();
+        assert_eq!(&buf[..n], b"ping");
+        assert_eq!(addr, peer.local_addr().unwrap());
+    });
+}
+
+#[test]
+fn into_std_in_flight() {
+    tokio_uring::start(async {
+        let mut socket = UdpSocket::bind("127.0.0.1:30127".parse().unwrap())
+            .await
+            .unwrap();
+
+        // Leave a read in-flight
+        let mut buf = [0; 32];
+        let mut read_buf = tokio::io::ReadBuf::new(&mut buf);
+        let poll = poll_fn(|cx| {
+            std::task::Poll::Ready(Pin::new(&mut socket).poll_read(cx, &mut read_buf))
+        })
+        .await;
+        assert!(poll.is_pending());
+
+        // The canceled read completes on the next driver tick only
+        assert!(socket.into_std().is_err());
+    });
+}
+
+#[test]
+fn send_to_batch() {
+    tokio_uring::start(async {
+        let socket = UdpSocket::bind("127.0.0.1:0".parse().unwrap())
+            .await
+            .unwrap();
+        let peers: Vec<_> = (0..4)
+            .map(|_| std::net::UdpSocket::bind("127.0.0.1:0").unwrap())
+            .collect();
+
+        let v6_addr: SocketAddr = "[::1]:30128".parse().unwrap();
+        let mut packets = vec![];
+        for (i, peer) in peers.iter().enumerate() {
+            packets.push((vec![i as u8; 8], peer.local_addr().unwrap()));
+        }
+        // Cannot send to an IPv6 address from an IPv4 socket
+        packets.insert(2, (vec![0xff; 8], v6_addr));
+
+        let results = socket.send_to_batch(packets).await;
+        assert_eq!(results.len(), 5);
+
+        let mut results = results.into_iter();
+        for (i, peer) in peers.iter().enumerate() {
+            if i == 2 {
+                let (res, buf) = results.next().unwrap();
+                assert!(res.is_err());
+                assert_eq!(buf, [0xff; 8]);
+            }
+
+            let (res, buf) = results.next().unwrap();
+            assert_eq!(res.unwrap(), 8);
+            assert_eq!(buf, [i as u8; 8]);
+
+            let mut recv = [0; 32];
+            let n = peer.recv(&mut recv).unwrap();
+            assert_eq!(&recv[..n], &[i as u8; 8]);
+        }
+    });
+}
+
+#[test]
+fn send_to_batch_larger_than_queue() {
+    tokio_uring::start(async {
+        let socket = UdpSocket::bind("127.0.0.1:0".parse().unwrap())
+            .await
+            .unwrap();
+        let peer = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        let addr = peer.local_addr().unwrap();
+
+        let packets = (0..300u32).map(|i| (i.to_le_bytes().to_vec(), addr));
+        let results = socket.send_to_batch(packets).await;
+
+        assert_eq!(results.len(), 300);
+        for (i, (res, buf)) in results.into_iter().enumerate() {
+            assert_eq!(res.unwrap(), 4);
+            assert_eq!(buf, (i as u32).to_le_bytes());
+        }
+    });
+}
+
+#[test]
+fn buffer_sizes() {
+    tokio_uring::start(async {
+        let socket = UdpSocket::bind("127.0.0.1:0".parse().unwrap())
+            .await
+            .unwrap();
+
+        socket.set_recv_buffer_size(32 * 1024).unwrap();
+        assert_eq!(socket.recv_buffer_size().unwrap(), 64 * 1024);
+
+        socket.set_send_buffer_size(16 * 1024).unwrap();
+        assert_eq!(socket.send_buffer_size().unwrap(), 32 * 1024);
+
+        let err = socket.set_recv_buffer_size(usize::MAX).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    });
+}
+
+async fn next<S: Stream + Unpin>(stream: &mut S) -> Option<S::Item> {
+    poll_fn(|cx| Pin::new(&mut *stream).poll_next(cx)).await
+}
+
+#[test]
+fn recv_multishot_datagrams() {
+    tokio_uring::start(async {
+        let socket = UdpSocket::bind("127.0.0.1:30108".parse().unwrap())
+            .await
+            .unwrap();
+        let ring = BufRing::register(4, 256).unwrap();
+
+        let peer = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        let mut datagrams = Box::pin(socket.recv_multishot(ring));
+
+        // More datagrams than buffers, each buffer is recycled on drop
+        for i in 0..8u8 {
+            peer.send_to(&[i; 16], "127.0.0.1:30108").unwrap();
+
+            let slot = next(&mut datagrams).await.unwrap().unwrap();
+            assert_eq!(&slot[..], &[i; 16]);
+            assert_eq!(slot.addr(), peer.local_addr().unwrap());
+        }
+    });
+}
+
+#[test]
+fn recv_multishot_out_of_buffers() {
+    tokio_uring::start(async {
+        let socket = UdpSocket::bind("127.0.0.1:30109".parse().unwrap())
+            .await
+            .unwrap();
+        let ring = BufRing::register(2, 256).unwrap();
+
+        let peer = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        for payload in [b"one", b"two", b"six"] {
+            peer.send_to(payload, "127.0.0.1:30109").unwrap();
+        }
+
+        let mut datagrams = Box::pin(socket.recv_multishot(ring));
+
+        let first = next(&mut datagrams).await.unwrap().unwrap();
+        let second = next(&mut datagrams).await.unwrap().unwrap();
+        assert_eq!(&first[..], b"one");
+        assert_eq!(&second[..], b"two");
+
+        let err = next(&mut datagrams).await.unwrap().unwrap_err();
+        assert_eq!(err.raw_os_error(), Some(libc::ENOBUFS));
+
+        // Returning the buffers lets the stream pick up where it stopped
+        drop((first, second));
+
+        let third = next(&mut datagrams).await.unwrap().unwrap();
+        assert_eq!(&third[..], b"six");
+    });
+}
+
+#[test]
+fn use_fixed_file() {
+    tokio_uring::start(async {
+        let socket1 = UdpSocket::bind("127.0.0.1:30129".parse().unwrap())
+            .await
+            .unwrap();
+        let socket2 = UdpSocket::bind("127.0.0.1:30130".parse().unwrap())
+            .await
+            .unwrap();
+        socket1.use_fixed_file().unwrap();
+        socket2.use_fixed_file().unwrap();
+
+        let (res, _) = socket1
+            .send_to(b"hello".as_slice(), "127.0.0.1:30130".parse().unwrap())
+            .await;
+        res.unwrap();
+
+        let (res, buf) = socket2.recv_from(vec![0; 32]).await;
+        let (n, addr) = res.unwrap();
+        assert_eq!(&buf[..n], b"hello");
+        assert_eq!(addr, "127.0.0.1:30129".parse().unwrap());
+    });
+}
+
+#[test]
+fn batch() {
+    tokio_uring::start(async {
+        let socket = UdpSocket::bind("127.0.0.1:30131".parse().unwrap())
+            .await
+            .unwrap();
+        let addr = socket.local_addr().unwrap();
+
+        let mut received = tokio_uring::batch(async {
+            let (a, b) = tokio::join!(
+                socket.send_to(b"a".to_vec(), addr),
+                tokio_uring::batch(socket.send_to(b"b".to_vec(), addr)),
+            );
+            assert_eq!(a.0.unwrap(), 1);
+            assert_eq!(b.0.unwrap(), 1);
+
+            // Awaiting within the batch still submits
+            let mut received = Vec::new();
+            for _ in 0..2 {
+                let (res, buf) = socket.recv_from(vec![0; 8]).await;
+                received.extend_from_slice(&buf[..res.unwrap().0]);
+            }
+            received
+        })
+        .await;
+
+        received.sort_unstable();
+        assert_eq!(received, b"ab");
+    });
+}
+
+#[test]
+fn close_after_canceled_recv() {
+    tokio_uring::start(async {
+        let socket = UdpSocket::bind("127.0.0.1:0".parse().unwrap())
+            .await
+            .unwrap();
+
+        // The canceled receive holds the socket until its completion lands,
+        // the close waits for it.
+        let recv =
+            tokio::time::timeout(Duration::from_millis(5), socket.recv_from(vec![0; 8])).await;
+        assert!(recv.is_err());
+
+        tokio::time::timeout(Duration::from_secs(5), socket.close())
+            .await
+            .expect("close did not complete")
+            .unwrap();
+    });
+}
+
+#[test]
+fn connect_refused() {
+    tokio_uring::start(async {
+        let addr = std::net::UdpSocket::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+
+        let socket = UdpSocket::bind("127.0.0.1:0".parse().unwrap())
+            .await
+            .unwrap();
+        socket.connect(addr).await.unwrap();
+
+        // The refusal surfaces on the operation after the first send
+        let (res, _) = socket.send(b"ping".as_slice()).await;
+        res.unwrap();
+        let (res, _) = socket.recv(vec![0; 32]).await;
+        let err = res.unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::ConnectionRefused);
+    });
+}
+
+#[test]
+fn bind_connect_host() {
+    tokio_uring::start(async {
+        let peer = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        let port = peer.local_addr().unwrap().port();
+
+        let socket = UdpSocket::bind_host("127.0.0.1:0").await.unwrap();
+        let addr = socket
+            .connect_host(format!("localhost:{}", port))
+            .await
+            .unwrap();
+        assert_eq!(addr, peer.local_addr().unwrap());
+
+        let (res, _) = socket.send(b"ping".as_slice()).await;
+        res.unwrap();
+        let mut buf = [0; 4];
+        assert_eq!(peer.recv(&mut buf).unwrap(), 4);
+    });
+}
+
+#[test]
+fn so_error() {
+    tokio_uring::start(async {
+        let addr = std::net::UdpSocket::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+
+        let socket = UdpSocket::bind("127.0.0.1:0".parse().unwrap())
+            .await
+            .unwrap();
+        assert!(socket.so_error().unwrap().is_none());
+
+        // The ICMP answer to the datagram sets the pending error
+        socket.connect(addr).await.unwrap();
+        let (res, _) = socket.send(b"ping".as_slice()).await;
+        res.unwrap();
+        tokio_uring::time::sleep(Duration::from_millis(50)).await;
+
+        let err = socket.so_error().unwrap().unwrap();
+        assert_eq!(err.kind(), std::io::ErrorKind::ConnectionRefused);
+        assert!(socket.so_error().unwrap().is_none());
+    });
+}
+
+#[test]
+fn bind_and_connect() {
+    tokio_uring::start(async {
+        let peer = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        let peer_addr = peer.local_addr().unwrap();
+
+        let socket = UdpSocket::bind_and_connect("127.0.0.3:0".parse().unwrap(), peer_addr)
+            .await
+            .unwrap();
+        assert_eq!(
+            socket.local_addr().unwrap().ip(),
+            Ipv4Addr::new(127, 0, 0, 3)
+        );
+
+        let (res, _) = socket.send(b"hello".as_slice()).await;
+        res.unwrap();
+
+        let mut buf = [0; 5];
+        let (n, from) = peer.recv_from(&mut buf).unwrap();
+        assert_eq!(&buf[..n], b"hello");
+        assert_eq!(from, socket.local_addr().unwrap());
+
+        let err = UdpSocket::bind_and_connect("[::1]:0".parse().unwrap(), peer_addr)
+            .await
+            .err()
+            .unwrap();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    });
+}
+
+#[test]
+fn raw_sockopts() {
+    tokio_uring::start(async {
+        let socket = UdpSocket::bind("127.0.0.1:0".parse().unwrap())
+            .await
+            .unwrap();
+
+        let tos: libc::c_int = 0x10;
+        socket
+            .set_sockopt(libc::IPPROTO_IP, libc::IP_TOS, &tos.to_ne_bytes())
+            .unwrap();
+
+        let mut buf = [0; 8];
+        let len = socket
+            .get_sockopt(libc::IPPROTO_IP, libc::IP_TOS, &mut buf)
+            .unwrap();
+        assert_eq!(len, 4);
+        assert_eq!(&buf[..4], &tos.to_ne_bytes());
+
+        // Errors of the kernel are returned as is
+        let err = socket
+            .set_sockopt(libc::SOL_SOCKET, -1, &tos.to_ne_bytes())
+            .unwrap_err();
+        assert_eq!(err.raw_os_error(), Some(libc::ENOPROTOOPT));
+
+        // Marking needs privileges
+        match socket.set_mark(42) {
+            Ok(()) => assert_eq!(socket.mark().unwrap(), 42),
+            Err(e) => assert_eq!(e.raw_os_error(), Some(libc::EPERM)),
+        }
+    });
+}
+
+#[test]
+fn freebind_and_transparent() {
+    tokio_uring::start(async {
+        let socket = UdpSocket::bind("127.0.0.1:0".parse().unwrap())
+            .await
+            .unwrap();
+        assert!(!socket.freebind().unwrap());
+        socket.set_freebind(true).unwrap();
+        assert!(socket.freebind().unwrap());
+        assert!(!socket.transparent().unwrap());
+
+        // Binding to an address of another host needs privileges
+        let addr = "192.0.2.1:0".parse().unwrap();
+        match UdpSocket::bind_transparent(addr).await {
+            Ok(socket) => {
+                assert!(socket.transparent().unwrap());
+                assert!(socket.freebind().unwrap());
+                assert_eq!(socket.local_addr().unwrap().ip(), addr.ip());
+            }
+            Err(e) => assert_eq!(e.raw_os_error(), Some(libc::EPERM)),
+        }
+    });
+}
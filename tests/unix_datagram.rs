@@ -0,0 +1,115 @@
+use std::os::linux::net::SocketAddrExt;
+use std::path::PathBuf;
+
+use tokio_uring::net::UnixDatagram;
+
+fn temp_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("tokio-uring-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+#[test]
+fn send_recv_path() {
+    let server_path = temp_path("dgram-server");
+    let client_path = temp_path("dgram-client");
+
+    tokio_uring::start(async {
+        let server = UnixDatagram::bind(&server_path).unwrap();
+        let client = UnixDatagram::bind(&client_path).unwrap();
+        assert_eq!(
+            server.local_addr().unwrap().as_pathname(),
+            Some(server_path.as_path())
+        );
+
+        let (res, _) = client.send_to(b"ping".as_slice(), &server_path).await;
+        assert_eq!(res.unwrap(), 4);
+
+        let (res, buf) = server.recv_from(vec![0; 32]).await;
+        let (n, addr) = res.unwrap();
+        assert_eq!(&buf[..n], b"ping");
+        assert_eq!(addr.as_pathname(), Some(client_path.as_path()));
+
+        // Reply to the sender
+        let (res, _) = server
+            .send_to(b"pong".as_slice(), addr.as_pathname().unwrap())
+            .await;
+        assert_eq!(res.unwrap(), 4);
+
+        let (res, buf) = client.recv_from(vec![0; 32]).await;
+        let (n, _) = res.unwrap();
+        assert_eq!(&buf[..n], b"pong");
+    });
+
+    std::fs::remove_file(&server_path).unwrap();
+    std::fs::remove_file(&client_path).unwrap();
+}
+
+#[test]
+fn send_recv_abstract() {
+    tokio_uring::start(async {
+        let name = format!("\0tokio-uring-dgram-{}", std::process::id());
+        let server = UnixDatagram::bind(&name).unwrap();
+        let client = UnixDatagram::bind(format!("{}-client", name)).unwrap();
+
+        let (res, _) = client.send_to(b"ping".as_slice(), &name).await;
+        assert_eq!(res.unwrap(), 4);
+
+        let (res, buf) = server.recv_from(vec![0; 32]).await;
+        let (n, addr) = res.unwrap();
+        assert_eq!(&buf[..n], b"ping");
+        assert_eq!(
+            addr.as_abstract_name(),
+            Some(format!("{}-client", &name[1..]).as_bytes())
+        );
+        assert!(addr.as_pathname().is_none());
+    });
+}
+
+#[test]
+fn send_recv_unbound() {
+    tokio_uring::start(async {
+        let name = format!("\0tokio-uring-dgram-unbound-{}", std::process::id());
+        let server = UnixDatagram::bind(&name).unwrap();
+        let client = UnixDatagram::unbound().unwrap();
+
+        let (res, _) = client.send_to(b"ping".as_slice(), &name).await;
+        assert_eq!(res.unwrap(), 4);
+
+        let (res, buf) = server.recv_from(vec![0; 32]).await;
+        let (n, addr) = res.unwrap();
+        assert_eq!(&buf[..n], b"ping");
+        assert!(addr.is_unnamed());
+    });
+}
+
+#[test]
+fn send_recv_connected() {
+    tokio_uring::start(async {
+        let server_name = format!("\0tokio-uring-dgram-connected-{}", std::process::id());
+        let client_name = format!("{}-client", server_name);
+        let server = UnixDatagram::bind(&server_name).unwrap();
+        let client = UnixDatagram::bind(&client_name).unwrap();
+
+        client.connect(&server_name).await.unwrap();
+        server.connect(&client_name).await.unwrap();
+        assert_eq!(
+            client.peer_addr().unwrap().as_abstract_name(),
+            Some(&server_name.as_bytes()[1..])
+        );
+
+        let (res, _) = client.send(b"ping".as_slice()).await;
+        assert_eq!(res.unwrap(), 4);
+
+        let (res, buf) = server.recv(vec![0; 32]).await;
+        let n = res.unwrap();
+        assert_eq!(&buf[..n], b"ping");
+
+        let (res, _) = server.send(b"pong".as_slice()).await;
+        assert_eq!(res.unwrap(), 4);
+
+        let (res, buf) = client.recv(vec![0; 32]).await;
+        let n = res.unwrap();
+        assert_eq!(&buf[..n], b"pong");
+    });
+}
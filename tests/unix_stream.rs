@@ -0,0 +1,113 @@
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::os::linux::net::SocketAddrExt;
+use std::os::unix::io::AsRawFd;
+use std::path::PathBuf;
+
+use tokio_uring::net::{UnixListener, UnixStream};
+
+fn temp_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("tokio-uring-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+#[test]
+fn send_recv_fds() {
+    let path = temp_path("stream-fds");
+    let listener = UnixListener::bind(&path).unwrap();
+
+    tokio_uring::start(async {
+        let (tx, rx) = tokio::try_join!(UnixStream::connect(&path), listener.accept()).unwrap();
+
+        let mut file = tempfile::tempfile().unwrap();
+        file.write_all(b"passed").unwrap();
+
+        let (res, _) = tx.send_fds(b"fd".as_slice(), &[file.as_raw_fd()]).await;
+        assert_eq!(res.unwrap(), 2);
+
+        let (res, buf) = rx.recv_fds(vec![0; 8], 4).await;
+        let (n, fds, truncated) = res.unwrap();
+        assert_eq!(&buf[..n], b"fd");
+        assert_eq!(fds.len(), 1);
+        assert!(!truncated);
+
+        // The received descriptor refers to the same file
+        let mut received = std::fs::File::from(fds.into_iter().next().unwrap());
+        received.seek(SeekFrom::Start(0)).unwrap();
+        let mut contents = String::new();
+        received.read_to_string(&mut contents).unwrap();
+        assert_eq!(contents, "passed");
+    });
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn recv_fds_truncated() {
+    let path = temp_path("stream-fds-truncated");
+    let listener = UnixListener::bind(&path).unwrap();
+
+    tokio_uring::start(async {
+        let (tx, rx) = tokio::try_join!(UnixStream::connect(&path), listener.accept()).unwrap();
+
+        let files: Vec<_> = (0..4).map(|_| tempfile::tempfile().unwrap()).collect();
+        let fds: Vec<_> = files.iter().map(|file| file.as_raw_fd()).collect();
+
+        let (res, _) = tx.send_fds(b"fds".as_slice(), &fds).await;
+        res.unwrap();
+
+        // Room for a single descriptor
+        let (res, buf) = rx.recv_fds(Vec::with_capacity(8), 1).await;
+        let (n, fds, truncated) = res.unwrap();
+        assert_eq!(&buf[..n], b"fds");
+        // The control buffer is padded, which may leave room for another
+        assert!(!fds.is_empty() && fds.len() < 4);
+        assert!(truncated);
+    });
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn abstract_namespace() {
+    let name = format!("\0tokio-uring-stream-{}", std::process::id());
+    let listener = UnixListener::bind(&name).unwrap();
+    assert_eq!(
+        listener.local_addr().unwrap().as_abstract_name(),
+        Some(&name.as_bytes()[1..])
+    );
+
+    tokio_uring::start(async {
+        let (tx, rx) = tokio::try_join!(UnixStream::connect(&name), listener.accept()).unwrap();
+        assert!(tx.local_addr().unwrap().is_unnamed());
+        assert_eq!(
+            tx.peer_addr().unwrap().as_abstract_name(),
+            Some(&name.as_bytes()[1..])
+        );
+
+        let (res, _) = tx.write(b"ping".as_slice()).await;
+        res.unwrap();
+        let (res, buf) = rx.read(vec![0; 8]).await;
+        assert_eq!(&buf[..res.unwrap()], b"ping");
+    });
+}
+
+#[test]
+fn peer_cred() {
+    let path = temp_path("stream-cred");
+    let mut listener = UnixListener::bind(&path).unwrap();
+    listener.set_unlink_on_drop(true).unwrap();
+
+    tokio_uring::start(async {
+        let (tx, rx) = tokio::try_join!(UnixStream::connect(&path), listener.accept()).unwrap();
+
+        for stream in [&tx, &rx] {
+            let cred = stream.peer_cred().unwrap();
+            assert_eq!(cred.pid(), Some(std::process::id() as i32));
+            assert_eq!(cred.uid(), unsafe { libc::getuid() });
+            assert_eq!(cred.gid(), unsafe { libc::getgid() });
+        }
+    });
+
+    assert!(path.exists());
+    drop(listener);
+    assert!(!path.exists());
+}